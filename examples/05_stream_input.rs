@@ -1,7 +1,6 @@
-use btleplug::api::Peripheral as _;
 use futures::stream::StreamExt;
 use joy2_rs::joycon2::connection::{init_controller, Side};
-use joy2_rs::joycon2::controller::Joy2L;
+use joy2_rs::joycon2::controller::{ControllerInput, Joy2L};
 use std::error::Error;
 
 #[tokio::main]
@@ -24,12 +23,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("Try pressing buttons and moving the stick!\n");
     println!("=================================================\n");
     
-    // Create controller state tracker
-    let mut controller = Joy2L::new();
-    
-    // Get peripheral and subscribe to notifications
-    let peripheral = connection.peripheral();
-    let mut notification_stream = peripheral.notifications().await?;
+    // Subscribe to parsed controller states
+    let mut input_stream = connection.input_stream().await?;
     
     // Track previous state for change detection
     let mut prev_buttons = String::new();
@@ -38,11 +33,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     
     println!("Listening for input... (Press Ctrl+C to exit)\n");
     
-    // Process notifications
-    while let Some(notification) = notification_stream.next().await {
-        // Update controller state
-        controller.update(&notification.value);
-        
+    // Process controller states
+    while let Some(ControllerInput::Left(controller)) = input_stream.next().await {
         // Detect button changes
         let current_buttons = format_buttons(&controller);
         if current_buttons != prev_buttons && !current_buttons.is_empty() {
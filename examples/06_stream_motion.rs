@@ -99,13 +99,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
             print_motion_data(&controller);
             
             // Check motion thresholds
-            let has_rotation = gyro.x.abs() > MOTION_THRESHOLD_GYRO 
-                || gyro.y.abs() > MOTION_THRESHOLD_GYRO 
-                || gyro.z.abs() > MOTION_THRESHOLD_GYRO;
+            let has_rotation = gyro.magnitude() > MOTION_THRESHOLD_GYRO;
             
-            let has_movement = (accel.x.abs() - 1.0).abs() > MOTION_THRESHOLD_ACCEL 
-                || accel.y.abs() > MOTION_THRESHOLD_ACCEL 
-                || accel.z.abs() > MOTION_THRESHOLD_ACCEL;
+            // At rest the accelerometer only measures gravity (1G)
+            let has_movement = (accel.magnitude() - 1.0).abs() > MOTION_THRESHOLD_ACCEL;
             
             let current_motion = has_rotation || has_movement;
             
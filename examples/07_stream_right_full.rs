@@ -1,7 +1,6 @@
-use btleplug::api::Peripheral as _;
 use futures::stream::StreamExt;
 use joy2_rs::joycon2::connection::{init_controller, Side};
-use joy2_rs::joycon2::controller::Joy2R;
+use joy2_rs::joycon2::controller::ControllerInput;
 use std::error::Error;
 use std::time::{Duration, Instant};
 
@@ -25,12 +24,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("Try pressing buttons, moving the stick, and moving the controller!");
     println!("\n=================================================\n");
     
-    // Create controller state tracker
-    let mut controller = Joy2R::new();
-    
-    // Get peripheral and subscribe to notifications
-    let peripheral = connection.peripheral();
-    let mut notification_stream = peripheral.notifications().await?;
+    // Subscribe to parsed controller states
+    let mut input_stream = connection.input_stream().await?;
     
     println!("Listening for input... (Press Ctrl+C to exit)\n");
     
@@ -47,11 +42,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let mut last_buttons_display = String::new();
     let mut last_stick_display = String::new();
     
-    // Process notifications
-    while let Some(notification) = notification_stream.next().await {
-        // Update controller state
-        controller.update(&notification.value);
-        
+    // Process controller states
+    while let Some(ControllerInput::Right(controller)) = input_stream.next().await {
         let now = Instant::now();
         
         // Only display every 100ms to avoid spam
@@ -86,19 +78,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
             
             // Check for stick changes (with deadzone)
             let deadzone = 0.1;
-            let stick_display = if stick.x.abs() < deadzone && stick.y.abs() < deadzone {
+            let stick_display = if stick.magnitude() < deadzone {
                 "centered".to_string()
             } else {
-                let direction = if stick.y > 0.5 {
-                    " [↑]"
-                } else if stick.y < -0.5 {
-                    " [↓]"
-                } else if stick.x > 0.5 {
-                    " [→]"
-                } else if stick.x < -0.5 {
-                    " [←]"
-                } else {
+                let direction = if stick.magnitude() <= 0.5 {
                     ""
+                } else {
+                    match stick.angle() {
+                        a if (45.0..135.0).contains(&a) => " [↑]",
+                        a if (-135.0..-45.0).contains(&a) => " [↓]",
+                        a if (-45.0..45.0).contains(&a) => " [→]",
+                        _ => " [←]",
+                    }
                 };
                 format!("X={:+.2}, Y={:+.2}{}", stick.x, stick.y, direction)
             };
@@ -119,13 +110,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
             let motion_threshold = 5.0; // degrees/second for gyro
             let accel_threshold = 0.15; // G-force
             
-            let has_gyro_activity = gyro.x.abs() > motion_threshold 
-                || gyro.y.abs() > motion_threshold 
-                || gyro.z.abs() > motion_threshold;
+            let has_gyro_activity = gyro.magnitude() > motion_threshold;
                 
-            let has_accel_activity = accel.x.abs() > accel_threshold 
-                || (accel.y + 1.0).abs() > accel_threshold // Y should be ~-1.0 at rest
-                || accel.z.abs() > accel_threshold;
+            let has_accel_activity = (accel.magnitude() - 1.0).abs() > accel_threshold; // only gravity (1G) at rest
             
             if has_gyro_activity || has_accel_activity {
                 println!("\n┌─────────────────────────────────────────────────────────┐");
@@ -18,6 +18,7 @@ fn main() {
     // Load ETS2 config
     match Config::load("configs/ETS2.toml") {
         Ok(config) => {
+            config.log_startup_report();
             println!("\n✅ Config loaded successfully!");
             println!("   Profiles:");
             for profile in &config.profiles {
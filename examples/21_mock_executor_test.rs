@@ -23,6 +23,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Test 1: Load ETS2.toml
     println!("📋 Test 1: Loading ETS2.toml...");
     let config = Config::load("configs/ETS2.toml")?;
+    config.log_startup_report();
     println!("   ✓ Loaded {} profiles", config.profiles.len());
     for profile in &config.profiles {
         println!("     - {}: {} (buttons: {})", 
@@ -43,14 +44,14 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Test 3: Simulate some events
     println!("🔘 Test 3: Simulating button events...");
     println!("   Pressing A button (should trigger action)...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::A));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
     
     println!("   Releasing A button...");
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::A));
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
     
     println!("   Pressing SLR button (CycleProfiles)...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::SLR));
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::SLR));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::SLR, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::SLR, side: ControllerSide::Right });
     println!();
 
     // Test 4: Simulate stick movement
@@ -73,8 +74,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Test 5: Simulate gyro toggle and movement
     println!("🎯 Test 5: Simulating gyro toggle and movement...");
     println!("   Toggling gyro mouse for right controller...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::SRR));
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::SRR));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::SRR, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::SRR, side: ControllerSide::Right });
     
     println!("   Simulating gyro movement (pitch: 0.1, yaw: 0.2)...");
     executor.process_event(&JoyConEvent::GyroUpdate {
@@ -88,12 +89,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Test 6: Cycle sensitivity
     println!("🎚️  Test 6: Cycling sensitivity...");
     println!("   Pressing Plus button (CycleSensitivity)...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::Plus));
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::Plus));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Plus, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Plus, side: ControllerSide::Right });
     
     println!("   Pressing Plus button again...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::Plus));
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::Plus));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Plus, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Plus, side: ControllerSide::Right });
     println!();
 
     // Test 7: Profile cycling
@@ -103,12 +104,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     );
     
     println!("   Pressing SLR (CycleProfiles) to switch profile...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::SLR));
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::SLR));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::SLR, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::SLR, side: ControllerSide::Right });
     
     println!("   Pressing A button in new profile...");
-    executor.process_event(&JoyConEvent::ButtonPressed(ButtonType::A));
-    executor.process_event(&JoyConEvent::ButtonReleased(ButtonType::A));
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
     println!();
 
     println!("✅ All tests completed successfully!\n");
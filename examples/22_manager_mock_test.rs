@@ -38,68 +38,29 @@ fn main() -> Result<(), Box<dyn Error>> {
         eprintln!("  Creating fallback configuration...");
         
         // Fallback: Create a simple configuration with profile-based mappings
-        use joy2_rs::mapping::config::{
-            Action, ButtonType, Profile, Settings, StickMappings, GyroSettings,
-            StickMapping, StickMode, DirectionalKeys,
-        };
-        use std::collections::HashMap;
+        use joy2_rs::mapping::config::{Action, ButtonType, DirectionalKeys, StickMapping, StickType};
+        use joy2_rs::mapping::{ConfigBuilder, ProfileBuilder};
         
-        let mut buttons = HashMap::new();
+        let key = |k: &str| Action::KeyHold { key: Some(k.to_string()) };
         
-        // Map some buttons to keyboard keys
-        buttons.insert(ButtonType::A, vec![Action::KeyHold {
-            key: Some("space".to_string()),
-        }]);
+        let base_profile = ProfileBuilder::new("base")
+            .description("Fallback test profile")
+            // Map some buttons to keyboard keys
+            .button(ButtonType::A, key("space"))
+            .button(ButtonType::B, key("w"))
+            .button(ButtonType::X, key("a"))
+            .button(ButtonType::Y, key("s"))
+            // Add profile cycling button
+            .button(ButtonType::SLR, Action::CycleProfiles)
+            // Add gyro mouse toggle
+            .button(ButtonType::SRR, Action::ToggleGyroMouseR)
+            .stick(StickType::Left, StickMapping::directional(DirectionalKeys::new("w", "s", "a", "d")))
+            .stick(StickType::Right, StickMapping::mouse(1.0));
         
-        buttons.insert(ButtonType::B, vec![Action::KeyHold {
-            key: Some("w".to_string()),
-        }]);
-        
-        buttons.insert(ButtonType::X, vec![Action::KeyHold {
-            key: Some("a".to_string()),
-        }]);
-        
-        buttons.insert(ButtonType::Y, vec![Action::KeyHold {
-            key: Some("s".to_string()),
-        }]);
-        
-        // Add profile cycling button
-        buttons.insert(ButtonType::SLR, vec![Action::CycleProfiles]);
-        
-        // Add gyro mouse toggle
-        buttons.insert(ButtonType::SRR, vec![Action::ToggleGyroMouseR]);
-        
-        // Create a base profile
-        let base_profile = Profile {
-            name: "base".to_string(),
-            description: "Fallback test profile".to_string(),
-            buttons: buttons.clone(),
-            sticks: StickMappings {
-                left: Some(StickMapping {
-                    mode: StickMode::Directional,
-                    sensitivity: 1.0,
-                    directions: Some(DirectionalKeys {
-                        up: "w".to_string(),
-                        down: "s".to_string(),
-                        left: "a".to_string(),
-                        right: "d".to_string(),
-                    }),
-                }),
-                right: Some(StickMapping {
-                    mode: StickMode::Mouse,
-                    sensitivity: 1.0,
-                    directions: None,
-                }),
-            },
-            gyro: GyroSettings::default(),
-            gyro_mouse_overrides_left: HashMap::new(),
-            gyro_mouse_overrides_right: HashMap::new(),
-        };
-        
-        Config {
-            settings: Settings::default(),
-            profiles: vec![base_profile],
-        }
+        ConfigBuilder::new()
+            .profile(base_profile)
+            .build()
+            .expect("fallback config is valid")
     };
 
     // Create mock backends
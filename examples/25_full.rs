@@ -6,7 +6,7 @@
 //! ⚠️  WARNING: This will send REAL keyboard and mouse input to your system!
 //! ⚠️  Make sure you have your config set up correctly before running.
 
-use joy2_rs::backend::{KeyboardSendInputBackend, MouseSendInputBackend};
+use joy2_rs::backend::{get_keyboard_backend, get_mouse_backend};
 use joy2_rs::mapping::config::Config;
 use joy2_rs::JoyConManager;
 use std::error::Error;
@@ -43,73 +43,34 @@ fn main() -> Result<(), Box<dyn Error>> {
         eprintln!("  Creating fallback configuration...");
         
         // Fallback: Create a simple configuration with profile-based mappings
-        use joy2_rs::mapping::config::{
-            Action, ButtonType, Profile, Settings, StickMappings, GyroSettings,
-            StickMapping, StickMode, DirectionalKeys,
-        };
-        use std::collections::HashMap;
+        use joy2_rs::mapping::config::{Action, ButtonType, DirectionalKeys, StickMapping, StickType};
+        use joy2_rs::mapping::{ConfigBuilder, ProfileBuilder};
         
-        let mut buttons = HashMap::new();
+        let key = |k: &str| Action::KeyHold { key: Some(k.to_string()) };
         
-        // Map some buttons to keyboard keys
-        buttons.insert(ButtonType::A, vec![Action::KeyHold {
-            key: Some("a".to_string()),
-        }]);
+        let base_profile = ProfileBuilder::new("base")
+            .description("Fallback test profile")
+            // Map some buttons to keyboard keys
+            .button(ButtonType::A, key("a"))
+            .button(ButtonType::B, key("b"))
+            .button(ButtonType::X, key("x"))
+            .button(ButtonType::Y, key("y"))
+            // Add profile cycling button
+            .button(ButtonType::SLR, Action::CycleProfiles)
+            // Add gyro mouse toggle
+            .button(ButtonType::SRR, Action::ToggleGyroMouseR)
+            .stick(StickType::Left, StickMapping::directional(DirectionalKeys::new("w", "s", "a", "d")))
+            .stick(StickType::Right, StickMapping::mouse(1.0));
         
-        buttons.insert(ButtonType::B, vec![Action::KeyHold {
-            key: Some("b".to_string()),
-        }]);
-        
-        buttons.insert(ButtonType::X, vec![Action::KeyHold {
-            key: Some("x".to_string()),
-        }]);
-        
-        buttons.insert(ButtonType::Y, vec![Action::KeyHold {
-            key: Some("y".to_string()),
-        }]);
-        
-        // Add profile cycling button
-        buttons.insert(ButtonType::SLR, vec![Action::CycleProfiles]);
-        
-        // Add gyro mouse toggle
-        buttons.insert(ButtonType::SRR, vec![Action::ToggleGyroMouseR]);
-        
-        // Create a base profile
-        let base_profile = Profile {
-            name: "base".to_string(),
-            description: "Fallback test profile".to_string(),
-            buttons: buttons.clone(),
-            sticks: StickMappings {
-                left: Some(StickMapping {
-                    mode: StickMode::Directional,
-                    sensitivity: 1.0,
-                    directions: Some(DirectionalKeys {
-                        up: "w".to_string(),
-                        down: "s".to_string(),
-                        left: "a".to_string(),
-                        right: "d".to_string(),
-                    }),
-                }),
-                right: Some(StickMapping {
-                    mode: StickMode::Mouse,
-                    sensitivity: 1.0,
-                    directions: None,
-                }),
-            },
-            gyro: GyroSettings::default(),
-            gyro_mouse_overrides_left: HashMap::new(),
-            gyro_mouse_overrides_right: HashMap::new(),
-        };
-        
-        Config {
-            settings: Settings::default(),
-            profiles: vec![base_profile],
-        }
+        ConfigBuilder::new()
+            .profile(base_profile)
+            .build()
+            .expect("fallback config is valid")
     };
 
-    // Create real backends (unit structs - no new() needed)
-    let keyboard = KeyboardSendInputBackend;
-    let mouse = MouseSendInputBackend;
+    // Create real backends (a NullBackend that only warns on unsupported platforms)
+    let keyboard = get_keyboard_backend();
+    let mouse = get_mouse_backend();
 
     // Create the manager
     let mut manager = JoyConManager::new(config, keyboard, mouse);
@@ -48,7 +48,7 @@
 #[cfg(windows)]
 use windows::Win32::UI::Input::KeyboardAndMouse::{
     SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
-    KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, VIRTUAL_KEY,
+    KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, KEYEVENTF_UNICODE, VIRTUAL_KEY,
 };
 
 #[cfg(windows)]
@@ -370,6 +370,41 @@ impl KeyboardSendInputBackend {
         Self::key_up_scancode(key.scancode())
     }
 
+    /// Type a string as Unicode characters.
+    ///
+    /// Uses `KEYEVENTF_UNICODE` instead of scancodes, so any character works
+    /// (accents, emoji) regardless of the keyboard layout. Some games that read
+    /// raw scancodes ignore this input; text fields and chat boxes accept it.
+    pub fn type_text(text: &str) -> Result<(), String> {
+        let inputs: Vec<INPUT> = text.encode_utf16()
+            .flat_map(|unit| [KEYEVENTF_UNICODE, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP].map(|flags| INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: VIRTUAL_KEY(0),
+                        wScan: unit,
+                        dwFlags: flags,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    },
+                },
+            }))
+            .collect();
+        if inputs.is_empty() {
+            return Ok(());
+        }
+        log::trace!("Type text: {:?} ({} inputs)", text, inputs.len());
+
+        // SAFETY: SendInput only reads the slice of fully initialized inputs.
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            use windows::Win32::Foundation::GetLastError;
+            let err = unsafe { GetLastError() };
+            return Err(format!("{}", windows::core::Error::from_hresult(err.to_hresult())));
+        }
+        Ok(())
+    }
+
     /// Low-level helper to send a single keyboard input using a hardware scancode.
     ///
     /// Flags should include `KEYEVENTF_SCANCODE` and optionally `KEYEVENTF_KEYUP`.
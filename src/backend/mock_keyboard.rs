@@ -1,19 +1,50 @@
 //! Mock keyboard backend for testing.
 //!
 //! This backend logs keyboard events instead of actually sending them
-//! to the OS. Useful for testing the manager and mapping logic without
+//! to the OS, and records them so tests can assert what was sent.
+//! Useful for testing the manager and mapping logic without
 //! requiring actual input injection.
 
 use log::info;
+use std::sync::{Arc, Mutex};
 
-/// Mock keyboard backend that logs events instead of sending them.
-#[derive(Clone, Copy, Debug)]
-pub struct MockKeyboardBackend;
+/// A `KeyboardBackend` call recorded by `MockKeyboardBackend`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyboardEvent {
+    KeyDown(String),
+    KeyUp(String),
+    KeyPress(String),
+    TypeText(String),
+}
+
+/// Mock keyboard backend that logs and records events instead of sending them.
+/// 
+/// Clones share one recording, so keep a clone to inspect what an executor
+/// or manager sent through its copy.
+#[derive(Clone, Debug)]
+pub struct MockKeyboardBackend {
+    events: Arc<Mutex<Vec<KeyboardEvent>>>,
+}
 
 impl MockKeyboardBackend {
     /// Create a new mock keyboard backend.
     pub fn new() -> Self {
-        Self
+        Self { events: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    /// Events sent through the `KeyboardBackend` trait so far, oldest first
+    pub fn events(&self) -> Vec<KeyboardEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Forget the recorded events
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    /// Record a trait call
+    pub(crate) fn record(&self, event: KeyboardEvent) {
+        self.events.lock().unwrap().push(event);
     }
 
     /// Press a key (logs to info level).
@@ -33,6 +64,12 @@ impl MockKeyboardBackend {
         info!("[MOCK KEYBOARD] Key PRESS: {}", key);
         Ok(())
     }
+
+    /// Type a string (logs to info level).
+    pub fn type_text(text: &str) -> Result<(), String> {
+        info!("[MOCK KEYBOARD] Type text: {:?}", text);
+        Ok(())
+    }
 }
 
 impl Default for MockKeyboardBackend {
@@ -43,7 +80,8 @@ impl Default for MockKeyboardBackend {
 
 #[cfg(test)]
 mod tests {
-    use super::MockKeyboardBackend;
+    use super::{KeyboardEvent, MockKeyboardBackend};
+    use crate::backend::KeyboardBackend;
 
     #[test]
     fn mock_keyboard_works() {
@@ -51,8 +89,30 @@ mod tests {
         assert!(MockKeyboardBackend::key_down("w").is_ok());
         assert!(MockKeyboardBackend::key_up("w").is_ok());
         assert!(MockKeyboardBackend::key_press("space").is_ok());
+        assert!(MockKeyboardBackend::type_text("gg wp 👍").is_ok());
         
         // Mock accepts any key name
         assert!(MockKeyboardBackend::key_down("invalid_key").is_ok());
     }
+
+    #[test]
+    fn mock_keyboard_records_events() {
+        let keyboard = MockKeyboardBackend::new();
+        let clone = keyboard.clone();
+        KeyboardBackend::key_down(&clone, "w").unwrap();
+        KeyboardBackend::key_up(&clone, "w").unwrap();
+        KeyboardBackend::key_press(&keyboard, "space").unwrap();
+        KeyboardBackend::type_text(&keyboard, "gg").unwrap();
+
+        // Clones share the recording
+        assert_eq!(keyboard.events(), vec![
+            KeyboardEvent::KeyDown("w".to_string()),
+            KeyboardEvent::KeyUp("w".to_string()),
+            KeyboardEvent::KeyPress("space".to_string()),
+            KeyboardEvent::TypeText("gg".to_string()),
+        ]);
+
+        clone.clear();
+        assert!(keyboard.events().is_empty());
+    }
 }
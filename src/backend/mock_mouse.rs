@@ -1,19 +1,65 @@
 //! Mock mouse backend for testing.
 //!
 //! This backend logs mouse events instead of actually sending them
-//! to the OS. Useful for testing the manager and mapping logic without
+//! to the OS, and records them so tests can assert what was sent.
+//! Useful for testing the manager and mapping logic without
 //! requiring actual input injection.
 
+use super::MouseButton;
 use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
-/// Mock mouse backend that logs events instead of sending them.
-#[derive(Clone, Copy, Debug)]
-pub struct MockMouseBackend;
+/// A `MouseBackend` call recorded by `MockMouseBackend`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseEvent {
+    Move { dx: i32, dy: i32 },
+    Click(MouseButton),
+    ButtonDown(MouseButton),
+    ButtonUp(MouseButton),
+    Scroll { dx: i32, dy: i32 },
+    WarpToCenter,
+}
+
+/// Mock mouse backend that logs and records events instead of sending them.
+/// 
+/// Clones share one recording, so keep a clone to inspect what an executor
+/// or manager sent through its copy.
+#[derive(Clone, Debug)]
+pub struct MockMouseBackend {
+    events: Arc<Mutex<Vec<MouseEvent>>>,
+    near_edge: Arc<AtomicBool>,
+}
 
 impl MockMouseBackend {
     /// Create a new mock mouse backend.
     pub fn new() -> Self {
-        Self
+        Self { events: Arc::new(Mutex::new(Vec::new())), near_edge: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Events sent through the `MouseBackend` trait so far, oldest first
+    pub fn events(&self) -> Vec<MouseEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Forget the recorded events
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    /// Make `cursor_near_edge` report the cursor at a screen edge (false by default)
+    pub fn set_near_edge(&self, near_edge: bool) {
+        self.near_edge.store(near_edge, Ordering::SeqCst);
+    }
+
+    /// What `cursor_near_edge` reports
+    pub(crate) fn near_edge(&self) -> bool {
+        self.near_edge.load(Ordering::SeqCst)
+    }
+
+    /// Record a trait call
+    pub(crate) fn record(&self, event: MouseEvent) {
+        self.events.lock().unwrap().push(event);
     }
 
     /// Move mouse relatively (logs to info level).
@@ -34,6 +80,20 @@ impl MockMouseBackend {
         Ok(())
     }
 
+    /// Scroll the wheel (logs to info level).
+    pub fn scroll(dx: i32, dy: i32) -> Result<(), String> {
+        info!("[MOCK MOUSE] Scroll: dx={}, dy={}", dx, dy);
+        Ok(())
+    }
+
+    /// Warp the cursor to the screen center (logs the target at info level).
+    /// 
+    /// Logs a nominal 1920x1080 center since there is no real screen to query.
+    pub fn warp_to_center() -> Result<(), String> {
+        info!("[MOCK MOUSE] Warp to center: x={}, y={}", 1920 / 2, 1080 / 2);
+        Ok(())
+    }
+
     /// Click a mouse button (logs to info level).
     pub fn button_click(button: &str) -> Result<(), String> {
         info!("[MOCK MOUSE] Button CLICK: {}", button);
@@ -49,7 +109,8 @@ impl Default for MockMouseBackend {
 
 #[cfg(test)]
 mod tests {
-    use super::MockMouseBackend;
+    use super::{MockMouseBackend, MouseEvent};
+    use crate::backend::{MouseBackend, MouseButton};
 
     #[test]
     fn mock_mouse_works() {
@@ -58,8 +119,35 @@ mod tests {
         assert!(MockMouseBackend::button_down("left").is_ok());
         assert!(MockMouseBackend::button_up("left").is_ok());
         assert!(MockMouseBackend::button_click("right").is_ok());
+        assert!(MockMouseBackend::scroll(0, 120).is_ok());
+        assert!(MockMouseBackend::warp_to_center().is_ok());
         
         // Mock accepts any button name
         assert!(MockMouseBackend::button_down("invalid_button").is_ok());
     }
+
+    #[test]
+    fn mock_mouse_records_events() {
+        let mouse = MockMouseBackend::new();
+        let clone = mouse.clone();
+        MouseBackend::move_relative(&clone, 10, -5).unwrap();
+        MouseBackend::button_down(&clone, MouseButton::Left).unwrap();
+        MouseBackend::button_up(&clone, MouseButton::Left).unwrap();
+        MouseBackend::click(&mouse, MouseButton::Right).unwrap();
+        MouseBackend::scroll(&mouse, 0, 120).unwrap();
+        MouseBackend::warp_to_center(&mouse).unwrap();
+
+        // Clones share the recording
+        assert_eq!(mouse.events(), vec![
+            MouseEvent::Move { dx: 10, dy: -5 },
+            MouseEvent::ButtonDown(MouseButton::Left),
+            MouseEvent::ButtonUp(MouseButton::Left),
+            MouseEvent::Click(MouseButton::Right),
+            MouseEvent::Scroll { dx: 0, dy: 120 },
+            MouseEvent::WarpToCenter,
+        ]);
+
+        clone.clear();
+        assert!(mouse.events().is_empty());
+    }
 }
@@ -1,20 +1,29 @@
-//! Backend abstraction for keyboard and mouse input injection
+//! Backend abstraction for keyboard, mouse and gamepad input injection
 //!
 //! This module provides a unified interface for sending keyboard and mouse
-//! events to the operating system.
+//! events to the operating system, and axis values to a virtual gamepad.
+//!
+//! Backends that own a device (e.g. a uinput or ViGEm virtual device) create it in
+//! `init` and remove it in `shutdown`. `JoyConManager::start` initializes the keyboard,
+//! mouse, then gamepad backend before any input is mapped, and `stop` shuts them down
+//! in reverse order once the executor has stopped. Both default to doing nothing.
 
 pub mod keyboard_sendinput;
 pub mod mouse_sendinput;
 pub mod mock_keyboard;
 pub mod mock_mouse;
+pub mod mock_gamepad;
+pub mod null;
 
 #[cfg(windows)]
 pub use keyboard_sendinput::{KeyboardSendInputBackend, AllowedKey};
 #[cfg(windows)]
 pub use mouse_sendinput::MouseSendInputBackend;
 
-pub use mock_keyboard::MockKeyboardBackend;
-pub use mock_mouse::MockMouseBackend;
+pub use mock_keyboard::{KeyboardEvent, MockKeyboardBackend};
+pub use mock_mouse::{MockMouseBackend, MouseEvent};
+pub use mock_gamepad::MockGamepadBackend;
+pub use null::NullBackend;
 
 use thiserror::Error;
 
@@ -44,6 +53,83 @@ pub trait KeyboardBackend {
         self.key_up(key)?;
         Ok(())
     }
+    
+    /// Type a string
+    /// 
+    /// The default taps each character's key on a US layout (with shift as needed)
+    /// and fails on the first character without one, see `char_key`.
+    fn type_text(&self, text: &str) -> Result<(), BackendError> {
+        for c in text.chars() {
+            let (key, shift) = char_key(c).ok_or_else(|| BackendError::UnsupportedKey(c.to_string()))?;
+            if shift {
+                self.key_down("shift")?;
+            }
+            let result = self.key_press(&key);
+            if shift {
+                self.key_up("shift")?;
+            }
+            result?;
+        }
+        Ok(())
+    }
+    
+    /// Whether this backend actually sends input (false for `NullBackend`)
+    fn is_available(&self) -> bool {
+        true
+    }
+    
+    /// Set up the backend before the first key is sent
+    fn init(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+    
+    /// Release the backend's resources after the last key was sent
+    fn shutdown(&mut self) {}
+}
+
+/// Key name and whether shift is needed to type a character on a US layout
+pub fn char_key(c: char) -> Option<(String, bool)> {
+    let key = match c {
+        'a'..='z' | '0'..='9' => return Some((c.to_string(), false)),
+        'A'..='Z' => return Some((c.to_ascii_lowercase().to_string(), true)),
+        ' ' => ("space", false),
+        '\n' => ("enter", false),
+        '\t' => ("tab", false),
+        '-' => ("minus", false),
+        '_' => ("minus", true),
+        '=' => ("equals", false),
+        '+' => ("equals", true),
+        '[' => ("leftbracket", false),
+        '{' => ("leftbracket", true),
+        ']' => ("rightbracket", false),
+        '}' => ("rightbracket", true),
+        ';' => ("semicolon", false),
+        ':' => ("semicolon", true),
+        '\'' => ("apostrophe", false),
+        '"' => ("apostrophe", true),
+        '`' => ("grave", false),
+        '~' => ("grave", true),
+        '\\' => ("backslash", false),
+        '|' => ("backslash", true),
+        ',' => ("comma", false),
+        '<' => ("comma", true),
+        '.' => ("period", false),
+        '>' => ("period", true),
+        '/' => ("slash", false),
+        '?' => ("slash", true),
+        '!' => ("1", true),
+        '@' => ("2", true),
+        '#' => ("3", true),
+        '$' => ("4", true),
+        '%' => ("5", true),
+        '^' => ("6", true),
+        '&' => ("7", true),
+        '*' => ("8", true),
+        '(' => ("9", true),
+        ')' => ("0", true),
+        _ => return None,
+    };
+    Some((key.0.to_string(), key.1))
 }
 
 /// Unified backend interface for mouse operations
@@ -59,15 +145,108 @@ pub trait MouseBackend {
     
     /// Release a mouse button (button up)
     fn button_up(&self, button: MouseButton) -> Result<(), BackendError>;
+    
+    /// Scroll the wheel by (dx, dy) in wheel units (120 = one notch), dy > 0 scrolls up
+    fn scroll(&self, _dx: i32, _dy: i32) -> Result<(), BackendError> {
+        Err(BackendError::Operation("Scrolling not supported by this backend".into()))
+    }
+    
+    /// Move the cursor to the center of the primary monitor
+    fn warp_to_center(&self) -> Result<(), BackendError> {
+        Ok(())
+    }
+    
+    /// Whether the cursor is within `margin` pixels of a screen edge (false if unknown)
+    fn cursor_near_edge(&self, _margin: i32) -> bool {
+        false
+    }
+    
+    /// Whether this backend actually sends input (false for `NullBackend`)
+    fn is_available(&self) -> bool {
+        true
+    }
+    
+    /// Set up the backend before the mouse is first used
+    fn init(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+    
+    /// Release the backend's resources after the mouse was last used
+    fn shutdown(&mut self) {}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
 }
 
+/// Unified backend interface for a virtual gamepad
+/// 
+/// Optional: the executor only drives one when it's set with `set_gamepad_backend`.
+/// Shared between threads, so implementations guard any device handle themselves.
+pub trait GamepadBackend: Send + Sync {
+    /// Set an axis, -1.0 to 1.0 for sticks (negative = left/up) and 0.0 to 1.0 for triggers
+    fn set_axis(&self, axis: GamepadAxis, value: f32) -> Result<(), BackendError>;
+    
+    /// Create the virtual device before the first axis is set (takes `&self` as the
+    /// backend is shared, like `set_axis`)
+    fn init(&self) -> Result<(), BackendError> {
+        Ok(())
+    }
+    
+    /// Remove the virtual device after the last axis was set
+    fn shutdown(&self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    /// Convert an axis value to the XInput range a virtual gamepad driver expects
+    /// 
+    /// Sticks map -1.0..1.0 to -32768..32767 with Y flipped (XInput Y is positive up),
+    /// triggers map 0.0..1.0 to 0..255. Out of range values are clamped.
+    pub fn xinput_value(&self, value: f32) -> i32 {
+        match self {
+            GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => {
+                (value.clamp(0.0, 1.0) * 255.0).round() as i32
+            }
+            GamepadAxis::LeftX | GamepadAxis::LeftY | GamepadAxis::RightX | GamepadAxis::RightY => {
+                let value = match self {
+                    GamepadAxis::LeftY | GamepadAxis::RightY => -value,
+                    _ => value,
+                }.clamp(-1.0, 1.0);
+                if value < 0.0 {
+                    (value * 32768.0).round() as i32
+                } else {
+                    (value * 32767.0).round() as i32
+                }
+            }
+        }
+    }
+    
+    /// Axis name (snake_case, as used in logs)
+    pub fn name(&self) -> &'static str {
+        match self {
+            GamepadAxis::LeftX => "left_x",
+            GamepadAxis::LeftY => "left_y",
+            GamepadAxis::RightX => "right_x",
+            GamepadAxis::RightY => "right_y",
+            GamepadAxis::LeftTrigger => "left_trigger",
+            GamepadAxis::RightTrigger => "right_trigger",
+        }
+    }
+}
+
 // Windows implementations
 #[cfg(windows)]
 impl KeyboardBackend for KeyboardSendInputBackend {
@@ -80,6 +259,11 @@ impl KeyboardBackend for KeyboardSendInputBackend {
         KeyboardSendInputBackend::key_up(key)
             .map_err(|e| BackendError::Operation(e))
     }
+    
+    fn type_text(&self, text: &str) -> Result<(), BackendError> {
+        KeyboardSendInputBackend::type_text(text)
+            .map_err(BackendError::Operation)
+    }
 }
 
 #[cfg(windows)]
@@ -114,33 +298,58 @@ impl MouseBackend for MouseSendInputBackend {
         MouseSendInputBackend::button_up(button_str)
             .map_err(|e| BackendError::Operation(e))
     }
+    
+    fn scroll(&self, dx: i32, dy: i32) -> Result<(), BackendError> {
+        MouseSendInputBackend::scroll(dx, dy)
+            .map_err(BackendError::Operation)
+    }
+    
+    fn warp_to_center(&self) -> Result<(), BackendError> {
+        MouseSendInputBackend::warp_to_center()
+            .map_err(BackendError::Operation)
+    }
+    
+    fn cursor_near_edge(&self, margin: i32) -> bool {
+        MouseSendInputBackend::cursor_near_edge(margin)
+    }
 }
 
 // Mock backend implementations
 impl KeyboardBackend for MockKeyboardBackend {
     fn key_down(&self, key: &str) -> Result<(), BackendError> {
+        self.record(KeyboardEvent::KeyDown(key.to_string()));
         MockKeyboardBackend::key_down(key)
             .map_err(|e| BackendError::Operation(e))
     }
     
     fn key_up(&self, key: &str) -> Result<(), BackendError> {
+        self.record(KeyboardEvent::KeyUp(key.to_string()));
         MockKeyboardBackend::key_up(key)
             .map_err(|e| BackendError::Operation(e))
     }
     
     fn key_press(&self, key: &str) -> Result<(), BackendError> {
+        self.record(KeyboardEvent::KeyPress(key.to_string()));
         MockKeyboardBackend::key_press(key)
             .map_err(|e| BackendError::Operation(e))
     }
+    
+    fn type_text(&self, text: &str) -> Result<(), BackendError> {
+        self.record(KeyboardEvent::TypeText(text.to_string()));
+        MockKeyboardBackend::type_text(text)
+            .map_err(BackendError::Operation)
+    }
 }
 
 impl MouseBackend for MockMouseBackend {
     fn move_relative(&self, dx: i32, dy: i32) -> Result<(), BackendError> {
+        self.record(MouseEvent::Move { dx, dy });
         MockMouseBackend::move_relative(dx, dy)
             .map_err(|e| BackendError::Operation(e))
     }
     
     fn click(&self, button: MouseButton) -> Result<(), BackendError> {
+        self.record(MouseEvent::Click(button));
         let button_str = match button {
             MouseButton::Left => "left",
             MouseButton::Right => "right",
@@ -151,6 +360,7 @@ impl MouseBackend for MockMouseBackend {
     }
     
     fn button_down(&self, button: MouseButton) -> Result<(), BackendError> {
+        self.record(MouseEvent::ButtonDown(button));
         let button_str = match button {
             MouseButton::Left => "left",
             MouseButton::Right => "right",
@@ -161,6 +371,7 @@ impl MouseBackend for MockMouseBackend {
     }
     
     fn button_up(&self, button: MouseButton) -> Result<(), BackendError> {
+        self.record(MouseEvent::ButtonUp(button));
         let button_str = match button {
             MouseButton::Left => "left",
             MouseButton::Right => "right",
@@ -169,36 +380,129 @@ impl MouseBackend for MockMouseBackend {
         MockMouseBackend::button_up(button_str)
             .map_err(|e| BackendError::Operation(e))
     }
+    
+    fn scroll(&self, dx: i32, dy: i32) -> Result<(), BackendError> {
+        self.record(MouseEvent::Scroll { dx, dy });
+        MockMouseBackend::scroll(dx, dy)
+            .map_err(BackendError::Operation)
+    }
+    
+    fn warp_to_center(&self) -> Result<(), BackendError> {
+        self.record(MouseEvent::WarpToCenter);
+        MockMouseBackend::warp_to_center()
+            .map_err(BackendError::Operation)
+    }
+    
+    fn cursor_near_edge(&self, _margin: i32) -> bool {
+        self.near_edge()
+    }
+}
+
+impl GamepadBackend for MockGamepadBackend {
+    fn set_axis(&self, axis: GamepadAxis, value: f32) -> Result<(), BackendError> {
+        MockGamepadBackend::set_axis(axis.name(), value)
+            .map_err(BackendError::Operation)
+    }
 }
 
 /// Get the default keyboard backend for the current platform
 #[cfg(windows)]
-pub fn get_keyboard_backend() -> impl KeyboardBackend {
+pub fn get_keyboard_backend() -> impl KeyboardBackend + Clone + Send {
     KeyboardSendInputBackend
 }
 
 /// Get the default mouse backend for the current platform
 #[cfg(windows)]
-pub fn get_mouse_backend() -> impl MouseBackend {
+pub fn get_mouse_backend() -> impl MouseBackend + Clone + Send {
     MouseSendInputBackend
 }
 
 /// Get a mock keyboard backend for testing
 pub fn get_mock_keyboard_backend() -> impl KeyboardBackend {
-    MockKeyboardBackend
+    MockKeyboardBackend::new()
 }
 
 /// Get a mock mouse backend for testing
 pub fn get_mock_mouse_backend() -> impl MouseBackend {
-    MockMouseBackend
+    MockMouseBackend::new()
 }
 
+/// Get the default keyboard backend for the current platform
+/// 
+/// No injection is implemented here yet, so this is a `NullBackend`.
 #[cfg(not(windows))]
-pub fn get_keyboard_backend() -> Result<(), BackendError> {
-    Err(BackendError::PlatformNotSupported)
+pub fn get_keyboard_backend() -> impl KeyboardBackend + Clone + Send {
+    NullBackend
 }
 
+/// Get the default mouse backend for the current platform
+/// 
+/// No injection is implemented here yet, so this is a `NullBackend`.
 #[cfg(not(windows))]
-pub fn get_mouse_backend() -> Result<(), BackendError> {
-    Err(BackendError::PlatformNotSupported)
+pub fn get_mouse_backend() -> impl MouseBackend + Clone + Send {
+    NullBackend
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{char_key, BackendError, GamepadAxis, KeyboardBackend};
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_xinput_value() {
+        assert_eq!(GamepadAxis::LeftX.xinput_value(1.0), 32767);
+        assert_eq!(GamepadAxis::LeftX.xinput_value(-1.0), -32768);
+        assert_eq!(GamepadAxis::LeftX.xinput_value(0.0), 0);
+        assert_eq!(GamepadAxis::RightX.xinput_value(2.0), 32767);
+        
+        // Up is negative here and positive in XInput
+        assert_eq!(GamepadAxis::LeftY.xinput_value(-1.0), 32767);
+        assert_eq!(GamepadAxis::RightY.xinput_value(0.5), -16384);
+        
+        assert_eq!(GamepadAxis::LeftTrigger.xinput_value(1.0), 255);
+        assert_eq!(GamepadAxis::RightTrigger.xinput_value(-0.5), 0);
+    }
+
+    #[test]
+    fn test_char_key() {
+        assert_eq!(char_key('a'), Some(("a".to_string(), false)));
+        assert_eq!(char_key('G'), Some(("g".to_string(), true)));
+        assert_eq!(char_key('7'), Some(("7".to_string(), false)));
+        assert_eq!(char_key('?'), Some(("slash".to_string(), true)));
+        assert_eq!(char_key(' '), Some(("space".to_string(), false)));
+        // Left to backends with Unicode input
+        assert_eq!(char_key('é'), None);
+    }
+
+    /// Keyboard that only records key_down/key_up, keeping the default type_text
+    #[derive(Default)]
+    struct KeyLog(RefCell<Vec<String>>);
+
+    impl KeyboardBackend for KeyLog {
+        fn key_down(&self, key: &str) -> Result<(), BackendError> {
+            self.0.borrow_mut().push(format!("down:{}", key));
+            Ok(())
+        }
+
+        fn key_up(&self, key: &str) -> Result<(), BackendError> {
+            self.0.borrow_mut().push(format!("up:{}", key));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_default_type_text() {
+        let keyboard = KeyLog::default();
+        keyboard.type_text("Hi!").unwrap();
+        assert_eq!(*keyboard.0.borrow(), vec![
+            "down:shift", "down:h", "up:h", "up:shift",
+            "down:i", "up:i",
+            "down:shift", "down:1", "up:1", "up:shift",
+        ]);
+
+        // Stops at the first character without a key
+        keyboard.0.borrow_mut().clear();
+        assert!(matches!(keyboard.type_text("aé"), Err(BackendError::UnsupportedKey(_))));
+        assert_eq!(*keyboard.0.borrow(), vec!["down:a", "up:a"]);
+    }
 }
@@ -11,7 +11,10 @@ use windows::Win32::UI::Input::KeyboardAndMouse::{
     SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEINPUT, MOUSE_EVENT_FLAGS, 
     MOUSEEVENTF_MOVE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
     MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP,
+    MOUSEEVENTF_WHEEL, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_ABSOLUTE,
 };
+#[cfg(windows)]
+use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
 
 #[cfg(windows)]
 #[derive(Clone, Copy, Debug)]
@@ -47,6 +50,51 @@ impl MouseSendInputBackend {
         }
     }
 
+    /// Move the cursor to the center of the primary monitor.
+    pub fn warp_to_center() -> Result<(), String> {
+        // SAFETY: Win32 call with no preconditions.
+        let (width, height) = unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+        if width <= 1 || height <= 1 {
+            return Err(format!("invalid screen size: {width}x{height}"));
+        }
+
+        // Absolute coordinates are normalized to 0..=65535 across the primary monitor
+        let mi = MOUSEINPUT {
+            dx: (width / 2) * 65535 / (width - 1),
+            dy: (height / 2) * 65535 / (height - 1),
+            mouseData: 0,
+            dwFlags: MOUSE_EVENT_FLAGS(MOUSEEVENTF_MOVE.0 | MOUSEEVENTF_ABSOLUTE.0),
+            time: 0,
+            dwExtraInfo: 0,
+        };
+
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 { mi },
+        };
+
+        // SAFETY: Win32 call; we pass a single INPUT struct slice.
+        let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+        if sent == 0 {
+            use windows::Win32::Foundation::GetLastError;
+            let err = unsafe { GetLastError() };
+            Err(format!("SendInput failed: 0x{:08X}", err.0))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether the cursor is within `margin` pixels of a primary monitor edge.
+    pub fn cursor_near_edge(margin: i32) -> bool {
+        let mut pos = Default::default();
+        // SAFETY: Win32 calls; `pos` is a valid POINT to write into.
+        if unsafe { GetCursorPos(&mut pos) }.is_err() {
+            return false;
+        }
+        let (width, height) = unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+        pos.x <= margin || pos.y <= margin || pos.x >= width - 1 - margin || pos.y >= height - 1 - margin
+    }
+
     /// Press a mouse button (button down event).
     pub fn button_down(button: &str) -> Result<(), String> {
         let flags = Self::parse_button_down_flag(button)?;
@@ -79,6 +127,44 @@ impl MouseSendInputBackend {
         }
     }
 
+    /// Scroll the wheel by (dx, dy) in wheel units (120 = one notch), dy > 0 scrolls up.
+    pub fn scroll(dx: i32, dy: i32) -> Result<(), String> {
+        if dy != 0 {
+            Self::send_wheel_event(MOUSEEVENTF_WHEEL, dy)?;
+        }
+        if dx != 0 {
+            Self::send_wheel_event(MOUSEEVENTF_HWHEEL, dx)?;
+        }
+        Ok(())
+    }
+
+    /// Send a single wheel event; the signed amount goes in mouseData.
+    fn send_wheel_event(flags: MOUSE_EVENT_FLAGS, amount: i32) -> Result<(), String> {
+        let mi = MOUSEINPUT {
+            dx: 0,
+            dy: 0,
+            mouseData: amount as u32,
+            dwFlags: flags,
+            time: 0,
+            dwExtraInfo: 0,
+        };
+
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 { mi },
+        };
+
+        // SAFETY: Win32 call; we pass a single INPUT struct slice.
+        let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+        if sent == 0 {
+            use windows::Win32::Foundation::GetLastError;
+            let err = unsafe { GetLastError() };
+            Err(format!("SendInput failed: 0x{:08X}", err.0))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Send a mouse button event.
     fn send_button_event(flags: MOUSE_EVENT_FLAGS) -> Result<(), String> {
         let mi = MOUSEINPUT {
@@ -0,0 +1,76 @@
+//! Null backend for platforms without input injection.
+//!
+//! Every operation fails with `BackendError::PlatformNotSupported`, so the
+//! scanning and parsing parts of the crate still run (e.g. for development on
+//! Linux/macOS) while nothing is sent to the OS.
+
+use super::{BackendError, GamepadAxis, GamepadBackend, KeyboardBackend, MouseBackend, MouseButton};
+
+/// Backend that rejects every keyboard, mouse and gamepad operation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullBackend;
+
+impl KeyboardBackend for NullBackend {
+    fn key_down(&self, _key: &str) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn key_up(&self, _key: &str) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn is_available(&self) -> bool {
+        false
+    }
+}
+
+impl MouseBackend for NullBackend {
+    fn move_relative(&self, _dx: i32, _dy: i32) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn click(&self, _button: MouseButton) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn button_down(&self, _button: MouseButton) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn button_up(&self, _button: MouseButton) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn scroll(&self, _dx: i32, _dy: i32) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn warp_to_center(&self) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+
+    fn is_available(&self) -> bool {
+        false
+    }
+}
+
+impl GamepadBackend for NullBackend {
+    fn set_axis(&self, _axis: GamepadAxis, _value: f32) -> Result<(), BackendError> {
+        Err(BackendError::PlatformNotSupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_backend_rejects_everything() {
+        let backend = NullBackend;
+        assert!(matches!(backend.key_press("a"), Err(BackendError::PlatformNotSupported)));
+        assert!(matches!(backend.click(MouseButton::Left), Err(BackendError::PlatformNotSupported)));
+        assert!(matches!(backend.set_axis(GamepadAxis::LeftX, 0.0), Err(BackendError::PlatformNotSupported)));
+        assert!(!KeyboardBackend::is_available(&backend));
+        assert!(!MouseBackend::is_available(&backend));
+    }
+}
@@ -0,0 +1,358 @@
+//! Text control server for automation tools
+//!
+//! `JoyConManager::start_control_server` listens on a TCP address and accepts one
+//! command per line, answering each with one line that starts with `ok` or `error`:
+//!
+//! - `status`: `ok left=connected right=disconnected paused=false gyro_left=off gyro_right=on sensitivity=1.0 profile=ETS2`
+//! - `set-profile <name>`: switch to the profile with that name (may contain spaces)
+//! - `toggle-gyro <left|right>`: toggle the gyro mouse of that side
+//! - `pause`, `resume`, `toggle-pause`: pause or resume input injection
+//!
+//! Commands are applied by the executor thread, so `status` can take up to 16ms
+//! to reflect a change. A line longer than `MAX_LINE` bytes is answered with
+//! `error line too long` and the connection is closed.
+//!
+//! There is no authentication, so the server should only listen on a loopback address.
+
+use crate::manager::ManagerState;
+use crate::mapping::config::ControllerSide;
+use crossbeam_channel::{Sender, TrySendError};
+use log::{debug, info, warn};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+
+/// How often the server checks for new connections and for the manager stopping
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest accepted protocol line in bytes, newline included
+pub const MAX_LINE: usize = 1024;
+
+/// Runtime change applied by the executor thread
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    SetProfile(String),
+    ToggleGyroMouse(ControllerSide),
+    SetPaused(bool),
+}
+
+/// One line of the control protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    Status,
+    SetProfile(String),
+    ToggleGyro(ControllerSide),
+    Pause,
+    Resume,
+    TogglePause,
+}
+
+/// Why a control request failed, sent back as `error <message>`
+#[derive(Debug, Error, PartialEq)]
+pub enum ControlError {
+    #[error("empty command")]
+    Empty,
+
+    #[error("unknown command '{0}'")]
+    UnknownCommand(String),
+
+    #[error("'{0}' needs an argument")]
+    MissingArgument(&'static str),
+
+    #[error("unknown side '{0}', expected left or right")]
+    UnknownSide(String),
+
+    #[error("unknown profile '{0}'")]
+    UnknownProfile(String),
+
+    #[error("line too long")]
+    LineTooLong,
+
+    #[error("busy, try again")]
+    Busy,
+
+    #[error("manager is not running")]
+    NotRunning,
+}
+
+impl Request {
+    /// Parse a protocol line (surrounding whitespace is ignored)
+    pub fn parse(line: &str) -> Result<Self, ControlError> {
+        let line = line.trim();
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+        let argument = |name: &'static str| {
+            if argument.is_empty() {
+                Err(ControlError::MissingArgument(name))
+            } else {
+                Ok(argument)
+            }
+        };
+
+        match command.to_lowercase().as_str() {
+            "" => Err(ControlError::Empty),
+            "status" => Ok(Request::Status),
+            "set-profile" => Ok(Request::SetProfile(argument("set-profile")?.to_string())),
+            "toggle-gyro" => match argument("toggle-gyro")?.to_lowercase().as_str() {
+                "left" => Ok(Request::ToggleGyro(ControllerSide::Left)),
+                "right" => Ok(Request::ToggleGyro(ControllerSide::Right)),
+                other => Err(ControlError::UnknownSide(other.to_string())),
+            },
+            "pause" => Ok(Request::Pause),
+            "resume" => Ok(Request::Resume),
+            "toggle-pause" => Ok(Request::TogglePause),
+            _ => Err(ControlError::UnknownCommand(command.to_string())),
+        }
+    }
+}
+
+/// Cloneable access to the manager's runtime controls (see `JoyConManager::control_handle`)
+#[derive(Debug, Clone)]
+pub struct ControlHandle {
+    pub(crate) sender: Sender<ControlCommand>,
+    pub(crate) state: Arc<RwLock<ManagerState>>,
+    pub(crate) profiles: Vec<String>,
+    pub(crate) running: Arc<AtomicBool>,
+}
+
+impl ControlHandle {
+    /// Switch to the profile with the given name
+    pub fn set_profile(&self, name: &str) -> Result<(), ControlError> {
+        if !self.profiles.iter().any(|p| p == name) {
+            return Err(ControlError::UnknownProfile(name.to_string()));
+        }
+        self.send(ControlCommand::SetProfile(name.to_string()))
+    }
+
+    /// Toggle the gyro mouse of `side`
+    pub fn toggle_gyro_mouse(&self, side: ControllerSide) -> Result<(), ControlError> {
+        self.send(ControlCommand::ToggleGyroMouse(side))
+    }
+
+    /// Pause or resume input injection
+    pub fn set_paused(&self, paused: bool) -> Result<(), ControlError> {
+        self.send(ControlCommand::SetPaused(paused))
+    }
+
+    fn send(&self, command: ControlCommand) -> Result<(), ControlError> {
+        if !self.running.load(Ordering::SeqCst) {
+            return Err(ControlError::NotRunning);
+        }
+        match self.sender.try_send(command) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(ControlError::Busy),
+            Err(TrySendError::Disconnected(_)) => Err(ControlError::NotRunning),
+        }
+    }
+
+    /// Handle one protocol line and return the response line (without newline)
+    pub fn handle_line(&self, line: &str) -> String {
+        let result = Request::parse(line).and_then(|request| match request {
+            Request::Status => Ok(Some(self.status())),
+            Request::SetProfile(name) => self.set_profile(&name).map(|_| None),
+            Request::ToggleGyro(side) => self.toggle_gyro_mouse(side).map(|_| None),
+            Request::Pause => self.set_paused(true).map(|_| None),
+            Request::Resume => self.set_paused(false).map(|_| None),
+            Request::TogglePause => {
+                let paused = self.state.read().unwrap().paused;
+                self.set_paused(!paused).map(|_| None)
+            }
+        });
+
+        match result {
+            Ok(Some(status)) => format!("ok {}", status),
+            Ok(None) => "ok".to_string(),
+            Err(e) => format!("error {}", e),
+        }
+    }
+
+    /// Status line fields, the profile last as its name may contain spaces
+    fn status(&self) -> String {
+        let state = self.state.read().unwrap();
+        let connection = |connected: bool| if connected { "connected" } else { "disconnected" };
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        format!(
+            "left={} right={} paused={} gyro_left={} gyro_right={} sensitivity={:.1} profile={}",
+            connection(state.left.connected),
+            connection(state.right.connected),
+            state.paused,
+            on_off(state.gyro_left_enabled),
+            on_off(state.gyro_right_enabled),
+            state.sensitivity,
+            state.profile_name,
+        )
+    }
+}
+
+/// Accept connections on `listener` until the handle's manager stops
+///
+/// Each connection gets its own thread, so a client that keeps its connection open
+/// doesn't block others.
+pub(crate) fn serve(listener: TcpListener, handle: ControlHandle) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    thread::Builder::new()
+        .name("control-server".to_string())
+        .spawn(move || {
+            while handle.running.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, peer)) => {
+                        debug!("Control connection from {}", peer);
+                        let handle = handle.clone();
+                        let spawned = thread::Builder::new()
+                            .name("control-client".to_string())
+                            .spawn(move || {
+                                if let Err(e) = handle_client(stream, &handle) {
+                                    debug!("Control connection from {} closed: {}", peer, e);
+                                }
+                            });
+                        if let Err(e) = spawned {
+                            warn!("Failed to spawn control connection thread: {}", e);
+                        }
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        warn!("Control server accept failed: {}", e);
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+            info!("Control server stopped");
+        })?;
+    Ok(())
+}
+
+/// Answer each line from `stream` until it closes or the manager stops
+fn handle_client(stream: TcpStream, handle: &ControlHandle) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    while handle.running.load(Ordering::SeqCst) {
+        let limit = MAX_LINE.saturating_sub(line.len()) as u64;
+        match (&mut reader).take(limit).read_line(&mut line) {
+            Ok(0) if limit > 0 => break,
+            // Hit the limit without a newline, there is no telling where the next line starts
+            Ok(_) if !line.ends_with('\n') && line.len() >= MAX_LINE => {
+                debug!("Control: closing connection after a line over {} bytes", MAX_LINE);
+                writeln!(writer, "error {}", ControlError::LineTooLong)?;
+                break;
+            }
+            Ok(_) => {
+                let response = handle.handle_line(&line);
+                debug!("Control: {} -> {}", line.trim(), response);
+                writeln!(writer, "{}", response)?;
+                line.clear();
+            }
+            // A partial line stays in `line` until the rest arrives
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::builder::{ConfigBuilder, ProfileBuilder};
+    use crossbeam_channel::{bounded, Receiver};
+
+    fn test_handle() -> (ControlHandle, Receiver<ControlCommand>) {
+        let config = ConfigBuilder::new().default_profile("Euro Truck").profile(ProfileBuilder::new("Euro Truck")).build().unwrap();
+        let (sender, receiver) = bounded(10);
+        let handle = ControlHandle {
+            sender,
+            state: Arc::new(RwLock::new(ManagerState::new(&config))),
+            profiles: config.profiles.iter().map(|p| p.name.clone()).collect(),
+            running: Arc::new(AtomicBool::new(true)),
+        };
+        (handle, receiver)
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        assert_eq!(Request::parse("status\n"), Ok(Request::Status));
+        assert_eq!(Request::parse("  set-profile  Euro Truck \r\n"), Ok(Request::SetProfile("Euro Truck".to_string())));
+        assert_eq!(Request::parse("TOGGLE-GYRO Right"), Ok(Request::ToggleGyro(ControllerSide::Right)));
+        assert_eq!(Request::parse("toggle-gyro up"), Err(ControlError::UnknownSide("up".to_string())));
+        assert_eq!(Request::parse("set-profile"), Err(ControlError::MissingArgument("set-profile")));
+        assert_eq!(Request::parse("  "), Err(ControlError::Empty));
+        assert_eq!(Request::parse("reboot now"), Err(ControlError::UnknownCommand("reboot".to_string())));
+    }
+
+    #[test]
+    fn test_handle_line_routes_commands() {
+        let (handle, receiver) = test_handle();
+
+        assert_eq!(handle.handle_line("set-profile Euro Truck"), "ok");
+        assert_eq!(receiver.try_recv(), Ok(ControlCommand::SetProfile("Euro Truck".to_string())));
+        assert_eq!(handle.handle_line("set-profile Nope"), "error unknown profile 'Nope'");
+        assert!(receiver.try_recv().is_err());
+
+        assert_eq!(handle.handle_line("toggle-gyro left"), "ok");
+        assert_eq!(receiver.try_recv(), Ok(ControlCommand::ToggleGyroMouse(ControllerSide::Left)));
+
+        handle.state.write().unwrap().paused = true;
+        assert_eq!(handle.handle_line("toggle-pause"), "ok");
+        assert_eq!(receiver.try_recv(), Ok(ControlCommand::SetPaused(false)));
+
+        assert_eq!(
+            handle.handle_line("status"),
+            "ok left=disconnected right=disconnected paused=true gyro_left=off gyro_right=off sensitivity=1.0 profile=Euro Truck"
+        );
+
+        handle.running.store(false, Ordering::SeqCst);
+        assert_eq!(handle.handle_line("pause"), "error manager is not running");
+    }
+
+    #[test]
+    fn test_serve_over_tcp() {
+        let (handle, receiver) = test_handle();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve(listener, handle.clone()).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response = String::new();
+
+        stream.write_all(b"pause\nbogus\n").unwrap();
+        reader.read_line(&mut response).unwrap();
+        reader.read_line(&mut response).unwrap();
+        assert_eq!(response, "ok\nerror unknown command 'bogus'\n");
+        assert_eq!(receiver.try_recv(), Ok(ControlCommand::SetPaused(true)));
+
+        handle.running.store(false, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_serve_rejects_long_line() {
+        let (handle, receiver) = test_handle();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve(listener, handle.clone()).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response = String::new();
+
+        stream.write_all(b"pause\n").unwrap();
+        stream.write_all(&[b'x'; MAX_LINE]).unwrap();
+        reader.read_to_string(&mut response).unwrap();
+        assert_eq!(response, "ok\nerror line too long\n");
+        assert_eq!(receiver.try_recv(), Ok(ControlCommand::SetPaused(true)));
+        assert!(receiver.try_recv().is_err());
+
+        handle.running.store(false, Ordering::SeqCst);
+    }
+}
@@ -3,23 +3,110 @@
 //! This module handles the Bluetooth connection to the Joy-Con controllers,
 //! including pairing, input reporting, and disconnection.
 
-use btleplug::api::{Central, Characteristic, Manager as _, Peripheral as _, WriteType};
-use btleplug::platform::{Manager, Peripheral};
-use futures::stream::StreamExt;
+use btleplug::api::{BDAddr, Central, Characteristic, Manager as _, Peripheral as _, WriteType};
+use btleplug::platform::{Adapter, Manager, Peripheral};
+use futures::stream::{Stream, StreamExt};
 use log::{debug, info};
-use std::error::Error;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use thiserror::Error;
 use tokio::time::sleep;
 
 use crate::joycon2::constants::*;
+use crate::joycon2::controller::{ControllerInput, ReportKind};
+use crate::joycon2::types::{DeviceInfo, RumblePattern};
 
 /// Controller side/type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Side {
     Left,
     Right,
 }
 
+/// Errors that can occur while scanning, connecting or talking to a Joy-Con
+#[derive(Debug, Error)]
+pub enum JoyConError {
+    #[error("No Bluetooth adapter found")]
+    NoAdapter,
+    
+    #[error("No Joy-Con controller found")]
+    ControllerNotFound,
+    
+    #[error("Failed to connect to Joy-Con: {0}")]
+    ConnectFailed(#[source] btleplug::Error),
+    
+    #[error("Failed to find required characteristics")]
+    CharacteristicsMissing,
+    
+    #[error("Not connected (CMD characteristic not found)")]
+    NotConnected,
+    
+    #[error("Controller {0} already connected to another side")]
+    AlreadyConnected(String),
+    
+    #[error("Timed out connecting to Joy-Con")]
+    Timeout,
+    
+    #[error("Invalid MAC address '{0}' (expected AA:BB:CC:DD:EE:FF)")]
+    InvalidAddress(String),
+    
+    #[error("Controller {0} is not known to the Bluetooth adapter")]
+    UnknownAddress(String),
+    
+    #[error("Bluetooth error: {0}")]
+    Bluetooth(#[from] btleplug::Error),
+}
+
+/// `JoyConError::Timeout` for btleplug's own timeout, otherwise `wrap(error)`
+fn timed_out_or(error: btleplug::Error, wrap: fn(btleplug::Error) -> JoyConError) -> JoyConError {
+    match error {
+        btleplug::Error::TimedOut(_) => JoyConError::Timeout,
+        error => wrap(error),
+    }
+}
+
+/// Rumble pulses waiting to be sent, as (due time, vibration preset) in due order
+#[derive(Debug, Default)]
+pub(crate) struct RumbleQueue {
+    pulses: VecDeque<(Instant, u8)>,
+}
+
+impl RumbleQueue {
+    /// Replace the queued pulses with `pulses` (offset from `start`, vibration preset)
+    pub(crate) fn play(&mut self, pulses: Vec<(Duration, u8)>, start: Instant) {
+        self.pulses = pulses.into_iter()
+            .map(|(offset, preset)| (start + offset, preset))
+            .collect();
+    }
+    
+    /// Remove and return the presets of the pulses due at `now`
+    pub(crate) fn take_due(&mut self, now: Instant) -> Vec<u8> {
+        let mut due = Vec::new();
+        while let Some(&(at, preset)) = self.pulses.front() {
+            if at > now {
+                break;
+            }
+            self.pulses.pop_front();
+            due.push(preset);
+        }
+        due
+    }
+    
+    /// Whether pulses are still waiting
+    pub(crate) fn is_active(&self) -> bool {
+        !self.pulses.is_empty()
+    }
+}
+
+/// Pulses of a rumble of one vibration preset (`intensity`) repeated until `duration` has
+/// elapsed, one per `COMMAND_DELAY_MS` slot like `RumblePattern::pulses`
+pub fn rumble_pulses(intensity: u8, duration: Duration) -> Vec<(Duration, u8)> {
+    let pulses = (duration.as_millis() as u64 / COMMAND_DELAY_MS).max(1);
+    (0..pulses)
+        .map(|slot| (Duration::from_millis(slot * COMMAND_DELAY_MS), intensity))
+        .collect()
+}
+
 /// Connection state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConnectionState {
@@ -42,6 +129,18 @@ pub struct JoyConConnection {
     
     // Optional MAC address for pairing (Joy-Con 2 specific)
     mac_address: Option<[u8; 6]>,
+    
+    // Player LED pattern last sent (re-sent as keepalive)
+    player_led: u8,
+    
+    // Serial/firmware read during initialization
+    device_info: DeviceInfo,
+    
+    // Pending rumble pulses, sent by `poll_rumble`
+    rumble_queue: RumbleQueue,
+    
+    // Play the connection vibration during initialization
+    vibration_enabled: bool,
 }
 
 impl JoyConConnection {
@@ -55,9 +154,18 @@ impl JoyConConnection {
             cmd_char: None,
             cmd_response_char: None,
             mac_address: None,
+            player_led: 0x01,
+            device_info: DeviceInfo::default(),
+            rumble_queue: RumbleQueue::default(),
+            vibration_enabled: true,
         }
     }
     
+    /// Enable or disable the vibration played while initializing (enabled by default)
+    pub fn set_vibration_enabled(&mut self, enabled: bool) {
+        self.vibration_enabled = enabled;
+    }
+    
     /// Set MAC address for pairing (Joy-Con 2 specific, optional)
     pub fn set_mac_address(&mut self, mac_address: [u8; 6]) {
         self.mac_address = Some(mac_address);
@@ -67,14 +175,14 @@ impl JoyConConnection {
     /// 
     /// This will only return a controller that matches the requested side,
     /// preventing race conditions where multiple threads try to connect to the same controller.
-    pub async fn scan(expected_side: Side) -> Result<Peripheral, Box<dyn Error>> {
+    pub async fn scan(expected_side: Side) -> Result<Peripheral, JoyConError> {
         info!("Scanning for Joy-Con controllers...");
         
         let manager = Manager::new().await?;
         let adapters = manager.adapters().await?;
         
         if adapters.is_empty() {
-            return Err("No Bluetooth adapters found".into());
+            return Err(JoyConError::NoAdapter);
         }
         
         let adapter = adapters.into_iter().next().unwrap();
@@ -84,12 +192,6 @@ impl JoyConConnection {
         
         let mut events = adapter.events().await?;
         
-        // Expected byte value for the requested side
-        let expected_byte = match expected_side {
-            Side::Left => 0x67,
-            Side::Right => 0x66,
-        };
-        
         // Scan for Joy-Con controller
         while let Some(event) = events.next().await {
             if let btleplug::api::CentralEvent::ManufacturerDataAdvertisement {
@@ -97,62 +199,66 @@ impl JoyConConnection {
                 manufacturer_data,
             } = event
             {
-                // Check if manufacturer data contains Nintendo company ID
-                if let Some(data) = manufacturer_data.get(&NINTENDO_COMPANY_ID) {
-                    // Check if data starts with Joy-Con prefix
-                    if data.len() >= JOYCON_DATA_PREFIX.len()
-                        && data[..JOYCON_DATA_PREFIX.len()] == JOYCON_DATA_PREFIX
-                    {
-                        // Check if this is the correct side (byte 5)
-                        if data.len() >= 6 {
-                            let side_byte = data[5];
-                            
-                            // Only accept controllers that match the expected side
-                            if side_byte != expected_byte {
-                                debug!("Skipping controller with side byte 0x{:02x} (expected 0x{:02x})", side_byte, expected_byte);
-                                continue;
-                            }
-                            
-                            let peripheral = adapter.peripheral(&id).await?;
-                            let properties = peripheral.properties().await?.unwrap();
-                            let address = properties.address;
-                            let name = properties.local_name.unwrap_or_else(|| "Unknown".to_string());
-                            
-                            let detected_side_name = match side_byte {
-                                0x67 => "Left (0x67)",
-                                0x66 => "Right (0x66)",
-                                0x73 => "GCCon (0x73)",
-                                byte => {
-                                    debug!("Unknown device type: 0x{:02x}", byte);
-                                    "Unknown"
-                                }
-                            };
-                            
-                            info!("✓ Controller found!");
-                            info!("  Address: {}", address);
-                            info!("  Name: {}", name);
-                            info!("  Detected side: {}", detected_side_name);
-                            
-                            adapter.stop_scan().await?;
-                            return Ok(peripheral);
-                        }
-                    }
+                // Only accept Joy-Con 2 controllers that match the expected side
+                let Some(side) = manufacturer_data.get(&NINTENDO_COMPANY_ID).and_then(|data| detect_side(data)) else {
+                    continue;
+                };
+                if side != expected_side {
+                    debug!("Skipping {:?} controller (expected {:?})", side, expected_side);
+                    continue;
                 }
+                
+                let peripheral = adapter.peripheral(&id).await?;
+                let properties = peripheral.properties().await?.unwrap();
+                let address = properties.address;
+                let name = properties.local_name.unwrap_or_else(|| "Unknown".to_string());
+                
+                info!("✓ Controller found!");
+                info!("  Address: {}", address);
+                info!("  Name: {}", name);
+                info!("  Detected side: {:?}", side);
+                
+                adapter.stop_scan().await?;
+                return Ok(peripheral);
             }
         }
         
-        Err("No Joy-Con controller found".into())
+        Err(JoyConError::ControllerNotFound)
+    }
+    
+    /// Connect to a controller the adapter already knows, by MAC address, without scanning
+    /// 
+    /// `address` uses the "AA:BB:CC:DD:EE:FF" format stored in the MAC cache.
+    /// The returned connection still needs `initialize()`.
+    pub async fn connect_by_address(adapter: &Adapter, address: &str, side: Side) -> Result<Self, JoyConError> {
+        let address = BDAddr::from(parse_mac_address(address)?);
+        
+        let peripheral = adapter.peripherals().await?
+            .into_iter()
+            .find(|p| p.address() == address)
+            .ok_or_else(|| JoyConError::UnknownAddress(address.to_string()))?;
+        
+        info!("Connecting to {:?} Joy-Con at {}", side, address);
+        let mut connection = Self::new(peripheral, side);
+        connection.connect().await?;
+        Ok(connection)
     }
     
     /// Connect to the Joy-Con
-    pub async fn connect(&mut self) -> Result<(), Box<dyn Error>> {
+    pub async fn connect(&mut self) -> Result<(), JoyConError> {
         self.state = ConnectionState::Connecting;
         
+        let timeout = Duration::from_millis(CONNECT_TIMEOUT_MS);
+        
         info!("Connecting to Joy-Con...");
-        self.peripheral.connect().await?;
+        tokio::time::timeout(timeout, self.peripheral.connect()).await
+            .map_err(|_| JoyConError::Timeout)?
+            .map_err(|e| timed_out_or(e, JoyConError::ConnectFailed))?;
         
         info!("Discovering services...");
-        self.peripheral.discover_services().await?;
+        tokio::time::timeout(timeout, self.peripheral.discover_services()).await
+            .map_err(|_| JoyConError::Timeout)?
+            .map_err(|e| timed_out_or(e, JoyConError::Bluetooth))?;
         
         // Find the Joy-Con 2 characteristics
         let characteristics = self.peripheral.characteristics();
@@ -171,7 +277,7 @@ impl JoyConConnection {
         }
         
         if self.tx_char.is_none() || self.cmd_char.is_none() || self.cmd_response_char.is_none() {
-            return Err("Failed to find required characteristics".into());
+            return Err(JoyConError::CharacteristicsMissing);
         }
         
         info!("✓ Connected successfully!");
@@ -180,7 +286,7 @@ impl JoyConConnection {
     
     /// Initialize the Joy-Con (handshake process)
     /// This sends initialization commands and sets up the controller for data streaming
-    pub async fn initialize(&mut self) -> Result<(), Box<dyn Error>> {
+    pub async fn initialize(&mut self) -> Result<(), JoyConError> {
         self.state = ConnectionState::Initializing;
         
         info!("Initializing Joy-Con...");
@@ -200,6 +306,13 @@ impl JoyConConnection {
             debug!("Subscribed to TX notifications (input data)");
         }
         
+        self.device_info = self.read_device_info().await;
+        if self.device_info.is_empty() {
+            debug!("Controller does not expose device info");
+        } else {
+            info!("  Device info: {}", self.device_info);
+        }
+        
         self.state = ConnectionState::Ready;
         info!("✓ Joy-Con initialized and ready!");
         
@@ -207,7 +320,7 @@ impl JoyConConnection {
     }
     
     /// Send initialization commands to the controller
-    async fn send_initialization_commands(&mut self) -> Result<(), Box<dyn Error>> {
+    async fn send_initialization_commands(&mut self) -> Result<(), JoyConError> {
         // Joy-Con 2 specific initialization sequence
         // Based on Joy2Win Python implementation
         
@@ -218,14 +331,16 @@ impl JoyConConnection {
         }
         
         // 1. Connection vibration (feedback to user)
-        info!("  Sending connection vibration...");
-        self.send_connection_vibration().await?;
+        if self.vibration_enabled {
+            info!("  Sending connection vibration...");
+            self.send_connection_vibration().await?;
+        } else {
+            info!("  Skipping connection vibration (vibration disabled)");
+        }
         
         // 2. Set player LED (default: LED 1 only)
         info!("  Setting player LED...");
-        let mut led_command = JOY2_SET_PLAYER_LED_TEMPLATE;
-        led_command[JOY2_LED_VALUE_INDEX] = 0x01;  // LED 1 only
-        self.send_command(&led_command, true).await?;
+        self.set_player_led(self.player_led).await?;
         
         // 3. Initialize sensor data (IMU step 1)
         info!("  Initializing sensor data...");
@@ -244,14 +359,99 @@ impl JoyConConnection {
         Ok(())
     }
     
+    /// Read the serial number and firmware version from the Device Information characteristics
+    /// 
+    /// Missing characteristics and failed reads leave the field None.
+    async fn read_device_info(&self) -> DeviceInfo {
+        let mut info = DeviceInfo::default();
+        
+        for char in self.peripheral.characteristics() {
+            let field = if char.uuid == SERIAL_NUMBER_CHARACTERISTIC_UUID {
+                &mut info.serial_number
+            } else if char.uuid == FIRMWARE_REVISION_CHARACTERISTIC_UUID {
+                &mut info.firmware_version
+            } else {
+                continue;
+            };
+            
+            match self.peripheral.read(&char).await {
+                Ok(data) => *field = parse_info_string(&data),
+                Err(e) => debug!("Failed to read device info characteristic {}: {}", char.uuid, e),
+            }
+        }
+        
+        info
+    }
+    
     /// Send connection vibration (user feedback)
-    async fn send_connection_vibration(&mut self) -> Result<(), Box<dyn Error>> {
+    async fn send_connection_vibration(&mut self) -> Result<(), JoyConError> {
         self.send_command(JOY2_CONNECTED_VIBRATION, true).await
     }
     
+    /// Play a rumble pulse and wait until it finished
+    /// 
+    /// Joy-Con 2 only exposes fixed vibration presets, so `intensity` selects the preset
+    /// and the preset is repeated until `duration` has elapsed. Input isn't read meanwhile
+    /// when called from the task reading notifications, use `play_rumble` there.
+    pub async fn rumble(&mut self, intensity: u8, duration: Duration) -> Result<(), JoyConError> {
+        let mut command = JOY2_VIBRATION_TEMPLATE;
+        command[JOY2_VIBRATION_PRESET_INDEX] = intensity;
+        
+        for _ in rumble_pulses(intensity, duration) {
+            self.send_command(&command, true).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Start a rumble pulse like `rumble`, replacing any rumble still playing
+    /// 
+    /// Doesn't wait: the pulses are sent by `poll_rumble` as they come due.
+    pub fn play_rumble(&mut self, intensity: u8, duration: Duration) {
+        self.rumble_queue.play(rumble_pulses(intensity, duration), Instant::now());
+    }
+    
+    /// Start a rumble pattern, replacing any rumble still playing
+    /// 
+    /// Doesn't wait: the pulses are sent by `poll_rumble` as they come due.
+    pub fn play_rumble_pattern(&mut self, pattern: RumblePattern) {
+        self.rumble_queue.play(pattern.pulses(), Instant::now());
+    }
+    
+    /// Whether a rumble started by `play_rumble`/`play_rumble_pattern` still has pulses to send
+    pub fn is_rumbling(&self) -> bool {
+        self.rumble_queue.is_active()
+    }
+    
+    /// Send the rumble pulses that are due (call this regularly from the controller task)
+    pub async fn poll_rumble(&mut self) -> Result<(), JoyConError> {
+        for preset in self.rumble_queue.take_due(Instant::now()) {
+            let mut command = JOY2_VIBRATION_TEMPLATE;
+            command[JOY2_VIBRATION_PRESET_INDEX] = preset;
+            self.send_command(&command, false).await?;
+        }
+        Ok(())
+    }
+    
+    /// Set the player LEDs (bit 0 = LED 1 ... bit 3 = LED 4)
+    pub async fn set_player_led(&mut self, leds: u8) -> Result<(), JoyConError> {
+        let mut command = JOY2_SET_PLAYER_LED_TEMPLATE;
+        command[JOY2_LED_VALUE_INDEX] = leds;
+        self.send_command(&command, true).await?;
+        self.player_led = leds;
+        Ok(())
+    }
+    
+    /// Send a harmless command (the current player LEDs) so idle links aren't dropped
+    pub async fn keepalive(&mut self) -> Result<(), JoyConError> {
+        let mut command = JOY2_SET_PLAYER_LED_TEMPLATE;
+        command[JOY2_LED_VALUE_INDEX] = self.player_led;
+        self.send_command(&command, false).await
+    }
+    
     /// Save MAC address for pairing (Joy-Con 2 specific)
     /// This allows the Joy-Con 2 to pair with a Nintendo Switch
-    async fn save_mac_address(&mut self, mac_addr: [u8; 6]) -> Result<(), Box<dyn Error>> {
+    async fn save_mac_address(&mut self, mac_addr: [u8; 6]) -> Result<(), JoyConError> {
         // Calculate the two MAC addresses needed
         // mac_addr1 = original MAC address
         // mac_addr2 = first byte - 1, rest stays the same
@@ -278,7 +478,7 @@ impl JoyConConnection {
     }
     
     /// Send a command to the controller (Joy-Con 2 specific format)
-    async fn send_command(&mut self, data: &[u8], wait_response: bool) -> Result<(), Box<dyn Error>> {
+    async fn send_command(&mut self, data: &[u8], wait_response: bool) -> Result<(), JoyConError> {
         if let Some(cmd_char) = &self.cmd_char {
             debug!("Sending command: {} bytes", data.len());
             
@@ -292,12 +492,12 @@ impl JoyConConnection {
             
             Ok(())
         } else {
-            Err("CMD characteristic not found".into())
+            Err(JoyConError::NotConnected)
         }
     }
     
     /// Disconnect from the Joy-Con
-    pub async fn disconnect(&mut self) -> Result<(), Box<dyn Error>> {
+    pub async fn disconnect(&mut self) -> Result<(), JoyConError> {
         info!("Disconnecting from Joy-Con...");
         
         // Unsubscribe from notifications
@@ -321,7 +521,7 @@ impl JoyConConnection {
     }
     
     /// Check if connected
-    pub async fn is_connected(&self) -> Result<bool, Box<dyn Error>> {
+    pub async fn is_connected(&self) -> Result<bool, JoyConError> {
         Ok(self.peripheral.is_connected().await?)
     }
     
@@ -335,34 +535,85 @@ impl JoyConConnection {
         self.side
     }
     
-    /// Detect controller side from manufacturer data
+    /// Serial number and firmware version read during `initialize()`
+    pub fn device_info(&self) -> &DeviceInfo {
+        &self.device_info
+    }
+    
+    /// Stream of parsed controller states, one per input report
     /// 
-    /// Byte 5 (index 5) of the manufacturer data indicates the device type:
-    /// - 0x67 = Left Joy-Con
-    /// - 0x66 = Right Joy-Con
-    /// - 0x73 = GameCube Controller
+    /// Takes ownership of the connection so it stays alive as long as the stream.
+    /// Command responses and short or unknown reports are skipped.
+    pub async fn input_stream(self) -> Result<impl Stream<Item = ControllerInput> + Send + Unpin, JoyConError> {
+        let notifications = self.peripheral.notifications().await?;
+        let mut state = ControllerInput::new(self.side);
+        let connection = self;
+        
+        Ok(notifications.filter_map(move |notification| {
+            let _keep_alive = &connection;
+            let parsed = ReportKind::classify(notification.uuid, &notification.value) == ReportKind::Input
+                && state.update(&notification.value);
+            futures::future::ready(parsed.then(|| state.clone()))
+        }))
+    }
+    
+    /// Detect controller side from manufacturer data (see `detect_side`)
     pub async fn detect_side_from_manufacturer_data(&self) -> Option<Side> {
         let properties = self.peripheral.properties().await.ok()??;
-        
-        if let Some(manufacturer_data) = properties.manufacturer_data.get(&NINTENDO_COMPANY_ID) {
-            if manufacturer_data.len() >= 6 {
-                let byte5 = manufacturer_data[5];
-                match byte5 {
-                    0x67 => Some(Side::Left),
-                    0x66 => Some(Side::Right),
-                    _ => None,
-                }
-            } else {
-                None
-            }
-        } else {
+        properties.manufacturer_data.get(&NINTENDO_COMPANY_ID).and_then(|data| detect_side(data))
+    }
+}
+
+/// Detect the Joy-Con side from Nintendo manufacturer data
+/// 
+/// The data must start with `JOYCON_DATA_PREFIX`; the byte at `DEVICE_TYPE_INDEX`
+/// tells the device type. Other devices (e.g. the GameCube controller) have no side.
+pub fn detect_side(data: &[u8]) -> Option<Side> {
+    if !data.starts_with(&JOYCON_DATA_PREFIX) {
+        return None;
+    }
+    match *data.get(DEVICE_TYPE_INDEX)? {
+        DEVICE_TYPE_LEFT => Some(Side::Left),
+        DEVICE_TYPE_RIGHT => Some(Side::Right),
+        DEVICE_TYPE_GCCON => {
+            debug!("Ignoring GameCube controller (not supported)");
+            None
+        }
+        byte => {
+            debug!("Unknown device type: 0x{:02x}", byte);
             None
         }
     }
 }
 
+/// Parse a Device Information string value (UTF-8, possibly NUL padded)
+/// 
+/// Returns None for empty values.
+pub fn parse_info_string(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Parse a MAC address in "AA:BB:CC:DD:EE:FF" format (as stored in the MAC cache)
+pub fn parse_mac_address(address: &str) -> Result<[u8; 6], JoyConError> {
+    let invalid = || JoyConError::InvalidAddress(address.to_string());
+    
+    let mut bytes = [0u8; 6];
+    let mut parts = address.trim().split(':');
+    for byte in bytes.iter_mut() {
+        let part = parts.next().filter(|p| p.len() == 2).ok_or_else(invalid)?;
+        *byte = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+    }
+    
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(bytes)
+}
+
 /// Initialize a controller (combines scan, connect, and initialize)
-pub async fn init_controller(side: Side) -> Result<JoyConConnection, Box<dyn Error>> {
+pub async fn init_controller(side: Side) -> Result<JoyConConnection, JoyConError> {
     info!("Scanning for Joy-Con {}, press the sync button...", match side {
         Side::Left => "Left",
         Side::Right => "Right",
@@ -382,3 +633,76 @@ pub async fn init_controller(side: Side) -> Result<JoyConConnection, Box<dyn Err
     
     Ok(connection)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_timed_out_or() {
+        let timed_out = timed_out_or(btleplug::Error::TimedOut(Duration::from_secs(1)), JoyConError::ConnectFailed);
+        assert!(matches!(timed_out, JoyConError::Timeout));
+        let failed = timed_out_or(btleplug::Error::DeviceNotFound, JoyConError::ConnectFailed);
+        assert!(matches!(failed, JoyConError::ConnectFailed(btleplug::Error::DeviceNotFound)));
+    }
+    
+    #[test]
+    fn test_parse_mac_address() {
+        assert_eq!(
+            parse_mac_address("98:B6:E9:0A:1b:ff").unwrap(),
+            [0x98, 0xB6, 0xE9, 0x0A, 0x1B, 0xFF]
+        );
+        
+        for bad in ["", "98:B6:E9:0A:1B", "98:B6:E9:0A:1B:FF:00", "98-B6-E9-0A-1B-FF", "98:B6:E9:0A:1B:GG", "8:B6:E9:0A:1B:FF"] {
+            assert!(matches!(parse_mac_address(bad), Err(JoyConError::InvalidAddress(_))), "{}", bad);
+        }
+    }
+    
+    #[test]
+    fn test_detect_side() {
+        let data = |device_type: u8| {
+            let mut data = JOYCON_DATA_PREFIX.to_vec();
+            data.push(device_type);
+            data
+        };
+        
+        assert_eq!(detect_side(&data(DEVICE_TYPE_LEFT)), Some(Side::Left));
+        assert_eq!(detect_side(&data(DEVICE_TYPE_RIGHT)), Some(Side::Right));
+        assert_eq!(detect_side(&data(DEVICE_TYPE_GCCON)), None);
+        assert_eq!(detect_side(&data(0x00)), None);
+        
+        // Missing type byte or foreign prefix
+        assert_eq!(detect_side(&JOYCON_DATA_PREFIX), None);
+        assert_eq!(detect_side(&[0x02, 0x00, 0x03, 0x7e, 0x05, DEVICE_TYPE_LEFT]), None);
+    }
+    
+    #[test]
+    fn test_rumble_pulses() {
+        let pulses = rumble_pulses(3, Duration::from_millis(200));
+        assert_eq!(pulses.len(), 4);
+        assert!(pulses.iter().all(|(_, preset)| *preset == 3));
+        assert_eq!(pulses[1].0, Duration::from_millis(COMMAND_DELAY_MS));
+        
+        // Shorter than one slot still pulses once
+        assert_eq!(rumble_pulses(1, Duration::ZERO), vec![(Duration::ZERO, 1)]);
+        
+        let start = Instant::now();
+        let mut queue = RumbleQueue::default();
+        queue.play(pulses, start);
+        assert_eq!(queue.take_due(start), vec![3]);
+        assert!(queue.is_active());
+        assert_eq!(queue.take_due(start + Duration::from_secs(1)), vec![3, 3, 3]);
+        assert!(!queue.is_active());
+    }
+    
+    #[test]
+    fn test_parse_info_string() {
+        assert_eq!(parse_info_string(b"1.2.3\0\0"), Some("1.2.3".to_string()));
+        assert_eq!(parse_info_string(b" XKJ10012345678 "), Some("XKJ10012345678".to_string()));
+        assert_eq!(parse_info_string(b"\0\0"), None);
+        assert_eq!(parse_info_string(b""), None);
+        
+        let info = DeviceInfo { serial_number: None, firmware_version: Some("1.2.3".to_string()) };
+        assert_eq!(info.to_string(), "v1.2.3");
+    }
+}
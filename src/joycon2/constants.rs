@@ -18,6 +18,18 @@ pub const NINTENDO_COMPANY_ID: u16 = 0x0553;
 /// Manufacturer data prefix for Joy-Con 2 controllers
 pub const JOYCON_DATA_PREFIX: [u8; 5] = [0x01, 0x00, 0x03, 0x7e, 0x05];
 
+/// Index of the device type byte in the manufacturer data
+pub const DEVICE_TYPE_INDEX: usize = 5;
+
+/// Device type byte of a Left Joy-Con 2
+pub const DEVICE_TYPE_LEFT: u8 = 0x67;
+
+/// Device type byte of a Right Joy-Con 2
+pub const DEVICE_TYPE_RIGHT: u8 = 0x66;
+
+/// Device type byte of a Switch 2 GameCube controller (not supported yet)
+pub const DEVICE_TYPE_GCCON: u8 = 0x73;
+
 // ============================================================================
 // BLE Service & Characteristic UUIDs
 // ============================================================================
@@ -37,6 +49,10 @@ pub const CMD_CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0x649d4ac9_8eb7_4e6c_a
 /// This is the NOTIFY characteristic we subscribe to for command responses
 pub const CMD_RESPONSE_CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0xc765a961_d9d8_4d36_a20a_5315b111836a);
 
+/// Standard BLE Device Information characteristics, read when the controller exposes them
+pub const SERIAL_NUMBER_CHARACTERISTIC_UUID: Uuid = btleplug::api::bleuuid::uuid_from_u16(0x2A25);
+pub const FIRMWARE_REVISION_CHARACTERISTIC_UUID: Uuid = btleplug::api::bleuuid::uuid_from_u16(0x2A26);
+
 // ============================================================================
 // Joy-Con 2 Specific Commands (hex format)
 // ============================================================================
@@ -44,6 +60,11 @@ pub const CMD_RESPONSE_CHARACTERISTIC_UUID: Uuid = Uuid::from_u128(0xc765a961_d9
 /// Connected vibration command (feedback when controller connects)
 pub const JOY2_CONNECTED_VIBRATION: &[u8] = &[0x0A, 0x91, 0x01, 0x02, 0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00];
 
+/// Vibration command template (same format as the connected vibration)
+/// Byte 8 (index 8) selects the vibration preset (0x03 = connection pulse)
+pub const JOY2_VIBRATION_TEMPLATE: [u8; 12] = [0x0A, 0x91, 0x01, 0x02, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+pub const JOY2_VIBRATION_PRESET_INDEX: usize = 8;
+
 /// Set player LED command (complete 16-byte command)
 /// Byte 8 (index 8) is the LED value: 1=LED1, 2=LED2, 4=LED3, 8=LED4, combinations possible
 /// Format: 09 91 00 07 00 08 00 00 0X 00 00 00 00 00 00 00
@@ -72,9 +93,27 @@ pub const JOY2_SAVE_MAC_ADDR_STEP3: &[u8] = &[0x15, 0x91, 0x01, 0x02, 0x00, 0x11
 /// Save MAC address step 4
 pub const JOY2_SAVE_MAC_ADDR_STEP4: &[u8] = &[0x15, 0x91, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00];
 
+// ============================================================================
+// Input Reports
+// ============================================================================
+
+/// Minimum length of the standard input report (buttons, sticks, mouse, IMU)
+/// It has no report ID, the first four bytes are the timestamp
+pub const INPUT_REPORT_LEN: usize = 0x3C;
+
+/// Length of the command header (command ID, direction, subcommand, payload length)
+/// that a command response echoes before its own data
+pub const COMMAND_HEADER_LEN: usize = 8;
+
+/// Direction byte (index 1) of a command response, requests carry 0x91 there
+pub const COMMAND_RESPONSE_MARKER: u8 = 0x01;
+
 // ============================================================================
 // Timing Constants
 // ============================================================================
 
 /// Delay between commands (milliseconds)
 pub const COMMAND_DELAY_MS: u64 = 50;
+
+/// Time allowed for connecting and for discovering services (milliseconds)
+pub const CONNECT_TIMEOUT_MS: u64 = 10_000;
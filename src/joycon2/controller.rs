@@ -3,7 +3,126 @@
 //! This module handles the input processing and state management for the
 //! Joy-Con controllers, including button mapping and stick input.
 
+use crate::joycon2::connection::Side;
+use crate::joycon2::constants::{CMD_RESPONSE_CHARACTERISTIC_UUID, COMMAND_HEADER_LEN, COMMAND_RESPONSE_MARKER, INPUT_REPORT_LEN, TX_CHARACTERISTIC_UUID};
 use crate::joycon2::types::{Accelerometer, Buttons, Gyroscope, Stick};
+use crate::mapping::config::ButtonType;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kind of a BLE notification received from the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    /// Standard input report (buttons, sticks, mouse, IMU)
+    Input,
+    /// Acknowledgement of the command with this ID (e.g. 0x09 for the player LEDs)
+    CommandResponse { command: u8 },
+    /// Unknown characteristic or report type, or a report too short for its layout
+    Unknown,
+}
+
+impl ReportKind {
+    /// Classify a notification by the characteristic it came from and its report type
+    /// 
+    /// Input reports carry no ID byte (they start with the timestamp), so only their
+    /// length is checked. Command responses start with the ID of the command they
+    /// answer followed by `COMMAND_RESPONSE_MARKER`, and must hold the full header.
+    pub fn classify(characteristic: Uuid, data: &[u8]) -> Self {
+        if characteristic == TX_CHARACTERISTIC_UUID && data.len() >= INPUT_REPORT_LEN {
+            Self::Input
+        } else if characteristic == CMD_RESPONSE_CHARACTERISTIC_UUID
+            && data.len() >= COMMAND_HEADER_LEN
+            && data[1] == COMMAND_RESPONSE_MARKER
+        {
+            Self::CommandResponse { command: data[0] }
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// Parsed state of either controller (yielded by `JoyConConnection::input_stream`)
+#[derive(Debug, Clone)]
+pub enum ControllerInput {
+    Left(Joy2L),
+    Right(Joy2R),
+}
+
+impl ControllerInput {
+    /// Fresh state for a controller side
+    pub fn new(side: Side) -> Self {
+        match side {
+            Side::Left => Self::Left(Joy2L::new()),
+            Side::Right => Self::Right(Joy2R::new()),
+        }
+    }
+    
+    /// Parse an input report, returns false if the report was too short
+    pub fn update(&mut self, data: &[u8]) -> bool {
+        match self {
+            Self::Left(controller) => controller.update(data),
+            Self::Right(controller) => controller.update(data),
+        }
+    }
+    
+    /// Controller side of this state
+    pub fn side(&self) -> Side {
+        match self {
+            Self::Left(_) => Side::Left,
+            Self::Right(_) => Side::Right,
+        }
+    }
+    
+    /// The side-independent view of the state
+    pub fn state(&self) -> &dyn ControllerState {
+        match self {
+            Self::Left(controller) => controller,
+            Self::Right(controller) => controller,
+        }
+    }
+}
+
+/// Parsed controller state, independent of the controller side
+pub trait ControllerState: Send {
+    /// Update the state from an input report, returns false if the report was ignored
+    fn update(&mut self, data: &[u8]) -> bool;
+    
+    /// Current state of every button, paired with its mapping button type
+    fn button_states(&self) -> Vec<(ButtonType, bool)>;
+    
+    /// Convert to generic Buttons struct (for mapping)
+    fn to_buttons(&self) -> Buttons;
+    
+    /// Analog stick (mapped for upright usage)
+    fn analog_stick(&self) -> Stick;
+    
+    /// Trigger value (ZL on the left controller, ZR on the right), 0.0 to 1.0
+    fn trigger(&self) -> f32;
+    
+    /// Gyroscope data
+    fn gyroscope(&self) -> Gyroscope;
+    
+    /// Accelerometer data
+    fn accelerometer(&self) -> Accelerometer;
+    
+    /// Optical mouse sensor data
+    fn mouse(&self) -> &MouseData;
+    
+    /// Battery level (0.0 to 100.0)
+    fn battery_level(&self) -> f32;
+    
+    /// Raw 12-bit stick position (x, y) of the last report, before calibration
+    fn raw_stick(&self) -> (u16, u16);
+    
+    /// Calibration currently used to normalize the stick
+    fn stick_calibration(&self) -> StickCalibration;
+    
+    /// Calibration used to normalize the stick from now on
+    fn set_stick_calibration(&mut self, calibration: StickCalibration);
+    
+    /// Print controller status (for debugging)
+    fn print_status(&self);
+}
 
 /// Orientation of the controller
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,9 +139,42 @@ impl Default for Orientation {
     }
 }
 
-/// Joy-Con 2 Left controller state
+/// What differs between the Left and Right controllers: report offsets, buttons and stick orientation
+pub trait SideLayout {
+    /// Button states of this side
+    type Buttons: std::fmt::Debug + Clone + Default + Send;
+    
+    /// Side name, for status output and alerts
+    const SIDE: &'static str;
+    
+    /// Offset of the two button bytes (big-endian) in the input report
+    const BUTTON_OFFSET: usize;
+    
+    /// Offset of the three stick bytes in the input report
+    const STICK_OFFSET: usize;
+    
+    /// Decode the button bytes
+    fn decode_buttons(bits: u16) -> Self::Buttons;
+    
+    /// Current state of every button, paired with its mapping button type
+    fn button_states(buttons: &Self::Buttons) -> Vec<(ButtonType, bool)>;
+    
+    /// Convert to generic Buttons struct (for mapping)
+    fn to_buttons(buttons: &Self::Buttons) -> Buttons;
+    
+    /// The shoulder button and trigger (L/ZL or R/ZR), also used as left/right mouse buttons
+    fn shoulders(buttons: &Self::Buttons) -> (bool, bool);
+    
+    /// Turn a normalized stick position for the horizontal orientation
+    fn horizontal_stick(x: f32, y: f32) -> (f32, f32);
+    
+    /// Print the button lines of the status
+    fn print_buttons(buttons: &Self::Buttons);
+}
+
+/// Joy-Con 2 controller state, `Joy2L` or `Joy2R` depending on the layout
 #[derive(Debug, Clone)]
-pub struct Joy2L {
+pub struct JoyCon2<L: SideLayout> {
     /// Controller name
     pub name: String,
     
@@ -36,11 +188,14 @@ pub struct Joy2L {
     pub mac_address: String,
     
     /// Button states (mapped for upright usage)
-    pub buttons: LeftButtons,
+    pub buttons: L::Buttons,
     
     /// Analog stick (mapped for upright usage)
     pub analog_stick: Stick,
     
+    /// Trigger value, ZL or ZR (0.0 to 1.0)
+    pub trigger: f32,
+    
     /// Accelerometer data
     pub accelerometer: Accelerometer,
     
@@ -67,8 +222,20 @@ pub struct Joy2L {
     
     /// Connection status
     pub is_connected: bool,
+    
+    /// Raw 12-bit stick position (x, y) of the last report
+    pub raw_stick: (u16, u16),
+    
+    /// Calibration used to normalize the stick
+    pub stick_calibration: StickCalibration,
 }
 
+/// Joy-Con 2 Left controller state
+pub type Joy2L = JoyCon2<LeftLayout>;
+
+/// Joy-Con 2 Right controller state
+pub type Joy2R = JoyCon2<RightLayout>;
+
 /// Left Joy-Con specific buttons
 #[derive(Debug, Clone, Default)]
 pub struct LeftButtons {
@@ -85,6 +252,115 @@ pub struct LeftButtons {
     pub capture: bool,
 }
 
+impl LeftButtons {
+    /// Current state of every button, paired with its mapping button type
+    pub fn states(&self) -> Vec<(ButtonType, bool)> {
+        vec![
+            (ButtonType::ZL, self.zl),
+            (ButtonType::L, self.l),
+            (ButtonType::Minus, self.minus),
+            (ButtonType::Capture, self.capture),
+            (ButtonType::LeftStickClick, self.l3),
+            (ButtonType::DpadUp, self.up),
+            (ButtonType::DpadDown, self.down),
+            (ButtonType::DpadLeft, self.left),
+            (ButtonType::DpadRight, self.right),
+            (ButtonType::SLL, self.sll),
+            (ButtonType::SRL, self.srl),
+        ]
+    }
+    
+    /// Buttons that changed since `prev`, as (button, pressed) transitions
+    pub fn changed_since(&self, prev: &LeftButtons) -> Vec<(ButtonType, bool)> {
+        diff_states(self.states(), prev.states())
+    }
+}
+
+/// Report layout of the Left Joy-Con 2
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeftLayout;
+
+impl SideLayout for LeftLayout {
+    type Buttons = LeftButtons;
+    
+    const SIDE: &'static str = "Left";
+    const BUTTON_OFFSET: usize = 5;
+    const STICK_OFFSET: usize = 10;
+    
+    fn decode_buttons(bits: u16) -> LeftButtons {
+        LeftButtons {
+            sll: (bits & 0x0020) != 0,
+            srl: (bits & 0x0010) != 0,
+            minus: (bits & 0x0100) != 0,
+            l: (bits & 0x0040) != 0,
+            zl: (bits & 0x0080) != 0,
+            left: (bits & 0x0008) != 0,
+            down: (bits & 0x0001) != 0,
+            up: (bits & 0x0002) != 0,
+            right: (bits & 0x0004) != 0,
+            l3: (bits & 0x0800) != 0,
+            capture: (bits & 0x2000) != 0,
+        }
+    }
+    
+    fn button_states(buttons: &LeftButtons) -> Vec<(ButtonType, bool)> {
+        buttons.states()
+    }
+    
+    fn to_buttons(buttons: &LeftButtons) -> Buttons {
+        Buttons {
+            l: buttons.l,
+            zl: buttons.zl,
+            minus: buttons.minus,
+            capture: buttons.capture,
+            left_stick_click: buttons.l3,
+            dpad_up: buttons.up,
+            dpad_down: buttons.down,
+            dpad_left: buttons.left,
+            dpad_right: buttons.right,
+            ..Buttons::default()
+        }
+    }
+    
+    fn shoulders(buttons: &LeftButtons) -> (bool, bool) {
+        (buttons.l, buttons.zl)
+    }
+    
+    fn horizontal_stick(x: f32, y: f32) -> (f32, f32) {
+        (y, x)
+    }
+    
+    fn print_buttons(buttons: &LeftButtons) {
+        println!("  Buttons: ZL={}, L={}, Minus={}, Capture={}, L3={}", 
+            buttons.zl, buttons.l, buttons.minus, buttons.capture, buttons.l3);
+        println!("  D-Pad: U={}, D={}, L={}, R={}", 
+            buttons.up, buttons.down, buttons.left, buttons.right);
+    }
+}
+
+/// Compare two button state lists (same order) and return the transitions
+pub fn diff_states(current: Vec<(ButtonType, bool)>, prev: Vec<(ButtonType, bool)>) -> Vec<(ButtonType, bool)> {
+    current
+        .into_iter()
+        .zip(prev)
+        .filter(|((_, now), (_, before))| now != before)
+        .map(|((button, now), _)| (button, now))
+        .collect()
+}
+
+/// Swap A with B and X with Y (Xbox layout: the bottom face button reports as A)
+pub fn swap_face_buttons(states: &mut [(ButtonType, bool)]) {
+    for (button, _) in states {
+        *button = match *button {
+            ButtonType::A => ButtonType::B,
+            ButtonType::B => ButtonType::A,
+            ButtonType::X => ButtonType::Y,
+            ButtonType::Y => ButtonType::X,
+            other => other,
+        };
+    }
+}
+
 /// Mouse data from Joy-Con 2
 #[derive(Debug, Clone, Default)]
 pub struct MouseData {
@@ -102,13 +378,16 @@ pub struct MouseButtons {
     pub scroll_y: i16,
 }
 
-/// Stick calibration values
-#[derive(Debug, Clone, Copy)]
+/// Stick calibration values (raw 12-bit readings)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StickCalibration {
     pub x_min: u16,
     pub x_max: u16,
     pub y_min: u16,
     pub y_max: u16,
+    /// Reading at rest, maps to 0.0 (min and max map to -1.0 and 1.0)
+    pub x_center: u16,
+    pub y_center: u16,
 }
 
 impl Default for StickCalibration {
@@ -119,19 +398,81 @@ impl Default for StickCalibration {
             x_max: 3260,
             y_min: 820,
             y_max: 3250,
+            x_center: (780 + 3260) / 2,
+            y_center: (820 + 3250) / 2,
+        }
+    }
+}
+
+impl StickCalibration {
+    /// Smallest distance (raw counts) from the center to each extreme a calibration accepts
+    pub const MIN_RANGE: u16 = 500;
+    
+    /// Build a calibration from readings at rest (averaged into the center) and while
+    /// rotating the stick fully (their extremes become min/max)
+    /// 
+    /// None when either set is empty or the stick didn't move at least `MIN_RANGE`
+    /// from the center in every direction.
+    pub fn from_samples(rest: &[(u16, u16)], rotation: &[(u16, u16)]) -> Option<Self> {
+        if rest.is_empty() || rotation.is_empty() {
+            return None;
         }
+        let average = |axis: fn(&(u16, u16)) -> u16| {
+            (rest.iter().map(|s| axis(s) as u32).sum::<u32>() / rest.len() as u32) as u16
+        };
+        let calibration = Self {
+            x_min: rotation.iter().map(|s| s.0).min()?,
+            x_max: rotation.iter().map(|s| s.0).max()?,
+            y_min: rotation.iter().map(|s| s.1).min()?,
+            y_max: rotation.iter().map(|s| s.1).max()?,
+            x_center: average(|s| s.0),
+            y_center: average(|s| s.1),
+        };
+        let covers = |min: u16, center: u16, max: u16| {
+            center >= min.saturating_add(Self::MIN_RANGE) && max >= center.saturating_add(Self::MIN_RANGE)
+        };
+        (covers(calibration.x_min, calibration.x_center, calibration.x_max)
+            && covers(calibration.y_min, calibration.y_center, calibration.y_max))
+            .then_some(calibration)
+    }
+    
+    /// Normalize raw readings to -1.0..1.0 (x right, y up)
+    pub fn normalize(&self, x_raw: u16, y_raw: u16) -> (f32, f32) {
+        // Raw Y grows downwards
+        (normalize_axis(x_raw, self.x_min, self.x_center, self.x_max),
+            -normalize_axis(y_raw, self.y_min, self.y_center, self.y_max))
+    }
+}
+
+/// Map a raw axis reading to -1.0..1.0, with each side of the center scaled separately
+fn normalize_axis(raw: u16, min: u16, center: u16, max: u16) -> f32 {
+    if raw < center {
+        -((center - raw) as f32 / center.saturating_sub(min).max(1) as f32).min(1.0)
+    } else {
+        ((raw - center) as f32 / max.saturating_sub(center).max(1) as f32).min(1.0)
     }
 }
 
-impl Default for Joy2L {
+/// Raw 12-bit stick readings (x, y) from the 3 stick bytes of a report
+fn decode_raw_stick(data: &[u8]) -> Option<(u16, u16)> {
+    if data.len() != 3 {
+        return None;
+    }
+    let x_raw = ((data[1] as u16 & 0x0F) << 8) | (data[0] as u16);
+    let y_raw = ((data[2] as u16) << 4) | ((data[1] as u16 & 0xF0) >> 4);
+    Some((x_raw, y_raw))
+}
+
+impl<L: SideLayout> Default for JoyCon2<L> {
     fn default() -> Self {
         Self {
             name: "Joy-Con".to_string(),
-            side: "Left".to_string(),
+            side: L::SIDE.to_string(),
             orientation: Orientation::default(),
             mac_address: String::new(),
-            buttons: LeftButtons::default(),
+            buttons: L::Buttons::default(),
             analog_stick: Stick::default(),
+            trigger: 0.0,
             accelerometer: Accelerometer::default(),
             gyroscope: Gyroscope::default(),
             mouse: MouseData::default(),
@@ -141,12 +482,14 @@ impl Default for Joy2L {
             battery_level: 100.0,
             alert_sent: false,
             is_connected: false,
+            raw_stick: (0, 0),
+            stick_calibration: StickCalibration::default(),
         }
     }
 }
 
-impl Joy2L {
-    /// Create a new Joy-Con 2 Left controller
+impl<L: SideLayout> JoyCon2<L> {
+    /// Create a new Joy-Con 2 controller
     pub fn new() -> Self {
         Self::default()
     }
@@ -156,22 +499,22 @@ impl Joy2L {
         self.mac_address = mac_address;
     }
     
-    /// Update controller state from BLE data
-    pub fn update(&mut self, data: &[u8]) {
-        self.parse_input_report(data);
+    /// Update controller state from BLE data, returns false if the report was ignored
+    pub fn update(&mut self, data: &[u8]) -> bool {
+        self.parse_input_report(data)
     }
     
     /// Parse input report data
-    fn parse_input_report(&mut self, data: &[u8]) {
-        if data.len() < 0x3C {
-            return; // Not enough data
+    fn parse_input_report(&mut self, data: &[u8]) -> bool {
+        if data.len() < INPUT_REPORT_LEN {
+            return false; // Not enough data
         }
         
-        // Parse button data (bytes 5-6)
-        let btn_data = ((data[5] as u16) << 8) | (data[6] as u16);
+        // Parse button data (bytes 5-6 on the left Joy-Con, 4-5 on the right)
+        let btn_data = ((data[L::BUTTON_OFFSET] as u16) << 8) | (data[L::BUTTON_OFFSET + 1] as u16);
         
-        // Parse joystick data (bytes 10-12)
-        let joystick_data = &data[10..13];
+        // Parse joystick data (bytes 10-12 on the left Joy-Con, 13-15 on the right)
+        let joystick_data = &data[L::STICK_OFFSET..L::STICK_OFFSET + 3];
         
         // Parse mouse data (bytes 16-23)
         if data.len() >= 24 {
@@ -193,192 +536,61 @@ impl Joy2L {
             ]);
         }
         
-        // Parse accelerometer (bytes 0x30-0x35)
-        if data.len() >= 0x36 {
-            let accel_x_raw = i16::from_le_bytes([data[0x30], data[0x31]]);
-            let accel_y_raw = i16::from_le_bytes([data[0x32], data[0x33]]);
-            let accel_z_raw = i16::from_le_bytes([data[0x34], data[0x35]]);
-            
-            let accel_factor = 1.0 / 4096.0; // 1G = 4096
-            
-            self.accelerometer.x = -(accel_x_raw as f32) * accel_factor;
-            self.accelerometer.y = -(accel_z_raw as f32) * accel_factor;
-            self.accelerometer.z = (accel_y_raw as f32) * accel_factor;
-        }
-        
-        // Parse gyroscope (bytes 0x36-0x3B)
-        if data.len() >= 0x3C {
-            let gyro_x_raw = i16::from_le_bytes([data[0x36], data[0x37]]);
-            let gyro_y_raw = i16::from_le_bytes([data[0x38], data[0x39]]);
-            let gyro_z_raw = i16::from_le_bytes([data[0x3A], data[0x3B]]);
-            
-            let gyro_factor = 360.0 / 6048.0; // 360° = 6048
-            
-            self.gyroscope.x = (gyro_x_raw as f32) * gyro_factor; // Pitch
-            self.gyroscope.y = -(gyro_z_raw as f32) * gyro_factor; // Roll
-            self.gyroscope.z = (gyro_y_raw as f32) * gyro_factor; // Yaw
-        }
+        // Parse accelerometer (bytes 0x30-0x35) and gyroscope (bytes 0x36-0x3B)
+        (self.accelerometer, self.gyroscope) = parse_motion(data);
         
         // Parse button states
-        self.buttons.sll = (btn_data & 0x0020) != 0;
-        self.buttons.srl = (btn_data & 0x0010) != 0;
-        self.buttons.minus = (btn_data & 0x0100) != 0;
-        self.buttons.l = (btn_data & 0x0040) != 0;
-        self.buttons.zl = (btn_data & 0x0080) != 0;
-        self.buttons.left = (btn_data & 0x0008) != 0;
-        self.buttons.down = (btn_data & 0x0001) != 0;
-        self.buttons.up = (btn_data & 0x0002) != 0;
-        self.buttons.right = (btn_data & 0x0004) != 0;
-        self.buttons.l3 = (btn_data & 0x0800) != 0;
-        self.buttons.capture = (btn_data & 0x2000) != 0;
+        self.buttons = L::decode_buttons(btn_data);
         
         // Parse analog stick
-        let (x, y) = Self::decode_joystick(joystick_data, self.orientation, &StickCalibration::default());
+        self.raw_stick = decode_raw_stick(joystick_data).unwrap_or_default();
+        let (x, y) = Self::decode_joystick(joystick_data, self.orientation, &self.stick_calibration);
         self.analog_stick.x = x;
         self.analog_stick.y = y;
         
-        // Parse mouse buttons (mapped from controller buttons)
-        self.mouse_btn.left = self.buttons.l;  // L button
-        self.mouse_btn.right = self.buttons.zl; // ZL button
+        // The input report has no analog trigger value, ZL/ZR is digital
+        let (shoulder, trigger) = L::shoulders(&self.buttons);
+        self.trigger = if trigger { 1.0 } else { 0.0 };
+        
+        // Parse mouse buttons (L/R and ZL/ZR)
+        self.mouse_btn.left = shoulder;
+        self.mouse_btn.right = trigger;
         
         // Parse scroll from joystick
-        let (scroll_x, scroll_y) = Self::decode_scroll(joystick_data, &StickCalibration::default());
+        let (scroll_x, scroll_y) = decode_scroll(joystick_data, &self.stick_calibration);
         self.mouse_btn.scroll_x = scroll_x;
         self.mouse_btn.scroll_y = scroll_y;
         
         // Parse battery level (bytes 31-32)
-        if data.len() >= 33 {
-            let battery_raw = (data[31] as u16) | ((data[32] as u16) << 8);
-            let new_battery = (battery_raw as f32 * 100.0 / 4095.0).round();
-            
-            // Only update if lower (or first reading)
-            if new_battery < self.battery_level || !self.is_connected {
-                self.battery_level = new_battery;
-            }
-            
-            // Check for low battery
-            if self.battery_level < 10.0 && self.is_connected && !self.alert_sent {
-                self.notify_low_battery();
-                self.alert_sent = true;
-            }
+        let reading = parse_battery_percent(data);
+        if update_battery(&mut self.battery_level, &mut self.alert_sent, self.is_connected, reading) {
+            notify_low_battery(&self.name, &self.side, self.battery_level);
         }
         
         self.is_connected = true;
+        true
     }
     
     /// Decode joystick data (returns normalized -1.0 to 1.0)
     fn decode_joystick(data: &[u8], orientation: Orientation, cal: &StickCalibration) -> (f32, f32) {
-        if data.len() != 3 {
+        let Some((x_raw, y_raw)) = decode_raw_stick(data) else {
             return (0.0, 0.0);
-        }
-        
-        // Decode 12-bit values
-        let x_raw = ((data[1] as u16 & 0x0F) << 8) | (data[0] as u16);
-        let y_raw = ((data[2] as u16) << 4) | ((data[1] as u16 & 0xF0) >> 4);
-        
-        // Normalize to 0.0-1.0
-        let x_norm = ((x_raw.saturating_sub(cal.x_min) as f32) 
-            / (cal.x_max - cal.x_min) as f32)
-            .clamp(0.0, 1.0);
+        };
         
-        let y_norm = 1.0 - ((y_raw.saturating_sub(cal.y_min) as f32) 
-            / (cal.y_max - cal.y_min) as f32)
-            .clamp(0.0, 1.0);
+        // Normalize to -1.0 to 1.0 around the calibrated center
+        let (x, y) = cal.normalize(x_raw, y_raw);
         
-        // Convert to -1.0 to 1.0 range
-        let mut x = x_norm * 2.0 - 1.0;
-        let mut y = y_norm * 2.0 - 1.0;
-        
-        // Swap for horizontal orientation
         if orientation == Orientation::Horizontal {
-            std::mem::swap(&mut x, &mut y);
-        }
-        
-        (x, y)
-    }
-    
-    /// Decode scroll values from joystick
-    fn decode_scroll(data: &[u8], cal: &StickCalibration) -> (i16, i16) {
-        if data.len() != 3 {
-            return (0, 0);
-        }
-        
-        let x_raw = ((data[1] as u16 & 0x0F) << 8) | (data[0] as u16);
-        let y_raw = ((data[2] as u16) << 4) | ((data[1] as u16 & 0xF0) >> 4);
-        
-        // Center around zero
-        let x_center = (cal.x_max + cal.x_min) as f32 / 2.0;
-        let y_center = (cal.y_max + cal.y_min) as f32 / 2.0;
-        
-        let x = x_raw as f32 - x_center;
-        let y = y_raw as f32 - y_center;
-        
-        // Normalize to [-32767, 32767]
-        let x_range = (cal.x_max - cal.x_min) as f32 / 2.0;
-        let y_range = (cal.y_max - cal.y_min) as f32 / 2.0;
-        
-        let mut x_scroll = ((x / x_range).clamp(-1.0, 1.0) * 32767.0) as i16;
-        let mut y_scroll = ((y / y_range).clamp(-1.0, 1.0) * 32767.0) as i16;
-        
-        // Apply deadzone
-        const SCROLL_DEADZONE: i16 = 3000;
-        if x_scroll.abs() < SCROLL_DEADZONE {
-            x_scroll = 0;
-        }
-        if y_scroll.abs() < SCROLL_DEADZONE {
-            y_scroll = 0;
-        }
-        
-        (x_scroll, y_scroll)
-    }
-    
-    /// Notify user of low battery
-    fn notify_low_battery(&self) {
-        let msg = format!("{} {} : low battery ({:.0}%)", 
-            self.name, self.side, self.battery_level);
-        
-        #[cfg(windows)]
-        {
-            use std::ffi::OsStr;
-            use std::os::windows::ffi::OsStrExt;
-            use std::iter::once;
-            
-            let title: Vec<u16> = OsStr::new("Alert Joy-Con")
-                .encode_wide()
-                .chain(once(0))
-                .collect();
-            
-            let message: Vec<u16> = OsStr::new(&msg)
-                .encode_wide()
-                .chain(once(0))
-                .collect();
-            
-            unsafe {
-                use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_OK, MB_ICONWARNING};
-                let _ = MessageBoxW(
-                    None,
-                    windows::core::PCWSTR(message.as_ptr()),
-                    windows::core::PCWSTR(title.as_ptr()),
-                    MB_OK | MB_ICONWARNING,
-                );
-            }
-        }
-        
-        #[cfg(not(windows))]
-        {
-            eprintln!("[Alert] {}", msg);
+            L::horizontal_stick(x, y)
+        } else {
+            (x, y)
         }
     }
     
     /// Print controller status (for debugging)
     pub fn print_status(&self) {
-        println!("JoyCon Left Status:");
-        println!("  Buttons: ZL={}, L={}, Minus={}, Capture={}, L3={}", 
-            self.buttons.zl, self.buttons.l, self.buttons.minus, 
-            self.buttons.capture, self.buttons.l3);
-        println!("  D-Pad: U={}, D={}, L={}, R={}", 
-            self.buttons.up, self.buttons.down, 
-            self.buttons.left, self.buttons.right);
+        println!("JoyCon {} Status:", L::SIDE);
+        L::print_buttons(&self.buttons);
         println!("  Analog Stick: X={:.2}, Y={:.2}", 
             self.analog_stick.x, self.analog_stick.y);
         println!("  Mouse: X={}, Y={}, Distance=0x{:02X}", 
@@ -389,28 +601,202 @@ impl Joy2L {
     
     /// Convert to generic Buttons struct (for mapping)
     pub fn to_buttons(&self) -> Buttons {
-        Buttons {
-            // Map Joy-Con Left buttons to generic buttons
-            a: false, // Not on left Joy-Con
-            b: false,
-            x: false,
-            y: false,
-            l: self.buttons.l,
-            r: false,
-            zl: self.buttons.zl,
-            zr: false,
-            plus: false,
-            minus: self.buttons.minus,
-            home: false,
-            capture: self.buttons.capture,
-            chat: false,  // Not on left Joy-Con
-            left_stick_click: self.buttons.l3,
-            right_stick_click: false,
-            dpad_up: self.buttons.up,
-            dpad_down: self.buttons.down,
-            dpad_left: self.buttons.left,
-            dpad_right: self.buttons.right,
+        L::to_buttons(&self.buttons)
+    }
+}
+
+/// Parse the accelerometer (bytes 0x30-0x35) and gyroscope (bytes 0x36-0x3B) of a full input report
+fn parse_motion(data: &[u8]) -> (Accelerometer, Gyroscope) {
+    let accel_x_raw = i16::from_le_bytes([data[0x30], data[0x31]]);
+    let accel_y_raw = i16::from_le_bytes([data[0x32], data[0x33]]);
+    let accel_z_raw = i16::from_le_bytes([data[0x34], data[0x35]]);
+    
+    let accel_factor = 1.0 / 4096.0; // 1G = 4096
+    
+    let accelerometer = Accelerometer {
+        x: -(accel_x_raw as f32) * accel_factor,
+        y: -(accel_z_raw as f32) * accel_factor,
+        z: (accel_y_raw as f32) * accel_factor,
+    };
+    
+    let gyro_x_raw = i16::from_le_bytes([data[0x36], data[0x37]]);
+    let gyro_y_raw = i16::from_le_bytes([data[0x38], data[0x39]]);
+    let gyro_z_raw = i16::from_le_bytes([data[0x3A], data[0x3B]]);
+    
+    let gyro_factor = 360.0 / 6048.0; // 360° = 6048
+    
+    let gyroscope = Gyroscope {
+        x: (gyro_x_raw as f32) * gyro_factor,
+        y: -(gyro_z_raw as f32) * gyro_factor,
+        z: (gyro_y_raw as f32) * gyro_factor,
+    };
+    
+    (accelerometer, gyroscope)
+}
+
+/// Battery level below which the low-battery notification is shown
+const LOW_BATTERY_PERCENT: f32 = 10.0;
+
+/// Battery level at which the low-battery notification is re-armed (after charging)
+const LOW_BATTERY_REARM_PERCENT: f32 = 15.0;
+
+/// Weight of a new reading in the battery moving average
+const BATTERY_SMOOTHING: f32 = 0.05;
+
+/// Convert the raw 12-bit battery reading to a percentage (0-100%)
+pub fn raw_to_battery_percent(raw: u16) -> f32 {
+    (raw.min(4095) as f32 * 100.0 / 4095.0).round()
+}
+
+/// Blend a new battery reading into the current level
+/// 
+/// Readings jitter by a few percent from report to report, so the level follows
+/// them through an exponential moving average. It can rise as well as fall, so
+/// a charging controller reports its real level.
+pub fn smooth_battery_level(current: f32, reading: f32) -> f32 {
+    current + (reading - current) * BATTERY_SMOOTHING
+}
+
+/// Fold a battery reading into `level` and track the low-battery alert
+/// 
+/// The first report after connecting sets the level directly, later ones are
+/// smoothed. Returns true when the alert should be shown; it is re-armed once
+/// the level recovers (charging).
+fn update_battery(level: &mut f32, alert_sent: &mut bool, is_connected: bool, reading: f32) -> bool {
+    *level = if is_connected {
+        smooth_battery_level(*level, reading)
+    } else {
+        reading
+    };
+    
+    if *level < LOW_BATTERY_PERCENT && is_connected && !*alert_sent {
+        *alert_sent = true;
+        true
+    } else {
+        if *level >= LOW_BATTERY_REARM_PERCENT {
+            *alert_sent = false;
         }
+        false
+    }
+}
+
+/// Battery level (0-100%) from bytes 31-32 of a full input report
+fn parse_battery_percent(data: &[u8]) -> f32 {
+    raw_to_battery_percent((data[31] as u16) | ((data[32] as u16) << 8))
+}
+
+impl<L: SideLayout> ControllerState for JoyCon2<L> {
+    fn update(&mut self, data: &[u8]) -> bool {
+        JoyCon2::update(self, data)
+    }
+    
+    fn button_states(&self) -> Vec<(ButtonType, bool)> {
+        L::button_states(&self.buttons)
+    }
+    
+    fn to_buttons(&self) -> Buttons {
+        JoyCon2::to_buttons(self)
+    }
+    
+    fn analog_stick(&self) -> Stick {
+        self.analog_stick
+    }
+    
+    fn trigger(&self) -> f32 {
+        self.trigger
+    }
+    
+    fn gyroscope(&self) -> Gyroscope {
+        self.gyroscope
+    }
+    
+    fn accelerometer(&self) -> Accelerometer {
+        self.accelerometer
+    }
+    
+    fn mouse(&self) -> &MouseData {
+        &self.mouse
+    }
+    
+    fn battery_level(&self) -> f32 {
+        self.battery_level
+    }
+    
+    fn raw_stick(&self) -> (u16, u16) {
+        self.raw_stick
+    }
+    
+    fn stick_calibration(&self) -> StickCalibration {
+        self.stick_calibration
+    }
+    
+    fn set_stick_calibration(&mut self, calibration: StickCalibration) {
+        self.stick_calibration = calibration;
+    }
+    
+    fn print_status(&self) {
+        JoyCon2::print_status(self)
+    }
+}
+
+/// Decode scroll values from joystick, with the stick's calibration
+fn decode_scroll(data: &[u8], cal: &StickCalibration) -> (i16, i16) {
+    let Some((x_raw, y_raw)) = decode_raw_stick(data) else {
+        return (0, 0);
+    };
+    
+    // Normalize around the calibrated center, scroll Y grows downwards like the raw reading
+    let (x, y) = cal.normalize(x_raw, y_raw);
+    let mut x_scroll = (x * 32767.0) as i16;
+    let mut y_scroll = (-y * 32767.0) as i16;
+    
+    // Apply deadzone
+    const SCROLL_DEADZONE: i16 = 3000;
+    if x_scroll.abs() < SCROLL_DEADZONE {
+        x_scroll = 0;
+    }
+    if y_scroll.abs() < SCROLL_DEADZONE {
+        y_scroll = 0;
+    }
+    
+    (x_scroll, y_scroll)
+}
+
+/// Notify user of low battery
+fn notify_low_battery(name: &str, side: &str, battery_level: f32) {
+    let msg = format!("{} {} : low battery ({:.0}%)", 
+        name, side, battery_level);
+    
+    #[cfg(windows)]
+    {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+        use std::iter::once;
+        
+        let title: Vec<u16> = OsStr::new("Alert Joy-Con")
+            .encode_wide()
+            .chain(once(0))
+            .collect();
+        
+        let message: Vec<u16> = OsStr::new(&msg)
+            .encode_wide()
+            .chain(once(0))
+            .collect();
+        
+        unsafe {
+            use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_OK, MB_ICONWARNING};
+            let _ = MessageBoxW(
+                None,
+                windows::core::PCWSTR(message.as_ptr()),
+                windows::core::PCWSTR(title.as_ptr()),
+                MB_OK | MB_ICONWARNING,
+            );
+        }
+    }
+    
+    #[cfg(not(windows))]
+    {
+        eprintln!("[Alert] {}", msg);
     }
 }
 
@@ -435,347 +821,276 @@ pub struct RightButtons {
     pub chat: bool,  // Chat button (Joy-Con 2 specific)
 }
 
-/// Joy-Con 2 Right controller state
-#[derive(Debug, Clone)]
-pub struct Joy2R {
-    /// Controller name
-    pub name: String,
-    
-    /// Controller side
-    pub side: String,
-    
-    /// Controller orientation
-    pub orientation: Orientation,
-    
-    /// MAC address
-    pub mac_address: String,
-    
-    /// Button states (mapped for upright usage)
-    pub buttons: RightButtons,
-    
-    /// Analog stick (mapped for upright usage)
-    pub analog_stick: Stick,
-    
-    /// Accelerometer data
-    pub accelerometer: Accelerometer,
+impl RightButtons {
+    /// Current state of every button, paired with its mapping button type
+    pub fn states(&self) -> Vec<(ButtonType, bool)> {
+        vec![
+            (ButtonType::A, self.a),
+            (ButtonType::B, self.b),
+            (ButtonType::X, self.x),
+            (ButtonType::Y, self.y),
+            (ButtonType::R, self.r),
+            (ButtonType::ZR, self.zr),
+            (ButtonType::Plus, self.plus),
+            (ButtonType::Home, self.home),
+            (ButtonType::RightStickClick, self.r3),
+            (ButtonType::SLR, self.slr),
+            (ButtonType::SRR, self.srr),
+            (ButtonType::Chat, self.chat),
+        ]
+    }
     
-    /// Gyroscope data
-    pub gyroscope: Gyroscope,
+    /// Buttons that changed since `prev`, as (button, pressed) transitions
+    pub fn changed_since(&self, prev: &RightButtons) -> Vec<(ButtonType, bool)> {
+        diff_states(self.states(), prev.states())
+    }
+}
+
+/// Report layout of the Right Joy-Con 2
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RightLayout;
+
+impl SideLayout for RightLayout {
+    type Buttons = RightButtons;
     
-    /// Mouse position (from Joy-Con 2 trackpad/sensor)
-    pub mouse: MouseData,
+    const SIDE: &'static str = "Right";
+    const BUTTON_OFFSET: usize = 4;
+    const STICK_OFFSET: usize = 13;
     
-    /// Mouse button states
-    pub mouse_btn: MouseButtons,
+    fn decode_buttons(bits: u16) -> RightButtons {
+        RightButtons {
+            zr: (bits & 0x8000) != 0,
+            r: (bits & 0x4000) != 0,
+            plus: (bits & 0x0002) != 0,
+            slr: (bits & 0x2000) != 0,
+            srr: (bits & 0x1000) != 0,
+            y: (bits & 0x0100) != 0,
+            b: (bits & 0x0400) != 0,
+            x: (bits & 0x0200) != 0,
+            a: (bits & 0x0800) != 0,
+            r3: (bits & 0x0004) != 0,
+            home: (bits & 0x0010) != 0,
+            chat: (bits & 0x0040) != 0,
+        }
+    }
     
-    /// Timestamp from controller
-    pub timestamp: u32,
+    fn button_states(buttons: &RightButtons) -> Vec<(ButtonType, bool)> {
+        buttons.states()
+    }
     
-    /// Motion timestamp
-    pub motion_timestamp: i32,
+    fn to_buttons(buttons: &RightButtons) -> Buttons {
+        Buttons {
+            a: buttons.a,
+            b: buttons.b,
+            x: buttons.x,
+            y: buttons.y,
+            r: buttons.r,
+            zr: buttons.zr,
+            plus: buttons.plus,
+            home: buttons.home,
+            chat: buttons.chat,
+            right_stick_click: buttons.r3,
+            ..Buttons::default()
+        }
+    }
     
-    /// Battery level (0.0 to 100.0)
-    pub battery_level: f32,
+    fn shoulders(buttons: &RightButtons) -> (bool, bool) {
+        (buttons.r, buttons.zr)
+    }
     
-    /// Low battery alert sent flag
-    pub alert_sent: bool,
+    fn horizontal_stick(x: f32, y: f32) -> (f32, f32) {
+        // Held the other way round, so X is inverted
+        (-y, x)
+    }
     
-    /// Connection status
-    pub is_connected: bool,
-}
-
-impl Default for Joy2R {
-    fn default() -> Self {
-        Self {
-            name: "Joy-Con".to_string(),
-            side: "Right".to_string(),
-            orientation: Orientation::default(),
-            mac_address: String::new(),
-            buttons: RightButtons::default(),
-            analog_stick: Stick::default(),
-            accelerometer: Accelerometer::default(),
-            gyroscope: Gyroscope::default(),
-            mouse: MouseData::default(),
-            mouse_btn: MouseButtons::default(),
-            timestamp: 0,
-            motion_timestamp: 0,
-            battery_level: 100.0,
-            alert_sent: false,
-            is_connected: false,
-        }
+    fn print_buttons(buttons: &RightButtons) {
+        println!("  Buttons: A={}, B={}, X={}, Y={}", 
+            buttons.a, buttons.b, buttons.x, buttons.y);
+        println!("  Shoulders: R={}, ZR={}, Plus={}, Home={}, Chat={}, R3={}", 
+            buttons.r, buttons.zr, buttons.plus, buttons.home, buttons.chat, buttons.r3);
     }
 }
 
-impl Joy2R {
-    /// Create a new Joy-Con 2 Right controller
-    pub fn new() -> Self {
-        Self::default()
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
     
-    /// Set the MAC address
-    pub fn set_mac_address(&mut self, mac_address: String) {
-        self.mac_address = mac_address;
+    #[test]
+    fn test_left_buttons_changed_since() {
+        let prev = LeftButtons { zl: true, up: true, ..Default::default() };
+        let current = LeftButtons { up: true, sll: true, ..Default::default() };
+        
+        let changes = current.changed_since(&prev);
+        assert_eq!(changes, vec![(ButtonType::ZL, false), (ButtonType::SLL, true)]);
+        assert!(current.changed_since(&current).is_empty());
     }
     
-    /// Update controller state from BLE data
-    pub fn update(&mut self, data: &[u8]) {
-        self.parse_input_report(data);
+    #[test]
+    fn test_right_buttons_changed_since() {
+        let prev = RightButtons::default();
+        let current = RightButtons { a: true, chat: true, ..Default::default() };
+        
+        let changes = current.changed_since(&prev);
+        assert_eq!(changes, vec![(ButtonType::A, true), (ButtonType::Chat, true)]);
+        assert_eq!(current.states().len(), 12);
     }
     
-    /// Parse input report data
-    fn parse_input_report(&mut self, data: &[u8]) {
-        if data.len() < 0x3C {
-            return; // Not enough data
-        }
-        
-        // Parse button data (bytes 4-5 for right Joy-Con)
-        let btn_data = ((data[4] as u16) << 8) | (data[5] as u16);
-        
-        // Parse joystick data (bytes 13-15 for right Joy-Con)
-        let joystick_data = &data[13..16];
-        
-        // Parse mouse data (bytes 16-23)
-        if data.len() >= 24 {
-            let mouse_data = &data[16..24];
-            self.mouse.x = i16::from_le_bytes([mouse_data[0], mouse_data[1]]);
-            self.mouse.y = i16::from_le_bytes([mouse_data[2], mouse_data[3]]);
-            if mouse_data.len() >= 8 {
-                self.mouse.distance = mouse_data[7];
-            }
-        }
-        
-        // Parse timestamp (bytes 0-3)
-        self.timestamp = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    #[test]
+    fn test_report_kind_classify() {
+        let report = [0u8; INPUT_REPORT_LEN];
+        assert_eq!(ReportKind::classify(TX_CHARACTERISTIC_UUID, &report), ReportKind::Input);
+        assert_eq!(ReportKind::classify(TX_CHARACTERISTIC_UUID, &report[..20]), ReportKind::Unknown);
+        assert_eq!(ReportKind::classify(Uuid::nil(), &report), ReportKind::Unknown);
         
-        // Parse motion timestamp (bytes 0x2A-0x2D)
-        if data.len() >= 0x2E {
-            self.motion_timestamp = i32::from_le_bytes([
-                data[0x2A], data[0x2B], data[0x2C], data[0x2D]
-            ]);
-        }
+        // Same length, told apart by the command ID byte
+        let led_ack = [0x09, COMMAND_RESPONSE_MARKER, 0x00, 0x07, 0x00, 0x00, 0x00, 0x00];
+        let vibration_ack = [0x0A, COMMAND_RESPONSE_MARKER, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(ReportKind::classify(CMD_RESPONSE_CHARACTERISTIC_UUID, &led_ack), ReportKind::CommandResponse { command: 0x09 });
+        assert_eq!(ReportKind::classify(CMD_RESPONSE_CHARACTERISTIC_UUID, &vibration_ack), ReportKind::CommandResponse { command: 0x0A });
         
-        // Parse accelerometer (bytes 0x30-0x35)
-        if data.len() >= 0x36 {
-            let accel_x_raw = i16::from_le_bytes([data[0x30], data[0x31]]);
-            let accel_y_raw = i16::from_le_bytes([data[0x32], data[0x33]]);
-            let accel_z_raw = i16::from_le_bytes([data[0x34], data[0x35]]);
-            
-            let accel_factor = 1.0 / 4096.0; // 1G = 4096
-            
-            self.accelerometer.x = -(accel_x_raw as f32) * accel_factor;
-            self.accelerometer.y = -(accel_z_raw as f32) * accel_factor;
-            self.accelerometer.z = (accel_y_raw as f32) * accel_factor;
-        }
+        // Not a response (echoed request direction), or a truncated header
+        let request = [0x09, 0x91, 0x00, 0x07, 0x00, 0x08, 0x00, 0x00];
+        assert_eq!(ReportKind::classify(CMD_RESPONSE_CHARACTERISTIC_UUID, &request), ReportKind::Unknown);
+        assert_eq!(ReportKind::classify(CMD_RESPONSE_CHARACTERISTIC_UUID, &led_ack[..4]), ReportKind::Unknown);
+    }
+    
+    #[test]
+    fn test_controller_input_dispatches_by_side() {
+        let mut input = ControllerInput::new(Side::Right);
+        assert_eq!(input.side(), Side::Right);
         
-        // Parse gyroscope (bytes 0x36-0x3B)
-        if data.len() >= 0x3C {
-            let gyro_x_raw = i16::from_le_bytes([data[0x36], data[0x37]]);
-            let gyro_y_raw = i16::from_le_bytes([data[0x38], data[0x39]]);
-            let gyro_z_raw = i16::from_le_bytes([data[0x3A], data[0x3B]]);
-            
-            let gyro_factor = 360.0 / 6048.0; // 360° = 6048
-            
-            self.gyroscope.x = (gyro_x_raw as f32) * gyro_factor; // Roll
-            self.gyroscope.y = -(gyro_z_raw as f32) * gyro_factor; // Pitch
-            self.gyroscope.z = (gyro_y_raw as f32) * gyro_factor; // Yaw
+        let mut report = [0u8; INPUT_REPORT_LEN];
+        report[4] = 0x08; // A
+        assert!(input.update(&report));
+        assert!(!input.update(&report[..20]));
+        match input {
+            ControllerInput::Right(controller) => assert!(controller.buttons.a),
+            ControllerInput::Left(_) => panic!("expected Right"),
         }
+    }
+    
+    #[test]
+    fn test_controller_state_is_side_independent() {
+        let mut report = [0u8; INPUT_REPORT_LEN];
+        report[6] = 0x02; // Left: DpadUp
+        report[0x3A] = 0x10; // raw gyro Z, parsed as -y
         
-        // Parse button states (different bitmask for right Joy-Con)
-        self.buttons.zr = (btn_data & 0x8000) != 0;
-        self.buttons.r = (btn_data & 0x4000) != 0;
-        self.buttons.plus = (btn_data & 0x0002) != 0;
-        self.buttons.slr = (btn_data & 0x2000) != 0;
-        self.buttons.srr = (btn_data & 0x1000) != 0;
-        self.buttons.y = (btn_data & 0x0100) != 0;
-        self.buttons.b = (btn_data & 0x0400) != 0;
-        self.buttons.x = (btn_data & 0x0200) != 0;
-        self.buttons.a = (btn_data & 0x0800) != 0;
-        self.buttons.r3 = (btn_data & 0x0004) != 0;
-        self.buttons.home = (btn_data & 0x0010) != 0;
-        self.buttons.chat = (btn_data & 0x0040) != 0;
-        
-        // Parse analog stick
-        let (x, y) = Self::decode_joystick(joystick_data, self.orientation, &StickCalibration::default());
-        self.analog_stick.x = x;
-        self.analog_stick.y = y;
-        
-        // Parse mouse buttons (mapped from controller buttons)
-        self.mouse_btn.left = self.buttons.r;  // R button
-        self.mouse_btn.right = self.buttons.zr; // ZR button
-        
-        // Parse scroll from joystick
-        let (scroll_x, scroll_y) = Self::decode_scroll(joystick_data, &StickCalibration::default());
-        self.mouse_btn.scroll_x = scroll_x;
-        self.mouse_btn.scroll_y = scroll_y;
-        
-        // Parse battery level (bytes 31-32)
-        if data.len() >= 33 {
-            let battery_raw = (data[31] as u16) | ((data[32] as u16) << 8);
-            let new_battery = (battery_raw as f32 * 100.0 / 4095.0).round();
-            
-            // Only update if lower (or first reading)
-            if new_battery < self.battery_level || !self.is_connected {
-                self.battery_level = new_battery;
-            }
-            
-            // Check for low battery
-            if self.battery_level < 10.0 && self.is_connected && !self.alert_sent {
-                self.notify_low_battery();
-                self.alert_sent = true;
-            }
+        let mut controllers: Vec<Box<dyn ControllerState>> = vec![Box::new(Joy2L::new()), Box::new(Joy2R::new())];
+        for controller in controllers.iter_mut() {
+            assert!(controller.update(&report));
+            assert!(!controller.update(&report[..20]));
+            assert!(controller.gyroscope().y < 0.0);
         }
         
-        self.is_connected = true;
+        assert!(controllers[0].button_states().contains(&(ButtonType::DpadUp, true)));
+        assert!(controllers[0].to_buttons().dpad_up);
+        assert_eq!(controllers[1].button_states().len(), 12);
     }
     
-    /// Decode joystick data (returns normalized -1.0 to 1.0)
-    fn decode_joystick(data: &[u8], orientation: Orientation, cal: &StickCalibration) -> (f32, f32) {
-        if data.len() != 3 {
-            return (0.0, 0.0);
-        }
+    #[test]
+    fn test_raw_to_battery_percent() {
+        assert_eq!(raw_to_battery_percent(0), 0.0);
+        assert_eq!(raw_to_battery_percent(4095), 100.0);
+        assert_eq!(raw_to_battery_percent(2048), 50.0);
         
-        // Decode 12-bit values
-        let x_raw = ((data[1] as u16 & 0x0F) << 8) | (data[0] as u16);
-        let y_raw = ((data[2] as u16) << 4) | ((data[1] as u16 & 0xF0) >> 4);
-        
-        // Normalize to 0.0-1.0
-        let x_norm = ((x_raw.saturating_sub(cal.x_min) as f32) 
-            / (cal.x_max - cal.x_min) as f32)
-            .clamp(0.0, 1.0);
-        
-        let y_norm = 1.0 - ((y_raw.saturating_sub(cal.y_min) as f32) 
-            / (cal.y_max - cal.y_min) as f32)
-            .clamp(0.0, 1.0);
-        
-        // Convert to -1.0 to 1.0 range
-        let mut x = x_norm * 2.0 - 1.0;
-        let mut y = y_norm * 2.0 - 1.0;
-        
-        // Swap and invert for horizontal orientation (different for right Joy-Con)
-        if orientation == Orientation::Horizontal {
-            std::mem::swap(&mut x, &mut y);
-            x = -x; // Invert X for horizontal on right Joy-Con
-        }
-        
-        (x, y)
+        // Out-of-range readings are capped
+        assert_eq!(raw_to_battery_percent(u16::MAX), 100.0);
     }
     
-    /// Decode scroll values from joystick
-    fn decode_scroll(data: &[u8], cal: &StickCalibration) -> (i16, i16) {
-        if data.len() != 3 {
-            return (0, 0);
+    #[test]
+    fn test_battery_level_rises_when_charging() {
+        let mut level = 20.0;
+        for _ in 0..200 {
+            level = smooth_battery_level(level, 60.0);
         }
+        assert!((level - 60.0).abs() < 0.1);
         
-        let x_raw = ((data[1] as u16 & 0x0F) << 8) | (data[0] as u16);
-        let y_raw = ((data[2] as u16) << 4) | ((data[1] as u16 & 0xF0) >> 4);
-        
-        // Center around zero
-        let x_center = (cal.x_max + cal.x_min) as f32 / 2.0;
-        let y_center = (cal.y_max + cal.y_min) as f32 / 2.0;
-        
-        let x = x_raw as f32 - x_center;
-        let y = y_raw as f32 - y_center;
+        // A single noisy reading barely moves the level
+        assert!((smooth_battery_level(level, 0.0) - level).abs() < 5.0);
+    }
+    
+    #[test]
+    fn test_low_battery_alert_rearms() {
+        let (mut level, mut alert_sent) = (100.0, false);
         
-        // Normalize to [-32767, 32767]
-        let x_range = (cal.x_max - cal.x_min) as f32 / 2.0;
-        let y_range = (cal.y_max - cal.y_min) as f32 / 2.0;
+        // The first report sets the level without alerting
+        assert!(!update_battery(&mut level, &mut alert_sent, false, 5.0));
+        assert_eq!(level, 5.0);
         
-        let mut x_scroll = ((x / x_range).clamp(-1.0, 1.0) * 32767.0) as i16;
-        let mut y_scroll = ((y / y_range).clamp(-1.0, 1.0) * 32767.0) as i16;
+        // Alerts once while low
+        assert!(update_battery(&mut level, &mut alert_sent, true, 5.0));
+        assert!(!update_battery(&mut level, &mut alert_sent, true, 5.0));
         
-        // Apply deadzone
-        const SCROLL_DEADZONE: i16 = 3000;
-        if x_scroll.abs() < SCROLL_DEADZONE {
-            x_scroll = 0;
-        }
-        if y_scroll.abs() < SCROLL_DEADZONE {
-            y_scroll = 0;
-        }
-        
-        (x_scroll, y_scroll)
+        // Re-armed after charging past the threshold, alerts again when it drops
+        level = LOW_BATTERY_REARM_PERCENT;
+        assert!(!update_battery(&mut level, &mut alert_sent, true, LOW_BATTERY_REARM_PERCENT));
+        assert!(!alert_sent);
+        level = 5.0;
+        assert!(update_battery(&mut level, &mut alert_sent, true, 5.0));
     }
     
-    /// Notify user of low battery
-    fn notify_low_battery(&self) {
-        let msg = format!("{} {} : low battery ({:.0}%)", 
-            self.name, self.side, self.battery_level);
+    #[test]
+    fn test_swap_face_buttons() {
+        let mut states = vec![(ButtonType::A, true), (ButtonType::B, false), (ButtonType::X, false), (ButtonType::Y, true), (ButtonType::R, true)];
+        swap_face_buttons(&mut states);
+        assert_eq!(states, vec![(ButtonType::B, true), (ButtonType::A, false), (ButtonType::Y, false), (ButtonType::X, true), (ButtonType::R, true)]);
+    }
+    
+    #[test]
+    fn test_digital_trigger_value() {
+        let mut report = vec![0u8; INPUT_REPORT_LEN];
+        report[6] = 0x80; // Left: ZL
+        report[4] = 0x80; // Right: ZR
         
-        #[cfg(windows)]
-        {
-            use std::ffi::OsStr;
-            use std::os::windows::ffi::OsStrExt;
-            use std::iter::once;
-            
-            let title: Vec<u16> = OsStr::new("Alert Joy-Con")
-                .encode_wide()
-                .chain(once(0))
-                .collect();
-            
-            let message: Vec<u16> = OsStr::new(&msg)
-                .encode_wide()
-                .chain(once(0))
-                .collect();
-            
-            unsafe {
-                use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_OK, MB_ICONWARNING};
-                let _ = MessageBoxW(
-                    None,
-                    windows::core::PCWSTR(message.as_ptr()),
-                    windows::core::PCWSTR(title.as_ptr()),
-                    MB_OK | MB_ICONWARNING,
-                );
-            }
-        }
+        let mut left = Joy2L::new();
+        let mut right = Joy2R::new();
+        left.update(&report);
+        right.update(&report);
+        assert_eq!(ControllerState::trigger(&left), 1.0);
+        assert_eq!(ControllerState::trigger(&right), 1.0);
         
-        #[cfg(not(windows))]
-        {
-            eprintln!("[Alert] {}", msg);
-        }
+        report[4] = 0;
+        right.update(&report);
+        assert_eq!(right.trigger, 0.0);
     }
     
-    /// Print controller status (for debugging)
-    pub fn print_status(&self) {
-        println!("JoyCon Right Status:");
-        println!("  Buttons: A={}, B={}, X={}, Y={}", 
-            self.buttons.a, self.buttons.b, self.buttons.x, self.buttons.y);
-        println!("  Shoulders: R={}, ZR={}, Plus={}, Home={}, Chat={}", 
-            self.buttons.r, self.buttons.zr, self.buttons.plus,
-            self.buttons.home, self.buttons.chat);
-        println!("  Analog Stick: X={:.2}, Y={:.2}, R3={}", 
-            self.analog_stick.x, self.analog_stick.y, self.buttons.r3);
-        println!("  Mouse: X={}, Y={}, Distance=0x{:02X}", 
-            self.mouse.x, self.mouse.y, self.mouse.distance);
-        println!("  Battery Level: {:.0}%", self.battery_level);
-        println!("  Connected: {}", self.is_connected);
+    #[test]
+    fn test_scroll_uses_stored_calibration() {
+        // Raw stick (2500, 2035) on the left Joy-Con
+        let (x, y) = (2500u16, 2035u16);
+        let mut report = vec![0u8; INPUT_REPORT_LEN];
+        report[10] = (x & 0xFF) as u8;
+        report[11] = ((x >> 8) & 0x0F) as u8 | ((y & 0x0F) << 4) as u8;
+        report[12] = (y >> 4) as u8;
+        
+        let mut left = Joy2L::new();
+        left.update(&report);
+        assert_eq!(left.raw_stick, (x, y));
+        assert!(left.mouse_btn.scroll_x > 0);
+        assert_eq!(left.mouse_btn.scroll_y, 0);
+        
+        // Centered on that reading, the stick is at rest
+        left.set_stick_calibration(StickCalibration { x_center: x, ..StickCalibration::default() });
+        left.update(&report);
+        assert_eq!(left.mouse_btn.scroll_x, 0);
     }
     
-    /// Convert to generic Buttons struct (for mapping)
-    pub fn to_buttons(&self) -> Buttons {
-        Buttons {
-            // Map Joy-Con Right buttons to generic buttons
-            a: self.buttons.a,
-            b: self.buttons.b,
-            x: self.buttons.x,
-            y: self.buttons.y,
-            l: false,
-            r: self.buttons.r,
-            zl: false,
-            zr: self.buttons.zr,
-            plus: self.buttons.plus,
-            minus: false,
-            home: self.buttons.home,
-            capture: false,
-            chat: self.buttons.chat,
-            left_stick_click: false,
-            right_stick_click: self.buttons.r3,
-            dpad_up: false,
-            dpad_down: false,
-            dpad_left: false,
-            dpad_right: false,
-        }
+    #[test]
+    fn test_stick_calibration_from_samples() {
+        let rest = [(2000, 2100), (2010, 2090)];
+        let rotation = [(1000, 2095), (3100, 2095), (2005, 900), (2005, 3300)];
+        let cal = StickCalibration::from_samples(&rest, &rotation).unwrap();
+        assert_eq!((cal.x_min, cal.x_center, cal.x_max), (1000, 2005, 3100));
+        assert_eq!((cal.y_min, cal.y_center, cal.y_max), (900, 2095, 3300));
+        
+        // Each side of an off-center rest position is scaled separately
+        assert_eq!(cal.normalize(2005, 2095), (0.0, 0.0));
+        assert_eq!(cal.normalize(1000, 900), (-1.0, 1.0));
+        assert_eq!(cal.normalize(3100, 3300), (1.0, -1.0));
+        assert_eq!(cal.normalize(4000, 0), (1.0, 1.0));
+        
+        // A stick that barely moved is rejected
+        assert!(StickCalibration::from_samples(&rest, &[(1800, 1900), (2200, 2300)]).is_none());
+        assert!(StickCalibration::from_samples(&[], &rotation).is_none());
     }
 }
-
-
-
-
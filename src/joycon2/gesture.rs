@@ -0,0 +1,299 @@
+//! Motion gesture detection
+//!
+//! Detects simple gestures from controller input: shakes from accelerometer
+//! readings, button combos held for a while (e.g. SL+SR sync) and a stick that
+//! rests off-center (drift).
+
+use crate::joycon2::types::{Accelerometer, Stick};
+use crate::mapping::config::ButtonType;
+use std::time::{Duration, Instant};
+
+/// Number of acceleration peaks within the window that count as a shake
+pub const SHAKE_PEAKS: usize = 3;
+
+/// Detects shakes as repeated acceleration spikes
+#[derive(Debug, Clone)]
+pub struct ShakeDetector {
+    /// Acceleration magnitude (G) a reading must exceed to count as a peak
+    threshold: f32,
+    /// Time window the peaks must fall into
+    window: Duration,
+    /// Quiet time after a shake so one shake doesn't trigger repeatedly
+    cooldown: Duration,
+    peaks: Vec<Instant>,
+    above_threshold: bool,
+    last_shake: Option<Instant>,
+}
+
+impl ShakeDetector {
+    /// Create a detector with the given threshold (G), window and cooldown
+    pub fn new(threshold: f32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            cooldown,
+            peaks: Vec::new(),
+            above_threshold: false,
+            last_shake: None,
+        }
+    }
+
+    /// Feed a reading, returns true when a shake is detected
+    pub fn update(&mut self, accel: &Accelerometer, now: Instant) -> bool {
+        let magnitude = accel.magnitude();
+
+        // Count each spike once, on its rising edge
+        let above = magnitude > self.threshold;
+        let rising = above && !self.above_threshold;
+        self.above_threshold = above;
+
+        if !rising {
+            return false;
+        }
+
+        if let Some(last) = self.last_shake {
+            if now.duration_since(last) < self.cooldown {
+                return false;
+            }
+        }
+
+        self.peaks.retain(|t| now.duration_since(*t) <= self.window);
+        self.peaks.push(now);
+
+        if self.peaks.len() >= SHAKE_PEAKS {
+            self.peaks.clear();
+            self.last_shake = Some(now);
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Detects a set of buttons held together for a minimum time
+#[derive(Debug, Clone)]
+pub struct ComboHoldDetector {
+    buttons: Vec<ButtonType>,
+    hold: Duration,
+    held_since: Option<Instant>,
+    fired: bool,
+}
+
+impl ComboHoldDetector {
+    /// Create a detector for `buttons` held for `hold` (an empty combo never fires)
+    pub fn new(buttons: Vec<ButtonType>, hold: Duration) -> Self {
+        Self {
+            buttons,
+            hold,
+            held_since: None,
+            fired: false,
+        }
+    }
+
+    /// Feed the current button states, returns true once per hold when the combo is complete
+    pub fn update(&mut self, states: &[(ButtonType, bool)], now: Instant) -> bool {
+        let all_held = !self.buttons.is_empty()
+            && self.buttons.iter().all(|b| states.iter().any(|(button, pressed)| button == b && *pressed));
+
+        if !all_held {
+            self.held_since = None;
+            self.fired = false;
+            return false;
+        }
+
+        let since = *self.held_since.get_or_insert(now);
+        if !self.fired && now.duration_since(since) >= self.hold {
+            self.fired = true;
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Stick magnitude above which a resting stick is taken as pushed on purpose, not drifting
+pub const DRIFT_MAX_MAGNITUDE: f32 = 0.5;
+
+/// How far (per axis) the stick may wander and still count as resting in one spot
+pub const DRIFT_STEADY_TOLERANCE: f32 = 0.05;
+
+/// Angular speed (deg/s) above which the controller is taken as in use
+pub const DRIFT_REST_ANGULAR_SPEED: f32 = 15.0;
+
+/// Detects a stick that sits still off-center while the controller isn't being used
+#[derive(Debug, Clone)]
+pub struct StickDriftDetector {
+    /// Magnitude a resting stick must reach to count as drifting
+    threshold: f32,
+    /// How long the stick must rest off-center before it's reported
+    duration: Duration,
+    /// Start and stick position of the current off-center rest
+    resting: Option<(Instant, (f32, f32))>,
+    reported: bool,
+}
+
+impl StickDriftDetector {
+    /// Create a detector for drift of at least `threshold` lasting `duration`
+    pub fn new(threshold: f32, duration: Duration) -> Self {
+        Self {
+            threshold,
+            duration,
+            resting: None,
+            reported: false,
+        }
+    }
+
+    /// Feed the normalized stick position and the controller's angular speed (deg/s),
+    /// returns the drift magnitude once per off-center rest
+    pub fn update(&mut self, stick: &Stick, angular_speed: f32, now: Instant) -> Option<f32> {
+        let (x, y) = (stick.x, stick.y);
+        let magnitude = stick.magnitude();
+
+        if magnitude < self.threshold {
+            self.resting = None;
+            self.reported = false;
+            return None;
+        }
+
+        // A deliberate push or a moving controller means someone is playing
+        if magnitude > DRIFT_MAX_MAGNITUDE || angular_speed > DRIFT_REST_ANGULAR_SPEED {
+            self.resting = None;
+            return None;
+        }
+
+        let (since, origin) = match self.resting {
+            Some((since, origin))
+                if (x - origin.0).abs() <= DRIFT_STEADY_TOLERANCE && (y - origin.1).abs() <= DRIFT_STEADY_TOLERANCE =>
+            {
+                (since, origin)
+            }
+            _ => (now, (x, y)),
+        };
+        self.resting = Some((since, origin));
+
+        if !self.reported && now.duration_since(since) >= self.duration {
+            self.reported = true;
+            return Some(magnitude);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accel(g: f32) -> Accelerometer {
+        Accelerometer { x: 0.0, y: 0.0, z: g }
+    }
+
+    fn stick(x: f32, y: f32) -> Stick {
+        Stick { x, y }
+    }
+
+    #[test]
+    fn test_shake_detection_and_cooldown() {
+        let mut detector = ShakeDetector::new(2.0, Duration::from_millis(500), Duration::from_secs(1));
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        // Holding a spike counts once
+        assert!(!detector.update(&accel(3.0), at(0)));
+        assert!(!detector.update(&accel(3.0), at(10)));
+        assert!(!detector.update(&accel(1.0), at(50)));
+        assert!(!detector.update(&accel(3.0), at(100)));
+        assert!(!detector.update(&accel(1.0), at(150)));
+        assert!(detector.update(&accel(3.0), at(200)));
+
+        // Still shaking during the cooldown doesn't re-trigger
+        for i in 0..6 {
+            let g = if i % 2 == 0 { 1.0 } else { 3.0 };
+            assert!(!detector.update(&accel(g), at(300 + i * 50)));
+        }
+
+        // Peaks spread beyond the window don't add up
+        assert!(!detector.update(&accel(1.0), at(1300)));
+        assert!(!detector.update(&accel(3.0), at(1400)));
+        assert!(!detector.update(&accel(1.0), at(1450)));
+        assert!(!detector.update(&accel(3.0), at(2000)));
+        assert!(!detector.update(&accel(1.0), at(2050)));
+        assert!(!detector.update(&accel(3.0), at(2600)));
+    }
+
+    #[test]
+    fn test_combo_hold_detection() {
+        let mut detector = ComboHoldDetector::new(vec![ButtonType::SLL, ButtonType::SRL], Duration::from_millis(1000));
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let both = [(ButtonType::SLL, true), (ButtonType::SRL, true), (ButtonType::ZL, false)];
+        let one = [(ButtonType::SLL, true), (ButtonType::SRL, false), (ButtonType::ZL, false)];
+
+        // One button isn't the combo
+        assert!(!detector.update(&one, at(0)));
+        assert!(!detector.update(&one, at(2000)));
+
+        // Fires once after the hold time
+        assert!(!detector.update(&both, at(2000)));
+        assert!(!detector.update(&both, at(2999)));
+        assert!(detector.update(&both, at(3000)));
+        assert!(!detector.update(&both, at(5000)));
+
+        // Releasing restarts the timer
+        assert!(!detector.update(&one, at(5100)));
+        assert!(!detector.update(&both, at(5200)));
+        assert!(detector.update(&both, at(6200)));
+
+        // An empty combo never fires
+        let mut empty = ComboHoldDetector::new(Vec::new(), Duration::ZERO);
+        assert!(!empty.update(&both, at(0)));
+    }
+    
+    #[test]
+    fn test_stick_drift_detection() {
+        let mut detector = StickDriftDetector::new(0.1, Duration::from_secs(5));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // Centered or barely off-center is fine
+        assert_eq!(detector.update(&stick(0.0, 0.0), 0.0, at(0)), None);
+        assert_eq!(detector.update(&stick(0.05, 0.0), 0.0, at(10)), None);
+
+        // Resting off-center is reported once after the duration
+        assert_eq!(detector.update(&stick(0.2, 0.0), 0.0, at(20)), None);
+        assert_eq!(detector.update(&stick(0.21, 0.01), 0.0, at(24)), None);
+        let magnitude = detector.update(&stick(0.2, 0.0), 0.0, at(25)).unwrap();
+        assert!((magnitude - 0.2).abs() < 1e-6);
+        assert_eq!(detector.update(&stick(0.2, 0.0), 0.0, at(60)), None);
+
+        // Returning to center re-arms it
+        assert_eq!(detector.update(&stick(0.0, 0.0), 0.0, at(61)), None);
+        assert_eq!(detector.update(&stick(0.0, -0.3), 0.0, at(62)), None);
+        assert!(detector.update(&stick(0.0, -0.3), 0.0, at(67)).is_some());
+    }
+
+    #[test]
+    fn test_stick_drift_ignores_active_use() {
+        let mut detector = StickDriftDetector::new(0.1, Duration::from_secs(5));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // A full push is deliberate
+        assert_eq!(detector.update(&stick(0.9, 0.0), 0.0, at(0)), None);
+        assert_eq!(detector.update(&stick(0.9, 0.0), 0.0, at(10)), None);
+
+        // Moving the controller restarts the timer
+        assert_eq!(detector.update(&stick(0.2, 0.0), 0.0, at(20)), None);
+        assert_eq!(detector.update(&stick(0.2, 0.0), 100.0, at(23)), None);
+        assert_eq!(detector.update(&stick(0.2, 0.0), 0.0, at(24)), None);
+        assert_eq!(detector.update(&stick(0.2, 0.0), 0.0, at(28)), None);
+        assert!(detector.update(&stick(0.2, 0.0), 0.0, at(29)).is_some());
+
+        // So does the stick wandering around
+        let mut detector = StickDriftDetector::new(0.1, Duration::from_secs(5));
+        assert_eq!(detector.update(&stick(0.2, 0.0), 0.0, at(0)), None);
+        assert_eq!(detector.update(&stick(0.3, 0.0), 0.0, at(3)), None);
+        assert_eq!(detector.update(&stick(0.3, 0.0), 0.0, at(7)), None);
+        assert!(detector.update(&stick(0.3, 0.0), 0.0, at(8)).is_some());
+    }
+}
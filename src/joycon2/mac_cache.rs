@@ -4,6 +4,8 @@
 //! storing their MAC addresses and device types for faster reconnection.
 
 use crate::joycon2::connection::Side;
+use crate::joycon2::controller::StickCalibration;
+use crate::joycon2::types::DeviceInfo;
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -13,6 +15,20 @@ use std::path::PathBuf;
 /// Cache file name
 const CACHE_FILENAME: &str = "joycon_cache.json";
 
+/// Controllers not seen for this long are dropped when the cache is loaded (180 days)
+pub const CACHE_MAX_AGE_SECS: u64 = 180 * 24 * 60 * 60;
+
+/// Most controllers kept in the cache, the least recently seen are evicted beyond this
+pub const MAX_CACHE_ENTRIES: usize = 16;
+
+/// Current Unix timestamp in seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// Get the cache file path (in the same directory as the executable or current dir)
 fn get_cache_path() -> PathBuf {
     // Try to use the executable directory first
@@ -42,6 +58,14 @@ pub struct CachedController {
     /// Last seen timestamp (Unix timestamp)
     #[serde(default)]
     pub last_seen: u64,
+    
+    /// Serial number and firmware version from the last connection
+    #[serde(default)]
+    pub device_info: Option<DeviceInfo>,
+    
+    /// Stick calibration saved by `JoyConManager::run_calibration`, applied on connect
+    #[serde(default)]
+    pub stick_calibration: Option<StickCalibration>,
 }
 
 /// Serializable version of Side enum
@@ -85,15 +109,22 @@ impl ControllerCache {
         }
     }
     
-    /// Load cache from disk
+    /// Load cache from disk, dropping stale controllers
+    /// 
+    /// Controllers not seen within `CACHE_MAX_AGE_SECS` are pruned and at most
+    /// `MAX_CACHE_ENTRIES` are kept. The file is rewritten on the next save.
     pub fn load() -> Self {
         let path = get_cache_path();
         
         match fs::read_to_string(&path) {
             Ok(content) => {
-                match serde_json::from_str(&content) {
-                    Ok(cache) => {
+                match serde_json::from_str::<Self>(&content) {
+                    Ok(mut cache) => {
                         debug!("Loaded controller cache from: {}", path.display());
+                        let removed = cache.prune(CACHE_MAX_AGE_SECS) + cache.limit_entries(MAX_CACHE_ENTRIES);
+                        if removed > 0 {
+                            info!("Pruned {} stale controllers from the cache", removed);
+                        }
                         cache
                     }
                     Err(e) => {
@@ -120,22 +151,98 @@ impl ControllerCache {
         Ok(())
     }
     
-    /// Add or update a controller in the cache
+    /// Add or update a controller in the cache, keeping any known device info
+    /// 
+    /// Evicts the least recently seen controllers beyond `MAX_CACHE_ENTRIES`.
     pub fn add_controller(&mut self, mac_address: String, side: Side, name: Option<String>) {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let timestamp = unix_now();
         
+        let previous = self.controllers.get(&mac_address);
+        let device_info = previous.and_then(|c| c.device_info.clone());
+        let stick_calibration = previous.and_then(|c| c.stick_calibration);
         let cached = CachedController {
             mac_address: mac_address.clone(),
             side: side.into(),
             name,
             last_seen: timestamp,
+            device_info,
+            stick_calibration,
         };
         
         info!("Caching controller: {} ({:?})", mac_address, side);
-        self.controllers.insert(mac_address, cached);
+        self.controllers.insert(mac_address.clone(), cached);
+        self.evict_oldest(MAX_CACHE_ENTRIES, Some(&mac_address));
+    }
+    
+    /// Remove controllers not seen in the last `max_age_secs`, returns how many were removed
+    /// 
+    /// Entries without a timestamp (`last_seen` of 0, e.g. hand-written) are kept.
+    pub fn prune(&mut self, max_age_secs: u64) -> usize {
+        self.prune_at(max_age_secs, unix_now())
+    }
+    
+    fn prune_at(&mut self, max_age_secs: u64, now: u64) -> usize {
+        let before = self.controllers.len();
+        self.controllers.retain(|mac, c| {
+            let keep = c.last_seen == 0 || now.saturating_sub(c.last_seen) <= max_age_secs;
+            if !keep {
+                debug!("Pruning cached controller {} (last seen {})", mac, c.last_seen);
+            }
+            keep
+        });
+        before - self.controllers.len()
+    }
+    
+    /// Evict the least recently seen controllers until at most `max_entries` remain
+    /// 
+    /// Returns how many were evicted.
+    pub fn limit_entries(&mut self, max_entries: usize) -> usize {
+        self.evict_oldest(max_entries, None)
+    }
+    
+    /// Evict down to `max_entries`, never evicting `keep` (ties on `last_seen` go by MAC)
+    fn evict_oldest(&mut self, max_entries: usize, keep: Option<&str>) -> usize {
+        if self.controllers.len() <= max_entries {
+            return 0;
+        }
+        
+        let mut by_age: Vec<(u64, String)> = self.controllers.values()
+            .filter(|c| Some(c.mac_address.as_str()) != keep)
+            .map(|c| (c.last_seen, c.mac_address.clone()))
+            .collect();
+        by_age.sort();
+        let evict = self.controllers.len() - max_entries;
+        for (_, mac) in by_age.into_iter().take(evict) {
+            debug!("Evicting cached controller {}", mac);
+            self.controllers.remove(&mac);
+        }
+        evict
+    }
+    
+    /// Store the device info read from a cached controller
+    /// 
+    /// Returns false if the controller isn't cached.
+    pub fn set_device_info(&mut self, mac_address: &str, device_info: DeviceInfo) -> bool {
+        match self.controllers.get_mut(mac_address) {
+            Some(controller) => {
+                controller.device_info = Some(device_info);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Store a stick calibration for a cached controller
+    /// 
+    /// Returns false if the controller isn't cached.
+    pub fn set_stick_calibration(&mut self, mac_address: &str, calibration: StickCalibration) -> bool {
+        match self.controllers.get_mut(mac_address) {
+            Some(controller) => {
+                controller.stick_calibration = Some(calibration);
+                true
+            }
+            None => false,
+        }
     }
     
     /// Get a controller from the cache by MAC address
@@ -209,4 +316,66 @@ mod tests {
         let right_controllers = cache.get_by_side(Side::Right);
         assert_eq!(right_controllers.len(), 1);
     }
+    
+    #[test]
+    fn test_cache_device_info_survives_refresh() {
+        let mut cache = ControllerCache::new();
+        let info = DeviceInfo { serial_number: Some("XKJ1".to_string()), firmware_version: Some("1.2.3".to_string()) };
+        assert!(!cache.set_device_info("AA:BB:CC:DD:EE:FF", info.clone()));
+        
+        cache.add_controller("AA:BB:CC:DD:EE:FF".to_string(), Side::Right, None);
+        assert!(cache.set_device_info("AA:BB:CC:DD:EE:FF", info.clone()));
+        cache.add_controller("AA:BB:CC:DD:EE:FF".to_string(), Side::Right, None);
+        assert_eq!(cache.get_controller("AA:BB:CC:DD:EE:FF").unwrap().device_info, Some(info));
+        
+        // Caches written before device info existed still load
+        let old: CachedController = serde_json::from_str(
+            r#"{"mac_address": "AA:BB:CC:DD:EE:FF", "side": "left"}"#
+        ).unwrap();
+        assert!(old.device_info.is_none());
+    }
+    
+    #[test]
+    fn test_cache_prune_and_limit() {
+        let mut cache = ControllerCache::new();
+        for (i, last_seen) in [100u64, 5_000, 9_000, 0].into_iter().enumerate() {
+            let mac = format!("AA:BB:CC:DD:EE:0{}", i);
+            cache.add_controller(mac.clone(), Side::Left, None);
+            cache.controllers.get_mut(&mac).unwrap().last_seen = last_seen;
+        }
+        
+        // Older than 5000 seconds at t=10000, the entry without a timestamp stays
+        assert_eq!(cache.prune_at(5_000, 10_000), 1);
+        assert!(cache.get_controller("AA:BB:CC:DD:EE:00").is_none());
+        assert!(cache.get_controller("AA:BB:CC:DD:EE:03").is_some());
+        
+        // Least recently seen go first
+        assert_eq!(cache.limit_entries(1), 2);
+        assert_eq!(cache.list_all()[0].mac_address, "AA:BB:CC:DD:EE:02");
+        assert_eq!(cache.limit_entries(1), 0);
+        
+        // Adding past the cap always keeps the controller just added
+        for i in (0..MAX_CACHE_ENTRIES + 2).rev() {
+            cache.add_controller(format!("11:22:33:44:55:{:02}", i), Side::Right, None);
+        }
+        assert_eq!(cache.len(), MAX_CACHE_ENTRIES);
+        assert!(cache.get_controller("11:22:33:44:55:00").is_some());
+    }
+    
+    #[test]
+    fn test_cache_stick_calibration_survives_refresh() {
+        let mut cache = ControllerCache::new();
+        let cal = StickCalibration { x_min: 700, x_max: 3300, y_min: 750, y_max: 3350, x_center: 2000, y_center: 2050 };
+        assert!(!cache.set_stick_calibration("AA:BB:CC:DD:EE:FF", cal));
+        
+        cache.add_controller("AA:BB:CC:DD:EE:FF".to_string(), Side::Left, None);
+        assert!(cache.set_stick_calibration("AA:BB:CC:DD:EE:FF", cal));
+        cache.add_controller("AA:BB:CC:DD:EE:FF".to_string(), Side::Left, None);
+        assert_eq!(cache.get_controller("AA:BB:CC:DD:EE:FF").unwrap().stick_calibration, Some(cal));
+        
+        let old: CachedController = serde_json::from_str(
+            r#"{"mac_address": "AA:BB:CC:DD:EE:FF", "side": "left"}"#
+        ).unwrap();
+        assert!(old.stick_calibration.is_none());
+    }
 }
@@ -11,6 +11,7 @@ pub mod types;
 pub mod controller;
 pub mod connection;
 pub mod mac_cache;
+pub mod gesture;
 
 // Re-export commonly used items
 pub use constants::*;
@@ -18,6 +19,7 @@ pub use types::*;
 pub use controller::*;
 pub use connection::*;
 pub use mac_cache::*;
+pub use gesture::*;
 
 // TODO: Add these modules as we implement them
 // pub mod protocol;
@@ -21,6 +21,18 @@ impl Default for Stick {
     }
 }
 
+impl Stick {
+    /// Distance from the center (0.0 at rest, about 1.0 at full deflection)
+    pub fn magnitude(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+    
+    /// Direction in degrees, counterclockwise from right (0 = right, 90 = up, ±180 = left)
+    pub fn angle(&self) -> f32 {
+        self.y.atan2(self.x).to_degrees()
+    }
+}
+
 /// Gyroscope data (angular velocity in degrees per second)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Gyroscope {
@@ -40,6 +52,13 @@ impl Default for Gyroscope {
     }
 }
 
+impl Gyroscope {
+    /// Total angular speed (magnitude of the rotation vector)
+    pub fn magnitude(&self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    }
+}
+
 /// Accelerometer data (linear acceleration in Gs)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Accelerometer {
@@ -59,6 +78,31 @@ impl Default for Accelerometer {
     }
 }
 
+impl Accelerometer {
+    /// Total acceleration in G (about 1.0 at rest)
+    pub fn magnitude(&self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    }
+    
+    /// Estimate tilt from gravity (only meaningful while the controller isn't accelerating)
+    pub fn tilt(&self) -> Tilt {
+        Tilt {
+            roll: self.y.atan2(self.z).to_degrees(),
+            pitch: (-self.x).atan2((self.y * self.y + self.z * self.z).sqrt()).to_degrees(),
+        }
+    }
+}
+
+/// Controller tilt estimated from the accelerometer (degrees)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tilt {
+    /// Rotation around the X-axis
+    pub roll: f32,
+    
+    /// Rotation around the Y-axis
+    pub pitch: f32,
+}
+
 /// Generic button states
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Buttons {
@@ -91,3 +135,116 @@ pub struct Buttons {
     pub dpad_left: bool,
     pub dpad_right: bool,
 }
+
+/// Serial number and firmware version reported by a controller
+/// 
+/// Each field is None when the controller doesn't expose it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Whether nothing could be read
+    pub fn is_empty(&self) -> bool {
+        self.serial_number.is_none() && self.firmware_version.is_none()
+    }
+}
+
+impl std::fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.firmware_version {
+            Some(version) => write!(f, "v{}", version.trim_start_matches(['v', 'V']))?,
+            None => write!(f, "unknown firmware")?,
+        }
+        if let Some(serial) = &self.serial_number {
+            write!(f, " (serial {})", serial)?;
+        }
+        Ok(())
+    }
+}
+
+/// Named vibration pattern, a sequence of rumble pulses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RumblePattern {
+    /// One short pulse
+    Short,
+    /// Two short pulses
+    Double,
+    /// One long pulse
+    Long,
+    /// Pulses getting stronger
+    Ramp,
+}
+
+impl RumblePattern {
+    /// Pulses as (offset from the start, vibration preset), one per `COMMAND_DELAY_MS` slot
+    pub fn pulses(&self) -> Vec<(std::time::Duration, u8)> {
+        // (start slot, preset, length in slots)
+        let segments: &[(u64, u8, u64)] = match self {
+            RumblePattern::Short => &[(0, 3, 1)],
+            RumblePattern::Double => &[(0, 3, 1), (3, 3, 1)],
+            RumblePattern::Long => &[(0, 3, 8)],
+            RumblePattern::Ramp => &[(0, 1, 2), (2, 2, 2), (4, 3, 2)],
+        };
+        
+        segments.iter()
+            .flat_map(|&(start, preset, slots)| (start..start + slots).map(move |slot| (slot, preset)))
+            .map(|(slot, preset)| (std::time::Duration::from_millis(slot * crate::joycon2::constants::COMMAND_DELAY_MS), preset))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_stick_magnitude_and_angle() {
+        assert_eq!(Stick::default().magnitude(), 0.0);
+        
+        let stick = Stick { x: 0.6, y: -0.8 };
+        assert!((stick.magnitude() - 1.0).abs() < 1e-6);
+        
+        for (x, y, angle) in [(1.0, 0.0, 0.0), (0.0, 1.0, 90.0), (-1.0, 0.0, 180.0), (0.0, -1.0, -90.0), (0.5, 0.5, 45.0)] {
+            assert!((Stick { x, y }.angle() - angle).abs() < 1e-4, "({}, {})", x, y);
+        }
+    }
+    
+    #[test]
+    fn test_gyro_magnitude() {
+        let gyro = Gyroscope { x: 3.0, y: 4.0, z: 12.0 };
+        assert!((gyro.magnitude() - 13.0).abs() < 1e-5);
+    }
+    
+    #[test]
+    fn test_accelerometer_tilt() {
+        // Lying flat: gravity on Z only
+        let flat = Accelerometer { x: 0.0, y: 0.0, z: 1.0 };
+        assert_eq!(flat.tilt(), Tilt { roll: 0.0, pitch: 0.0 });
+        
+        // Rolled 90 degrees: gravity on Y
+        let rolled = Accelerometer { x: 0.0, y: 1.0, z: 0.0 };
+        assert!((rolled.tilt().roll - 90.0).abs() < 1e-3);
+        
+        // Nose down: gravity on -X
+        let pitched = Accelerometer { x: -1.0, y: 0.0, z: 0.0 };
+        assert!((pitched.tilt().pitch - 90.0).abs() < 1e-3);
+        assert!((pitched.magnitude() - 1.0).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_rumble_pattern_pulses() {
+        let double = RumblePattern::Double.pulses();
+        assert_eq!(double.len(), 2);
+        assert!(double[1].0 > double[0].0 + std::time::Duration::from_millis(50));
+        
+        let ramp: Vec<u8> = RumblePattern::Ramp.pulses().iter().map(|(_, preset)| *preset).collect();
+        assert_eq!(ramp, vec![1, 1, 2, 2, 3, 3]);
+        assert!(RumblePattern::Long.pulses().len() > RumblePattern::Short.pulses().len());
+    }
+}
@@ -4,12 +4,15 @@
 //! mapping controller inputs to keyboard and mouse actions.
 
 pub mod backend;
+pub mod control;
 pub mod joycon2;
+pub mod logging;
 pub mod mapping;
 pub mod manager;
 
 // Re-export commonly used items
-pub use backend::{KeyboardBackend, MouseBackend};
+pub use backend::{GamepadBackend, KeyboardBackend, MouseBackend};
 pub use joycon2::{Joy2L, Joy2R, Buttons, Stick, Gyroscope, Accelerometer};
-pub use manager::JoyConManager;
+pub use joycon2::JoyConError;
+pub use manager::{ControllerStatus, JoyConManager, ManagerCommand, ManagerState};
 pub use mapping::{Config, MappingExecutor};
@@ -0,0 +1,144 @@
+//! Logging setup for the application
+//!
+//! Logs go to stderr through `env_logger`. When `settings.log_file` is set they
+//! are also appended to that file, which is rotated once it reaches
+//! `settings.log_max_size_kb` (the previous log is kept as `<log_file>.1`),
+//! so a log can be attached to a support ticket.
+
+use crate::mapping::config::Settings;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// File writer that starts over once the file reaches a size cap
+///
+/// On rotation the current file is renamed to `<path>.1` (replacing an older one),
+/// so at most twice the cap is kept on disk.
+#[derive(Debug)]
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    file: File,
+    /// Bytes in the current file
+    written: u64,
+    max_bytes: u64,
+}
+
+impl RotatingFileWriter {
+    /// Open `path` for appending, rotating at `max_bytes`
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path, file, written, max_bytes })
+    }
+
+    /// Path of the rotated-out previous log
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        std::fs::rename(&self.path, self.rotated_path())?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Rotate between records, a record larger than the cap still goes in whole
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Writes log records to stderr and the log file
+struct StderrAndFile(RotatingFileWriter);
+
+impl Write for StderrAndFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A closed stderr must not stop the file log
+        let _ = io::stderr().write_all(buf);
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = io::stderr().flush();
+        self.0.flush()
+    }
+}
+
+/// Initialize the global logger from the settings
+///
+/// `RUST_LOG` still works and `log_level` sets the level of joy2-rs's own messages.
+/// Fails if the log file can't be opened (stderr logging is set up either way).
+pub fn init(settings: &Settings) -> io::Result<()> {
+    let level = settings.log_level.parse().unwrap_or(log::LevelFilter::Info);
+    let mut builder = env_logger::Builder::from_env(env_logger::Env::default());
+    builder
+        .filter_level(log::LevelFilter::Warn)                  // default
+        .filter_module("joy2_rs", level)
+        // .filter_module("joy2_rs::mapping::executor", log::LevelFilter::Trace)
+        .filter_module("btleplug", log::LevelFilter::Warn);
+
+    let file = settings.log_file.as_ref()
+        .map(|path| RotatingFileWriter::open(path, settings.log_max_size_kb.saturating_mul(1024)));
+    let error = match file {
+        Some(Ok(file)) => {
+            builder.target(env_logger::Target::Pipe(Box::new(StderrAndFile(file))));
+            None
+        }
+        Some(Err(e)) => Some(e),
+        None => None,
+    };
+    builder.init();
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_file_writer() {
+        let path = std::env::temp_dir().join(format!("joy2-rs-log-test-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut writer = RotatingFileWriter::open(&path, 16).unwrap();
+        let rotated = writer.rotated_path();
+        let _ = std::fs::remove_file(&rotated);
+        writer.write_all(b"first line\n").unwrap();
+        writer.write_all(b"second\n").unwrap();
+        // 11 + 7 bytes would pass the cap, so the second record starts a new file
+        writer.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap(), "first line\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second\n");
+
+        // Reopening appends and keeps counting the existing bytes
+        drop(writer);
+        let mut writer = RotatingFileWriter::open(&path, 16).unwrap();
+        writer.write_all(b"third\n").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second\nthird\n");
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&rotated);
+    }
+}
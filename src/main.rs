@@ -6,7 +6,8 @@
 //! ⚠️  WARNING: This will send REAL keyboard and mouse input to your system!
 //! ⚠️  Make sure you have your config set up correctly before running.
 
-use joy2_rs::backend::{KeyboardSendInputBackend, MouseSendInputBackend};
+use joy2_rs::backend::{get_keyboard_backend, get_mouse_backend};
+use joy2_rs::logging;
 use joy2_rs::mapping::config::Config;
 use joy2_rs::JoyConManager;
 use std::error::Error;
@@ -14,13 +15,14 @@ use std::thread;
 use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize logging
-    env_logger::Builder::from_env(env_logger::Env::default())
-        .filter_level(log::LevelFilter::Warn)                  // default
-        .filter_module("joy2_rs", log::LevelFilter::Info)
-        // .filter_module("joy2_rs::mapping::executor", log::LevelFilter::Trace)
-        .filter_module("btleplug", log::LevelFilter::Warn)
-        .init();
+    // Load default configuration (first, it configures logging)
+    let config = Config::load_default()?;
+
+    // Initialize logging, then report what was loaded
+    if let Err(e) = logging::init(&config.settings) {
+        eprintln!("⚠️  Could not open log file {:?}: {}", config.settings.log_file, e);
+    }
+    config.log_startup_report();
 
     println!("=== Joy-Con 2 Manager ===");
     println!();
@@ -38,21 +40,31 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Press Ctrl+C to stop");
     println!();
 
-    // Load default configuration
-    let config = Config::load_default()?;
     println!("✓ Loaded configuration from configs/default.toml");
+    if let Some(log_file) = &config.settings.log_file {
+        println!("✓ Logging to {}", log_file);
+    }
 
-    // Create real backends (unit structs - no new() needed)
-    let keyboard = KeyboardSendInputBackend;
-    let mouse = MouseSendInputBackend;
+    // Create real backends (a NullBackend that only warns on unsupported platforms)
+    let keyboard = get_keyboard_backend();
+    let mouse = get_mouse_backend();
 
     // Create the manager
+    let control_address = config.settings.control_address.clone();
     let mut manager = JoyConManager::new(config, keyboard, mouse);
 
     // Start the manager (spawns threads for executor and controllers)
     println!("Starting manager...");
     manager.start()?;
 
+    // Optional text control server for automation tools (see the README)
+    if let Some(address) = &control_address {
+        match manager.start_control_server(address.as_str()) {
+            Ok(addr) => println!("✓ Control server listening on {}", addr),
+            Err(e) => eprintln!("⚠️  Could not start control server on {}: {}", address, e),
+        }
+    }
+
     println!("Manager started! Waiting for controller events...");
     println!();
 
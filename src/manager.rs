@@ -3,23 +3,562 @@
 //! This module provides a high-level interface for managing Joy-Con 2 controllers,
 //! handling connection, event forwarding, and executor integration.
 
-use crate::backend::{KeyboardBackend, MouseBackend};
-use crate::joycon2::connection::{JoyConConnection, Side};
-use crate::joycon2::controller::{Joy2L, Joy2R};
+use crate::backend::{GamepadBackend, KeyboardBackend, MouseBackend};
+use crate::control::{self, ControlCommand, ControlHandle};
+use crate::joycon2::connection::{detect_side, JoyConConnection, JoyConError, Side};
+use crate::joycon2::controller::{diff_states, swap_face_buttons, ControllerState, Joy2L, Joy2R, ReportKind, StickCalibration};
+use crate::joycon2::gesture::{ComboHoldDetector, ShakeDetector, StickDriftDetector};
 use crate::joycon2::mac_cache::ControllerCache;
-use crate::mapping::config::{ButtonType, Config, ControllerSide, JoyConEvent, StickType};
+use crate::joycon2::types::RumblePattern;
+use crate::mapping::config::{ButtonType, CalibrationStep, ChannelFullPolicy, Config, ControllerSide, GestureKind, JoyConEvent, StickType};
 use crate::mapping::executor::MappingExecutor;
 use btleplug::api::Peripheral as _;
 use btleplug::platform::Peripheral;
-use crossbeam_channel::{bounded, Receiver, Sender};
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
 use futures::stream::StreamExt;
 use log::{debug, info, warn};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use tokio::runtime::Runtime;
+use thiserror::Error;
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+
+/// How long to wait for a cached controller before falling back to scanning
+const CACHED_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How long shutdown waits for scanner and controller tasks to exit
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often each controller loop reads the link's RSSI
+const SIGNAL_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often each controller loop computes its packet rate and sends a latency probe
+const TELEMETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How many button and connection events can wait for room in a full event channel
+const MAX_PENDING_EVENTS: usize = 1000;
+
+/// Number of latency probes averaged per controller
+const LATENCY_SAMPLES: usize = 10;
+
+/// How long the stick is recorded at rest for its calibrated center
+const CALIBRATION_REST: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long the stick is recorded while the user rotates it for its calibrated range
+const CALIBRATION_ROTATE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often calibration collects samples and sends a progress event
+const CALIBRATION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How often `wait_for_connection` checks whether the controller is connected
+/// 
+/// Polled rather than subscribed: the event channels have a single consumer (the
+/// caller's receivers), and a few ms of latency don't matter next to a BLE connect.
+const CONNECTION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Why `JoyConManager::wait_for_connection` returned without a connected controller
+#[derive(Debug, Error, PartialEq)]
+pub enum WaitError {
+    #[error("{0:?} controller did not connect within {1:?}")]
+    Timeout(ControllerSide, std::time::Duration),
+    
+    #[error("the manager is not running")]
+    NotRunning,
+}
+
+/// Why `JoyConManager::run_calibration` failed
+#[derive(Debug, Error)]
+pub enum CalibrationError {
+    #[error("{0:?} controller is not connected")]
+    NotConnected(ControllerSide),
+    
+    #[error("{0:?} controller is already being calibrated")]
+    AlreadyRunning(ControllerSide),
+    
+    #[error("no input from the {0:?} controller")]
+    NoInput(ControllerSide),
+    
+    #[error("calibration cancelled")]
+    Cancelled,
+    
+    #[error("the stick didn't reach its full range, rotate it all the way around a few times")]
+    InsufficientRange,
+}
+
+/// Stick calibration in progress, shared by `run_calibration` and the side's controller loop
+#[derive(Debug, Default)]
+struct CalibrationSession {
+    /// Raw stick readings recorded by the controller loop since they were last collected
+    samples: Vec<(u16, u16)>,
+    /// Result for the controller loop to apply, which then ends the session
+    apply: Option<StickCalibration>,
+    cancelled: bool,
+}
+
+/// Commands sent from the executor back to the controller threads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManagerCommand {
+    /// Play a short rumble pulse on the given controller
+    Rumble { side: ControllerSide, intensity: u8, duration_ms: u64 },
+    /// Play a rumble pattern on the given controller (without blocking its input)
+    RumblePattern { side: ControllerSide, pattern: RumblePattern },
+    /// Set the player LEDs of the given controller (bit 0 = LED 1 ... bit 3 = LED 4)
+    SetPlayerLed { side: ControllerSide, leds: u8 },
+}
+
+impl ManagerCommand {
+    /// Controller side this command is meant for
+    pub fn side(&self) -> ControllerSide {
+        match self {
+            ManagerCommand::Rumble { side, .. } => *side,
+            ManagerCommand::RumblePattern { side, .. } => *side,
+            ManagerCommand::SetPlayerLed { side, .. } => *side,
+        }
+    }
+}
+
+/// Status of one controller side, part of `ManagerState`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ControllerStatus {
+    /// MAC address of the controller being set up or connected on this side
+    pub mac_address: Option<String>,
+    /// Set once the controller is initialized and streaming input
+    pub connected: bool,
+    /// Battery level (0-100%), None until the first input report
+    pub battery_level: Option<f32>,
+    /// Latest RSSI (dBm), None if the platform doesn't report it
+    pub signal_strength: Option<i16>,
+    /// Input reports per second over the last second, None until the first second passed
+    pub packet_hz: Option<f32>,
+    /// Average delay (ms) from packet receipt until the executor processed its events
+    pub avg_latency_ms: Option<f32>,
+}
+
+/// Snapshot of the manager for status displays and GUIs
+/// 
+/// Shared as `Arc<RwLock<ManagerState>>` (see `JoyConManager::state()`), so a GUI
+/// can take a read lock every frame. Writers only lock when something changed:
+/// - controller status: on connect/disconnect, battery when it moves by 1% or more,
+///   RSSI every 5 seconds, packet rate and latency every second
+/// - profile, sensitivity, pause and gyro flags: by the executor thread, checked after
+///   every event and at least every 16ms
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagerState {
+    pub left: ControllerStatus,
+    pub right: ControllerStatus,
+    /// Index of the active profile in the config
+    pub profile_index: usize,
+    pub profile_name: String,
+    /// Effective sensitivity factor (current level including held clutches)
+    pub sensitivity: f32,
+    /// Input injection paused by a `togglepause` button
+    pub paused: bool,
+    pub gyro_left_enabled: bool,
+    pub gyro_right_enabled: bool,
+    /// The next connection gets the `startup_profiles` selection window (set by `start()`)
+    startup_select_armed: bool,
+}
+
+impl ManagerState {
+    /// State before the executor publishes, matching its starting profile and sensitivity
+    pub(crate) fn new(config: &Config) -> Self {
+        let profile_index = config.profiles.iter()
+            .position(|p| p.name == config.settings.default_profile)
+            .unwrap_or(0);
+        
+        Self {
+            left: ControllerStatus::default(),
+            right: ControllerStatus::default(),
+            profile_index,
+            profile_name: config.profiles.get(profile_index).map(|p| p.name.clone()).unwrap_or_default(),
+            sensitivity: config.settings.sensitivity_factor.first().copied().unwrap_or(1.0),
+            paused: false,
+            gyro_left_enabled: false,
+            gyro_right_enabled: false,
+            startup_select_armed: false,
+        }
+    }
+    
+    /// Status of the controller on `side`
+    pub fn controller(&self, side: ControllerSide) -> &ControllerStatus {
+        match side {
+            ControllerSide::Left => &self.left,
+            ControllerSide::Right => &self.right,
+        }
+    }
+    
+    fn controller_mut(&mut self, side: ControllerSide) -> &mut ControllerStatus {
+        match side {
+            ControllerSide::Left => &mut self.left,
+            ControllerSide::Right => &mut self.right,
+        }
+    }
+    
+    /// Claim the `startup_profiles` selection window, only the first connection after `start()` gets it
+    fn take_startup_select(&mut self) -> bool {
+        std::mem::take(&mut self.startup_select_armed)
+    }
+    
+    /// Whether a controller with this MAC is being set up or connected
+    fn has_mac(&self, mac_address: &str) -> bool {
+        [&self.left, &self.right].iter().any(|c| c.mac_address.as_deref() == Some(mac_address))
+    }
+    
+    /// Sides with a ready controller
+    fn connected_sides(&self) -> Vec<Side> {
+        let mut sides = Vec::new();
+        if self.left.connected {
+            sides.push(Side::Left);
+        }
+        if self.right.connected {
+            sides.push(Side::Right);
+        }
+        sides
+    }
+    
+    /// Copy the executor's profile, sensitivity, pause and gyro state (write lock only on change)
+    fn publish<K: KeyboardBackend, M: MouseBackend>(state: &RwLock<ManagerState>, executor: &MappingExecutor<K, M>) {
+        let profile_index = executor.current_profile_index();
+        let sensitivity = executor.current_sensitivity();
+        let paused = executor.is_paused();
+        let gyro_left_enabled = executor.is_gyro_mouse_enabled(ControllerSide::Left);
+        let gyro_right_enabled = executor.is_gyro_mouse_enabled(ControllerSide::Right);
+        
+        {
+            let current = state.read().unwrap();
+            if current.profile_index == profile_index
+                && current.sensitivity == sensitivity
+                && current.paused == paused
+                && current.gyro_left_enabled == gyro_left_enabled
+                && current.gyro_right_enabled == gyro_right_enabled
+            {
+                return;
+            }
+        }
+        
+        let mut current = state.write().unwrap();
+        if current.profile_index != profile_index {
+            current.profile_name = executor.current_profile_name().to_string();
+        }
+        current.profile_index = profile_index;
+        current.sensitivity = sensitivity;
+        current.paused = paused;
+        current.gyro_left_enabled = gyro_left_enabled;
+        current.gyro_right_enabled = gyro_right_enabled;
+    }
+}
+
+/// Decides which sides the scanner still looks for (scan_once / scan_timeout_secs)
+struct ScanState {
+    expected_sides: Vec<Side>,
+    state: Arc<RwLock<ManagerState>>,
+    event_sender: EventSender,
+    scan_once: bool,
+    scan_timeout: Option<std::time::Duration>,
+    /// When each missing side started being scanned for
+    waiting_since: HashMap<Side, std::time::Instant>,
+    /// Sides we gave up on
+    timed_out: HashSet<Side>,
+}
+
+impl ScanState {
+    /// Sides to scan for right now, reporting sides whose scan timed out
+    fn wanted_sides(&mut self) -> Vec<Side> {
+        let now = std::time::Instant::now();
+        let connected = self.state.read().unwrap().connected_sides();
+        let mut wanted = Vec::new();
+        
+        for &side in &self.expected_sides {
+            if self.timed_out.contains(&side) {
+                continue;
+            }
+            
+            if connected.contains(&side) {
+                self.waiting_since.remove(&side);
+                if !self.scan_once {
+                    wanted.push(side);
+                }
+                continue;
+            }
+            
+            let since = *self.waiting_since.entry(side).or_insert(now);
+            if let Some(timeout) = self.scan_timeout {
+                if now.duration_since(since) >= timeout {
+                    warn!("No {:?} Joy-Con found within {:?}, stopped scanning for it", side, timeout);
+                    self.timed_out.insert(side);
+                    self.event_sender.send(JoyConEvent::ScanTimedOut { side: side.into() });
+                    continue;
+                }
+            }
+            wanted.push(side);
+        }
+        
+        wanted
+    }
+}
+
+/// The first held `startup_profiles` button and its profile
+fn startup_profile<'a>(startup_profiles: &'a HashMap<ButtonType, String>, states: &[(ButtonType, bool)]) -> Option<(ButtonType, &'a str)> {
+    states.iter()
+        .filter(|(_, pressed)| *pressed)
+        .find_map(|(button, _)| startup_profiles.get(button).map(|profile| (*button, profile.as_str())))
+}
+
+/// Drop the transitions of the button that picked the startup profile until it is released,
+/// so its own mapping doesn't fire
+fn suppress_startup_selector(changes: &mut Vec<(ButtonType, bool)>, selector: &mut Option<ButtonType>) {
+    let Some(button) = *selector else {
+        return;
+    };
+    if changes.iter().any(|&(b, pressed)| b == button && !pressed) {
+        *selector = None;
+    }
+    changes.retain(|(b, _)| *b != button);
+}
+
+/// Drop transitions that come within `window` of the same button's last reported transition
+/// 
+/// Dropped transitions are undone in `buttons`, so a button that stays in its new state
+/// is reported by the first packet after the window.
+fn debounce_buttons(
+    changes: Vec<(ButtonType, bool)>,
+    buttons: &mut [(ButtonType, bool)],
+    last_transition: &mut HashMap<ButtonType, std::time::Instant>,
+    window: std::time::Duration,
+    now: std::time::Instant,
+) -> Vec<(ButtonType, bool)> {
+    changes.into_iter()
+        .filter(|&(button, pressed)| {
+            let bounced = last_transition.get(&button)
+                .is_some_and(|last| now.duration_since(*last) < window);
+            if bounced {
+                if let Some(state) = buttons.iter_mut().find(|(b, _)| *b == button) {
+                    state.1 = !pressed;
+                }
+            } else {
+                last_transition.insert(button, now);
+            }
+            !bounced
+        })
+        .collect()
+}
+
+/// Rolling average of the last `LATENCY_SAMPLES` latency probes of one controller
+#[derive(Debug, Default)]
+struct LatencyWindow {
+    samples: std::collections::VecDeque<f32>,
+}
+
+impl LatencyWindow {
+    /// Add a probe's latency (ms) and return the new average
+    fn record(&mut self, latency_ms: f32) -> f32 {
+        if self.samples.len() == LATENCY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency_ms);
+        self.samples.iter().sum::<f32>() / self.samples.len() as f32
+    }
+}
+
+/// Whether an event may be dropped when the executor falls behind
+/// 
+/// A later event of the same kind carries the newer value (or it is diagnostic only).
+/// Dropping a button or connection event could leave a key held forever.
+fn is_mergeable(event: &JoyConEvent) -> bool {
+    matches!(
+        event,
+        JoyConEvent::StickMoved { .. }
+            | JoyConEvent::GyroUpdate { .. }
+            | JoyConEvent::Motion { .. }
+            | JoyConEvent::MouseSensor { .. }
+            | JoyConEvent::RawPacket { .. }
+            | JoyConEvent::TelemetryProbe { .. }
+            | JoyConEvent::SignalStrength { .. }
+    )
+}
+
+/// Sends controller events to the executor without blocking the controller loops on stick
+/// and motion updates
+/// 
+/// When the executor falls behind and the channel is full, the oldest queued mergeable
+/// event or the new one is dropped (`settings.event_channel_full`) and logged. Button and
+/// connection events are never dropped for a newer one: they wait in a pending queue
+/// (up to `MAX_PENDING_EVENTS`) that is flushed, in order, by the next `send` or `flush`.
+/// Nothing here ever blocks.
+#[derive(Debug, Clone)]
+struct EventSender {
+    sender: Sender<JoyConEvent>,
+    /// Receiving end, to make room by dropping the oldest event
+    receiver: Receiver<JoyConEvent>,
+    policy: ChannelFullPolicy,
+    /// Events dropped so far, shared by all clones
+    dropped: Arc<AtomicU64>,
+    /// Lossless events waiting for room, shared by all clones so they stay in order.
+    /// Locked while sending, so making room can't reorder another controller's events.
+    pending: Arc<Mutex<VecDeque<JoyConEvent>>>,
+}
+
+impl EventSender {
+    fn new(sender: Sender<JoyConEvent>, receiver: Receiver<JoyConEvent>, policy: ChannelFullPolicy) -> Self {
+        Self { sender, receiver, policy, dropped: Arc::new(AtomicU64::new(0)), pending: Arc::new(Mutex::new(VecDeque::new())) }
+    }
+    
+    fn send(&self, event: JoyConEvent) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.push_back(event);
+        if self.send_pending(&mut pending) {
+            return;
+        }
+        
+        if self.policy == ChannelFullPolicy::DropOldest && self.drop_oldest_mergeable() {
+            self.send_pending(&mut pending);
+        }
+        
+        // Mergeable events never wait, lossless ones stay queued for the next send or flush
+        pending.retain(|event| {
+            let waits = !is_mergeable(event);
+            if !waits {
+                self.record_drop(event);
+            }
+            waits
+        });
+        while pending.len() > MAX_PENDING_EVENTS {
+            if let Some(event) = pending.pop_front() {
+                warn!("Executor stalled with {} events pending, lost {:?}", MAX_PENDING_EVENTS, event);
+                self.record_drop(&event);
+            }
+        }
+    }
+    
+    /// Send the events waiting for room, called by the controller loops on every iteration
+    fn flush(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        if !pending.is_empty() {
+            self.send_pending(&mut pending);
+        }
+    }
+    
+    /// Send pending events in order until the channel is full, true when all were sent
+    fn send_pending(&self, pending: &mut VecDeque<JoyConEvent>) -> bool {
+        while let Some(event) = pending.pop_front() {
+            match self.sender.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(event)) => {
+                    pending.push_front(event);
+                    return false;
+                }
+                // The executor is gone
+                Err(TrySendError::Disconnected(_)) => pending.clear(),
+            }
+        }
+        true
+    }
+    
+    /// Drop the oldest queued mergeable event, keeping the others in order
+    /// 
+    /// Returns false when the queue holds only lossless events.
+    fn drop_oldest_mergeable(&self) -> bool {
+        let queued: Vec<JoyConEvent> = self.receiver.try_iter().collect();
+        let oldest = queued.iter().position(is_mergeable);
+        for (index, event) in queued.into_iter().enumerate() {
+            if Some(index) == oldest {
+                self.record_drop(&event);
+            } else if let Err(TrySendError::Full(event)) = self.sender.try_send(event) {
+                self.record_drop(&event);
+            }
+        }
+        oldest.is_some()
+    }
+    
+    fn record_drop(&self, event: &JoyConEvent) {
+        let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        debug!("Event channel full, dropped {:?}", event);
+        if dropped == 1 || dropped.is_multiple_of(100) {
+            warn!("Executor is falling behind: {} controller events dropped so far", dropped);
+        }
+    }
+    
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Controller commands `handle_commands` runs, implemented by `JoyConConnection`
+trait CommandTarget {
+    fn side(&self) -> Side;
+    fn play_rumble(&mut self, intensity: u8, duration: std::time::Duration);
+    fn play_rumble_pattern(&mut self, pattern: RumblePattern);
+    async fn poll_rumble(&mut self) -> Result<(), JoyConError>;
+    async fn set_player_led(&mut self, leds: u8) -> Result<(), JoyConError>;
+}
+
+impl CommandTarget for JoyConConnection {
+    fn side(&self) -> Side {
+        JoyConConnection::side(self)
+    }
+    
+    fn play_rumble(&mut self, intensity: u8, duration: std::time::Duration) {
+        JoyConConnection::play_rumble(self, intensity, duration)
+    }
+    
+    fn play_rumble_pattern(&mut self, pattern: RumblePattern) {
+        JoyConConnection::play_rumble_pattern(self, pattern)
+    }
+    
+    async fn poll_rumble(&mut self) -> Result<(), JoyConError> {
+        JoyConConnection::poll_rumble(self).await
+    }
+    
+    async fn set_player_led(&mut self, leds: u8) -> Result<(), JoyConError> {
+        JoyConConnection::set_player_led(self, leds).await
+    }
+}
+
+/// Shared handles and settings passed to each controller loop
+#[derive(Clone)]
+struct ControllerContext {
+    sender: EventSender,
+    running: Arc<AtomicBool>,
+    /// Shared status, also guards against connecting the same MAC twice
+    state: Arc<RwLock<ManagerState>>,
+    pending_macs: Arc<Mutex<HashSet<String>>>,
+    command_receiver: Receiver<ManagerCommand>,
+    /// Emit raw notification packets as events
+    raw_packets: bool,
+    /// Shake detector template, each connection gets a fresh copy
+    shake_detector: ShakeDetector,
+    /// Sync combo detector for this side, None when disabled
+    sync_combo: Option<ComboHoldDetector>,
+    /// Stick drift detector, None when disabled
+    drift_detector: Option<StickDriftDetector>,
+    /// Move the calibration center to where a drifting stick rests
+    drift_recenter: bool,
+    /// Profile switch requests for the executor thread
+    profile_sender: Sender<String>,
+    /// Buttons that pick a profile when held while connecting
+    startup_profiles: HashMap<ButtonType, String>,
+    /// How long after connecting a held startup button still counts
+    startup_select: std::time::Duration,
+    /// Interval between keepalive commands, None when disabled
+    keepalive_interval: Option<std::time::Duration>,
+    /// Cache updated with each controller's device info
+    mac_cache: Arc<Mutex<ControllerCache>>,
+    /// Report A/B and X/Y swapped (Xbox layout)
+    swap_ab_xy: bool,
+    /// Minimum stick axis change that emits a StickMoved event
+    stick_threshold: f32,
+    /// Minimum gyro axis change (deg/s) that emits a GyroUpdate event
+    gyro_threshold: f32,
+    /// Play the connection vibration when a controller initializes
+    vibration_enabled: bool,
+    /// Ignore button transitions this soon after the previous one, zero when disabled
+    debounce: std::time::Duration,
+    /// Stick calibrations in progress, the loop records raw samples for its side
+    calibrations: Arc<Mutex<HashMap<ControllerSide, CalibrationSession>>>,
+}
 
 /// Manager for handling Joy-Con 2 controllers
 pub struct JoyConManager<K, M>
@@ -30,17 +569,42 @@ where
     config: Config,
     keyboard: K,
     mouse: M,
-    event_sender: Sender<JoyConEvent>,
+    event_sender: EventSender,
     event_receiver: Receiver<JoyConEvent>,
+    /// Events derived by the executor (e.g. `StickActive`), dropped when not read
+    executor_event_sender: Sender<JoyConEvent>,
+    executor_event_receiver: Receiver<JoyConEvent>,
     /// Running flag
     running: Arc<AtomicBool>,
-    /// Track MAC addresses of connected controllers to avoid duplicates
-    connected_macs: Arc<Mutex<HashSet<String>>>,
+    /// Controller, profile and sensitivity status shared with status displays
+    state: Arc<RwLock<ManagerState>>,
+    /// MACs handed to a controller thread that haven't registered as connected yet
+    pending_macs: Arc<Mutex<HashSet<String>>>,
     /// Controller cache for quick reconnection
     mac_cache: Arc<Mutex<ControllerCache>>,
     /// Channel to send discovered peripherals to controller threads
     peripheral_sender: Sender<(Peripheral, Side, String)>,
     peripheral_receiver: Receiver<(Peripheral, Side, String)>,
+    /// Per-side channels for commands routed from the executor to the controller threads
+    left_command_sender: Sender<ManagerCommand>,
+    left_command_receiver: Receiver<ManagerCommand>,
+    right_command_sender: Sender<ManagerCommand>,
+    right_command_receiver: Receiver<ManagerCommand>,
+    /// Runtime controls (profile, gyro mouse, pause) applied by the executor thread
+    control_sender: Sender<ControlCommand>,
+    control_receiver: Receiver<ControlCommand>,
+    /// Virtual gamepad handed to the executor (for gamepad-mode mappings)
+    gamepad: Option<Arc<dyn GamepadBackend>>,
+    /// Stick calibrations in progress (see `run_calibration`)
+    calibrations: Arc<Mutex<HashMap<ControllerSide, CalibrationSession>>>,
+    /// Shared runtime for the scanner and controller tasks (created on first start)
+    runtime: Option<Runtime>,
+    /// Scanner and controller tasks, awaited on shutdown
+    tasks: Vec<JoinHandle<()>>,
+    /// Executor thread, joined on stop before the backends shut down
+    executor_thread: Option<thread::JoinHandle<()>>,
+    /// Backends were initialized by `start` and need a shutdown
+    backends_initialized: bool,
 }
 
 impl<K, M> JoyConManager<K, M>
@@ -51,26 +615,53 @@ where
     /// Create a new Joy-Con manager
     pub fn new(config: Config, keyboard: K, mouse: M) -> Self {
         let (event_sender, event_receiver) = bounded(100);
+        let event_sender = EventSender::new(event_sender, event_receiver.clone(), config.settings.event_channel_full);
+        let (executor_event_sender, executor_event_receiver) = bounded(100);
         let (peripheral_sender, peripheral_receiver) = bounded(10);
+        let (left_command_sender, left_command_receiver) = bounded(10);
+        let (right_command_sender, right_command_receiver) = bounded(10);
+        let (control_sender, control_receiver) = bounded(10);
         
         // Load MAC cache from disk
         let mac_cache = ControllerCache::load();
         info!("Loaded {} cached controllers", mac_cache.len());
         
+        let state = ManagerState::new(&config);
+        
         Self {
             config,
             keyboard,
             mouse,
             event_sender,
             event_receiver,
+            executor_event_sender,
+            executor_event_receiver,
             running: Arc::new(AtomicBool::new(false)),
-            connected_macs: Arc::new(Mutex::new(HashSet::new())),
+            state: Arc::new(RwLock::new(state)),
+            pending_macs: Arc::new(Mutex::new(HashSet::new())),
             mac_cache: Arc::new(Mutex::new(mac_cache)),
             peripheral_sender,
             peripheral_receiver,
+            left_command_sender,
+            left_command_receiver,
+            right_command_sender,
+            right_command_receiver,
+            control_sender,
+            control_receiver,
+            gamepad: None,
+            calibrations: Arc::new(Mutex::new(HashMap::new())),
+            runtime: None,
+            tasks: Vec::new(),
+            executor_thread: None,
+            backends_initialized: false,
         }
     }
     
+    /// Set the virtual gamepad driven by gamepad-mode mappings (takes effect on the next `start`)
+    pub fn set_gamepad_backend(&mut self, gamepad: impl GamepadBackend + 'static) {
+        self.gamepad = Some(Arc::new(gamepad));
+    }
+    
     /// Start the manager - scans for controllers and starts event processing
     pub fn start(&mut self) -> Result<(), Box<dyn Error>> {
         if self.running.load(Ordering::SeqCst) {
@@ -79,30 +670,152 @@ where
         
         self.running.store(true, Ordering::SeqCst);
         
+        // Undo whatever was started before the failure, so a later start begins clean
+        if let Err(e) = self.start_components() {
+            self.shutdown();
+            return Err(e);
+        }
+        Ok(())
+    }
+    
+    /// Backends, executor thread and tasks of `start` (the running flag is already set)
+    fn start_components(&mut self) -> Result<(), Box<dyn Error>> {
         info!("Starting Joy-Con Manager...");
+        if !self.keyboard.is_available() || !self.mouse.is_available() {
+            warn!("⚠️  No input backend for this platform: controllers are read, but keyboard/mouse input injection is DISABLED");
+        }
+        
+        // Only the first controller to connect can pick a startup profile, not later reconnects
+        self.state.write().unwrap().startup_select_armed = !self.config.settings.startup_profiles.is_empty();
+        
+        // Backends are set up before the executor (which gets clones of them) starts
+        self.init_backends()?;
         
         // Start executor thread
-        self.start_executor_thread();
+        let (profile_sender, profile_receiver) = bounded(10);
+        self.executor_thread = Some(self.start_executor_thread(profile_receiver));
+        
+        // Start foreground window watcher for automatic profile switching
+        #[cfg(windows)]
+        if !self.config.settings.window_profiles.is_empty() {
+            crate::mapping::window_profiles::spawn_window_watcher(
+                self.config.settings.window_profiles.clone(),
+                Arc::clone(&self.running),
+                profile_sender.clone(),
+            )?;
+        }
+        
+        // One runtime drives the scanner and all controller tasks
+        if self.runtime.is_none() {
+            self.runtime = Some(
+                tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .thread_name("joy2-runtime")
+                    .build()?
+            );
+        }
+        let runtime = self.runtime.as_ref().map(|rt| rt.handle().clone()).unwrap();
         
-        // Start single scan thread that finds both controllers
+        // Start single scan task that finds both controllers
         info!("Starting controller scanner...");
-        self.start_scan_thread()?;
+        let scan_task = self.start_scan_task(&runtime);
+        self.tasks.push(scan_task);
         
-        // Start controller handler threads (one for each side)
+        // Start controller handler tasks (one for each side)
         info!("Starting controller handlers...");
-        self.start_controller_thread(Side::Left)?;
-        self.start_controller_thread(Side::Right)?;
+        let expected_sides = self.expected_sides();
+        for side in &expected_sides {
+            let controller_task = self.start_controller_task(&runtime, *side, profile_sender.clone());
+            self.tasks.push(controller_task);
+        }
         
-        info!("✓ Manager started! Scanning for controllers...");
+        info!("✓ Manager started! Scanning for controllers: {:?}", expected_sides);
         info!("  Press the sync button on your Joy-Cons");
         
         Ok(())
     }
     
     /// Stop the manager
+    /// 
+    /// Waits for the executor thread to finish, shuts the backends down, then waits up to
+    /// `SHUTDOWN_TIMEOUT` for the scanner and controller tasks to disconnect (aborting them after).
     pub fn stop(&mut self) {
         info!("Stopping Joy-Con Manager...");
+        self.shutdown();
+    }
+    
+    /// Clear the running flag and wind down everything `start` set up
+    fn shutdown(&mut self) {
         self.running.store(false, Ordering::SeqCst);
+        self.join_executor_thread();
+        self.shutdown_backends();
+        self.finish_tasks();
+    }
+    
+    /// Wait for the tasks to notice the running flag, then abort any that are left
+    /// 
+    /// Waits on a channel rather than `block_on`, so it also works inside a tokio context.
+    fn finish_tasks(&mut self) {
+        let tasks = std::mem::take(&mut self.tasks);
+        let Some(runtime) = &self.runtime else {
+            return;
+        };
+        if tasks.is_empty() {
+            return;
+        }
+        
+        let abort_handles: Vec<_> = tasks.iter().map(|task| task.abort_handle()).collect();
+        let (done_sender, done_receiver) = bounded(1);
+        runtime.spawn(async move {
+            let finished = tokio::time::timeout(SHUTDOWN_TIMEOUT, futures::future::join_all(tasks)).await.is_ok();
+            let _ = done_sender.send(finished);
+        });
+        if done_receiver.recv_timeout(SHUTDOWN_TIMEOUT * 2) != Ok(true) {
+            warn!("Controller tasks did not exit within {:?}, aborting them", SHUTDOWN_TIMEOUT);
+        }
+        for handle in abort_handles {
+            handle.abort();
+        }
+    }
+    
+    /// Initialize the keyboard, mouse and gamepad backends in that order
+    /// 
+    /// On failure the backends initialized so far are shut down again.
+    fn init_backends(&mut self) -> Result<(), crate::backend::BackendError> {
+        self.keyboard.init()?;
+        if let Err(e) = self.mouse.init() {
+            self.keyboard.shutdown();
+            return Err(e);
+        }
+        if let Some(gamepad) = &self.gamepad {
+            if let Err(e) = gamepad.init() {
+                self.mouse.shutdown();
+                self.keyboard.shutdown();
+                return Err(e);
+            }
+        }
+        self.backends_initialized = true;
+        Ok(())
+    }
+    
+    /// Shut the backends down in reverse order of `init_backends` (once per start)
+    fn shutdown_backends(&mut self) {
+        if !std::mem::take(&mut self.backends_initialized) {
+            return;
+        }
+        if let Some(gamepad) = &self.gamepad {
+            gamepad.shutdown();
+        }
+        self.mouse.shutdown();
+        self.keyboard.shutdown();
+    }
+    
+    fn join_executor_thread(&mut self) {
+        if let Some(handle) = self.executor_thread.take() {
+            if handle.join().is_err() {
+                warn!("Executor thread panicked");
+            }
+        }
     }
     
     /// Check if the manager is running
@@ -110,46 +823,380 @@ where
         self.running.load(Ordering::SeqCst)
     }
     
+    /// Controller sides the manager scans for and waits on (from `expected_controllers`)
+    pub fn expected_sides(&self) -> Vec<Side> {
+        let mut sides = Vec::new();
+        for side in &self.config.settings.expected_controllers {
+            let side = match side {
+                ControllerSide::Left => Side::Left,
+                ControllerSide::Right => Side::Right,
+            };
+            if !sides.contains(&side) {
+                sides.push(side);
+            }
+        }
+        sides
+    }
+    
+    /// Shared status for GUIs and overlays, read it with `state().read()`
+    pub fn state(&self) -> Arc<RwLock<ManagerState>> {
+        Arc::clone(&self.state)
+    }
+    
+    /// Name of the active profile (empty if the config has no profiles)
+    pub fn current_profile_name(&self) -> &str {
+        self.config.profiles.get(self.current_profile_index())
+            .map(|p| p.name.as_str())
+            .unwrap_or("")
+    }
+    
+    /// Index of the active profile in the config
+    pub fn current_profile_index(&self) -> usize {
+        self.state.read().unwrap().profile_index
+    }
+    
+    /// Effective sensitivity factor (current level including held clutches)
+    pub fn current_sensitivity(&self) -> f32 {
+        self.state.read().unwrap().sensitivity
+    }
+    
+    /// Whether input injection is paused by a `togglepause` button
+    pub fn is_paused(&self) -> bool {
+        self.state.read().unwrap().paused
+    }
+    
+    /// Latest RSSI (dBm) of the controller on `side`
+    ///
+    /// `None` while the side is disconnected or the platform doesn't report RSSI.
+    pub fn signal_strength(&self, side: ControllerSide) -> Option<i16> {
+        self.state.read().unwrap().controller(side).signal_strength
+    }
+    
+    /// Input reports per second from the controller on `side`, updated every second
+    pub fn packet_rate(&self, side: ControllerSide) -> Option<f32> {
+        self.state.read().unwrap().controller(side).packet_hz
+    }
+    
+    /// Average delay (ms) from a packet of the controller on `side` arriving until the
+    /// executor processed its events
+    pub fn latency_ms(&self, side: ControllerSide) -> Option<f32> {
+        self.state.read().unwrap().controller(side).avg_latency_ms
+    }
+    
+    /// Handle for changing the profile, gyro mouse and pause state from other threads
+    /// 
+    /// Changes are applied by the executor thread while the manager is running.
+    pub fn control_handle(&self) -> ControlHandle {
+        ControlHandle {
+            sender: self.control_sender.clone(),
+            state: Arc::clone(&self.state),
+            profiles: self.config.profiles.iter().map(|p| p.name.clone()).collect(),
+            running: Arc::clone(&self.running),
+        }
+    }
+    
+    /// Accept text commands (`set-profile ETS2`, `toggle-gyro right`, `pause`, `status`, ...)
+    /// on a TCP address, for automation tools like a Stream Deck
+    /// 
+    /// Commands go through `control_handle()`, see the `control` module for the protocol.
+    /// Call after `start`, the server stops with the manager. Returns the bound address
+    /// (useful with port 0).
+    /// 
+    /// There is no authentication: anyone who can connect can switch profiles and pause
+    /// input, so bind to a loopback address such as `127.0.0.1:7878` only.
+    pub fn start_control_server(&self, addr: impl std::net::ToSocketAddrs) -> std::io::Result<std::net::SocketAddr> {
+        if !self.is_running() {
+            return Err(std::io::Error::other("Manager is not running"));
+        }
+        let listener = std::net::TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        control::serve(listener, self.control_handle())?;
+        info!("✓ Control server listening on {}", local_addr);
+        Ok(local_addr)
+    }
+    
+    /// Block until the controller on `side` is connected and streaming input, or `timeout` elapses
+    /// 
+    /// Returns right away if it already is. Checks the status in `state()`, which is set just
+    /// before the `Connected` event is sent, so no events are taken from the event receivers.
+    /// Call after `start`: a manager that isn't running (or stops while waiting) returns `NotRunning`.
+    pub fn wait_for_connection(&self, side: ControllerSide, timeout: std::time::Duration) -> Result<(), WaitError> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            if self.state.read().unwrap().controller(side).connected {
+                return Ok(());
+            }
+            if !self.running.load(Ordering::SeqCst) {
+                return Err(WaitError::NotRunning);
+            }
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(WaitError::Timeout(side, timeout));
+            }
+            thread::sleep(remaining.min(CONNECTION_POLL_INTERVAL));
+        }
+    }
+    
+    /// Calibrate the stick of the controller on `side` (blocks for about 7 seconds)
+    /// 
+    /// Guides the user through the log and `JoyConEvent::CalibrationProgress` events on the
+    /// executor event receiver: the stick is recorded at rest for its center, then while
+    /// being rotated fully for its range. The stick sends no events meanwhile. The result
+    /// is used right away and, with `persist`, saved in the controller cache and applied
+    /// whenever this controller connects.
+    pub fn run_calibration(&self, side: ControllerSide, persist: bool) -> Result<StickCalibration, CalibrationError> {
+        let mac_address = {
+            let state = self.state.read().unwrap();
+            let controller = state.controller(side);
+            match (&controller.mac_address, controller.connected) {
+                (Some(mac), true) => mac.clone(),
+                _ => return Err(CalibrationError::NotConnected(side)),
+            }
+        };
+        {
+            let mut calibrations = self.calibrations.lock().unwrap();
+            if calibrations.contains_key(&side) {
+                return Err(CalibrationError::AlreadyRunning(side));
+            }
+            calibrations.insert(side, CalibrationSession::default());
+        }
+        
+        let result = self.record_calibration(side);
+        let step = match &result {
+            Ok(calibration) => {
+                if let Some(session) = self.calibrations.lock().unwrap().get_mut(&side) {
+                    session.apply = Some(*calibration);
+                }
+                info!("✓ {:?} stick calibrated: {:?}", side, calibration);
+                CalibrationStep::Done
+            }
+            Err(e) => {
+                self.calibrations.lock().unwrap().remove(&side);
+                warn!("{:?} stick calibration failed: {}", side, e);
+                CalibrationStep::Failed
+            }
+        };
+        self.send_calibration_progress(side, step, 1.0, None);
+        
+        let calibration = result?;
+        if persist {
+            let mut cache = self.mac_cache.lock().unwrap();
+            if cache.set_stick_calibration(&mac_address, calibration) {
+                if let Err(e) = cache.save() {
+                    warn!("Failed to save stick calibration: {}", e);
+                }
+            }
+        }
+        Ok(calibration)
+    }
+    
+    /// Stop a running `run_calibration` on `side`, which then returns `Cancelled`
+    pub fn cancel_calibration(&self, side: ControllerSide) {
+        if let Some(session) = self.calibrations.lock().unwrap().get_mut(&side) {
+            session.cancelled = true;
+        }
+    }
+    
+    /// Record the center and range steps and compute the calibration
+    fn record_calibration(&self, side: ControllerSide) -> Result<StickCalibration, CalibrationError> {
+        info!("Calibrating {:?} stick: leave it at rest...", side);
+        let rest = self.collect_calibration_samples(side, CalibrationStep::Center, CALIBRATION_REST)?;
+        info!("Calibrating {:?} stick: rotate it fully a few times...", side);
+        let rotation = self.collect_calibration_samples(side, CalibrationStep::Rotate, CALIBRATION_ROTATE)?;
+        StickCalibration::from_samples(&rest, &rotation).ok_or(CalibrationError::InsufficientRange)
+    }
+    
+    /// Collect the raw samples the controller loop records during one step
+    fn collect_calibration_samples(
+        &self,
+        side: ControllerSide,
+        step: CalibrationStep,
+        duration: std::time::Duration,
+    ) -> Result<Vec<(u16, u16)>, CalibrationError> {
+        let start = std::time::Instant::now();
+        let mut samples = Vec::new();
+        loop {
+            thread::sleep(CALIBRATION_POLL_INTERVAL);
+            {
+                let mut calibrations = self.calibrations.lock().unwrap();
+                let session = calibrations.get_mut(&side).ok_or(CalibrationError::Cancelled)?;
+                if session.cancelled || !self.running.load(Ordering::SeqCst) {
+                    return Err(CalibrationError::Cancelled);
+                }
+                samples.append(&mut session.samples);
+            }
+            if !self.state.read().unwrap().controller(side).connected {
+                return Err(CalibrationError::NotConnected(side));
+            }
+            
+            let progress = (start.elapsed().as_secs_f32() / duration.as_secs_f32()).min(1.0);
+            self.send_calibration_progress(side, step, progress, samples.last().copied());
+            if progress >= 1.0 {
+                break;
+            }
+        }
+        
+        if samples.is_empty() {
+            return Err(CalibrationError::NoInput(side));
+        }
+        Ok(samples)
+    }
+    
+    fn send_calibration_progress(&self, side: ControllerSide, step: CalibrationStep, progress: f32, raw: Option<(u16, u16)>) {
+        let (raw_x, raw_y) = raw.unwrap_or_default();
+        let _ = self.executor_event_sender.try_send(JoyConEvent::CalibrationProgress { side, step, progress, raw_x, raw_y });
+    }
+    
+    /// Controller events dropped because the executor fell behind (see `event_channel_full`)
+    pub fn dropped_events(&self) -> u64 {
+        self.event_sender.dropped()
+    }
+    
     /// Get the event receiver (for external event processing)
     pub fn get_event_receiver(&self) -> &Receiver<JoyConEvent> {
         &self.event_receiver
     }
     
-    /// Start the scanner thread that finds both Left and Right controllers
-    fn start_scan_thread(&self) -> Result<(), Box<dyn Error>> {
+    /// Get the receiver for events derived by the executor (e.g. `StickActive` for overlays)
+    pub fn get_executor_event_receiver(&self) -> &Receiver<JoyConEvent> {
+        &self.executor_event_receiver
+    }
+    
+    /// Start the scanner task that finds the expected Left and/or Right controllers
+    fn start_scan_task(&self, runtime: &Handle) -> JoinHandle<()> {
         let peripheral_sender = self.peripheral_sender.clone();
         let running = Arc::clone(&self.running);
-        let connected_macs = Arc::clone(&self.connected_macs);
+        let state = Arc::clone(&self.state);
+        let pending_macs = Arc::clone(&self.pending_macs);
         let mac_cache = Arc::clone(&self.mac_cache);
+        let prefer_cached = self.config.settings.prefer_cached;
+        let expected_sides = self.expected_sides();
+        let mut scan_state = ScanState {
+            expected_sides: expected_sides.clone(),
+            state: Arc::clone(&self.state),
+            event_sender: self.event_sender.clone(),
+            scan_once: self.config.settings.scan_once,
+            scan_timeout: match self.config.settings.scan_timeout_secs {
+                0 => None,
+                secs => Some(std::time::Duration::from_secs(secs)),
+            },
+            waiting_since: HashMap::new(),
+            timed_out: HashSet::new(),
+        };
         
-        thread::Builder::new()
-            .name("scanner".to_string())
-            .spawn(move || {
-                let rt = Runtime::new().expect("Failed to create tokio runtime");
+        runtime.spawn(async move {
+            info!("Scanner task started");
+            
+            // Fast path: try known controllers first, then fall back to scanning
+            if prefer_cached {
+                if let Err(e) = Self::connect_cached_controllers(
+                    &peripheral_sender,
+                    &state,
+                    &pending_macs,
+                    &mac_cache,
+                    &expected_sides
+                ).await {
+                    warn!("Cached controller lookup failed: {}", e);
+                }
+            }
+            
+            while running.load(Ordering::SeqCst) {
+                // Nothing to look for (all connected in scan_once mode, or timed out)
+                if scan_state.wanted_sides().is_empty() {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                    continue;
+                }
                 
-                rt.block_on(async {
-                    info!("Scanner thread started");
-                    
-                    while running.load(Ordering::SeqCst) {
-                        match Self::scan_for_controllers(
-                            peripheral_sender.clone(),
-                            running.clone(),
-                            connected_macs.clone(),
-                            mac_cache.clone()
-                        ).await {
-                            Ok(_) => {
-                                debug!("Scan cycle completed");
-                            }
-                            Err(e) => {
-                                warn!("Scan error: {}, retrying in 5 seconds...", e);
-                                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                            }
-                        }
+                match Self::scan_for_controllers(
+                    peripheral_sender.clone(),
+                    running.clone(),
+                    state.clone(),
+                    pending_macs.clone(),
+                    mac_cache.clone(),
+                    &mut scan_state
+                ).await {
+                    Ok(_) => {
+                        debug!("Scan cycle completed");
                     }
+                    Err(e) => {
+                        warn!("Scan error: {}, retrying in 5 seconds...", e);
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
+                }
+            }
+            
+            info!("Scanner task exited");
+        })
+    }
+    
+    /// Try to connect directly to controllers from the MAC cache
+    /// 
+    /// Each cached controller the adapter already knows about gets a short connect
+    /// attempt. Controllers that are absent or don't answer in time are skipped, so
+    /// the regular advertisement scan can still discover new ones.
+    async fn connect_cached_controllers(
+        peripheral_sender: &Sender<(Peripheral, Side, String)>,
+        state: &Arc<RwLock<ManagerState>>,
+        pending_macs: &Arc<Mutex<HashSet<String>>>,
+        mac_cache: &Arc<Mutex<ControllerCache>>,
+        expected_sides: &[Side],
+    ) -> Result<(), JoyConError> {
+        use btleplug::api::{Central, Manager as _};
+        use btleplug::platform::Manager;
+        
+        let cached: Vec<(String, Side)> = {
+            let cache = mac_cache.lock().unwrap();
+            cache.list_all()
+                .into_iter()
+                .map(|c| (c.mac_address.clone(), c.side.into()))
+                .filter(|(_, side)| expected_sides.contains(side))
+                .collect()
+        };
+        
+        if cached.is_empty() {
+            return Ok(());
+        }
+        
+        let manager = Manager::new().await?;
+        let adapter = manager.adapters().await?
+            .into_iter()
+            .next()
+            .ok_or(JoyConError::NoAdapter)?;
+        
+        for peripheral in adapter.peripherals().await? {
+            let mac_address = peripheral.address().to_string();
+            
+            let Some((_, side)) = cached.iter().find(|(mac, _)| *mac == mac_address) else {
+                continue;
+            };
+            
+            if state.read().unwrap().has_mac(&mac_address)
+                || pending_macs.lock().unwrap().contains(&mac_address)
+            {
+                continue;
+            }
+            
+            info!("Trying cached {:?} Joy-Con: {}", side, mac_address);
+            
+            match tokio::time::timeout(CACHED_CONNECT_TIMEOUT, peripheral.connect()).await {
+                Ok(Ok(())) => {
+                    info!("✓ Reconnected to cached {:?} Joy-Con: {}", side, mac_address);
+                    Self::dispatch_peripheral(peripheral_sender, pending_macs, peripheral, *side, &mac_address);
                     
-                    info!("Scanner thread exited");
-                });
-            })?;
+                    // Refresh last_seen, keeping the cached name
+                    let mut cache = mac_cache.lock().unwrap();
+                    let name = cache.get_controller(&mac_address).and_then(|c| c.name.clone());
+                    cache.add_controller(mac_address, *side, name);
+                    let _ = cache.save();
+                }
+                Ok(Err(e)) => {
+                    debug!("Cached controller {} not reachable: {}", mac_address, e);
+                }
+                Err(_) => {
+                    debug!("Cached controller {} timed out, falling back to scan", mac_address);
+                }
+            }
+        }
         
         Ok(())
     }
@@ -158,21 +1205,22 @@ where
     async fn scan_for_controllers(
         peripheral_sender: Sender<(Peripheral, Side, String)>,
         running: Arc<AtomicBool>,
-        connected_macs: Arc<Mutex<HashSet<String>>>,
+        state: Arc<RwLock<ManagerState>>,
+        pending_macs: Arc<Mutex<HashSet<String>>>,
         mac_cache: Arc<Mutex<ControllerCache>>,
-    ) -> Result<(), Box<dyn Error>> {
+        scan_state: &mut ScanState,
+    ) -> Result<(), JoyConError> {
         use btleplug::api::{Central, Manager as _, CentralEvent};
         use btleplug::platform::Manager;
-        use crate::joycon2::constants::{NINTENDO_COMPANY_ID, JOYCON_DATA_PREFIX};
+        use crate::joycon2::constants::NINTENDO_COMPANY_ID;
         
         let manager = Manager::new().await?;
-        let adapters = manager.adapters().await?;
-        
-        if adapters.is_empty() {
-            return Err("No Bluetooth adapters found".into());
-        }
-        
-        let adapter = adapters.into_iter().next().unwrap();
+        let adapter = manager.adapters().await?
+            .into_iter()
+            .next()
+            .ok_or(JoyConError::NoAdapter)?;
+        let expected_sides = scan_state.wanted_sides();
+        debug!("Scanning for {:?}", expected_sides);
         adapter.start_scan(Default::default()).await?;
         
         let mut events = adapter.events().await?;
@@ -182,56 +1230,51 @@ where
             tokio::select! {
                 Some(event) = events.next() => {
                     if let CentralEvent::ManufacturerDataAdvertisement { id, manufacturer_data } = event {
-                        // Check if manufacturer data contains Nintendo company ID
-                        if let Some(data) = manufacturer_data.get(&NINTENDO_COMPANY_ID) {
-                            // Check if data starts with Joy-Con prefix
-                            if data.len() >= JOYCON_DATA_PREFIX.len()
-                                && data[..JOYCON_DATA_PREFIX.len()] == JOYCON_DATA_PREFIX
+                        // Only Joy-Con 2 controllers of the sides we were asked to handle
+                        let side = manufacturer_data.get(&NINTENDO_COMPANY_ID)
+                            .and_then(|data| detect_side(data))
+                            .filter(|side| expected_sides.contains(side));
+                        
+                        if let Some(side) = side {
+                            let peripheral = adapter.peripheral(&id).await?;
+                            let properties = peripheral.properties().await?.unwrap();
+                            let mac_address = properties.address.to_string();
+                            
+                            // Check if already connected
+                            if state.read().unwrap().has_mac(&mac_address) {
+                                continue; // Skip already connected controller
+                            }
+                            
+                            // Repeated advertisements arrive while a connection is in progress
+                            if pending_macs.lock().unwrap().contains(&mac_address) {
+                                debug!("Suppressed duplicate advertisement from {} (connection pending)", mac_address);
+                                continue;
+                            }
+                            
+                            let name = properties.local_name.unwrap_or_else(|| "Unknown".to_string());
+                            
+                            info!("✓ Found {:?} Joy-Con: {} ({})", side, name, mac_address);
+                            
+                            // Send to appropriate handler thread
+                            Self::dispatch_peripheral(&peripheral_sender, &pending_macs, peripheral, side, &mac_address);
+                            
+                            // Cache this controller
                             {
-                                // Determine side from byte 5
-                                if data.len() >= 6 {
-                                    let side_byte = data[5];
-                                    
-                                    let side = match side_byte {
-                                        0x67 => Some(Side::Left),
-                                        0x66 => Some(Side::Right),
-                                        _ => None,
-                                    };
-                                    
-                                    if let Some(side) = side {
-                                        let peripheral = adapter.peripheral(&id).await?;
-                                        let properties = peripheral.properties().await?.unwrap();
-                                        let mac_address = properties.address.to_string();
-                                        
-                                        // Check if already connected
-                                        {
-                                            let macs = connected_macs.lock().unwrap();
-                                            if macs.contains(&mac_address) {
-                                                continue; // Skip already connected controller
-                                            }
-                                        }
-                                        
-                                        let name = properties.local_name.unwrap_or_else(|| "Unknown".to_string());
-                                        
-                                        info!("✓ Found {:?} Joy-Con: {} ({})", side, name, mac_address);
-                                        
-                                        // Send to appropriate handler thread
-                                        let _ = peripheral_sender.send((peripheral, side, mac_address.clone()));
-                                        
-                                        // Cache this controller
-                                        {
-                                            let mut cache = mac_cache.lock().unwrap();
-                                            cache.add_controller(mac_address, side, Some(name));
-                                            let _ = cache.save();
-                                        }
-                                    }
-                                }
+                                let mut cache = mac_cache.lock().unwrap();
+                                cache.add_controller(mac_address, side, Some(name));
+                                let _ = cache.save();
                             }
                         }
                     }
                 }
                 _ = tokio::time::sleep(tokio::time::Duration::from_millis(100)) => {
-                    // Periodic check
+                    // Events a disconnected controller's loop left waiting for room
+                    scan_state.event_sender.flush();
+                    
+                    // Restart with a new filter once a side connects (scan_once) or times out
+                    if scan_state.wanted_sides() != expected_sides {
+                        break;
+                    }
                 }
             }
         }
@@ -240,23 +1283,61 @@ where
         Ok(())
     }
     
+    /// Hand a peripheral to the controller threads, marking its MAC as pending until registered
+    fn dispatch_peripheral(
+        peripheral_sender: &Sender<(Peripheral, Side, String)>,
+        pending_macs: &Arc<Mutex<HashSet<String>>>,
+        peripheral: Peripheral,
+        side: Side,
+        mac_address: &str,
+    ) {
+        pending_macs.lock().unwrap().insert(mac_address.to_string());
+        if peripheral_sender.send((peripheral, side, mac_address.to_string())).is_err() {
+            pending_macs.lock().unwrap().remove(mac_address);
+        }
+    }
+    
     /// Start the executor thread
-    fn start_executor_thread(&self) {
+    /// 
+    /// Profile names received on `profile_receiver` switch the active profile.
+    fn start_executor_thread(&self, profile_receiver: Receiver<String>) -> thread::JoinHandle<()> {
         let receiver = self.event_receiver.clone();
         let keyboard = self.keyboard.clone();
         let mouse = self.mouse.clone();
         let config = self.config.clone();
         let running = Arc::clone(&self.running);
+        let left_command_sender = self.left_command_sender.clone();
+        let right_command_sender = self.right_command_sender.clone();
+        let state = Arc::clone(&self.state);
+        let gamepad = self.gamepad.clone();
+        let executor_event_sender = self.executor_event_sender.clone();
+        let control_receiver = self.control_receiver.clone();
         
         thread::Builder::new()
             .name("executor".to_string())
             .spawn(move || {
                 info!("Executor thread started");
                 
+                let (command_sender, command_receiver) = bounded(10);
                 let mut executor = MappingExecutor::new(config, keyboard, mouse);
+                executor.set_command_sender(command_sender);
+                executor.set_event_sender(executor_event_sender.clone());
+                let mut latency: HashMap<ControllerSide, LatencyWindow> = HashMap::new();
+                if let Some(gamepad) = gamepad {
+                    executor.set_gamepad_backend(gamepad);
+                }
                 
                 while running.load(Ordering::SeqCst) {
                     match receiver.recv_timeout(std::time::Duration::from_millis(16)) {
+                        Ok(JoyConEvent::TelemetryProbe { side, packet_hz, received_at }) => {
+                            // Everything sent before the probe has been processed by now
+                            let latency_ms = received_at.elapsed().as_secs_f32() * 1000.0;
+                            let avg_latency_ms = latency.entry(side).or_default().record(latency_ms);
+                            debug!("{:?} controller: {:.0} packets/s, executor delay {:.1}ms (avg {:.1}ms)",
+                                side, packet_hz, latency_ms, avg_latency_ms);
+                            state.write().unwrap().controller_mut(side).avg_latency_ms = Some(avg_latency_ms);
+                            let _ = executor_event_sender.try_send(JoyConEvent::Telemetry { side, packet_hz, avg_latency_ms });
+                        }
                         Ok(event) => {
                             executor.process_event(&event);
                         }
@@ -270,80 +1351,209 @@ where
                         }
                     }
                     
-                    // Always update continuous movements on each loop iteration
-                    // This ensures smooth mouse movement when stick is held
-                    executor.update_continuous_movements();
+                    // Always update continuous movements on each loop iteration
+                    // This ensures smooth mouse movement when stick is held
+                    executor.update_continuous_movements();
+                    
+                    // Apply profile switch requests (e.g. from the window watcher)
+                    while let Ok(profile) = profile_receiver.try_recv() {
+                        executor.set_profile_by_name(&profile);
+                    }
+                    
+                    // Apply runtime controls (e.g. from the control server)
+                    while let Ok(command) = control_receiver.try_recv() {
+                        match command {
+                            ControlCommand::SetProfile(name) => {
+                                executor.set_profile_by_name(&name);
+                            }
+                            ControlCommand::ToggleGyroMouse(side) => executor.toggle_gyro_mouse(side),
+                            ControlCommand::SetPaused(paused) => executor.set_paused(paused),
+                        }
+                    }
+                    
+                    ManagerState::publish(&state, &executor);
+                    
+                    // Route executor commands to the matching controller thread
+                    while let Ok(command) = command_receiver.try_recv() {
+                        let target = match command.side() {
+                            ControllerSide::Left => &left_command_sender,
+                            ControllerSide::Right => &right_command_sender,
+                        };
+                        if target.try_send(command).is_err() {
+                            debug!("Dropped {:?} (controller busy or not connected)", command);
+                        }
+                    }
                 }
                 
                 info!("Executor thread stopped");
             })
-            .expect("Failed to spawn executor thread");
+            .expect("Failed to spawn executor thread")
     }
     
-    /// Start a controller thread for the given side
-    /// This thread waits for peripherals from the scanner thread
-    fn start_controller_thread(&self, side: Side) -> Result<(), Box<dyn Error>> {
-        let sender = self.event_sender.clone();
+    /// Start a controller task for the given side
+    /// This task waits for peripherals from the scanner task
+    fn start_controller_task(&self, runtime: &Handle, side: Side, profile_sender: Sender<String>) -> JoinHandle<()> {
         let running = Arc::clone(&self.running);
-        let connected_macs = Arc::clone(&self.connected_macs);
         let peripheral_receiver = self.peripheral_receiver.clone();
-        
-        let thread_name = match side {
-            Side::Left => "controller-left",
-            Side::Right => "controller-right",
+        let context = ControllerContext {
+            sender: self.event_sender.clone(),
+            running: Arc::clone(&self.running),
+            state: Arc::clone(&self.state),
+            pending_macs: Arc::clone(&self.pending_macs),
+            command_receiver: match side {
+                Side::Left => self.left_command_receiver.clone(),
+                Side::Right => self.right_command_receiver.clone(),
+            },
+            raw_packets: self.config.settings.raw_packets,
+            shake_detector: ShakeDetector::new(
+                self.config.settings.shake_threshold,
+                std::time::Duration::from_millis(self.config.settings.shake_window_ms),
+                std::time::Duration::from_millis(self.config.settings.shake_cooldown_ms),
+            ),
+            sync_combo: (self.config.settings.sync_combo_ms > 0).then(|| ComboHoldDetector::new(
+                match side {
+                    Side::Left => self.config.settings.sync_combo_left.clone(),
+                    Side::Right => self.config.settings.sync_combo_right.clone(),
+                },
+                std::time::Duration::from_millis(self.config.settings.sync_combo_ms),
+            )),
+            drift_detector: (self.config.settings.stick_drift_secs > 0).then(|| StickDriftDetector::new(
+                self.config.settings.stick_drift_threshold,
+                std::time::Duration::from_secs(self.config.settings.stick_drift_secs),
+            )),
+            drift_recenter: self.config.settings.stick_drift_recenter,
+            profile_sender,
+            startup_profiles: self.config.settings.startup_profiles.clone(),
+            startup_select: std::time::Duration::from_millis(self.config.settings.startup_select_ms),
+            keepalive_interval: match self.config.settings.keepalive_interval_secs {
+                0 => None,
+                secs => Some(std::time::Duration::from_secs(secs)),
+            },
+            mac_cache: Arc::clone(&self.mac_cache),
+            swap_ab_xy: self.config.settings.swap_ab_xy,
+            stick_threshold: self.config.settings.stick_event_threshold,
+            gyro_threshold: self.config.settings.gyro_event_threshold,
+            vibration_enabled: self.config.settings.vibration_enabled,
+            debounce: std::time::Duration::from_millis(self.config.settings.debounce_ms),
+            calibrations: Arc::clone(&self.calibrations),
         };
         
-        thread::Builder::new()
-            .name(thread_name.to_string())
-            .spawn(move || {
-                let rt = Runtime::new().expect("Failed to create tokio runtime");
-                
-                rt.block_on(async {
-                    info!("Controller {:?} handler started, waiting for peripheral...", side);
-                    
-                    while running.load(Ordering::SeqCst) {
-                        // Wait for a peripheral from the scanner
-                        match peripheral_receiver.recv_timeout(std::time::Duration::from_secs(1)) {
-                            Ok((peripheral, discovered_side, mac_address)) => {
-                                // Only handle peripherals for our side
-                                if discovered_side != side {
-                                    continue;
-                                }
-                                
-                                info!("Handling {:?} controller: {}", side, mac_address);
-                                
-                                match Self::controller_loop(
-                                    peripheral,
-                                    side,
-                                    mac_address.clone(),
-                                    sender.clone(),
-                                    running.clone(),
-                                    connected_macs.clone()
-                                ).await {
-                                    Ok(_) => {
-                                        info!("Controller {:?} disconnected", side);
-                                    }
-                                    Err(e) => {
-                                        warn!("Controller {:?} error: {}", side, e);
-                                    }
-                                }
-                            }
-                            Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
-                                // No peripheral yet, continue waiting
-                                continue;
+        runtime.spawn(async move {
+            info!("Controller {:?} handler started, waiting for peripheral...", side);
+            
+            while running.load(Ordering::SeqCst) {
+                // Poll for a peripheral from the scanner without blocking a runtime worker
+                match peripheral_receiver.try_recv() {
+                    Ok((peripheral, discovered_side, mac_address)) => {
+                        // Only handle peripherals for our side
+                        if discovered_side != side {
+                            context.pending_macs.lock().unwrap().remove(&mac_address);
+                            continue;
+                        }
+                        
+                        info!("Handling {:?} controller: {}", side, mac_address);
+                        
+                        match Self::controller_loop(
+                            peripheral,
+                            side,
+                            mac_address.clone(),
+                            context.clone()
+                        ).await {
+                            Ok(_) => {
+                                info!("Controller {:?} disconnected", side);
                             }
-                            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
-                                warn!("Peripheral channel disconnected");
-                                break;
+                            Err(e) => {
+                                warn!("Controller {:?} error: {}", side, e);
                             }
                         }
+                        
+                        // Free the side so the scanner looks for it again
+                        *context.state.write().unwrap().controller_mut(side.into()) = ControllerStatus::default();
+                        info!("Controller {:?} (MAC: {}) removed from tracking", side, mac_address);
                     }
-                    
-                    info!("Controller {:?} handler exited", side);
-                });
-            })?;
+                    Err(crossbeam_channel::TryRecvError::Empty) => {
+                        // No peripheral yet, continue waiting
+                        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                    }
+                    Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                        warn!("Peripheral channel disconnected");
+                        break;
+                    }
+                }
+            }
+            
+            info!("Controller {:?} handler exited", side);
+        })
+    }
+    
+    /// Execute pending commands for this controller
+    /// 
+    /// Rumbles only start here and are sent by `poll_rumble`, so input keeps being read meanwhile.
+    async fn handle_commands(connection: &mut impl CommandTarget, command_receiver: &Receiver<ManagerCommand>) {
+        while let Ok(command) = command_receiver.try_recv() {
+            match command {
+                ManagerCommand::Rumble { intensity, duration_ms, .. } => {
+                    connection.play_rumble(intensity, std::time::Duration::from_millis(duration_ms));
+                }
+                ManagerCommand::RumblePattern { pattern, .. } => {
+                    connection.play_rumble_pattern(pattern);
+                }
+                ManagerCommand::SetPlayerLed { leds, .. } => {
+                    if let Err(e) = connection.set_player_led(leds).await {
+                        warn!("Setting player LEDs failed on {:?} controller: {}", connection.side(), e);
+                    }
+                }
+            }
+        }
         
-        Ok(())
+        if let Err(e) = connection.poll_rumble().await {
+            warn!("Rumble failed on {:?} controller: {}", connection.side(), e);
+        }
+    }
+    
+    /// Request the profile of a held `startup_profiles` button until the selection window closes
+    /// 
+    /// Returns the button that picked it.
+    fn check_startup_profile(
+        deadline: &mut Option<std::time::Instant>,
+        startup_profiles: &HashMap<ButtonType, String>,
+        states: &[(ButtonType, bool)],
+        profile_sender: &Sender<String>,
+    ) -> Option<ButtonType> {
+        let until = (*deadline)?;
+        if std::time::Instant::now() > until {
+            *deadline = None;
+            return None;
+        }
+        
+        let (button, profile) = startup_profile(startup_profiles, states)?;
+        info!("Startup profile '{}' selected by held {:?}", profile, button);
+        let _ = profile_sender.try_send(profile.to_string());
+        *deadline = None;
+        Some(button)
+    }
+    
+    /// Read the link RSSI, store it and emit a SignalStrength event
+    ///
+    /// Platforms that don't expose RSSI for connected devices report `None`, which is skipped.
+    async fn poll_signal_strength(
+        connection: &JoyConConnection,
+        side: ControllerSide,
+        state: &RwLock<ManagerState>,
+        sender: &EventSender,
+    ) {
+        let rssi = match connection.peripheral().properties().await {
+            Ok(properties) => properties.and_then(|p| p.rssi),
+            Err(e) => {
+                debug!("Failed to read {:?} controller properties: {}", side, e);
+                None
+            }
+        };
+        
+        if let Some(rssi) = rssi {
+            state.write().unwrap().controller_mut(side).signal_strength = Some(rssi);
+            sender.send(JoyConEvent::SignalStrength { side, rssi });
+        }
     }
     
     /// Main controller loop (runs in async context)
@@ -351,160 +1561,282 @@ where
         peripheral: Peripheral,
         side: Side,
         mac_address: String,
-        sender: Sender<JoyConEvent>,
-        running: Arc<AtomicBool>,
-        connected_macs: Arc<Mutex<HashSet<String>>>,
-    ) -> Result<(), Box<dyn Error>> {
+        context: ControllerContext,
+    ) -> Result<(), JoyConError> {
+        let ControllerContext {
+            sender, running, state, pending_macs, command_receiver, raw_packets, mut shake_detector, mut sync_combo,
+            mut drift_detector, drift_recenter,
+            profile_sender, startup_profiles, startup_select, keepalive_interval, mac_cache, swap_ab_xy,
+            stick_threshold, gyro_threshold, vibration_enabled, debounce, calibrations,
+        } = context;
+        
         let controller_side = match side {
             Side::Left => ControllerSide::Left,
             Side::Right => ControllerSide::Right,
         };
         
-        // Check if this MAC is already connected
+        // Check if this MAC is already connected, otherwise claim it for this side
+        // (the controller task frees the side when this loop returns)
         {
-            let mut macs = connected_macs.lock().unwrap();
-            if macs.contains(&mac_address) {
-                return Err(format!("Controller {} already connected to another side", mac_address).into());
+            let mut state = state.write().unwrap();
+            let already_connected = state.has_mac(&mac_address);
+            
+            // The scanner hand-off is over either way, the shared state now guards duplicates
+            pending_macs.lock().unwrap().remove(&mac_address);
+            
+            if already_connected {
+                return Err(JoyConError::AlreadyConnected(mac_address));
             }
-            // Register this MAC
-            macs.insert(mac_address.clone());
+            state.controller_mut(controller_side).mac_address = Some(mac_address.clone());
         }
         
         // Create connection and initialize
         let mut connection = JoyConConnection::new(peripheral, side);
+        connection.set_vibration_enabled(vibration_enabled);
         
         info!("Connecting to {:?} controller ({})", side, mac_address);
         connection.connect().await?;
         connection.initialize().await?;
         
         info!("✓ Controller {:?} ready! (MAC: {})", side, mac_address);
+        if !connection.device_info().is_empty() {
+            let mut cache = mac_cache.lock().unwrap();
+            if cache.set_device_info(&mac_address, connection.device_info().clone()) {
+                let _ = cache.save();
+            }
+        }
+        state.write().unwrap().controller_mut(controller_side).connected = true;
+        
+        // Drop commands queued while this controller was disconnected
+        // (before announcing it, so commands sent in response to Connected are kept)
+        while command_receiver.try_recv().is_ok() {}
         
         // Send connected event
-        let _ = sender.send(JoyConEvent::Connected { side: controller_side });
+        sender.send(JoyConEvent::Connected { side: controller_side });
+        
+        // Window in which a held startup_profiles button picks the profile
+        let mut startup_deadline = state.write().unwrap().take_startup_select()
+            .then(|| std::time::Instant::now() + startup_select);
+        let mut startup_selector = None;
         
         // Get peripheral and notification stream
         let peripheral = connection.peripheral();
         let mut notification_stream = peripheral.notifications().await?;
+        let mut last_signal_poll: Option<std::time::Instant> = None;
+        let mut last_keepalive = std::time::Instant::now();
         
         // Create controller state tracker
-        match side {
-            Side::Left => {
-                let mut controller = Joy2L::new();
-                let mut prev_buttons = create_left_button_snapshot(&controller);
-                let mut prev_stick = (0.0f32, 0.0f32);
-                let mut prev_gyro = (0.0f32, 0.0f32, 0.0f32);
-                let mut battery_logged = false;
-                
-                while running.load(Ordering::SeqCst) {
-                    tokio::select! {
-                        Some(notification) = notification_stream.next() => {
-                            controller.update(&notification.value);
-                            
-                            // Log battery level once after first update
-                            if !battery_logged {
-                                info!("  Battery Level: {:.0}%", controller.battery_level);
-                                battery_logged = true;
-                            }
-                            
-                            // Check for button changes
-                            Self::process_left_button_events(&controller, &mut prev_buttons, &sender);
-                            
-                            // Check for stick changes
-                            let stick_x = controller.analog_stick.x;
-                            let stick_y = controller.analog_stick.y;
-                            
-                            if (stick_x - prev_stick.0).abs() > 0.05 || (stick_y - prev_stick.1).abs() > 0.05 {
-                                let _ = sender.send(JoyConEvent::StickMoved {
-                                    stick: StickType::Left,
-                                    x: stick_x,
-                                    y: stick_y,
-                                });
-                                prev_stick = (stick_x, stick_y);
-                            }
-                            
-                            // Check for gyro changes
-                            let gyro_x = controller.gyroscope.x;
-                            let gyro_y = controller.gyroscope.y;
-                            let gyro_z = controller.gyroscope.z;
-                            
-                            if (gyro_x - prev_gyro.0).abs() > 0.5 
-                                || (gyro_y - prev_gyro.1).abs() > 0.5 
-                                || (gyro_z - prev_gyro.2).abs() > 0.5 {
-                                let _ = sender.send(JoyConEvent::GyroUpdate {
-                                    side: controller_side,
-                                    x: gyro_x,
-                                    y: gyro_y,
-                                    z: gyro_z,
-                                });
-                                prev_gyro = (gyro_x, gyro_y, gyro_z);
-                            }
+        let mut controller: Box<dyn ControllerState> = match side {
+            Side::Left => Box::new(Joy2L::new()),
+            Side::Right => Box::new(Joy2R::new()),
+        };
+        let stick_type = match side {
+            Side::Left => StickType::Left,
+            Side::Right => StickType::Right,
+        };
+        let saved_calibration = mac_cache.lock().unwrap().get_controller(&mac_address).and_then(|c| c.stick_calibration);
+        if let Some(calibration) = saved_calibration {
+            info!("  Using saved stick calibration");
+            controller.set_stick_calibration(calibration);
+        }
+        let mut prev_mouse = (0i16, 0i16);
+        let mut prev_buttons = controller.button_states();
+        let mut last_transition: HashMap<ButtonType, std::time::Instant> = HashMap::new();
+        let mut prev_stick = (0.0f32, 0.0f32);
+        let mut prev_trigger = 0.0f32;
+        let mut prev_gyro = (0.0f32, 0.0f32, 0.0f32);
+        let mut battery_logged = false;
+        let mut published_battery: Option<f32> = None;
+        let mut packet_count = 0u32;
+        let mut telemetry_start = std::time::Instant::now();
+        
+        while running.load(Ordering::SeqCst) {
+            tokio::select! {
+                Some(notification) = notification_stream.next() => {
+                    let received_at = std::time::Instant::now();
+                    if raw_packets {
+                        sender.send(JoyConEvent::RawPacket {
+                            side: controller_side,
+                            data: notification.value.clone(),
+                        });
+                    }
+                    
+                    match ReportKind::classify(notification.uuid, &notification.value) {
+                        ReportKind::Input => {}
+                        ReportKind::CommandResponse { command } => {
+                            debug!("{:?} response to command {:02X}: {:02X?}", side, command, notification.value);
+                            continue;
                         }
-                        _ = tokio::time::sleep(tokio::time::Duration::from_millis(1)) => {
-                            // Timeout check
-                            if !running.load(Ordering::SeqCst) {
-                                break;
-                            }
+                        ReportKind::Unknown => {
+                            debug!("{:?} ignoring unknown {}-byte report {:02X?} from {}", side, notification.value.len(), notification.value.first(), notification.uuid);
+                            continue;
                         }
                     }
-                }
-            }
-            
-            Side::Right => {
-                let mut controller = Joy2R::new();
-                let mut prev_buttons = create_right_button_snapshot(&controller);
-                let mut prev_stick = (0.0f32, 0.0f32);
-                let mut prev_gyro = (0.0f32, 0.0f32, 0.0f32);
-                let mut battery_logged = false;
-                
-                while running.load(Ordering::SeqCst) {
-                    tokio::select! {
-                        Some(notification) = notification_stream.next() => {
-                            controller.update(&notification.value);
-                            
-                            // Log battery level once after first update
-                            if !battery_logged {
-                                info!("  Battery Level: {:.0}%", controller.battery_level);
-                                battery_logged = true;
-                            }
-                            
-                            // Check for button changes
-                            Self::process_right_button_events(&controller, &mut prev_buttons, &sender);
-                            
-                            // Check for stick changes
-                            let stick_x = controller.analog_stick.x;
-                            let stick_y = controller.analog_stick.y;
-                            
-                            if (stick_x - prev_stick.0).abs() > 0.05 || (stick_y - prev_stick.1).abs() > 0.05 {
-                                let _ = sender.send(JoyConEvent::StickMoved {
-                                    stick: StickType::Right,
-                                    x: stick_x,
-                                    y: stick_y,
-                                });
-                                prev_stick = (stick_x, stick_y);
-                            }
-                            
-                            // Check for gyro changes
-                            let gyro_x = controller.gyroscope.x;
-                            let gyro_y = controller.gyroscope.y;
-                            let gyro_z = controller.gyroscope.z;
-                            
-                            if (gyro_x - prev_gyro.0).abs() > 0.5 
-                                || (gyro_y - prev_gyro.1).abs() > 0.5 
-                                || (gyro_z - prev_gyro.2).abs() > 0.5 {
-                                let _ = sender.send(JoyConEvent::GyroUpdate {
-                                    side: controller_side,
-                                    x: gyro_x,
-                                    y: gyro_y,
-                                    z: gyro_z,
-                                });
-                                prev_gyro = (gyro_x, gyro_y, gyro_z);
-                            }
+                    
+                    controller.update(&notification.value);
+                    
+                    // Log battery level once after first update
+                    if !battery_logged {
+                        info!("  Battery Level: {:.0}%", controller.battery_level());
+                        battery_logged = true;
+                    }
+                    
+                    let battery = controller.battery_level();
+                    if published_battery.is_none_or(|published| (battery - published).abs() >= 1.0) {
+                        published_battery = Some(battery);
+                        state.write().unwrap().controller_mut(controller_side).battery_level = Some(battery);
+                    }
+                    
+                    // Check for button changes
+                    let mut buttons = controller.button_states();
+                    if swap_ab_xy {
+                        swap_face_buttons(&mut buttons);
+                    }
+                    let mut changes = diff_states(buttons.clone(), std::mem::take(&mut prev_buttons));
+                    if !debounce.is_zero() {
+                        changes = debounce_buttons(changes, &mut buttons, &mut last_transition, debounce, std::time::Instant::now());
+                    }
+                    if let Some(button) = Self::check_startup_profile(&mut startup_deadline, &startup_profiles, &buttons, &profile_sender) {
+                        startup_selector = Some(button);
+                    }
+                    suppress_startup_selector(&mut changes, &mut startup_selector);
+                    Self::send_button_events(changes, controller_side, &sender);
+                    
+                    // Check for the sync combo being held
+                    if let Some(detector) = sync_combo.as_mut() {
+                        if detector.update(&buttons, std::time::Instant::now()) {
+                            sender.send(JoyConEvent::SyncCombo { side: controller_side });
+                        }
+                    }
+                    prev_buttons = buttons;
+                    
+                    // Record the raw stick for a calibration, or apply its result
+                    let calibrating = {
+                        let mut calibrations = calibrations.lock().unwrap();
+                        match calibrations.get_mut(&controller_side) {
+                            Some(session) => match session.apply {
+                                Some(calibration) => {
+                                    controller.set_stick_calibration(calibration);
+                                    calibrations.remove(&controller_side);
+                                    false
+                                }
+                                None => {
+                                    session.samples.push(controller.raw_stick());
+                                    true
+                                }
+                            },
+                            None => false,
                         }
-                        _ = tokio::time::sleep(tokio::time::Duration::from_millis(1)) => {
-                            // Timeout check
-                            if !running.load(Ordering::SeqCst) {
-                                break;
+                    };
+                    
+                    // Check for stick changes (held back while the stick is rotated for calibration)
+                    let stick_x = controller.analog_stick().x;
+                    let stick_y = controller.analog_stick().y;
+                    
+                    if !calibrating && ((stick_x - prev_stick.0).abs() > stick_threshold || (stick_y - prev_stick.1).abs() > stick_threshold) {
+                        sender.send(JoyConEvent::StickMoved {
+                            stick: stick_type,
+                            x: stick_x,
+                            y: stick_y,
+                        });
+                        prev_stick = (stick_x, stick_y);
+                    }
+                    
+                    // Check for a stick resting off-center on a controller that lies still
+                    if let Some(detector) = drift_detector.as_mut().filter(|_| !calibrating) {
+                        let angular_speed = controller.gyroscope().magnitude();
+                        if let Some(magnitude) = detector.update(&controller.analog_stick(), angular_speed, std::time::Instant::now()) {
+                            warn!("{:?} stick drifts: rests at {:.2} off-center while the controller is idle", stick_type, magnitude);
+                            if drift_recenter {
+                                let (raw_x, raw_y) = controller.raw_stick();
+                                let mut calibration = controller.stick_calibration();
+                                calibration.x_center = raw_x;
+                                calibration.y_center = raw_y;
+                                controller.set_stick_calibration(calibration);
+                                info!("  Recentered the stick at raw ({}, {}) until disconnect", raw_x, raw_y);
                             }
+                            sender.send(JoyConEvent::StickDrift { stick: stick_type, magnitude });
+                        }
+                    }
+                    
+                    // Check for trigger changes
+                    let trigger = controller.trigger();
+                    if trigger != prev_trigger {
+                        sender.send(JoyConEvent::Trigger { side: controller_side, value: trigger });
+                        prev_trigger = trigger;
+                    }
+                    
+                    // Check for gyro changes
+                    let gyro_x = controller.gyroscope().x;
+                    let gyro_y = controller.gyroscope().y;
+                    let gyro_z = controller.gyroscope().z;
+                    
+                    if (gyro_x - prev_gyro.0).abs() > gyro_threshold 
+                        || (gyro_y - prev_gyro.1).abs() > gyro_threshold 
+                        || (gyro_z - prev_gyro.2).abs() > gyro_threshold {
+                        sender.send(JoyConEvent::GyroUpdate {
+                            side: controller_side,
+                            x: gyro_x,
+                            y: gyro_y,
+                            z: gyro_z,
+                        });
+                        sender.send(JoyConEvent::Motion {
+                            side: controller_side,
+                            angular_speed: controller.gyroscope().magnitude(),
+                            tilt: controller.accelerometer().tilt(),
+                        });
+                        prev_gyro = (gyro_x, gyro_y, gyro_z);
+                    }
+                    
+                    // Check for optical mouse sensor movement
+                    let mouse = (controller.mouse().x, controller.mouse().y);
+                    if mouse != prev_mouse {
+                        sender.send(JoyConEvent::MouseSensor {
+                            side: controller_side,
+                            x: mouse.0,
+                            y: mouse.1,
+                            distance: controller.mouse().distance,
+                        });
+                        prev_mouse = mouse;
+                    }
+                    
+                    // Check for shake gesture
+                    if shake_detector.update(&controller.accelerometer(), std::time::Instant::now()) {
+                        sender.send(JoyConEvent::Gesture {
+                            side: controller_side,
+                            kind: GestureKind::Shake,
+                        });
+                    }
+                    
+                    // Packet rate, and a probe behind this packet's events to time the executor
+                    packet_count += 1;
+                    let elapsed = telemetry_start.elapsed();
+                    if elapsed >= TELEMETRY_INTERVAL {
+                        let packet_hz = packet_count as f32 / elapsed.as_secs_f32();
+                        state.write().unwrap().controller_mut(controller_side).packet_hz = Some(packet_hz);
+                        sender.send(JoyConEvent::TelemetryProbe { side: controller_side, packet_hz, received_at });
+                        packet_count = 0;
+                        telemetry_start = std::time::Instant::now();
+                    }
+                }
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(1)) => {
+                    // Timeout check
+                    if !running.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    
+                    sender.flush();
+                    Self::handle_commands(&mut connection, &command_receiver).await;
+                    
+                    if last_signal_poll.is_none_or(|t| t.elapsed() >= SIGNAL_POLL_INTERVAL) {
+                        last_signal_poll = Some(std::time::Instant::now());
+                        Self::poll_signal_strength(&connection, controller_side, &state, &sender).await;
+                    }
+                    
+                    if keepalive_interval.is_some_and(|interval| last_keepalive.elapsed() >= interval) {
+                        last_keepalive = std::time::Instant::now();
+                        if let Err(e) = connection.keepalive().await {
+                            warn!("Keepalive failed on {:?} controller: {}", side, e);
                         }
                     }
                 }
@@ -517,77 +1849,21 @@ where
             warn!("Error disconnecting {:?} controller: {}", side, e);
         }
         
-        // Remove MAC from connected set
-        {
-            let mut macs = connected_macs.lock().unwrap();
-            macs.remove(&mac_address);
-            info!("Controller {:?} (MAC: {}) removed from tracking", side, mac_address);
-        }
-        
         // Send disconnected event
-        let _ = sender.send(JoyConEvent::Disconnected { side: controller_side });
+        sender.send(JoyConEvent::Disconnected { side: controller_side });
         
         Ok(())
     }
     
-    /// Process left controller button events
-    fn process_left_button_events(
-        controller: &Joy2L,
-        prev_buttons: &mut LeftButtonSnapshot,
-        sender: &Sender<JoyConEvent>,
-    ) {
-        let buttons = &controller.buttons;
-        
-        // Check each button for changes
-        Self::check_button_change(buttons.zl, &mut prev_buttons.zl, ButtonType::ZL, sender);
-        Self::check_button_change(buttons.l, &mut prev_buttons.l, ButtonType::L, sender);
-        Self::check_button_change(buttons.minus, &mut prev_buttons.minus, ButtonType::Minus, sender);
-        Self::check_button_change(buttons.capture, &mut prev_buttons.capture, ButtonType::Capture, sender);
-        Self::check_button_change(buttons.l3, &mut prev_buttons.l3, ButtonType::LeftStickClick, sender);
-        Self::check_button_change(buttons.up, &mut prev_buttons.up, ButtonType::DpadUp, sender);
-        Self::check_button_change(buttons.down, &mut prev_buttons.down, ButtonType::DpadDown, sender);
-        Self::check_button_change(buttons.left, &mut prev_buttons.left, ButtonType::DpadLeft, sender);
-        Self::check_button_change(buttons.right, &mut prev_buttons.right, ButtonType::DpadRight, sender);
-        Self::check_button_change(buttons.sll, &mut prev_buttons.sll, ButtonType::SLL, sender);
-        Self::check_button_change(buttons.srl, &mut prev_buttons.srl, ButtonType::SRL, sender);
-    }
-    
-    /// Process right controller button events
-    fn process_right_button_events(
-        controller: &Joy2R,
-        prev_buttons: &mut RightButtonSnapshot,
-        sender: &Sender<JoyConEvent>,
-    ) {
-        let buttons = &controller.buttons;
-        
-        // Check each button for changes
-        Self::check_button_change(buttons.a, &mut prev_buttons.a, ButtonType::A, sender);
-        Self::check_button_change(buttons.b, &mut prev_buttons.b, ButtonType::B, sender);
-        Self::check_button_change(buttons.x, &mut prev_buttons.x, ButtonType::X, sender);
-        Self::check_button_change(buttons.y, &mut prev_buttons.y, ButtonType::Y, sender);
-        Self::check_button_change(buttons.r, &mut prev_buttons.r, ButtonType::R, sender);
-        Self::check_button_change(buttons.zr, &mut prev_buttons.zr, ButtonType::ZR, sender);
-        Self::check_button_change(buttons.plus, &mut prev_buttons.plus, ButtonType::Plus, sender);
-        Self::check_button_change(buttons.home, &mut prev_buttons.home, ButtonType::Home, sender);
-        Self::check_button_change(buttons.r3, &mut prev_buttons.r3, ButtonType::RightStickClick, sender);
-        Self::check_button_change(buttons.slr, &mut prev_buttons.slr, ButtonType::SLR, sender);
-        Self::check_button_change(buttons.srr, &mut prev_buttons.srr, ButtonType::SRR, sender);
-        Self::check_button_change(buttons.chat, &mut prev_buttons.chat, ButtonType::Chat, sender);
-    }
-    
-    /// Check if a button state changed and send appropriate event
-    fn check_button_change(
-        current: bool,
-        previous: &mut bool,
-        button_type: ButtonType,
-        sender: &Sender<JoyConEvent>,
-    ) {
-        if current && !*previous {
-            let _ = sender.send(JoyConEvent::ButtonPressed(button_type));
-            *previous = true;
-        } else if !current && *previous {
-            let _ = sender.send(JoyConEvent::ButtonReleased(button_type));
-            *previous = false;
+    /// Send press/release events for button transitions of the controller on `side`
+    fn send_button_events(changes: Vec<(ButtonType, bool)>, side: ControllerSide, sender: &EventSender) {
+        for (button, pressed) in changes {
+            let event = if pressed {
+                JoyConEvent::ButtonPressed { button, side }
+            } else {
+                JoyConEvent::ButtonReleased { button, side }
+            };
+            sender.send(event);
         }
     }
 }
@@ -605,84 +1881,516 @@ where
         if was_running {
             info!("Shutting down Joy-Con Manager (Drop trait)...");
             
-            // Clear connected MACs to allow reconnection
+            // Clear controller status to allow reconnection
             {
-                let mut macs = self.connected_macs.lock().unwrap();
-                macs.clear();
+                let mut state = self.state.write().unwrap();
+                state.left = ControllerStatus::default();
+                state.right = ControllerStatus::default();
             }
+            self.pending_macs.lock().unwrap().clear();
             
-            // Give threads time to detect the running flag change and clean up
-            // The controller loops will exit, which will drop their JoyConConnection
-            // instances, triggering proper Bluetooth disconnection
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            
+        }
+        
+        self.join_executor_thread();
+        self.shutdown_backends();
+        
+        // Let the tasks disconnect their controllers before the runtime goes away. Inside
+        // async code that wait moves to a background thread instead of blocking the caller.
+        if tokio::runtime::Handle::try_current().is_ok() {
+            let tasks = std::mem::take(&mut self.tasks);
+            if let Some(runtime) = self.runtime.take() {
+                thread::spawn(move || {
+                    runtime.block_on(async {
+                        let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, futures::future::join_all(tasks)).await;
+                    });
+                    runtime.shutdown_timeout(SHUTDOWN_TIMEOUT);
+                });
+            }
+        } else {
+            self.finish_tasks();
+            if let Some(runtime) = self.runtime.take() {
+                runtime.shutdown_timeout(SHUTDOWN_TIMEOUT);
+            }
+        }
+        
+        if was_running {
             info!("✓ Joy-Con Manager shutdown complete");
         }
     }
 }
 
-/// Snapshot of left controller button states
-struct LeftButtonSnapshot {
-    zl: bool,
-    l: bool,
-    minus: bool,
-    capture: bool,
-    l3: bool,
-    up: bool,
-    down: bool,
-    left: bool,
-    right: bool,
-    sll: bool,
-    srl: bool,
-}
-
-/// Snapshot of right controller button states
-struct RightButtonSnapshot {
-    a: bool,
-    b: bool,
-    x: bool,
-    y: bool,
-    r: bool,
-    zr: bool,
-    plus: bool,
-    home: bool,
-    r3: bool,
-    slr: bool,
-    srr: bool,
-    chat: bool,
-}
-
-/// Create a snapshot of left controller button states
-fn create_left_button_snapshot(controller: &Joy2L) -> LeftButtonSnapshot {
-    LeftButtonSnapshot {
-        zl: controller.buttons.zl,
-        l: controller.buttons.l,
-        minus: controller.buttons.minus,
-        capture: controller.buttons.capture,
-        l3: controller.buttons.l3,
-        up: controller.buttons.up,
-        down: controller.buttons.down,
-        left: controller.buttons.left,
-        right: controller.buttons.right,
-        sll: controller.buttons.sll,
-        srl: controller.buttons.srl,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MockKeyboardBackend, MockMouseBackend};
+    use crate::mapping::builder::{ConfigBuilder, ProfileBuilder};
+    use crate::mapping::config::Action;
+    
+    #[test]
+    fn test_manager_state_publish() {
+        let config = ConfigBuilder::new()
+            .default_profile("menu")
+            .profile(ProfileBuilder::new("game").button(ButtonType::Plus, Action::CycleProfiles))
+            .profile(ProfileBuilder::new("menu").button(ButtonType::Plus, Action::CycleProfiles))
+            .build()
+            .unwrap();
+        let state = RwLock::new(ManagerState::new(&config));
+        assert_eq!(state.read().unwrap().profile_name, "menu");
+        
+        let mut executor = MappingExecutor::new(config, MockKeyboardBackend::new(), MockMouseBackend::new());
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Plus, side: ControllerSide::Right });
+        ManagerState::publish(&state, &executor);
+        
+        let state = state.read().unwrap();
+        assert_eq!((state.profile_index, state.profile_name.as_str()), (0, "game"));
+        assert!(!state.paused && !state.gyro_left_enabled && !state.gyro_right_enabled);
     }
-}
-
-/// Create a snapshot of right controller button states
-fn create_right_button_snapshot(controller: &Joy2R) -> RightButtonSnapshot {
-    RightButtonSnapshot {
-        a: controller.buttons.a,
-        b: controller.buttons.b,
-        x: controller.buttons.x,
-        y: controller.buttons.y,
-        r: controller.buttons.r,
-        zr: controller.buttons.zr,
-        plus: controller.buttons.plus,
-        home: controller.buttons.home,
-        r3: controller.buttons.r3,
-        slr: controller.buttons.slr,
-        srr: controller.buttons.srr,
-        chat: controller.buttons.chat,
+    
+    #[test]
+    fn test_manager_state_controllers() {
+        let mut state = ManagerState::new(&ConfigBuilder::new().profile(ProfileBuilder::new("base")).build().unwrap());
+        state.controller_mut(ControllerSide::Right).mac_address = Some("AA:BB:CC:DD:EE:FF".to_string());
+        
+        // Claimed but still initializing
+        assert!(state.has_mac("AA:BB:CC:DD:EE:FF"));
+        assert!(state.connected_sides().is_empty());
+        
+        state.controller_mut(ControllerSide::Right).connected = true;
+        assert_eq!(state.connected_sides(), vec![Side::Right]);
+        assert!(!state.controller(ControllerSide::Left).connected);
+    }
+    
+    #[test]
+    fn test_startup_profile_selection() {
+        let startup_profiles = HashMap::from([(ButtonType::ZR, "game".to_string())]);
+        let (profile_sender, profile_receiver) = bounded(10);
+        let held = [(ButtonType::A, true), (ButtonType::ZR, true)];
+        let check = |deadline: &mut Option<std::time::Instant>, states: &[(ButtonType, bool)]| {
+            JoyConManager::<MockKeyboardBackend, MockMouseBackend>::check_startup_profile(deadline, &startup_profiles, states, &profile_sender)
+        };
+        
+        let mut deadline = Some(std::time::Instant::now() + std::time::Duration::from_secs(60));
+        assert_eq!(check(&mut deadline, &[(ButtonType::ZR, false)]), None);
+        assert!(deadline.is_some());
+        assert_eq!(check(&mut deadline, &held), Some(ButtonType::ZR));
+        assert_eq!(profile_receiver.try_recv().as_deref(), Ok("game"));
+        
+        // One pick per window, and none once it has closed
+        assert_eq!(check(&mut deadline, &held), None);
+        let mut deadline = Some(std::time::Instant::now() - std::time::Duration::from_millis(1));
+        assert_eq!(check(&mut deadline, &held), None);
+        assert!(deadline.is_none() && profile_receiver.is_empty());
+        
+        // The selector's press and release never reach the mapping, other buttons do
+        let mut selector = Some(ButtonType::ZR);
+        let mut changes = vec![(ButtonType::A, true), (ButtonType::ZR, true)];
+        suppress_startup_selector(&mut changes, &mut selector);
+        assert_eq!(changes, vec![(ButtonType::A, true)]);
+        let mut changes = vec![(ButtonType::ZR, false)];
+        suppress_startup_selector(&mut changes, &mut selector);
+        assert!(changes.is_empty() && selector.is_none());
+        let mut changes = vec![(ButtonType::ZR, true)];
+        suppress_startup_selector(&mut changes, &mut selector);
+        assert_eq!(changes, vec![(ButtonType::ZR, true)]);
+    }
+    
+    #[test]
+    fn test_startup_select_only_after_start() {
+        let mut state = ManagerState::new(&ConfigBuilder::new().profile(ProfileBuilder::new("base")).build().unwrap());
+        assert!(!state.take_startup_select());
+        
+        // Armed by start(): the first connection takes it, a reconnect doesn't
+        state.startup_select_armed = true;
+        assert!(state.take_startup_select());
+        assert!(!state.take_startup_select());
+    }
+    
+    #[test]
+    fn test_scan_state_once_and_timeout() {
+        let config = ConfigBuilder::new().profile(ProfileBuilder::new("base")).build().unwrap();
+        let state = Arc::new(RwLock::new(ManagerState::new(&config)));
+        let (sender, receiver) = bounded(10);
+        let scan_state = |scan_once: bool, scan_timeout: Option<std::time::Duration>| ScanState {
+            expected_sides: vec![Side::Left, Side::Right],
+            state: Arc::clone(&state),
+            event_sender: EventSender::new(sender.clone(), receiver.clone(), ChannelFullPolicy::default()),
+            scan_once,
+            scan_timeout,
+            waiting_since: HashMap::new(),
+            timed_out: HashSet::new(),
+        };
+        state.write().unwrap().controller_mut(ControllerSide::Right).connected = true;
+        
+        // Connected sides are only scanned for again (reconnect) without scan_once
+        assert_eq!(scan_state(false, None).wanted_sides(), vec![Side::Left, Side::Right]);
+        let mut once = scan_state(true, None);
+        assert_eq!(once.wanted_sides(), vec![Side::Left]);
+        
+        // A missing side is given up on after the timeout, with a single event
+        let mut timed = scan_state(true, Some(std::time::Duration::from_millis(20)));
+        assert_eq!(timed.wanted_sides(), vec![Side::Left]);
+        assert!(timed.waiting_since.contains_key(&Side::Left));
+        assert!(receiver.try_recv().is_err());
+        
+        thread::sleep(std::time::Duration::from_millis(30));
+        assert!(timed.wanted_sides().is_empty());
+        assert!(timed.wanted_sides().is_empty());
+        assert!(timed.timed_out.contains(&Side::Left));
+        let events: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], JoyConEvent::ScanTimedOut { side: ControllerSide::Left }));
+        
+        // Connecting resets the wait, so a later disconnect gets a fresh timeout
+        let mut timed = scan_state(false, Some(std::time::Duration::from_millis(20)));
+        timed.wanted_sides();
+        state.write().unwrap().controller_mut(ControllerSide::Left).connected = true;
+        assert_eq!(timed.wanted_sides(), vec![Side::Left, Side::Right]);
+        assert!(timed.waiting_since.is_empty());
+    }
+    
+    #[test]
+    fn test_debounce_buttons() {
+        let window = std::time::Duration::from_millis(30);
+        let start = std::time::Instant::now();
+        let ms = |n| start + std::time::Duration::from_millis(n);
+        let mut last = HashMap::new();
+        
+        // First press goes through
+        let mut buttons = vec![(ButtonType::A, true), (ButtonType::B, false)];
+        let changes = debounce_buttons(vec![(ButtonType::A, true)], &mut buttons, &mut last, window, ms(0));
+        assert_eq!(changes, vec![(ButtonType::A, true)]);
+        
+        // A bounce is dropped and undone, another button is unaffected
+        let mut buttons = vec![(ButtonType::A, false), (ButtonType::B, true)];
+        let changes = debounce_buttons(vec![(ButtonType::A, false), (ButtonType::B, true)], &mut buttons, &mut last, window, ms(10));
+        assert_eq!(changes, vec![(ButtonType::B, true)]);
+        assert_eq!(buttons, vec![(ButtonType::A, true), (ButtonType::B, true)]);
+        
+        // Still released after the window: reported then
+        let mut buttons = vec![(ButtonType::A, false), (ButtonType::B, true)];
+        let changes = debounce_buttons(vec![(ButtonType::A, false)], &mut buttons, &mut last, window, ms(40));
+        assert_eq!(changes, vec![(ButtonType::A, false)]);
+        assert_eq!(buttons[0], (ButtonType::A, false));
+    }
+    
+    /// Controller that records LED changes and queues rumbles like `JoyConConnection`
+    #[derive(Default)]
+    struct FakeController {
+        rumble_queue: crate::joycon2::connection::RumbleQueue,
+        rumble_pulses_sent: usize,
+        leds: Vec<u8>,
+    }
+    
+    impl CommandTarget for FakeController {
+        fn side(&self) -> Side {
+            Side::Right
+        }
+        
+        fn play_rumble(&mut self, intensity: u8, duration: std::time::Duration) {
+            self.rumble_queue.play(crate::joycon2::connection::rumble_pulses(intensity, duration), std::time::Instant::now());
+        }
+        
+        fn play_rumble_pattern(&mut self, pattern: RumblePattern) {
+            self.rumble_queue.play(pattern.pulses(), std::time::Instant::now());
+        }
+        
+        async fn poll_rumble(&mut self) -> Result<(), JoyConError> {
+            self.rumble_pulses_sent += self.rumble_queue.take_due(std::time::Instant::now()).len();
+            Ok(())
+        }
+        
+        async fn set_player_led(&mut self, leds: u8) -> Result<(), JoyConError> {
+            self.leds.push(leds);
+            Ok(())
+        }
+    }
+    
+    #[test]
+    fn test_commands_handled_while_rumbling() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let (sender, receiver) = bounded(10);
+        let mut controller = FakeController::default();
+        
+        // A long rumble doesn't hold up the commands behind it
+        let start = std::time::Instant::now();
+        sender.send(ManagerCommand::Rumble { side: ControllerSide::Right, intensity: 3, duration_ms: 1000 }).unwrap();
+        sender.send(ManagerCommand::SetPlayerLed { side: ControllerSide::Right, leds: 0b0011 }).unwrap();
+        runtime.block_on(JoyConManager::<MockKeyboardBackend, MockMouseBackend>::handle_commands(&mut controller, &receiver));
+        assert!(start.elapsed() < std::time::Duration::from_millis(500));
+        assert_eq!(controller.leds, vec![0b0011]);
+        assert_eq!(controller.rumble_pulses_sent, 1);
+        assert!(controller.rumble_queue.is_active());
+        
+        // Later loop passes keep handling commands and send the rumble as it comes due
+        thread::sleep(std::time::Duration::from_millis(120));
+        sender.send(ManagerCommand::SetPlayerLed { side: ControllerSide::Right, leds: 0b0001 }).unwrap();
+        runtime.block_on(JoyConManager::<MockKeyboardBackend, MockMouseBackend>::handle_commands(&mut controller, &receiver));
+        assert_eq!(controller.leds, vec![0b0011, 0b0001]);
+        assert!(controller.rumble_pulses_sent >= 3);
+        assert!(controller.rumble_queue.is_active());
+    }
+    
+    #[test]
+    fn test_latency_window() {
+        let mut window = LatencyWindow::default();
+        assert_eq!(window.record(4.0), 4.0);
+        assert_eq!(window.record(2.0), 3.0);
+        
+        // Only the last LATENCY_SAMPLES probes count
+        for _ in 0..LATENCY_SAMPLES {
+            window.record(10.0);
+        }
+        assert_eq!(window.record(10.0), 10.0);
+        assert_eq!(window.samples.len(), LATENCY_SAMPLES);
+    }
+    
+    #[test]
+    fn test_event_sender_never_blocks() {
+        let stick = |x: f32| JoyConEvent::StickMoved { stick: StickType::Left, x, y: 0.0 };
+        let received = |receiver: &Receiver<JoyConEvent>| -> Vec<f32> {
+            receiver.try_iter().map(|e| match e { JoyConEvent::StickMoved { x, .. } => x, _ => unreachable!() }).collect()
+        };
+        
+        let (sender, receiver) = bounded(4);
+        let keep_newest = EventSender::new(sender, receiver.clone(), ChannelFullPolicy::DropOldest);
+        for i in 0..10 {
+            keep_newest.send(stick(i as f32));
+        }
+        assert_eq!(keep_newest.dropped(), 6);
+        assert_eq!(received(&receiver), vec![6.0, 7.0, 8.0, 9.0]);
+        
+        let (sender, receiver) = bounded(4);
+        let keep_oldest = EventSender::new(sender, receiver.clone(), ChannelFullPolicy::DropNewest);
+        for i in 0..10 {
+            keep_oldest.send(stick(i as f32));
+        }
+        assert_eq!(keep_oldest.dropped(), 6);
+        assert_eq!(received(&receiver), vec![0.0, 1.0, 2.0, 3.0]);
+        
+        // A release waits for room in a flooded channel instead of being dropped
+        let release = JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right };
+        let (sender, receiver) = bounded(4);
+        let event_sender = EventSender::new(sender, receiver.clone(), ChannelFullPolicy::DropNewest);
+        for i in 0..4 {
+            event_sender.send(stick(i as f32));
+        }
+        event_sender.send(release.clone());
+        assert_eq!(receiver.len(), 4);
+        
+        // ... and is sent by the next flush once the executor makes room
+        event_sender.flush();
+        assert_eq!(receiver.len(), 4);
+        receiver.recv().unwrap();
+        event_sender.flush();
+        assert_eq!(event_sender.dropped(), 0);
+        assert!(matches!(receiver.try_iter().last(), Some(JoyConEvent::ButtonReleased { button: ButtonType::A, .. })));
+        
+        // Pending events keep their order ahead of later ones
+        let (sender, receiver) = bounded(1);
+        let event_sender = EventSender::new(sender, receiver.clone(), ChannelFullPolicy::DropOldest);
+        let press = JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right };
+        event_sender.send(press.clone());
+        event_sender.send(release.clone());
+        event_sender.send(press);
+        event_sender.send(stick(1.0));
+        let mut order = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            order.push(event);
+            event_sender.flush();
+        }
+        assert!(matches!(order[..], [
+            JoyConEvent::ButtonPressed { .. },
+            JoyConEvent::ButtonReleased { .. },
+            JoyConEvent::ButtonPressed { .. },
+        ]));
+        assert_eq!(event_sender.dropped(), 1);
+        
+        // Dropping the oldest event skips a queued release
+        let (sender, receiver) = bounded(4);
+        let event_sender = EventSender::new(sender, receiver.clone(), ChannelFullPolicy::DropOldest);
+        event_sender.send(release);
+        for i in 0..10 {
+            event_sender.send(stick(i as f32));
+        }
+        assert_eq!(event_sender.dropped(), 7);
+        let events: Vec<JoyConEvent> = receiver.try_iter().collect();
+        assert!(matches!(events[0], JoyConEvent::ButtonReleased { button: ButtonType::A, .. }));
+        assert_eq!(events.len(), 4);
+    }
+    
+    #[test]
+    fn test_event_sender_flood_with_slow_executor() {
+        let (sender, receiver) = bounded(100);
+        let event_sender = EventSender::new(sender, receiver.clone(), ChannelFullPolicy::default());
+        let senders: Vec<_> = (0..2).map(|_| {
+            let event_sender = event_sender.clone();
+            thread::spawn(move || {
+                for i in 0..10_000 {
+                    event_sender.send(JoyConEvent::StickMoved { stick: StickType::Left, x: i as f32, y: 0.0 });
+                }
+            })
+        }).collect();
+        
+        // A controller loop's iteration stays short while the executor is stalled
+        let (stalled_sender, stalled_receiver) = bounded(4);
+        let stalled = EventSender::new(stalled_sender, stalled_receiver.clone(), ChannelFullPolicy::default());
+        let mut slowest = std::time::Duration::ZERO;
+        for i in 0..1000 {
+            let started = std::time::Instant::now();
+            stalled.flush();
+            stalled.send(JoyConEvent::StickMoved { stick: StickType::Left, x: i as f32, y: 0.0 });
+            stalled.send(JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+            slowest = slowest.max(started.elapsed());
+        }
+        assert!(slowest < std::time::Duration::from_millis(50), "slowest iteration took {:?}", slowest);
+        assert_eq!(stalled_receiver.len(), 4);
+        assert_eq!(stalled.pending.lock().unwrap().len(), 1000 - 4);
+        let executor = thread::spawn(move || {
+            let mut processed = 0u64;
+            while let Ok(_event) = receiver.recv_timeout(std::time::Duration::from_millis(200)) {
+                processed += 1;
+                if processed.is_multiple_of(100) {
+                    thread::sleep(std::time::Duration::from_millis(1));
+                }
+            }
+            processed
+        });
+        
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        let processed = executor.join().unwrap();
+        assert_eq!(processed + event_sender.dropped(), 20_000);
+        assert!(event_sender.dropped() > 0);
+    }
+    
+    /// Backend recording its lifecycle calls, optionally failing init
+    #[derive(Clone)]
+    struct LifecycleBackend {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        fail_init: bool,
+    }
+    
+    impl LifecycleBackend {
+        fn init(&self) -> Result<(), crate::backend::BackendError> {
+            self.log.lock().unwrap().push(format!("init {}", self.name));
+            if self.fail_init {
+                return Err(crate::backend::BackendError::Operation("no device".into()));
+            }
+            Ok(())
+        }
+        
+        fn shutdown(&self) {
+            self.log.lock().unwrap().push(format!("shutdown {}", self.name));
+        }
+    }
+    
+    impl KeyboardBackend for LifecycleBackend {
+        fn key_down(&self, _key: &str) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn key_up(&self, _key: &str) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn init(&mut self) -> Result<(), crate::backend::BackendError> { LifecycleBackend::init(self) }
+        fn shutdown(&mut self) { LifecycleBackend::shutdown(self) }
+    }
+    
+    impl MouseBackend for LifecycleBackend {
+        fn move_relative(&self, _dx: i32, _dy: i32) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn click(&self, _button: crate::backend::MouseButton) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn button_down(&self, _button: crate::backend::MouseButton) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn button_up(&self, _button: crate::backend::MouseButton) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn init(&mut self) -> Result<(), crate::backend::BackendError> { LifecycleBackend::init(self) }
+        fn shutdown(&mut self) { LifecycleBackend::shutdown(self) }
+    }
+    
+    impl GamepadBackend for LifecycleBackend {
+        fn set_axis(&self, _axis: crate::backend::GamepadAxis, _value: f32) -> Result<(), crate::backend::BackendError> { Ok(()) }
+        fn init(&self) -> Result<(), crate::backend::BackendError> { LifecycleBackend::init(self) }
+        fn shutdown(&self) { LifecycleBackend::shutdown(self) }
+    }
+    
+    #[test]
+    fn test_backend_lifecycle_order() {
+        let config = ConfigBuilder::new().profile(ProfileBuilder::new("base")).default_profile("base").build().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let backend = |name, fail_init| LifecycleBackend { name, log: Arc::clone(&log), fail_init };
+        
+        let mut manager = JoyConManager::new(config.clone(), backend("keyboard", false), backend("mouse", false));
+        manager.set_gamepad_backend(backend("gamepad", false));
+        manager.init_backends().unwrap();
+        manager.stop();
+        manager.stop();
+        drop(manager);
+        assert_eq!(*log.lock().unwrap(), vec![
+            "init keyboard", "init mouse", "init gamepad", "shutdown gamepad", "shutdown mouse", "shutdown keyboard",
+        ]);
+        
+        // A failed init shuts down what was already set up, and nothing again later
+        log.lock().unwrap().clear();
+        let mut manager = JoyConManager::new(config, backend("keyboard", false), backend("mouse", true));
+        assert!(manager.init_backends().is_err());
+        drop(manager);
+        assert_eq!(*log.lock().unwrap(), vec!["init keyboard", "init mouse", "shutdown keyboard"]);
+    }
+    
+    #[test]
+    fn test_stop_ends_tasks_and_drop_in_async_context() {
+        let config = ConfigBuilder::new().profile(ProfileBuilder::new("base")).default_profile("base").build().unwrap();
+        let spawn_task = |manager: &mut JoyConManager<MockKeyboardBackend, MockMouseBackend>| {
+            let running = Arc::clone(&manager.running);
+            let task = manager.runtime.as_ref().unwrap().spawn(async move {
+                while running.load(Ordering::SeqCst) {
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                }
+            });
+            let abort_handle = task.abort_handle();
+            manager.tasks.push(task);
+            abort_handle
+        };
+        
+        let mut manager = JoyConManager::new(config, MockKeyboardBackend::new(), MockMouseBackend::new());
+        manager.runtime = Some(tokio::runtime::Builder::new_multi_thread().worker_threads(1).enable_all().build().unwrap());
+        manager.running.store(true, Ordering::SeqCst);
+        let task = spawn_task(&mut manager);
+        
+        // Stop waits for the tasks, so a restart can't run them twice
+        manager.stop();
+        assert!(task.is_finished());
+        assert!(manager.tasks.is_empty());
+        
+        // Dropping a running manager from async code returns right away, the tasks still
+        // finish on their own
+        manager.running.store(true, Ordering::SeqCst);
+        let task = spawn_task(&mut manager);
+        let outer = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        outer.block_on(async move { drop(manager) });
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !task.is_finished() && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(task.is_finished());
+    }
+    
+    #[test]
+    fn test_wait_for_connection() {
+        let config = ConfigBuilder::new().profile(ProfileBuilder::new("base")).default_profile("base").build().unwrap();
+        let manager = JoyConManager::new(config, MockKeyboardBackend::new(), MockMouseBackend::new());
+        let timeout = std::time::Duration::from_millis(100);
+        assert_eq!(manager.wait_for_connection(ControllerSide::Right, timeout), Err(WaitError::NotRunning));
+        
+        manager.running.store(true, Ordering::SeqCst);
+        let start = std::time::Instant::now();
+        assert_eq!(manager.wait_for_connection(ControllerSide::Right, timeout), Err(WaitError::Timeout(ControllerSide::Right, timeout)));
+        assert!(start.elapsed() >= timeout);
+        
+        // Returns once the controller loop marks the side connected
+        let state = manager.state();
+        let connect = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(30));
+            state.write().unwrap().controller_mut(ControllerSide::Right).connected = true;
+        });
+        assert_eq!(manager.wait_for_connection(ControllerSide::Right, std::time::Duration::from_secs(5)), Ok(()));
+        connect.join().unwrap();
+        assert_eq!(manager.wait_for_connection(ControllerSide::Left, std::time::Duration::ZERO), Err(WaitError::Timeout(ControllerSide::Left, std::time::Duration::ZERO)));
+        manager.running.store(false, Ordering::SeqCst);
     }
 }
@@ -0,0 +1,278 @@
+//! Fluent builders for constructing configurations in code
+//!
+//! Handy for tests, examples and tools that generate configs (e.g. GUIs).
+//! The plain `Config`/`Profile` structs stay public for full control.
+
+use super::config::{
+    Action, ButtonType, Config, ConfigError, ControllerSide, GestureKind, GyroMapping, GyroSettings,
+    KeyRepeat, MouseSensorMapping, MouseSensorSettings, Profile, Settings, StickMapping, StickMappings,
+    StickType, TriggerMapping, TriggerMappings,
+};
+use std::collections::HashMap;
+
+/// Builder for a complete `Config`
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    settings: Settings,
+    profiles: Vec<Profile>,
+}
+
+impl ConfigBuilder {
+    /// Start with default settings and no profiles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the global settings
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set the profile that is active at startup
+    pub fn default_profile(mut self, name: impl Into<String>) -> Self {
+        self.settings.default_profile = name.into();
+        self
+    }
+
+    /// Add a profile (accepts a `Profile` or a `ProfileBuilder`)
+    pub fn profile(mut self, profile: impl Into<Profile>) -> Self {
+        self.profiles.push(profile.into());
+        self
+    }
+
+    /// Validate and build the config
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let config = Config {
+            settings: self.settings,
+            profiles: self.profiles,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Builder for a single `Profile`
+#[derive(Debug, Clone)]
+pub struct ProfileBuilder {
+    profile: Profile,
+}
+
+impl ProfileBuilder {
+    /// Start an empty profile with the given name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            profile: Profile {
+                name: name.into(),
+                description: String::new(),
+                buttons: HashMap::new(),
+                sticks: StickMappings::default(),
+                gyro: GyroSettings::default(),
+                key_repeat: HashMap::new(),
+                buttons_left: HashMap::new(),
+                buttons_right: HashMap::new(),
+                on_press: HashMap::new(),
+                on_release: HashMap::new(),
+                left_stick_deadzone: None,
+                right_stick_deadzone: None,
+                triggers: TriggerMappings::default(),
+                default_sensitivity_index: None,
+                dpad_as_stick: None,
+                on_enter: Vec::new(),
+                on_exit: Vec::new(),
+                gestures: HashMap::new(),
+                mouse_sensor: MouseSensorSettings::default(),
+                gyro_mouse_overrides_left: HashMap::new(),
+                gyro_mouse_overrides_right: HashMap::new(),
+            },
+        }
+    }
+
+    /// Set the description
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.profile.description = description.into();
+        self
+    }
+
+    /// Append an action to a button
+    pub fn button(mut self, button: ButtonType, action: Action) -> Self {
+        self.profile.buttons.entry(button).or_default().push(action);
+        self
+    }
+
+    /// Replace all actions of a button
+    pub fn buttons(mut self, button: ButtonType, actions: Vec<Action>) -> Self {
+        self.profile.buttons.insert(button, actions);
+        self
+    }
+
+    /// Set a stick mapping
+    pub fn stick(mut self, stick: StickType, mapping: StickMapping) -> Self {
+        match stick {
+            StickType::Left => self.profile.sticks.left = Some(mapping),
+            StickType::Right => self.profile.sticks.right = Some(mapping),
+        }
+        self
+    }
+
+    /// Drive a stick's mapping from the D-pad instead of the D-pad buttons
+    pub fn dpad_as_stick(mut self, stick: StickType) -> Self {
+        self.profile.dpad_as_stick = Some(stick);
+        self
+    }
+
+    /// Override the global deadzone of a stick in this profile
+    pub fn stick_deadzone(mut self, stick: StickType, deadzone: f32) -> Self {
+        match stick {
+            StickType::Left => self.profile.left_stick_deadzone = Some(deadzone),
+            StickType::Right => self.profile.right_stick_deadzone = Some(deadzone),
+        }
+        self
+    }
+
+    /// Select a sensitivity level (index into `sensitivity_factor`) when switching to this profile
+    pub fn default_sensitivity_index(mut self, index: usize) -> Self {
+        self.profile.default_sensitivity_index = Some(index);
+        self
+    }
+
+    /// Set the gyro mapping for one controller
+    pub fn gyro(mut self, side: ControllerSide, mapping: GyroMapping) -> Self {
+        match side {
+            ControllerSide::Left => self.profile.gyro.left = mapping,
+            ControllerSide::Right => self.profile.gyro.right = mapping,
+        }
+        self
+    }
+
+    /// Set the trigger mapping for one controller (ZL on the left, ZR on the right)
+    pub fn trigger(mut self, side: ControllerSide, mapping: TriggerMapping) -> Self {
+        match side {
+            ControllerSide::Left => self.profile.triggers.left = Some(mapping),
+            ControllerSide::Right => self.profile.triggers.right = Some(mapping),
+        }
+        self
+    }
+
+    /// Set the optical mouse sensor mapping for one controller
+    pub fn mouse_sensor(mut self, side: ControllerSide, mapping: MouseSensorMapping) -> Self {
+        match side {
+            ControllerSide::Left => self.profile.mouse_sensor.left = mapping,
+            ControllerSide::Right => self.profile.mouse_sensor.right = mapping,
+        }
+        self
+    }
+
+    /// Append an action to a button in the `side`'s own map (`buttons_left`/`buttons_right`)
+    pub fn side_button(mut self, side: ControllerSide, button: ButtonType, action: Action) -> Self {
+        let buttons = match side {
+            ControllerSide::Left => &mut self.profile.buttons_left,
+            ControllerSide::Right => &mut self.profile.buttons_right,
+        };
+        buttons.entry(button).or_default().push(action);
+        self
+    }
+
+    /// Append an action tapped when a button is pressed
+    pub fn on_press(mut self, button: ButtonType, action: Action) -> Self {
+        self.profile.on_press.entry(button).or_default().push(action);
+        self
+    }
+
+    /// Append an action tapped when a button is released
+    pub fn on_release(mut self, button: ButtonType, action: Action) -> Self {
+        self.profile.on_release.entry(button).or_default().push(action);
+        self
+    }
+
+    /// Repeat the keys this button's `keyhold` actions hold (overrides `settings.key_repeat`)
+    pub fn key_repeat(mut self, button: ButtonType, repeat: KeyRepeat) -> Self {
+        self.profile.key_repeat.insert(button, repeat);
+        self
+    }
+
+    /// Append an action run when switching to this profile
+    pub fn on_enter(mut self, action: Action) -> Self {
+        self.profile.on_enter.push(action);
+        self
+    }
+
+    /// Append an action tapped when switching away from this profile
+    pub fn on_exit(mut self, action: Action) -> Self {
+        self.profile.on_exit.push(action);
+        self
+    }
+
+    /// Append an action triggered by a motion gesture
+    pub fn gesture(mut self, kind: GestureKind, action: Action) -> Self {
+        self.profile.gestures.entry(kind).or_default().push(action);
+        self
+    }
+
+    /// Append an action used instead of the normal binding while that side's gyro mouse is on
+    pub fn gyro_override(mut self, side: ControllerSide, button: ButtonType, action: Action) -> Self {
+        let overrides = match side {
+            ControllerSide::Left => &mut self.profile.gyro_mouse_overrides_left,
+            ControllerSide::Right => &mut self.profile.gyro_mouse_overrides_right,
+        };
+        overrides.entry(button).or_default().push(action);
+        self
+    }
+
+    /// Build the profile
+    pub fn build(self) -> Profile {
+        self.profile
+    }
+}
+
+impl From<ProfileBuilder> for Profile {
+    fn from(builder: ProfileBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapping::config::{DirectionalKeys, StickMode};
+
+    #[test]
+    fn test_build_config() {
+        let config = ConfigBuilder::new()
+            .default_profile("game")
+            .profile(
+                ProfileBuilder::new("game")
+                    .description("Built in code")
+                    .button(ButtonType::A, Action::KeyHold { key: Some("space".to_string()) })
+                    .button(ButtonType::A, Action::KeyHold { key: Some("e".to_string()) })
+                    .key_repeat(ButtonType::A, crate::mapping::config::KeyRepeat::default())
+                    .button(ButtonType::Plus, Action::CycleProfiles)
+                    .stick(StickType::Left, StickMapping::directional(DirectionalKeys::new("w", "s", "a", "d")))
+                    .stick(StickType::Right, StickMapping::mouse(2.0))
+                    .gyro_override(ControllerSide::Right, ButtonType::ZR, Action::MouseClick {
+                        button: crate::mapping::config::MouseButton::Left,
+                    }),
+            )
+            .profile(ProfileBuilder::new("menu").button(ButtonType::Plus, Action::CycleProfiles))
+            .build()
+            .unwrap();
+
+        assert_eq!(config.settings.default_profile, "game");
+        assert_eq!(config.profiles.len(), 2);
+
+        let game = &config.profiles[0];
+        assert_eq!(game.buttons[&ButtonType::A].len(), 2);
+        assert_eq!(game.key_repeat[&ButtonType::A].repeat_delay_ms, 500);
+        assert_eq!(game.sticks.left.as_ref().unwrap().mode, StickMode::Directional);
+        assert_eq!(game.sticks.right.as_ref().unwrap().sensitivity, 2.0);
+        assert_eq!(game.gyro_mouse_overrides_right[&ButtonType::ZR].len(), 1);
+    }
+
+    #[test]
+    fn test_build_validates() {
+        let result = ConfigBuilder::new()
+            .profile(ProfileBuilder::new("base").button(ButtonType::ZL, Action::SensitivityClutch { factor: 0.0 }))
+            .build();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,72 @@
+//! Time source for the mapping executor
+//!
+//! Timing features (tap/hold, scheduled combo keys, keep-awake) read the time
+//! through a `Clock` so tests can drive them with a `MockClock` instead of
+//! sleeping.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    /// Current instant
+    fn now(&self) -> Instant;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Manually advanced clock for tests (clones share the same time)
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl MockClock {
+    /// Start at the current real time
+    pub fn new() -> Self {
+        Self { now: Arc::new(Mutex::new(Instant::now())) }
+    }
+
+    /// Move time forward
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_advances_shared_time() {
+        let clock = MockClock::new();
+        let shared = clock.clone();
+        let start = clock.now();
+
+        // Doesn't move on its own
+        assert_eq!(clock.now(), start);
+
+        shared.advance(Duration::from_millis(250));
+        assert_eq!(clock.now() - start, Duration::from_millis(250));
+    }
+}
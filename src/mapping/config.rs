@@ -2,6 +2,7 @@
 //!
 //! Loads mapping configuration from TOML files in the configs/ directory.
 
+use crate::joycon2::types::{RumblePattern, Tilt};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
@@ -22,6 +23,20 @@ pub enum ButtonType {
     
 }
 
+impl ButtonType {
+    /// Controller the button is on
+    pub fn side(self) -> ControllerSide {
+        match self {
+            ButtonType::A | ButtonType::B | ButtonType::X | ButtonType::Y |
+            ButtonType::R | ButtonType::ZR | ButtonType::Plus | ButtonType::Home |
+            ButtonType::RightStickClick | ButtonType::SLR | ButtonType::SRR | ButtonType::Chat => {
+                ControllerSide::Right
+            }
+            _ => ControllerSide::Left
+        }
+    }
+}
+
 /// Stick type enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StickType {
@@ -36,6 +51,35 @@ pub enum ControllerSide {
     Right,
 }
 
+impl From<crate::joycon2::Side> for ControllerSide {
+    fn from(side: crate::joycon2::Side) -> Self {
+        match side {
+            crate::joycon2::Side::Left => Self::Left,
+            crate::joycon2::Side::Right => Self::Right,
+        }
+    }
+}
+
+/// Motion gestures detected from the accelerometer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GestureKind {
+    /// Quick back-and-forth shake
+    Shake,
+}
+
+/// Step of a stick calibration (`JoyConManager::run_calibration`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CalibrationStep {
+    /// Recording the stick at rest
+    Center,
+    /// Recording the stick being rotated fully
+    Rotate,
+    /// Finished, the new calibration is in use
+    Done,
+    /// Cancelled, timed out or the stick didn't reach its full range
+    Failed,
+}
+
 /// Simplified Joy-Con state for mapping (TODO: integrate with Joy2L/Joy2R)
 #[derive(Debug, Clone, Default)]
 pub struct JoyConState {
@@ -45,13 +89,44 @@ pub struct JoyConState {
 /// Joy-Con event types
 #[derive(Debug, Clone)]
 pub enum JoyConEvent {
-    ButtonPressed(ButtonType),
-    ButtonReleased(ButtonType),
+    /// A button was pressed on the controller on `side`
+    ButtonPressed { button: ButtonType, side: ControllerSide },
+    /// A button was released on the controller on `side`
+    ButtonReleased { button: ButtonType, side: ControllerSide },
     StickMoved { stick: StickType, x: f32, y: f32 },
     GyroUpdate { side: ControllerSide, x: f32, y: f32, z: f32 },
     StateUpdate(Box<JoyConState>),
     Connected { side: ControllerSide },
     Disconnected { side: ControllerSide },
+    /// Derived motion: total angular speed (deg/s) and accelerometer tilt
+    Motion { side: ControllerSide, angular_speed: f32, tilt: Tilt },
+    /// No controller of this side was found within `scan_timeout_secs`, scanning for it stopped
+    ScanTimedOut { side: ControllerSide },
+    /// Motion gesture detected from the accelerometer
+    Gesture { side: ControllerSide, kind: GestureKind },
+    /// The sync combo (`sync_combo_left`/`sync_combo_right`) was held for `sync_combo_ms`
+    SyncCombo { side: ControllerSide },
+    /// Link signal strength in dBm, polled every few seconds while connected
+    SignalStrength { side: ControllerSide, rssi: i16 },
+    /// Trigger value (0.0-1.0) changed, ZL on the left controller and ZR on the right
+    Trigger { side: ControllerSide, value: f32 },
+    /// Optical mouse sensor position (cumulative sensor counts)
+    MouseSensor { side: ControllerSide, x: i16, y: i16, distance: u8 },
+    /// Raw notification packet (only emitted when `raw_packets` is enabled)
+    RawPacket { side: ControllerSide, data: Vec<u8> },
+    /// A stick left (`active`) or returned into its deadzone, emitted by the executor
+    StickActive { stick: StickType, active: bool },
+    /// Sent by a controller loop about once per second, right after the events of the packet
+    /// received at `received_at`; the executor thread answers it with `Telemetry`
+    TelemetryProbe { side: ControllerSide, packet_hz: f32, received_at: std::time::Instant },
+    /// Input report rate and average delay from packet receipt until its events were
+    /// processed by the executor, emitted by the executor thread
+    Telemetry { side: ControllerSide, packet_hz: f32, avg_latency_ms: f32 },
+    /// Stick calibration progress (0.0-1.0 within the step) and the latest raw stick reading
+    CalibrationProgress { side: ControllerSide, step: CalibrationStep, progress: f32, raw_x: u16, raw_y: u16 },
+    /// The stick rested off-center (`magnitude`, 0.0-1.0) for `stick_drift_secs` while the
+    /// controller lay still
+    StickDrift { stick: StickType, magnitude: f32 },
 }
 
 #[derive(Debug, Error)]
@@ -66,6 +141,20 @@ pub enum ConfigError {
     Invalid(String),
 }
 
+/// Suspicious but valid configuration found by `Config::lint`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigWarning {
+    /// Where the problem is (e.g. "profile 'base' button A")
+    pub context: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.context, self.message)
+    }
+}
+
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -100,8 +189,240 @@ pub struct Settings {
     /// Array of sensitivity multipliers to cycle through
     #[serde(default = "default_sensitivity_factors")]
     pub sensitivity_factor: Vec<f32>,
+    
+    /// Try to connect directly to cached controllers before scanning
+    #[serde(default)]
+    pub prefer_cached: bool,
+    
+    /// Rumble on profile switch, sensitivity change and gyro toggle
+    #[serde(default)]
+    pub feedback_rumble: bool,
+    
+    /// Feedback rumble intensity (Joy-Con 2 vibration preset)
+    #[serde(default = "default_rumble_intensity")]
+    pub feedback_rumble_intensity: u8,
+    
+    /// Feedback rumble duration in milliseconds
+    #[serde(default = "default_rumble_duration_ms")]
+    pub feedback_rumble_duration_ms: u64,
+    
+    /// Play this pattern as feedback instead of a single pulse
+    #[serde(default)]
+    pub feedback_rumble_pattern: Option<RumblePattern>,
+    
+    /// Show the active profile on the player LEDs (profile 1 = LED 1, ...)
+    #[serde(default)]
+    pub profile_led_indicator: bool,
+    
+    /// Emit raw notification packets as events (for protocol research)
+    #[serde(default)]
+    pub raw_packets: bool,
+    
+    /// Window title/exe substring -> profile name (Windows only)
+    #[serde(default)]
+    pub window_profiles: HashMap<String, String>,
+    
+    /// Controllers the manager scans for and waits on (e.g. only ["Right"] for single Joy-Con play)
+    #[serde(default = "default_expected_controllers")]
+    pub expected_controllers: Vec<ControllerSide>,
+    
+    /// Delay between key downs of a multi-key combo like "shift+w" (milliseconds, 0 = none)
+    #[serde(default)]
+    pub combo_key_delay_ms: u64,
+    
+    /// Keep the gyro mouse on/off state when switching profiles instead of using the profile's default
+    #[serde(default)]
+    pub persist_gyro_across_profiles: bool,
+    
+    /// Allow `runcommand` actions to spawn processes (off so untrusted configs can't run commands)
+    #[serde(default)]
+    pub allow_commands: bool,
+    
+    /// Cap on stick/gyro mouse movement per update (pixels), guards against glitchy spikes
+    #[serde(default = "default_max_mouse_speed")]
+    pub max_mouse_speed: f32,
+    
+    /// Pixels per update of a fully pushed mouse-mode stick at sensitivity 1.0; effective speed is
+    /// stick × mapping sensitivity × sensitivity factor × `stick_mouse_base`
+    #[serde(default = "default_stick_mouse_base")]
+    pub stick_mouse_base: f32,
+    
+    /// Reverse vertical scrolling ("natural" scrolling, content follows the motion)
+    #[serde(default)]
+    pub invert_scroll: bool,
+    
+    /// Reverse horizontal scrolling
+    #[serde(default)]
+    pub invert_scroll_x: bool,
+    
+    /// Acceleration (G) a reading must exceed to count toward a shake gesture
+    #[serde(default = "default_shake_threshold")]
+    pub shake_threshold: f32,
+    
+    /// Time window for the acceleration peaks of one shake (milliseconds)
+    #[serde(default = "default_shake_window_ms")]
+    pub shake_window_ms: u64,
+    
+    /// Minimum time between two shake gestures (milliseconds)
+    #[serde(default = "default_shake_cooldown_ms")]
+    pub shake_cooldown_ms: u64,
+    
+    /// Off-center magnitude (0.0-0.5) of a resting stick that counts as drift
+    #[serde(default = "default_stick_drift_threshold")]
+    pub stick_drift_threshold: f32,
+    
+    /// How long a stick must rest off-center before drift is reported (seconds, 0 = off)
+    #[serde(default = "default_stick_drift_secs")]
+    pub stick_drift_secs: u64,
+    
+    /// Move the stick's calibration center to its resting position when drift is detected
+    #[serde(default)]
+    pub stick_drift_recenter: bool,
+    
+    /// Minimum stick axis change that sends a stick event (lower = smoother, more CPU)
+    #[serde(default = "default_stick_event_threshold")]
+    pub stick_event_threshold: f32,
+    
+    /// Minimum gyro axis change (deg/s) that sends a gyro event (lower = smoother, more CPU)
+    #[serde(default = "default_gyro_event_threshold")]
+    pub gyro_event_threshold: f32,
+    
+    /// Give up scanning for a missing controller after this many seconds (0 = scan forever)
+    #[serde(default)]
+    pub scan_timeout_secs: u64,
+    
+    /// Stop scanning once all expected controllers are connected (resumes on disconnect)
+    #[serde(default)]
+    pub scan_once: bool,
+    
+    /// How long a `taphold` button must be held to count as a hold (milliseconds)
+    #[serde(default = "default_hold_time_ms")]
+    pub hold_time_ms: u64,
+    
+    /// Idle time after which `keepawake` nudges the mouse to stop the screen sleeping (seconds)
+    #[serde(default = "default_keep_awake_interval_secs")]
+    pub keep_awake_interval_secs: u64,
+    
+    /// How long the sync combo must be held to emit a SyncCombo event (milliseconds, 0 = off)
+    #[serde(default = "default_sync_combo_ms")]
+    pub sync_combo_ms: u64,
+    
+    /// Buttons of the Left controller that form the sync combo
+    #[serde(default = "default_sync_combo_left")]
+    pub sync_combo_left: Vec<ButtonType>,
+    
+    /// Buttons of the Right controller that form the sync combo
+    #[serde(default = "default_sync_combo_right")]
+    pub sync_combo_right: Vec<ButtonType>,
+    
+    /// Ignore re-triggers of profile/sensitivity cycling and gyro toggles on the same button
+    /// within this time, guards against bouncy buttons (milliseconds, 0 = off)
+    #[serde(default = "default_action_cooldown_ms")]
+    pub action_cooldown_ms: u64,
+    
+    /// Button -> profile name, picked when the button is held while the first controller connects
+    /// 
+    /// Reconnects don't pick again, and the held button's own mapping doesn't fire.
+    #[serde(default)]
+    pub startup_profiles: HashMap<ButtonType, String>,
+    
+    /// How long after the first controller connects a held `startup_profiles` button selects its profile (milliseconds)
+    #[serde(default = "default_startup_select_ms")]
+    pub startup_select_ms: u64,
+    
+    /// Shortest click a `mouseclick` sends, a quicker release is delayed (milliseconds, 0 = off)
+    #[serde(default = "default_click_min_duration_ms")]
+    pub click_min_duration_ms: u64,
+    
+    /// Ignore a `mouseclick` press this soon after the previous press of the same mouse button
+    /// (milliseconds, 0 = off)
+    #[serde(default = "default_click_debounce_ms")]
+    pub click_debounce_ms: u64,
+    
+    /// Ignore a press or release this soon after the previous transition of the same button,
+    /// for worn controllers that bounce (milliseconds, 0 = off)
+    #[serde(default)]
+    pub debounce_ms: u64,
+    
+    /// What the controller loops drop when the executor is 100 events behind, instead of
+    /// waiting for it and delaying the Bluetooth reads (stick and motion events only)
+    #[serde(default)]
+    pub event_channel_full: ChannelFullPolicy,
+    
+    /// Send a keepalive command to each controller this often, for BLE stacks that drop
+    /// idle links (seconds, 0 = off)
+    #[serde(default)]
+    pub keepalive_interval_secs: u64,
+    
+    /// Swap A with B and X with Y before events are generated (Xbox button layout)
+    #[serde(default)]
+    pub swap_ab_xy: bool,
+    
+    /// Button that cycles profiles in every profile, taking precedence over the profile's own mapping
+    #[serde(default)]
+    pub profile_switch_button: Option<ButtonType>,
+    
+    /// Also write the log to this file (see `logging::init`)
+    #[serde(default)]
+    pub log_file: Option<String>,
+    
+    /// Level of joy2-rs log messages: error, warn, info, debug or trace
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    
+    /// Size at which the log file is rotated to `<log_file>.1` (KiB)
+    #[serde(default = "default_log_max_size_kb")]
+    pub log_max_size_kb: u64,
+    
+    /// Start the text control server on this address, e.g. "127.0.0.1:7878" (off when unset)
+    #[serde(default)]
+    pub control_address: Option<String>,
+    
+    /// Re-send held `keyhold` keys like OS key repeat, for games that don't repeat injected
+    /// keys (off when unset, per-button overrides in a profile's `key_repeat`)
+    #[serde(default)]
+    pub key_repeat: Option<KeyRepeat>,
+    
+    /// Start paused and only send real input once these buttons are held together,
+    /// the player LEDs flash while waiting (off when empty)
+    #[serde(default)]
+    pub confirm_to_activate: Vec<ButtonType>,
+}
+
+/// OS-style key repeat for the keys a `keyhold` button holds
+/// 
+/// After `repeat_delay_ms` the last key of each held binding (e.g. "w" of "shift+w") gets
+/// another key_down every 1 / `repeat_rate_hz` seconds until the button is released.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KeyRepeat {
+    /// Time from the press until the first repeat (milliseconds)
+    #[serde(default = "default_key_repeat_delay_ms")]
+    pub repeat_delay_ms: u64,
+    
+    /// Repeats per second after the delay (0 = no repeat, to opt a button out)
+    #[serde(default = "default_key_repeat_rate_hz")]
+    pub repeat_rate_hz: f32,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            repeat_delay_ms: default_key_repeat_delay_ms(),
+            repeat_rate_hz: default_key_repeat_rate_hz(),
+        }
+    }
 }
 
+impl KeyRepeat {
+    /// Time between repeats, None when repeating is off
+    pub fn interval(&self) -> Option<std::time::Duration> {
+        (self.repeat_rate_hz > 0.0).then(|| std::time::Duration::from_secs_f64(1.0 / self.repeat_rate_hz as f64))
+    }
+}
+
+fn default_key_repeat_delay_ms() -> u64 { 500 }
+fn default_key_repeat_rate_hz() -> f32 { 30.0 }
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -110,14 +431,84 @@ impl Default for Settings {
             vibration_enabled: true,
             default_profile: default_profile_name(),
             sensitivity_factor: default_sensitivity_factors(),
+            prefer_cached: false,
+            feedback_rumble: false,
+            feedback_rumble_intensity: default_rumble_intensity(),
+            feedback_rumble_duration_ms: default_rumble_duration_ms(),
+            feedback_rumble_pattern: None,
+            profile_led_indicator: false,
+            raw_packets: false,
+            window_profiles: HashMap::new(),
+            expected_controllers: default_expected_controllers(),
+            combo_key_delay_ms: 0,
+            persist_gyro_across_profiles: false,
+            allow_commands: false,
+            max_mouse_speed: default_max_mouse_speed(),
+            stick_mouse_base: default_stick_mouse_base(),
+            invert_scroll: false,
+            invert_scroll_x: false,
+            shake_threshold: default_shake_threshold(),
+            shake_window_ms: default_shake_window_ms(),
+            shake_cooldown_ms: default_shake_cooldown_ms(),
+            stick_drift_threshold: default_stick_drift_threshold(),
+            stick_drift_secs: default_stick_drift_secs(),
+            stick_drift_recenter: false,
+            stick_event_threshold: default_stick_event_threshold(),
+            gyro_event_threshold: default_gyro_event_threshold(),
+            scan_timeout_secs: 0,
+            scan_once: false,
+            hold_time_ms: default_hold_time_ms(),
+            keep_awake_interval_secs: default_keep_awake_interval_secs(),
+            sync_combo_ms: default_sync_combo_ms(),
+            sync_combo_left: default_sync_combo_left(),
+            sync_combo_right: default_sync_combo_right(),
+            action_cooldown_ms: default_action_cooldown_ms(),
+            startup_profiles: HashMap::new(),
+            startup_select_ms: default_startup_select_ms(),
+            click_min_duration_ms: default_click_min_duration_ms(),
+            click_debounce_ms: default_click_debounce_ms(),
+            event_channel_full: ChannelFullPolicy::default(),
+            debounce_ms: 0,
+            keepalive_interval_secs: 0,
+            swap_ab_xy: false,
+            profile_switch_button: None,
+            log_file: None,
+            log_level: default_log_level(),
+            log_max_size_kb: default_log_max_size_kb(),
+            control_address: None,
+            key_repeat: None,
+            confirm_to_activate: Vec::new(),
         }
     }
 }
 
 fn default_deadzone() -> f32 { 0.15 }
+fn default_max_mouse_speed() -> f32 { 500.0 }
+fn default_stick_mouse_base() -> f32 { 10.0 }
+fn default_shake_threshold() -> f32 { 2.5 }
+fn default_shake_window_ms() -> u64 { 500 }
+fn default_shake_cooldown_ms() -> u64 { 1000 }
+fn default_stick_drift_threshold() -> f32 { 0.1 }
+fn default_stick_drift_secs() -> u64 { 10 }
+fn default_stick_event_threshold() -> f32 { 0.05 }
+fn default_gyro_event_threshold() -> f32 { 0.5 }
+fn default_hold_time_ms() -> u64 { 500 }
+fn default_keep_awake_interval_secs() -> u64 { 60 }
+fn default_sync_combo_ms() -> u64 { 2000 }
+fn default_action_cooldown_ms() -> u64 { 250 }
+fn default_startup_select_ms() -> u64 { 1500 }
+fn default_click_min_duration_ms() -> u64 { 20 }
+fn default_click_debounce_ms() -> u64 { 30 }
+fn default_sync_combo_left() -> Vec<ButtonType> { vec![ButtonType::SLL, ButtonType::SRL] }
+fn default_sync_combo_right() -> Vec<ButtonType> { vec![ButtonType::SLR, ButtonType::SRR] }
 fn default_true() -> bool { true }
 fn default_profile_name() -> String { "base".to_string() }
 fn default_sensitivity_factors() -> Vec<f32> { vec![1.0, 2.0, 3.0] }
+fn default_expected_controllers() -> Vec<ControllerSide> { vec![ControllerSide::Left, ControllerSide::Right] }
+fn default_rumble_intensity() -> u8 { 0x03 }
+fn default_rumble_duration_ms() -> u64 { 100 }
+fn default_log_level() -> String { "info".to_string() }
+fn default_log_max_size_kb() -> u64 { 5 * 1024 }
 
 /// A profile represents a complete set of mappings (renamed from Layer)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,13 +521,69 @@ pub struct Profile {
     #[serde(default)]
     pub buttons: HashMap<ButtonType, Vec<Action>>,
     
+    /// Bindings for buttons sent by the left controller, taking precedence over `buttons`
+    #[serde(default)]
+    pub buttons_left: HashMap<ButtonType, Vec<Action>>,
+    
+    /// Bindings for buttons sent by the right controller, taking precedence over `buttons`
+    #[serde(default)]
+    pub buttons_right: HashMap<ButtonType, Vec<Action>>,
+    
+    /// Actions tapped when a button is pressed, next to its `buttons` actions
+    #[serde(default)]
+    pub on_press: HashMap<ButtonType, Vec<Action>>,
+    
+    /// Actions tapped when a button is released, next to its `buttons` actions
+    #[serde(default)]
+    pub on_release: HashMap<ButtonType, Vec<Action>>,
+    
+    /// Key repeat for these buttons' `keyhold` keys (overrides `settings.key_repeat`)
+    #[serde(default)]
+    pub key_repeat: HashMap<ButtonType, KeyRepeat>,
+    
     #[serde(default)]
     pub sticks: StickMappings,
     
+    /// Left stick deadzone for this profile (overrides `settings.left_stick_deadzone`)
+    #[serde(default)]
+    pub left_stick_deadzone: Option<f32>,
+    
+    /// Right stick deadzone for this profile (overrides `settings.right_stick_deadzone`)
+    #[serde(default)]
+    pub right_stick_deadzone: Option<f32>,
+    
+    /// Drive this stick's mapping from the D-pad (8-way) instead of the D-pad button actions
+    #[serde(default)]
+    pub dpad_as_stick: Option<StickType>,
+    
+    /// Index into `settings.sensitivity_factor` selected when switching to this profile
+    #[serde(default)]
+    pub default_sensitivity_index: Option<usize>,
+    
     /// Gyroscope settings per controller
     #[serde(default)]
     pub gyro: GyroSettings,
     
+    /// ZL/ZR trigger value mappings
+    #[serde(default)]
+    pub triggers: TriggerMappings,
+    
+    /// Optical mouse sensor settings per controller
+    #[serde(default)]
+    pub mouse_sensor: MouseSensorSettings,
+    
+    /// Actions run when switching to this profile (held until the profile is left)
+    #[serde(default)]
+    pub on_enter: Vec<Action>,
+    
+    /// Actions tapped when switching away from this profile
+    #[serde(default)]
+    pub on_exit: Vec<Action>,
+    
+    /// Actions triggered by motion gestures (e.g. Shake)
+    #[serde(default)]
+    pub gestures: HashMap<GestureKind, Vec<Action>>,
+    
     /// Button overrides when RIGHT gyro mouse is active
     #[serde(default)]
     pub gyro_mouse_overrides_right: HashMap<ButtonType, Vec<Action>>,
@@ -146,6 +593,25 @@ pub struct Profile {
     pub gyro_mouse_overrides_left: HashMap<ButtonType, Vec<Action>>,
 }
 
+impl Profile {
+    /// Actions bound to a button sent by the controller on `side`: that side's map
+    /// (`buttons_left`/`buttons_right`) first, then `buttons`
+    pub fn button_actions(&self, button: ButtonType, side: ControllerSide) -> Option<&Vec<Action>> {
+        let side_buttons = match side {
+            ControllerSide::Left => &self.buttons_left,
+            ControllerSide::Right => &self.buttons_right,
+        };
+        side_buttons.get(&button).or_else(|| self.buttons.get(&button))
+    }
+    
+    /// All button bindings with the side sending them (`buttons` entries with the button's own side)
+    fn sided_buttons(&self) -> impl Iterator<Item = (ButtonType, ControllerSide, &Vec<Action>)> {
+        self.buttons.iter().map(|(button, actions)| (*button, button.side(), actions))
+            .chain(self.buttons_left.iter().map(|(button, actions)| (*button, ControllerSide::Left, actions)))
+            .chain(self.buttons_right.iter().map(|(button, actions)| (*button, ControllerSide::Right, actions)))
+    }
+}
+
 /// Gyroscope settings for both controllers
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GyroSettings {
@@ -176,12 +642,119 @@ pub struct StickMapping {
     #[serde(default = "default_sensitivity")]
     pub sensitivity: f32,
     
+    /// Per-direction sensitivity overrides (default to `sensitivity` when unset)
+    #[serde(default)]
+    pub sensitivity_left: Option<f32>,
+    #[serde(default)]
+    pub sensitivity_right: Option<f32>,
+    #[serde(default)]
+    pub sensitivity_up: Option<f32>,
+    #[serde(default)]
+    pub sensitivity_down: Option<f32>,
+    
     /// For directional mode: key bindings
     #[serde(default)]
     pub directions: Option<DirectionalKeys>,
+    
+    /// For mouse mode: snap movement to the nearest of N evenly spaced angles (e.g. 4 or 8)
+    #[serde(default)]
+    pub snap_angles: Option<u8>,
+    
+    /// For mouse mode: output just outside the deadzone starts at this fraction of full speed (0.0-1.0)
+    #[serde(default)]
+    pub anti_deadzone: f32,
+    
+    /// For mouse mode: warp the cursor back to the screen center when it reaches an edge
+    #[serde(default)]
+    pub warp_at_edge: bool,
+    
+    /// For mouse mode: (radius, multiplier) rings, a stick pushed at least `radius` far
+    /// moves at `multiplier` times the speed (the last ring reached wins, 1.0 inside the first)
+    #[serde(default)]
+    pub sensitivity_zones: Vec<(f32, f32)>,
+    
+    /// For gamepad mode: which gamepad stick to drive (defaults to the same-named stick)
+    #[serde(default)]
+    pub gamepad_stick: Option<StickType>,
+    
+    /// For pulse mode: keep tapping after the stick is held this long (ms, off when unset)
+    #[serde(default)]
+    pub pulse_repeat_delay_ms: Option<u64>,
+    
+    /// For pulse mode: time between repeated taps (ms)
+    #[serde(default = "default_pulse_repeat_interval_ms")]
+    pub pulse_repeat_interval_ms: u64,
+    
+    /// Actions for clicking the stick while it is pushed in a direction (any mode)
+    /// 
+    /// Used instead of the stick-click button mapping; a click in the deadzone or in
+    /// an unbound direction falls back to the button mapping.
+    #[serde(default)]
+    pub click_directions: Option<DirectionalKeys>,
+}
+
+impl StickMapping {
+    /// Mapping with the given mode and default sensitivity
+    pub fn new(mode: StickMode) -> Self {
+        Self {
+            mode,
+            sensitivity: default_sensitivity(),
+            sensitivity_left: None,
+            sensitivity_right: None,
+            sensitivity_up: None,
+            sensitivity_down: None,
+            directions: None,
+            snap_angles: None,
+            anti_deadzone: 0.0,
+            warp_at_edge: false,
+            sensitivity_zones: Vec::new(),
+            gamepad_stick: None,
+            pulse_repeat_delay_ms: None,
+            pulse_repeat_interval_ms: default_pulse_repeat_interval_ms(),
+            click_directions: None,
+        }
+    }
+    
+    /// Stick driving a virtual gamepad stick
+    pub fn gamepad(stick: StickType) -> Self {
+        Self { gamepad_stick: Some(stick), ..Self::new(StickMode::Gamepad) }
+    }
+    
+    /// Stick as mouse with the given sensitivity
+    pub fn mouse(sensitivity: f32) -> Self {
+        Self { sensitivity, ..Self::new(StickMode::Mouse) }
+    }
+    
+    /// Stick as directional keys
+    pub fn directional(directions: DirectionalKeys) -> Self {
+        Self { directions: Some(directions), ..Self::new(StickMode::Directional) }
+    }
+    
+    /// Stick tapping directional keys once per push
+    pub fn pulse(directions: DirectionalKeys) -> Self {
+        Self { directions: Some(directions), ..Self::new(StickMode::Pulse) }
+    }
+    
+    /// Get the (x, y) sensitivity for a stick position, picking the split value by sign
+    /// 
+    /// Negative Y is up on the controller.
+    pub fn axis_sensitivity(&self, x: f32, y: f32) -> (f32, f32) {
+        let sens_x = if x < 0.0 { self.sensitivity_left } else { self.sensitivity_right };
+        let sens_y = if y < 0.0 { self.sensitivity_up } else { self.sensitivity_down };
+        (sens_x.unwrap_or(self.sensitivity), sens_y.unwrap_or(self.sensitivity))
+    }
+    
+    /// Get the `sensitivity_zones` multiplier for a stick pushed `magnitude` far
+    pub fn zone_multiplier(&self, magnitude: f32) -> f32 {
+        self.sensitivity_zones.iter()
+            .rev()
+            .find(|(radius, _)| magnitude >= *radius)
+            .map_or(1.0, |(_, multiplier)| *multiplier)
+    }
 }
 
 fn default_sensitivity() -> f32 { 1.0 }
+fn default_pulse_repeat_interval_ms() -> u64 { 150 }
 
 /// Stick mapping modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -193,17 +766,201 @@ pub enum StickMode {
     /// Map to WASD/arrow keys (directional)
     Directional,
     
+    /// Tap the `directions` once per push, the stick must return to the deadzone
+    /// before the next (for turn-based and grid games)
+    Pulse,
+    
+    /// Drive a virtual gamepad stick (see `gamepad_stick`)
+    Gamepad,
+    
     /// Disabled
     Disabled,
 }
 
-/// Directional key bindings
+/// Trigger mappings (ZL on the left controller, ZR on the right)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerMappings {
+    /// ZL trigger mapping
+    pub left: Option<TriggerMapping>,
+    
+    /// ZR trigger mapping
+    pub right: Option<TriggerMapping>,
+}
+
+/// Trigger value mapping
+/// 
+/// Joy-Con 2 triggers are digital, so the value is 0.0 or 1.0. The trigger's button
+/// actions (`ZL`/`ZR`) still run, so usually only one of the two is mapped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerMapping {
+    /// Mapping mode
+    pub mode: TriggerMode,
+    
+    /// For threshold mode: value (0.0-1.0) at which the actions are pressed
+    #[serde(default = "default_trigger_threshold")]
+    pub threshold: f32,
+    
+    /// For threshold mode: actions held while the value is at or above the threshold
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl TriggerMapping {
+    /// Actions held while the trigger is past the default threshold
+    pub fn threshold(actions: Vec<Action>) -> Self {
+        Self { mode: TriggerMode::Threshold, threshold: default_trigger_threshold(), actions }
+    }
+    
+    /// Trigger value sent to the virtual gamepad's trigger axis
+    pub fn gamepad() -> Self {
+        Self { mode: TriggerMode::Gamepad, threshold: default_trigger_threshold(), actions: Vec::new() }
+    }
+}
+
+fn default_trigger_threshold() -> f32 { 0.5 }
+
+/// Trigger mapping modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerMode {
+    /// Press the actions when the value crosses the threshold
+    Threshold,
+    
+    /// Send the value to the gamepad backend's trigger axis
+    Gamepad,
+}
+
+/// Directional bindings, the actions of a direction run while the stick is in its sector
+/// 
+/// Each direction is a key string (`up = "shift+w"`, held like `keyhold`) or a list of
+/// actions (`up = [{ type = "keyhold", key = "w" }, { type = "mouseclick", button = "left" }]`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DirectionalKeys {
-    pub up: String,
-    pub down: String,
-    pub left: String,
-    pub right: String,
+    #[serde(deserialize_with = "deserialize_direction", serialize_with = "serialize_direction")]
+    pub up: Vec<Action>,
+    #[serde(deserialize_with = "deserialize_direction", serialize_with = "serialize_direction")]
+    pub down: Vec<Action>,
+    #[serde(deserialize_with = "deserialize_direction", serialize_with = "serialize_direction")]
+    pub left: Vec<Action>,
+    #[serde(deserialize_with = "deserialize_direction", serialize_with = "serialize_direction")]
+    pub right: Vec<Action>,
+    
+    /// Optional diagonal bindings (used instead of the two orthogonal bindings)
+    #[serde(default, deserialize_with = "deserialize_optional_direction", serialize_with = "serialize_optional_direction")]
+    pub up_left: Option<Vec<Action>>,
+    #[serde(default, deserialize_with = "deserialize_optional_direction", serialize_with = "serialize_optional_direction")]
+    pub up_right: Option<Vec<Action>>,
+    #[serde(default, deserialize_with = "deserialize_optional_direction", serialize_with = "serialize_optional_direction")]
+    pub down_left: Option<Vec<Action>>,
+    #[serde(default, deserialize_with = "deserialize_optional_direction", serialize_with = "serialize_optional_direction")]
+    pub down_right: Option<Vec<Action>>,
+}
+
+impl DirectionalKeys {
+    /// Orthogonal key bindings without diagonals
+    pub fn new(up: impl Into<String>, down: impl Into<String>, left: impl Into<String>, right: impl Into<String>) -> Self {
+        Self {
+            up: key_binding(up.into()),
+            down: key_binding(down.into()),
+            left: key_binding(left.into()),
+            right: key_binding(right.into()),
+            up_left: None,
+            up_right: None,
+            down_left: None,
+            down_right: None,
+        }
+    }
+    
+    /// Get the diagonal binding for the given vertical/horizontal direction, if one is set
+    pub fn diagonal(&self, up: bool, left: bool) -> Option<&[Action]> {
+        let actions = match (up, left) {
+            (true, true) => &self.up_left,
+            (true, false) => &self.up_right,
+            (false, true) => &self.down_left,
+            (false, false) => &self.down_right,
+        };
+        actions.as_deref().filter(|a| !a.is_empty())
+    }
+    
+    /// All bindings (orthogonal and diagonal) as (name, actions) pairs
+    pub fn all_directions(&self) -> Vec<(&'static str, &[Action])> {
+        let mut directions = vec![
+            ("up", self.up.as_slice()),
+            ("down", self.down.as_slice()),
+            ("left", self.left.as_slice()),
+            ("right", self.right.as_slice()),
+        ];
+        let diagonals = [
+            ("up_left", &self.up_left),
+            ("up_right", &self.up_right),
+            ("down_left", &self.down_left),
+            ("down_right", &self.down_right),
+        ];
+        for (name, actions) in diagonals {
+            if let Some(actions) = actions {
+                directions.push((name, actions.as_slice()));
+            }
+        }
+        directions
+    }
+}
+
+/// A key string as a direction binding (empty = no action)
+fn key_binding(key: String) -> Vec<Action> {
+    if key.is_empty() {
+        Vec::new()
+    } else {
+        vec![Action::KeyHold { key: Some(key) }]
+    }
+}
+
+/// A direction binding as written in TOML: a key string, one action or a list of actions
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DirectionBinding {
+    Key(String),
+    Actions(Vec<Action>),
+    Action(Action),
+}
+
+fn deserialize_direction<'de, D>(deserializer: D) -> Result<Vec<Action>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match DirectionBinding::deserialize(deserializer)? {
+        DirectionBinding::Key(key) => key_binding(key),
+        DirectionBinding::Actions(actions) => actions,
+        DirectionBinding::Action(action) => vec![action],
+    })
+}
+
+fn deserialize_optional_direction<'de, D>(deserializer: D) -> Result<Option<Vec<Action>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_direction(deserializer).map(Some)
+}
+
+/// Write single-key bindings back as plain strings
+fn serialize_direction<S>(actions: &[Action], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match actions {
+        [] => serializer.serialize_str(""),
+        [Action::KeyHold { key: Some(key) }] => serializer.serialize_str(key),
+        _ => actions.serialize(serializer),
+    }
+}
+
+fn serialize_optional_direction<S>(actions: &Option<Vec<Action>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match actions {
+        Some(actions) => serialize_direction(actions, serializer),
+        None => serializer.serialize_none(),
+    }
 }
 
 /// Gyroscope mapping per controller
@@ -213,7 +970,8 @@ pub struct GyroMapping {
     #[serde(default)]
     pub enabled: bool,
     
-    /// Output target (only "mouse" supported for PC)
+    /// Output target: "mouse" (move cursor), "scroll" (tilt to scroll) or "gamepad_rstick"
+    /// (deflect the virtual gamepad's right stick, see `GYRO_STICK_FULL_SCALE`)
     #[serde(default = "default_gyro_output")]
     pub output: String,
     
@@ -232,6 +990,44 @@ pub struct GyroMapping {
     /// Invert Y-axis
     #[serde(default)]
     pub invert_y: bool,
+    
+    /// Whether gyro mouse starts active when this profile becomes active
+    #[serde(default)]
+    pub default_enabled: bool,
+    
+    /// How the controller is held, selects which gyro axes drive the mouse
+    #[serde(default)]
+    pub orientation: GyroOrientation,
+    
+    /// Warp the cursor back to the screen center when it reaches an edge
+    #[serde(default)]
+    pub warp_at_edge: bool,
+}
+
+/// Which event to drop when the executor falls behind and its event channel is full
+/// 
+/// Only stick, gyro, motion, mouse sensor and diagnostic events are dropped, button and
+/// connection events wait for room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelFullPolicy {
+    /// Drop the oldest queued event, the executor catches up with the latest input
+    #[default]
+    DropOldest,
+    /// Drop the event being sent, keeping the queue in order
+    DropNewest,
+}
+
+/// How a controller is held for gyro aiming
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GyroOrientation {
+    /// Lying flat, buttons facing up (turning the controller like a steering wheel flat on a table)
+    #[default]
+    FaceUp,
+    /// Upright with the buttons facing the player, the top pointed at the ceiling
+    FaceForward,
+    /// Held horizontally on its own (rail away from the player), buttons facing up
+    Sideways,
 }
 
 impl Default for GyroMapping {
@@ -243,20 +1039,97 @@ impl Default for GyroMapping {
             sensitivity_y: 1.0,
             invert_x: false,
             invert_y: false,
+            default_enabled: false,
+            orientation: GyroOrientation::default(),
+            warp_at_edge: false,
         }
     }
 }
 
 fn default_gyro_output() -> String { "mouse".to_string() }
 
-/// Action to perform when input is triggered
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(tag = "type", rename_all = "lowercase")]
-pub enum Action {
-    /// Do nothing (explicit no-op)
-    None { 
-        #[serde(default, deserialize_with = "deserialize_optional_key")]
-        key: Option<String> 
+/// Supported values for `GyroMapping::output`
+pub const GYRO_OUTPUTS: &[&str] = &["mouse", "scroll", "gamepad_rstick"];
+
+/// Angular speed (deg/s, after sensitivity and sensitivity factor) that fully deflects the
+/// gamepad right stick with `output = "gamepad_rstick"`
+pub const GYRO_STICK_FULL_SCALE: f32 = 100.0;
+
+/// Optical mouse sensor settings for both controllers
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MouseSensorSettings {
+    #[serde(default)]
+    pub left: MouseSensorMapping,
+    
+    #[serde(default)]
+    pub right: MouseSensorMapping,
+}
+
+/// Optical mouse sensor mapping per controller
+/// 
+/// The sensor reports a cumulative position in sensor counts, so each packet is
+/// converted to a delta and scaled to pixels. Raw axes are used as reported:
+/// +x and +y move the cursor right and down with the sensor facing the surface.
+/// Use `swap_axes`/`invert_*` if the controller is held differently.
+/// 
+/// Calibration: with `sensitivity = 1.0` one count is one pixel. Slide the
+/// controller across a known distance and scale `sensitivity` until the cursor
+/// travels the distance you want.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MouseSensorMapping {
+    /// Enable sensor-to-mouse
+    #[serde(default)]
+    pub enabled: bool,
+    
+    /// Pixels per sensor count
+    #[serde(default = "default_sensitivity")]
+    pub sensitivity: f32,
+    
+    /// Per-axis deltas at or below this many counts are ignored
+    #[serde(default)]
+    pub deadzone: u16,
+    
+    /// Per-packet deltas above this many counts are dropped (sensor re-acquiring the surface)
+    #[serde(default = "default_sensor_max_jump")]
+    pub max_jump: u16,
+    
+    /// Swap X and Y axes
+    #[serde(default)]
+    pub swap_axes: bool,
+    
+    /// Invert X-axis
+    #[serde(default)]
+    pub invert_x: bool,
+    
+    /// Invert Y-axis
+    #[serde(default)]
+    pub invert_y: bool,
+}
+
+impl Default for MouseSensorMapping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sensitivity: 1.0,
+            deadzone: 0,
+            max_jump: default_sensor_max_jump(),
+            swap_axes: false,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+}
+
+fn default_sensor_max_jump() -> u16 { 200 }
+
+/// Action to perform when input is triggered
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Action {
+    /// Do nothing (explicit no-op)
+    None { 
+        #[serde(default, deserialize_with = "deserialize_optional_key")]
+        key: Option<String> 
     },
     
     /// Hold a key while button is held
@@ -268,17 +1141,45 @@ pub enum Action {
     /// Move mouse relatively
     MouseMove { dx: i32, dy: i32 },
     
+    /// Move mouse relatively every update while the button is held (e.g. camera pan)
+    #[serde(rename = "mousemovehold")]
+    MouseMoveHold { dx: i32, dy: i32 },
+    
     /// Click mouse button
     MouseClick { button: MouseButton },
     
+    /// Toggle a mouse button: first press locks it down, next press releases it
+    MouseToggle { button: MouseButton },
+    
+    /// Toggle a key (or "ctrl+m" combo): first press holds it down, next press releases it
+    KeyToggle { key: String },
+    
+    /// Type a string on press (Unicode input, so any character works)
+    TypeText { text: String },
+    
+    /// Start tapping a key (or combo) `rate_hz` times per second on one press, stop on the next
+    TurboToggle { key: String, rate_hz: f32 },
+    
     /// Cycle to the next profile
     #[serde(rename = "cycleprofiles")]
     CycleProfiles,
     
-    /// Cycle through sensitivity levels
+    /// Switch to profile `b` when on profile `a`, otherwise to `a`
+    #[serde(rename = "toggleprofiles")]
+    ToggleProfiles { a: String, b: String },
+    
+    /// Cycle through sensitivity levels (both controllers, re-syncing per-side levels)
     #[serde(rename = "cyclesensitivity")]
     CycleSensitivity,
     
+    /// Cycle the sensitivity level of the left stick, gyro and mouse sensor only
+    #[serde(rename = "cyclesensitivityleft")]
+    CycleSensitivityLeft,
+    
+    /// Cycle the sensitivity level of the right stick, gyro and mouse sensor only
+    #[serde(rename = "cyclesensitivityright")]
+    CycleSensitivityRight,
+    
     /// Toggle gyro mouse for left controller
     #[serde(rename = "togglegyromousel")]
     ToggleGyroMouseL,
@@ -286,6 +1187,180 @@ pub enum Action {
     /// Toggle gyro mouse for right controller
     #[serde(rename = "togglegyromouser")]
     ToggleGyroMouseR,
+    
+    /// Scale sensitivity by `factor` while the button is held (e.g. 0.3 for precise aim)
+    #[serde(rename = "sensitivityclutch")]
+    SensitivityClutch { factor: f32 },
+    
+    /// Panic button: release all held keys/mouse buttons and disable gyro mouse
+    #[serde(rename = "releaseall")]
+    ReleaseAll,
+    
+    /// Pause/resume all input injection (while paused only this button works)
+    #[serde(rename = "togglepause")]
+    TogglePause,
+    
+    /// Toggle keep-awake: nudge the mouse 1px and back when idle so the screen doesn't sleep
+    #[serde(rename = "keepawake")]
+    KeepAwake,
+    
+    /// Move the cursor to the center of the primary monitor
+    #[serde(rename = "warpcenter")]
+    WarpCenter,
+    
+    /// Play a rumble pattern on the button's controller
+    #[serde(rename = "rumble")]
+    Rumble { pattern: RumblePattern },
+    
+    /// Suppress gyro mouse output while held, without changing its toggle state
+    /// (`side` defaults to the controller the button is on)
+    #[serde(rename = "gyropause")]
+    GyroPause {
+        #[serde(default)]
+        side: Option<ControllerSide>,
+    },
+    
+    /// While held, buttons use another profile's mappings (falling back to the current profile)
+    #[serde(rename = "holdlayer")]
+    HoldLayer { profile: String },
+    
+    /// Launch an external program on press (requires `settings.allow_commands`)
+    #[serde(rename = "runcommand")]
+    RunCommand {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    
+    /// Run `tap` on a short press, or `hold` once the button is held for `hold_time_ms`
+    #[serde(rename = "taphold")]
+    TapHold {
+        tap: Box<Action>,
+        hold: Box<Action>,
+    },
+}
+
+/// Map entries sorted by their key's Debug name (for stable output)
+fn sorted_by_name<K: std::fmt::Debug, V>(map: &HashMap<K, V>) -> Vec<(String, &V)> {
+    let mut entries: Vec<(String, &V)> = map.iter().map(|(k, v)| (format!("{:?}", k), v)).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Print f32 settings as written (0.15, not 0.15000000596046448)
+fn tidy_floats(value: toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Float(f) => toml::Value::Float((f as f32).to_string().parse().unwrap_or(f)),
+        toml::Value::Array(items) => toml::Value::Array(items.into_iter().map(tidy_floats).collect()),
+        toml::Value::Table(table) => toml::Value::Table(
+            table.into_iter().map(|(k, v)| (k, tidy_floats(v))).collect()
+        ),
+        other => other,
+    }
+}
+
+/// Merge override TOML into base TOML
+/// 
+/// Tables merge key by key (override wins, missing keys keep the base), entries of
+/// the top-level `profiles` array replace the base profile with the same name or
+/// are appended, and any other value is replaced.
+fn merge_toml(base: &mut toml::Table, overrides: toml::Table, top_level: bool) {
+    for (key, value) in overrides {
+        let replacement = match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(table)) => {
+                merge_toml(base_table, table, false);
+                None
+            }
+            (Some(toml::Value::Array(base_profiles)), toml::Value::Array(profiles)) if top_level && key == "profiles" => {
+                for profile in profiles {
+                    let name = profile.get("name").cloned();
+                    match base_profiles.iter_mut().find(|p| name.is_some() && p.get("name") == name.as_ref()) {
+                        Some(existing) => *existing = profile,
+                        None => base_profiles.push(profile),
+                    }
+                }
+                None
+            }
+            (_, value) => Some(value),
+        };
+        if let Some(value) = replacement {
+            base.insert(key, value);
+        }
+    }
+}
+
+/// Format a value the way it would be written in the config file
+fn summarize_value<T: Serialize + std::fmt::Debug>(value: &T) -> String {
+    toml::Value::try_from(value)
+        .map(|v| tidy_floats(v).to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// Every action bound in a profile (buttons, press/release, gyro overrides, gestures, enter/exit, taphold halves)
+fn profile_actions(profile: &Profile) -> impl Iterator<Item = &Action> {
+    profile.buttons.values()
+        .chain(profile.buttons_left.values())
+        .chain(profile.buttons_right.values())
+        .chain(profile.on_press.values())
+        .chain(profile.on_release.values())
+        .chain(profile.gyro_mouse_overrides_left.values())
+        .chain(profile.gyro_mouse_overrides_right.values())
+        .chain(profile.gestures.values())
+        .flatten()
+        .chain(profile.on_enter.iter())
+        .chain(profile.on_exit.iter())
+        .flat_map(|action| match action {
+            Action::TapHold { tap, hold } => vec![action, tap.as_ref(), hold.as_ref()],
+            _ => vec![action],
+        })
+}
+
+/// Check that a key repeat rate is usable (0 turns repeating off)
+fn validate_key_repeat(repeat: &KeyRepeat, context: &str) -> Result<(), ConfigError> {
+    if !repeat.repeat_rate_hz.is_finite() || repeat.repeat_rate_hz < 0.0 {
+        return Err(ConfigError::Invalid(
+            format!("{} repeat_rate_hz must be 0 or positive, got {}", context, repeat.repeat_rate_hz)
+        ));
+    }
+    Ok(())
+}
+
+/// Actions that act once on press (switching, toggling, commands)
+fn is_one_shot_action(action: &Action) -> bool {
+    matches!(action,
+        Action::CycleProfiles | Action::ToggleProfiles { .. } | Action::CycleSensitivity | Action::CycleSensitivityLeft |
+        Action::CycleSensitivityRight | Action::ToggleGyroMouseL | Action::ToggleGyroMouseR |
+        Action::TogglePause | Action::MouseToggle { .. } | Action::KeyToggle { .. } | Action::TypeText { .. } | Action::TurboToggle { .. } | Action::ReleaseAll | Action::KeepAwake |
+        Action::WarpCenter | Action::RunCommand { .. } | Action::Rumble { .. })
+}
+
+/// Actions that last while the button is held
+fn is_hold_action(action: &Action) -> bool {
+    matches!(action,
+        Action::KeyHold { key: Some(_) } | Action::MouseClick { .. } | Action::MouseMoveHold { .. } |
+        Action::HoldLayer { .. } | Action::SensitivityClutch { .. } | Action::GyroPause { .. })
+}
+
+/// Format a list of actions the way it would be written in the config file
+fn summarize_actions(actions: &[Action]) -> String {
+    let actions: Vec<String> = actions.iter().map(summarize_value).collect();
+    format!("[{}]", actions.join(", "))
+}
+
+/// Key name with aliases resolved (e.g. "control" and "ctrl"), to spot a key used twice in a combo
+fn canonical_key_name(key: &str) -> String {
+    let key = key.to_lowercase();
+    let canonical = match key.as_str() {
+        "control" => "ctrl",
+        "lshift" => "leftshift",
+        "rshift" => "rightshift",
+        "lctrl" | "leftcontrol" => "leftctrl",
+        "rctrl" | "rightcontrol" => "rightctrl",
+        "lalt" => "leftalt",
+        "ralt" => "rightalt",
+        _ => return key,
+    };
+    canonical.to_string()
 }
 
 /// Custom deserializer to convert empty strings to None and warn
@@ -313,30 +1388,284 @@ pub enum MouseButton {
 }
 
 impl Config {
-    /// Load configuration from a TOML file
+    /// Load and validate configuration from a TOML file
+    /// 
+    /// Nothing is logged here: the settings configure logging, so the logger usually
+    /// doesn't exist yet. Call `log_startup_report` once it does.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
-        let path_ref = path.as_ref();
-        info!("Loading configuration from: {}", path_ref.display());
-        
-        let content = std::fs::read_to_string(path_ref)?;
+        let content = std::fs::read_to_string(path)?;
         let config: Config = toml::from_str(&content)?;
-        
-        info!("✓ Config parsed successfully");
-        debug!("  - Profiles: {}", config.profiles.len());
-        debug!("  - Default profile: '{}'", config.settings.default_profile);
-        debug!("  - Sensitivity levels: {:?}", config.settings.sensitivity_factor);
-        
         config.validate()?;
-        info!("✓ Config validation passed");
-        
         Ok(config)
     }
     
+    /// Log what was loaded, the `lint` warnings and the effective configuration
+    pub fn log_startup_report(&self) {
+        info!("✓ Config loaded and validated");
+        debug!("  - Profiles: {}", self.profiles.len());
+        debug!("  - Default profile: '{}'", self.settings.default_profile);
+        debug!("  - Sensitivity levels: {:?}", self.settings.sensitivity_factor);
+        self.log_lint_warnings();
+        info!("Effective configuration:\n{}", self.summary());
+    }
+    
     /// Load default configuration from configs/default.toml
     pub fn load_default() -> Result<Self, ConfigError> {
         Self::load("configs/default.toml")
     }
     
+    /// Load a base config and layer an override file on top (see `merge_toml_str`)
+    /// 
+    /// Like `load`, nothing is logged until `log_startup_report`.
+    pub fn load_with_overrides<P: AsRef<Path>, Q: AsRef<Path>>(base: P, overrides: Q) -> Result<Self, ConfigError> {
+        let base = std::fs::read_to_string(base)?;
+        let overrides = std::fs::read_to_string(overrides)?;
+        Self::merge_toml_str(&base, &overrides)
+    }
+    
+    /// Parse a base and an override config and merge them
+    /// 
+    /// Only what the override file spells out wins: settings merge field by field,
+    /// profiles replace the base profile of the same name or are added.
+    /// The merged config is validated.
+    pub fn merge_toml_str(base: &str, overrides: &str) -> Result<Self, ConfigError> {
+        let mut merged: toml::Table = toml::from_str(base)?;
+        merge_toml(&mut merged, toml::from_str(overrides)?, true);
+        
+        let config: Config = merged.try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+    
+    /// Layer an already parsed override config on top of this one
+    /// 
+    /// Settings that differ from their defaults in `overrides` win, so an override
+    /// can't reset a setting to its default value (use `merge_toml_str` for that).
+    /// Profiles replace the profile of the same name or are added.
+    /// The merged config is validated.
+    pub fn merge(&mut self, overrides: Config) -> Result<(), ConfigError> {
+        let to_table = |settings: &Settings| {
+            toml::Table::try_from(settings).map_err(|e| ConfigError::Invalid(format!("Failed to merge settings: {}", e)))
+        };
+        let defaults = to_table(&Settings::default())?;
+        let mut settings = to_table(&self.settings)?;
+        for (key, value) in to_table(&overrides.settings)? {
+            if defaults.get(&key) != Some(&value) {
+                settings.insert(key, value);
+            }
+        }
+        
+        let mut merged = self.clone();
+        merged.settings = settings.try_into()?;
+        for profile in overrides.profiles {
+            match merged.profiles.iter_mut().find(|p| p.name == profile.name) {
+                Some(existing) => *existing = profile,
+                None => merged.profiles.push(profile),
+            }
+        }
+        
+        merged.validate()?;
+        *self = merged;
+        Ok(())
+    }
+    
+    /// Human-readable dump of the fully resolved config (defaults filled in)
+    /// 
+    /// Settings, every profile's mappings and the profile-switching buttons,
+    /// sorted so the output is stable between runs.
+    pub fn summary(&self) -> String {
+        use std::fmt::Write;
+        
+        let mut out = String::new();
+        
+        let _ = writeln!(out, "[settings]");
+        match toml::Value::try_from(&self.settings).map(|v| toml::to_string(&tidy_floats(v))) {
+            Ok(Ok(settings)) => out.push_str(&settings),
+            _ => { let _ = writeln!(out, "{:?}", self.settings); }
+        }
+        
+        for profile in &self.profiles {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "profile '{}': {}", profile.name, profile.description);
+            
+            for (button, actions) in sorted_by_name(&profile.buttons) {
+                let _ = writeln!(out, "  {} = {}", button, summarize_actions(actions));
+            }
+            for (side, buttons) in [("left", &profile.buttons_left), ("right", &profile.buttons_right)] {
+                for (button, actions) in sorted_by_name(buttons) {
+                    let _ = writeln!(out, "  {} ({}) = {}", button, side, summarize_actions(actions));
+                }
+            }
+            for (button, repeat) in sorted_by_name(&profile.key_repeat) {
+                let _ = writeln!(out, "  {} key_repeat = {}", button, summarize_value(repeat));
+            }
+            for (name, stick) in [("left", &profile.sticks.left), ("right", &profile.sticks.right)] {
+                if let Some(stick) = stick {
+                    let _ = writeln!(out, "  {} stick = {}", name, summarize_value(stick));
+                }
+            }
+            for (name, trigger) in [("left", &profile.triggers.left), ("right", &profile.triggers.right)] {
+                if let Some(trigger) = trigger {
+                    let _ = writeln!(out, "  {} trigger = {}", name, summarize_value(trigger));
+                }
+            }
+            for (name, deadzone) in [("left", profile.left_stick_deadzone), ("right", profile.right_stick_deadzone)] {
+                if let Some(deadzone) = deadzone {
+                    let _ = writeln!(out, "  {}_stick_deadzone = {}", name, deadzone);
+                }
+            }
+            if let Some(stick) = profile.dpad_as_stick {
+                let _ = writeln!(out, "  dpad_as_stick = {:?}", stick);
+            }
+            if let Some(index) = profile.default_sensitivity_index {
+                let _ = writeln!(out, "  default_sensitivity_index = {}", index);
+            }
+            for (name, gyro) in [("left", &profile.gyro.left), ("right", &profile.gyro.right)] {
+                let _ = writeln!(out, "  {} gyro = {}", name, summarize_value(gyro));
+            }
+            for (name, sensor) in [("left", &profile.mouse_sensor.left), ("right", &profile.mouse_sensor.right)] {
+                if sensor.enabled {
+                    let _ = writeln!(out, "  {} mouse_sensor = {}", name, summarize_value(sensor));
+                }
+            }
+            for (edge, bindings) in [("on_press", &profile.on_press), ("on_release", &profile.on_release)] {
+                for (button, actions) in sorted_by_name(bindings) {
+                    let _ = writeln!(out, "  {} {} = {}", edge, button, summarize_actions(actions));
+                }
+            }
+            for (side, overrides) in [("left", &profile.gyro_mouse_overrides_left), ("right", &profile.gyro_mouse_overrides_right)] {
+                for (button, actions) in sorted_by_name(overrides) {
+                    let _ = writeln!(out, "  {} (gyro mouse {}) = {}", button, side, summarize_actions(actions));
+                }
+            }
+            for (gesture, actions) in sorted_by_name(&profile.gestures) {
+                let _ = writeln!(out, "  gesture {} = {}", gesture, summarize_actions(actions));
+            }
+            for (hook, actions) in [("on_enter", &profile.on_enter), ("on_exit", &profile.on_exit)] {
+                if !actions.is_empty() {
+                    let _ = writeln!(out, "  {} = {}", hook, summarize_actions(actions));
+                }
+            }
+        }
+        
+        // Buttons that change the active profile or global state
+        let mut switchers: Vec<String> = self.profiles.iter()
+            .flat_map(|p| p.buttons.iter().chain(&p.buttons_left).chain(&p.buttons_right))
+            .flat_map(|(button, actions)| actions.iter().map(move |action| (button, action)))
+            .filter(|(_, action)| matches!(
+                action,
+                Action::CycleProfiles | Action::ToggleProfiles { .. } | Action::HoldLayer { .. } |
+                Action::ToggleGyroMouseL | Action::ToggleGyroMouseR | Action::TogglePause
+            ))
+            .map(|(button, action)| format!("{:?} = {}", button, summarize_value(action)))
+            .collect();
+        switchers.sort();
+        switchers.dedup();
+        let _ = writeln!(out);
+        let _ = writeln!(out, "profile switchers: {}", if switchers.is_empty() { "none".to_string() } else { switchers.join(", ") });
+        
+        out
+    }
+    
+    /// Find valid but suspicious settings (likely mistakes), see `ConfigWarning`
+    pub fn lint(&self) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        let mut warn = |context: String, message: String| warnings.push(ConfigWarning { context, message });
+        
+        for profile in &self.profiles {
+            let bindings = [
+                ("", &profile.buttons), (" buttons_left", &profile.buttons_left), (" buttons_right", &profile.buttons_right),
+                (" gyro override (left)", &profile.gyro_mouse_overrides_left), (" gyro override (right)", &profile.gyro_mouse_overrides_right),
+            ];
+            for (kind, buttons) in bindings {
+                for (button, actions) in sorted_by_name(buttons) {
+                    let one_shot = actions.iter().find(|a| is_one_shot_action(a));
+                    let hold = actions.iter().find(|a| is_hold_action(a));
+                    if let (Some(one_shot), Some(hold)) = (one_shot, hold) {
+                        warn(
+                            format!("profile '{}'{} button {}", profile.name, kind, button),
+                            format!("mixes one-shot {} with hold {}", summarize_value(one_shot), summarize_value(hold)),
+                        );
+                    }
+                }
+            }
+            
+            for (side, stick) in [("left", &profile.sticks.left), ("right", &profile.sticks.right)] {
+                let Some(stick) = stick else { continue };
+                let context = format!("profile '{}' {} stick", profile.name, side);
+                match (stick.mode, &stick.directions) {
+                    (StickMode::Directional, None) => warn(context, "is directional but has no directions".into()),
+                    (StickMode::Pulse, None) => warn(context, "is pulse but has no directions".into()),
+                    (StickMode::Mouse | StickMode::Gamepad | StickMode::Disabled, Some(_)) => {
+                        warn(context, format!("has directions, which are ignored in {:?} mode", stick.mode))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        // Key repeat only applies to keys held by keyhold actions
+        for profile in &self.profiles {
+            let mut buttons: Vec<ButtonType> = profile.key_repeat.keys().copied().collect();
+            buttons.sort_by_key(|b| format!("{:?}", b));
+            for button in buttons {
+                let holds_key = [ControllerSide::Left, ControllerSide::Right].into_iter()
+                    .filter_map(|side| profile.button_actions(button, side))
+                    .any(|actions| actions.iter().any(|a| matches!(a, Action::KeyHold { key: Some(_) })));
+                if !holds_key {
+                    warn(
+                        format!("profile '{}' key_repeat button {:?}", profile.name, button),
+                        "has no keyhold action to repeat".into(),
+                    );
+                }
+            }
+        }
+        
+        // Gyro and a gamepad-mode right stick would both write the gamepad's right stick
+        for profile in &self.profiles {
+            let stick_drives_rstick = [(StickType::Left, &profile.sticks.left), (StickType::Right, &profile.sticks.right)]
+                .into_iter()
+                .any(|(stick, mapping)| mapping.as_ref().is_some_and(|m| {
+                    m.mode == StickMode::Gamepad && m.gamepad_stick.unwrap_or(stick) == StickType::Right
+                }));
+            for (side, gyro) in [("left", &profile.gyro.left), ("right", &profile.gyro.right)] {
+                if gyro.enabled && gyro.output == "gamepad_rstick" && stick_drives_rstick {
+                    warn(
+                        format!("profile '{}' {} gyro", profile.name, side),
+                        "drives the gamepad right stick, which a gamepad-mode stick also drives".into(),
+                    );
+                }
+            }
+        }
+        
+        // Gyro mouse that starts off can only be turned on by its toggle action
+        for (side, toggle) in [("left", Action::ToggleGyroMouseL), ("right", Action::ToggleGyroMouseR)] {
+            let toggled = self.profiles.iter().any(|p| profile_actions(p).any(|a| *a == toggle));
+            if toggled {
+                continue;
+            }
+            for profile in &self.profiles {
+                let gyro = if side == "left" { &profile.gyro.left } else { &profile.gyro.right };
+                if !gyro.enabled || gyro.default_enabled {
+                    continue;
+                }
+                warn(
+                    format!("profile '{}' {} gyro", profile.name, side),
+                    format!("is enabled but never turns on: no button is bound to {}", summarize_value(&toggle)),
+                );
+            }
+        }
+        
+        warnings
+    }
+    
+    /// Log the `lint` warnings
+    fn log_lint_warnings(&self) {
+        for warning in self.lint() {
+            warn!("Config: {}", warning);
+        }
+    }
+    
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), ConfigError> {
         // Validate deadzones
@@ -352,6 +1681,15 @@ impl Config {
             ));
         }
         
+        if self.settings.log_level.parse::<log::LevelFilter>().is_err() {
+            return Err(ConfigError::Invalid(
+                format!("log_level '{}' must be one of off, error, warn, info, debug, trace", self.settings.log_level)
+            ));
+        }
+        if self.settings.log_file.is_some() && self.settings.log_max_size_kb == 0 {
+            return Err(ConfigError::Invalid("log_max_size_kb must be positive".into()));
+        }
+        
         // Validate sensitivity factors
         for factor in &self.settings.sensitivity_factor {
             if *factor <= 0.0 {
@@ -378,6 +1716,86 @@ impl Config {
             ));
         }
         
+        if self.settings.expected_controllers.is_empty() {
+            return Err(ConfigError::Invalid(
+                "expected_controllers must list at least one side".into()
+            ));
+        }
+        
+        for (name, value) in [
+            ("stick_event_threshold", self.settings.stick_event_threshold),
+            ("gyro_event_threshold", self.settings.gyro_event_threshold),
+        ] {
+            if value < 0.0 {
+                return Err(ConfigError::Invalid(
+                    format!("{} must be non-negative, got {}", name, value)
+                ));
+            }
+        }
+        
+        if self.settings.shake_threshold <= 1.0 {
+            return Err(ConfigError::Invalid(
+                format!("shake_threshold must be above 1.0 G (gravity), got {}", self.settings.shake_threshold)
+            ));
+        }
+        
+        let drift_threshold = self.settings.stick_drift_threshold;
+        if self.settings.stick_drift_secs > 0
+            && !(drift_threshold > 0.0 && drift_threshold < crate::joycon2::gesture::DRIFT_MAX_MAGNITUDE)
+        {
+            return Err(ConfigError::Invalid(format!(
+                "stick_drift_threshold must be between 0.0 and {} (exclusive), got {}",
+                crate::joycon2::gesture::DRIFT_MAX_MAGNITUDE, drift_threshold
+            )));
+        }
+        
+        if self.settings.keep_awake_interval_secs == 0 {
+            return Err(ConfigError::Invalid(
+                "keep_awake_interval_secs must be at least 1".into()
+            ));
+        }
+        
+        if self.settings.max_mouse_speed <= 0.0 {
+            return Err(ConfigError::Invalid(
+                format!("max_mouse_speed must be positive, got {}", self.settings.max_mouse_speed)
+            ));
+        }
+        
+        if self.settings.stick_mouse_base <= 0.0 {
+            return Err(ConfigError::Invalid(
+                format!("stick_mouse_base must be positive, got {}", self.settings.stick_mouse_base)
+            ));
+        }
+        
+        if let Some(repeat) = &self.settings.key_repeat {
+            validate_key_repeat(repeat, "settings.key_repeat")?;
+        }
+        
+        let confirm = &self.settings.confirm_to_activate;
+        if let Some((_, button)) = confirm.iter().enumerate().find(|(i, b)| confirm[..*i].contains(b)) {
+            return Err(ConfigError::Invalid(
+                format!("confirm_to_activate lists {:?} twice", button)
+            ));
+        }
+        
+        // Check that window_profiles point at existing profiles
+        for (pattern, profile_name) in &self.settings.window_profiles {
+            if !self.profiles.iter().any(|p| &p.name == profile_name) {
+                return Err(ConfigError::Invalid(
+                    format!("window_profiles entry '{}' refers to unknown profile '{}'", pattern, profile_name)
+                ));
+            }
+        }
+        
+        // Check that startup_profiles point at existing profiles
+        for (button, profile_name) in &self.settings.startup_profiles {
+            if !self.profiles.iter().any(|p| &p.name == profile_name) {
+                return Err(ConfigError::Invalid(
+                    format!("startup_profiles entry {:?} refers to unknown profile '{}'", button, profile_name)
+                ));
+            }
+        }
+        
         // Validate each profile
         for profile in &self.profiles {
             self.validate_profile(profile)?;
@@ -391,6 +1809,16 @@ impl Config {
     
     /// Validate a single profile's actions and key names
     fn validate_profile(&self, profile: &Profile) -> Result<(), ConfigError> {
+        for (name, deadzone) in [("left", profile.left_stick_deadzone), ("right", profile.right_stick_deadzone)] {
+            if let Some(deadzone) = deadzone {
+                if !(0.0..=1.0).contains(&deadzone) {
+                    return Err(ConfigError::Invalid(
+                        format!("profile '{}' {}_stick_deadzone must be between 0.0 and 1.0", profile.name, name)
+                    ));
+                }
+            }
+        }
+        
         // Validate button actions
         for (button, actions) in &profile.buttons {
             for action in actions {
@@ -398,6 +1826,30 @@ impl Config {
             }
         }
         
+        // Any button can be bound per side, e.g. for two identical controllers
+        for (name, buttons) in [("buttons_left", &profile.buttons_left), ("buttons_right", &profile.buttons_right)] {
+            for (button, actions) in buttons {
+                let context = format!("profile '{}' {} button {:?}", profile.name, name, button);
+                for action in actions {
+                    self.validate_action(action, &context)?;
+                }
+            }
+        }
+        
+        for (button, repeat) in &profile.key_repeat {
+            validate_key_repeat(repeat, &format!("profile '{}' key_repeat button {:?}", profile.name, button))?;
+        }
+        
+        // Press/release actions are tapped, nothing can wait for the button
+        for (edge, bindings) in [("on_press", &profile.on_press), ("on_release", &profile.on_release)] {
+            for (button, actions) in bindings {
+                let context = format!("profile '{}' {} button {:?}", profile.name, edge, button);
+                for action in actions {
+                    self.validate_buttonless_action(action, &context)?;
+                }
+            }
+        }
+        
         // Validate gyro mouse override actions
         for (button, actions) in &profile.gyro_mouse_overrides_left {
             for action in actions {
@@ -411,28 +1863,168 @@ impl Config {
             }
         }
         
+        for (hook, actions) in [("on_enter", &profile.on_enter), ("on_exit", &profile.on_exit)] {
+            for action in actions {
+                let context = format!("profile '{}' {}", profile.name, hook);
+                // Switching profiles from a profile switch would loop
+                if matches!(action, Action::CycleProfiles | Action::ToggleProfiles { .. }) {
+                    return Err(ConfigError::Invalid(
+                        format!("{:?} is not allowed in {}", action, context)
+                    ));
+                }
+                self.validate_buttonless_action(action, &context)?;
+            }
+        }
+        
+        for (gesture, actions) in &profile.gestures {
+            for action in actions {
+                self.validate_buttonless_action(action, &format!("profile '{}' gesture {:?}", profile.name, gesture))?;
+            }
+        }
+        
+        // Validate gyro outputs
+        for (side, gyro) in [("left", &profile.gyro.left), ("right", &profile.gyro.right)] {
+            if !GYRO_OUTPUTS.contains(&gyro.output.as_str()) {
+                return Err(ConfigError::Invalid(
+                    format!("profile '{}' {} gyro output '{}' is not one of {:?}", profile.name, side, gyro.output, GYRO_OUTPUTS)
+                ));
+            }
+        }
+        
+        if let Some(index) = profile.default_sensitivity_index {
+            if index >= self.settings.sensitivity_factor.len() {
+                return Err(ConfigError::Invalid(
+                    format!(
+                        "profile '{}' default_sensitivity_index {} is out of range ({} sensitivity levels)",
+                        profile.name, index, self.settings.sensitivity_factor.len()
+                    )
+                ));
+            }
+        }
+        
+        // Validate mouse sensor settings
+        for (side, sensor) in [("left", &profile.mouse_sensor.left), ("right", &profile.mouse_sensor.right)] {
+            if sensor.sensitivity <= 0.0 {
+                return Err(ConfigError::Invalid(
+                    format!("profile '{}' {} mouse_sensor sensitivity must be positive", profile.name, side)
+                ));
+            }
+        }
+        
         // Validate directional keys if present
         if let Some(ref left_stick) = profile.sticks.left {
+            Self::validate_stick_sensitivity(left_stick, &format!("profile '{}' left stick", profile.name))?;
             if let Some(ref dirs) = left_stick.directions {
-                self.validate_key(&dirs.up, &format!("profile '{}' left stick up", profile.name))?;
-                self.validate_key(&dirs.down, &format!("profile '{}' left stick down", profile.name))?;
-                self.validate_key(&dirs.left, &format!("profile '{}' left stick left", profile.name))?;
-                self.validate_key(&dirs.right, &format!("profile '{}' left stick right", profile.name))?;
+                self.validate_directions(dirs, &format!("profile '{}' left stick", profile.name))?;
+            }
+            if let Some(ref dirs) = left_stick.click_directions {
+                self.validate_directions(dirs, &format!("profile '{}' left stick click", profile.name))?;
             }
         }
         
         if let Some(ref right_stick) = profile.sticks.right {
+            Self::validate_stick_sensitivity(right_stick, &format!("profile '{}' right stick", profile.name))?;
             if let Some(ref dirs) = right_stick.directions {
-                self.validate_key(&dirs.up, &format!("profile '{}' right stick up", profile.name))?;
-                self.validate_key(&dirs.down, &format!("profile '{}' right stick down", profile.name))?;
-                self.validate_key(&dirs.left, &format!("profile '{}' right stick left", profile.name))?;
-                self.validate_key(&dirs.right, &format!("profile '{}' right stick right", profile.name))?;
+                self.validate_directions(dirs, &format!("profile '{}' right stick", profile.name))?;
+            }
+            if let Some(ref dirs) = right_stick.click_directions {
+                self.validate_directions(dirs, &format!("profile '{}' right stick click", profile.name))?;
             }
         }
         
+        for (side, trigger) in [("left", &profile.triggers.left), ("right", &profile.triggers.right)] {
+            if let Some(trigger) = trigger {
+                let context = format!("profile '{}' {} trigger", profile.name, side);
+                if !(trigger.threshold > 0.0 && trigger.threshold <= 1.0) {
+                    return Err(ConfigError::Invalid(
+                        format!("Threshold in {} must be in 0..1 (got {})", context, trigger.threshold)
+                    ));
+                }
+                for action in &trigger.actions {
+                    self.validate_buttonless_action(action, &context)?;
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Validate that the split stick sensitivities are positive
+    fn validate_stick_sensitivity(stick: &StickMapping, context: &str) -> Result<(), ConfigError> {
+        let values = [
+            ("sensitivity_left", stick.sensitivity_left),
+            ("sensitivity_right", stick.sensitivity_right),
+            ("sensitivity_up", stick.sensitivity_up),
+            ("sensitivity_down", stick.sensitivity_down),
+        ];
+        for (name, value) in values {
+            if let Some(value) = value {
+                if value <= 0.0 {
+                    return Err(ConfigError::Invalid(
+                        format!("{} {} must be positive", context, name)
+                    ));
+                }
+            }
+        }
+        if !(0.0..1.0).contains(&stick.anti_deadzone) {
+            return Err(ConfigError::Invalid(
+                format!("{} anti_deadzone must be between 0.0 and 1.0, got {}", context, stick.anti_deadzone)
+            ));
+        }
+        let mut previous_radius = None;
+        for &(radius, multiplier) in &stick.sensitivity_zones {
+            if !(0.0..1.0).contains(&radius) {
+                return Err(ConfigError::Invalid(
+                    format!("{} sensitivity_zones radius must be between 0.0 and 1.0, got {}", context, radius)
+                ));
+            }
+            if let Some(previous) = previous_radius.filter(|previous| radius <= *previous) {
+                return Err(ConfigError::Invalid(
+                    format!("{} sensitivity_zones radii must be ascending, {} follows {}", context, radius, previous)
+                ));
+            }
+            if !multiplier.is_finite() || multiplier < 0.0 {
+                return Err(ConfigError::Invalid(
+                    format!("{} sensitivity_zones multiplier must be 0 or more, got {}", context, multiplier)
+                ));
+            }
+            previous_radius = Some(radius);
+        }
+        if stick.pulse_repeat_delay_ms.is_some() && stick.pulse_repeat_interval_ms == 0 {
+            return Err(ConfigError::Invalid(
+                format!("{} pulse_repeat_interval_ms must be positive", context)
+            ));
+        }
+        if stick.snap_angles == Some(0) {
+            return Err(ConfigError::Invalid(
+                format!("{} snap_angles must be at least 1", context)
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Validate every action bound to a stick direction
+    fn validate_directions(&self, dirs: &DirectionalKeys, context: &str) -> Result<(), ConfigError> {
+        for (direction, actions) in dirs.all_directions() {
+            let context = format!("{} {}", context, direction);
+            for action in actions {
+                self.validate_buttonless_action(action, &context)?;
+            }
+        }
         Ok(())
     }
     
+    /// Validate an action bound to something other than a button (a direction or trigger)
+    fn validate_buttonless_action(&self, action: &Action, context: &str) -> Result<(), ConfigError> {
+        // There's no button to hold a layer or time a tap on
+        if matches!(action, Action::TapHold { .. } | Action::HoldLayer { .. }) {
+            return Err(ConfigError::Invalid(
+                format!("{:?} is not allowed in {}", action, context)
+            ));
+        }
+        self.validate_action(action, context)
+    }
+    
     /// Validate a single action
     fn validate_action(&self, action: &Action, context: &str) -> Result<(), ConfigError> {
         match action {
@@ -441,11 +2033,89 @@ impl Config {
                     self.validate_key(key_name, context)?;
                 }
             }
-            Action::MouseMove { .. } | Action::MouseClick { .. } => {
+            Action::KeyToggle { key } => {
+                if key.trim().is_empty() {
+                    return Err(ConfigError::Invalid(
+                        format!("KeyToggle in {} must have a key", context)
+                    ));
+                }
+                self.validate_key(key, context)?;
+            }
+            Action::TurboToggle { key, rate_hz } => {
+                if key.trim().is_empty() {
+                    return Err(ConfigError::Invalid(
+                        format!("TurboToggle in {} must have a key", context)
+                    ));
+                }
+                if !rate_hz.is_finite() || *rate_hz <= 0.0 {
+                    return Err(ConfigError::Invalid(
+                        format!("TurboToggle rate_hz in {} must be positive, got {}", context, rate_hz)
+                    ));
+                }
+                self.validate_key(key, context)?;
+            }
+            Action::TypeText { text } => {
+                if text.is_empty() {
+                    return Err(ConfigError::Invalid(
+                        format!("TypeText in {} must have text", context)
+                    ));
+                }
+            }
+            Action::MouseMove { .. } | Action::MouseMoveHold { .. } | Action::MouseClick { .. } | Action::MouseToggle { .. } | Action::ReleaseAll | Action::KeepAwake | Action::WarpCenter | Action::GyroPause { .. } | Action::Rumble { .. } => {
+                // Always valid
+            }
+            Action::HoldLayer { profile } => {
+                if !self.profiles.iter().any(|p| &p.name == profile) {
+                    return Err(ConfigError::Invalid(
+                        format!("HoldLayer in {} refers to unknown profile '{}'", context, profile)
+                    ));
+                }
+            }
+            Action::ToggleProfiles { a, b } => {
+                for profile in [a, b] {
+                    if !self.profiles.iter().any(|p| &p.name == profile) {
+                        return Err(ConfigError::Invalid(
+                            format!("ToggleProfiles in {} refers to unknown profile '{}'", context, profile)
+                        ));
+                    }
+                }
+                if a == b {
+                    return Err(ConfigError::Invalid(
+                        format!("ToggleProfiles in {} must name two different profiles, got '{}' twice", context, a)
+                    ));
+                }
+                // Consistency between the two profiles is checked in validate_profile_switching_buttons
+            }
+            Action::RunCommand { command, .. } => {
+                if command.trim().is_empty() {
+                    return Err(ConfigError::Invalid(
+                        format!("RunCommand in {} must have a non-empty command", context)
+                    ));
+                }
+            }
+            Action::TapHold { tap, hold } => {
+                for inner in [tap.as_ref(), hold.as_ref()] {
+                    // The inner actions don't know their button, so they can't be button-bound themselves
+                    if matches!(inner, Action::TapHold { .. } | Action::HoldLayer { .. }) {
+                        return Err(ConfigError::Invalid(
+                            format!("{:?} is not allowed inside TapHold in {}", inner, context)
+                        ));
+                    }
+                    self.validate_action(inner, context)?;
+                }
+            }
+            Action::SensitivityClutch { factor } => {
+                if !factor.is_finite() || *factor <= 0.0 {
+                    return Err(ConfigError::Invalid(
+                        format!("SensitivityClutch factor in {} must be a positive number", context)
+                    ));
+                }
+            }
+            Action::CycleSensitivityLeft | Action::CycleSensitivityRight => {
                 // Always valid
             }
             Action::CycleProfiles | Action::CycleSensitivity | 
-            Action::ToggleGyroMouseL | Action::ToggleGyroMouseR => {
+            Action::ToggleGyroMouseL | Action::ToggleGyroMouseR | Action::TogglePause => {
                 // Validated separately in validate_profile_switching_buttons
             }
         }
@@ -456,12 +2126,23 @@ impl Config {
     fn validate_key(&self, key: &str, context: &str) -> Result<(), ConfigError> {
         // Check if it contains multi-key combo (e.g., "shift+w")
         if key.contains('+') {
-            // Validate each part of the combo
+            // Validate each part of the combo, a malformed combo would silently press fewer keys
+            let mut seen = Vec::new();
             for part in key.split('+') {
                 let trimmed = part.trim();
-                if !trimmed.is_empty() {
-                    self.validate_single_key(trimmed, context)?;
+                if trimmed.is_empty() {
+                    return Err(ConfigError::Invalid(
+                        format!("Invalid key combo '{}' in {}: empty key before or after a '+'", key, context)
+                    ));
+                }
+                let name = canonical_key_name(trimmed);
+                if seen.contains(&name) {
+                    return Err(ConfigError::Invalid(
+                        format!("Invalid key combo '{}' in {}: '{}' is used twice", key, context, trimmed)
+                    ));
                 }
+                self.validate_single_key(trimmed, context)?;
+                seen.push(name);
             }
             Ok(())
         } else {
@@ -497,23 +2178,32 @@ impl Config {
         }
         
         // Collect all buttons that have profile-switching actions
-        let mut cycle_profile_buttons: HashSet<ButtonType> = HashSet::new();
-        let mut toggle_gyro_l_buttons: HashSet<ButtonType> = HashSet::new();
-        let mut toggle_gyro_r_buttons: HashSet<ButtonType> = HashSet::new();
+        // (button, sending side) pairs, a side-specific binding only covers its controller
+        let mut cycle_profile_buttons: HashSet<(ButtonType, ControllerSide)> = HashSet::new();
+        let mut toggle_gyro_l_buttons: HashSet<(ButtonType, ControllerSide)> = HashSet::new();
+        let mut toggle_gyro_r_buttons: HashSet<(ButtonType, ControllerSide)> = HashSet::new();
+        let mut toggle_pause_buttons: HashSet<(ButtonType, ControllerSide)> = HashSet::new();
+        let mut toggle_profile_buttons: Vec<(ButtonType, ControllerSide, &String, &String)> = Vec::new();
         
         for profile in &self.profiles {
-            // Check regular buttons
-            for (button, actions) in &profile.buttons {
+            // Check regular and side-specific buttons
+            for (button, side, actions) in profile.sided_buttons() {
                 for action in actions {
                     match action {
                         Action::CycleProfiles => {
-                            cycle_profile_buttons.insert(*button);
+                            cycle_profile_buttons.insert((button, side));
                         }
                         Action::ToggleGyroMouseL => {
-                            toggle_gyro_l_buttons.insert(*button);
+                            toggle_gyro_l_buttons.insert((button, side));
                         }
                         Action::ToggleGyroMouseR => {
-                            toggle_gyro_r_buttons.insert(*button);
+                            toggle_gyro_r_buttons.insert((button, side));
+                        }
+                        Action::TogglePause => {
+                            toggle_pause_buttons.insert((button, side));
+                        }
+                        Action::ToggleProfiles { a, b } => {
+                            toggle_profile_buttons.push((button, side, a, b));
                         }
                         _ => {}
                     }
@@ -521,11 +2211,16 @@ impl Config {
             }
         }
         
+        // The global profile switch button cycles profiles everywhere regardless of the mappings
+        if let Some(button) = self.settings.profile_switch_button {
+            cycle_profile_buttons.retain(|(b, _)| *b != button);
+        }
+        
         // Now verify that ALL profiles have these buttons mapped to the same actions
         for profile in &self.profiles {
             // Check CycleProfiles consistency
-            for button in &cycle_profile_buttons {
-                let has_cycle = profile.buttons.get(button)
+            for &(button, side) in &cycle_profile_buttons {
+                let has_cycle = profile.button_actions(button, side)
                     .map(|actions| actions.iter().any(|a| matches!(a, Action::CycleProfiles)))
                     .unwrap_or(false);
                 
@@ -541,8 +2236,8 @@ impl Config {
             }
             
             // Check ToggleGyroMouseL consistency
-            for button in &toggle_gyro_l_buttons {
-                let has_toggle = profile.buttons.get(button)
+            for &(button, side) in &toggle_gyro_l_buttons {
+                let has_toggle = profile.button_actions(button, side)
                     .map(|actions| actions.iter().any(|a| matches!(a, Action::ToggleGyroMouseL)))
                     .unwrap_or(false);
                 
@@ -558,8 +2253,8 @@ impl Config {
             }
             
             // Check ToggleGyroMouseR consistency
-            for button in &toggle_gyro_r_buttons {
-                let has_toggle = profile.buttons.get(button)
+            for &(button, side) in &toggle_gyro_r_buttons {
+                let has_toggle = profile.button_actions(button, side)
                     .map(|actions| actions.iter().any(|a| matches!(a, Action::ToggleGyroMouseR)))
                     .unwrap_or(false);
                 
@@ -573,6 +2268,46 @@ impl Config {
                     ));
                 }
             }
+            
+            // Check TogglePause consistency
+            for &(button, side) in &toggle_pause_buttons {
+                let has_toggle = profile.button_actions(button, side)
+                    .map(|actions| actions.iter().any(|a| matches!(a, Action::TogglePause)))
+                    .unwrap_or(false);
+                
+                if !has_toggle {
+                    return Err(ConfigError::Invalid(
+                        format!(
+                            "Profile '{}' is missing TogglePause action on button {:?}. \
+                            All profiles must have the same pause button to allow resuming.",
+                            profile.name, button
+                        )
+                    ));
+                }
+            }
+        }
+        
+        // ToggleProfiles only flips between its two profiles, so only those must agree
+        for (button, side, a, b) in toggle_profile_buttons {
+            for profile in self.profiles.iter().filter(|p| &p.name == a || &p.name == b) {
+                let has_toggle = profile.button_actions(button, side)
+                    .map(|actions| actions.iter().any(|action| matches!(
+                        action,
+                        Action::ToggleProfiles { a: other_a, b: other_b }
+                            if (other_a == a && other_b == b) || (other_a == b && other_b == a)
+                    )))
+                    .unwrap_or(false);
+                
+                if !has_toggle {
+                    return Err(ConfigError::Invalid(
+                        format!(
+                            "Profile '{}' is missing ToggleProfiles between '{}' and '{}' on button {:?}. \
+                            Both toggled profiles must have it to allow switching back.",
+                            profile.name, a, b, button
+                        )
+                    ));
+                }
+            }
         }
         
         Ok(())
@@ -582,6 +2317,7 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mapping::builder::ProfileBuilder;
     
     #[test]
     fn test_default_settings() {
@@ -591,6 +2327,12 @@ mod tests {
         assert!(settings.vibration_enabled);
         assert_eq!(settings.default_profile, "base");
         assert_eq!(settings.sensitivity_factor, vec![1.0, 2.0, 3.0]);
+        assert!(!settings.prefer_cached);
+        assert_eq!(settings.max_mouse_speed, 500.0);
+        assert_eq!(settings.stick_mouse_base, 10.0);
+        assert_eq!(settings.stick_event_threshold, 0.05);
+        assert_eq!(settings.gyro_event_threshold, 0.5);
+        assert_eq!(settings.expected_controllers, vec![ControllerSide::Left, ControllerSide::Right]);
     }
     
     #[test]
@@ -598,15 +2340,7 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "Base profile".to_string(),
-                    buttons: HashMap::new(),
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").description("Base profile").build()
             ],
         };
         
@@ -618,15 +2352,7 @@ mod tests {
         let mut config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: HashMap::new(),
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").build()
             ],
         };
         
@@ -635,6 +2361,31 @@ mod tests {
         
         config.settings.left_stick_deadzone = -0.1;
         assert!(config.validate().is_err());
+        
+        // Per-profile overrides have the same range
+        config.settings.left_stick_deadzone = 0.15;
+        config.profiles[0].right_stick_deadzone = Some(0.3);
+        assert!(config.validate().is_ok());
+        config.profiles[0].right_stick_deadzone = Some(1.2);
+        assert!(config.validate().is_err());
+    }
+    
+    #[test]
+    fn test_event_thresholds() {
+        let mut config = Config {
+            settings: Settings::default(),
+            profiles: vec![
+                ProfileBuilder::new("base").build()
+            ],
+        };
+        
+        // Zero sends every change
+        config.settings.stick_event_threshold = 0.0;
+        config.settings.gyro_event_threshold = 0.0;
+        assert!(config.validate().is_ok());
+        
+        config.settings.gyro_event_threshold = -0.5;
+        assert!(config.validate().unwrap_err().to_string().contains("gyro_event_threshold"));
     }
     
     #[test]
@@ -642,15 +2393,7 @@ mod tests {
         let mut config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: HashMap::new(),
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").build()
             ],
         };
         
@@ -659,6 +2402,13 @@ mod tests {
         
         config.settings.sensitivity_factor = vec![1.0, -1.0, 2.0];
         assert!(config.validate().is_err());
+        
+        // default_sensitivity_index must point at a level
+        config.settings.sensitivity_factor = vec![1.0, 2.0];
+        config.profiles[0].default_sensitivity_index = Some(1);
+        assert!(config.validate().is_ok());
+        config.profiles[0].default_sensitivity_index = Some(2);
+        assert!(config.validate().is_err());
     }
     
     #[test]
@@ -669,15 +2419,7 @@ mod tests {
                 ..Settings::default()
             },
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: HashMap::new(),
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").build()
             ],
         };
         
@@ -700,21 +2442,11 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::A, vec![Action::KeyHold { key: Some("w".to_string()) }]);
-                        map.insert(ButtonType::B, vec![Action::KeyHold { key: Some("space".to_string()) }]);
-                        map.insert(ButtonType::X, vec![Action::KeyHold { key: Some("f1".to_string()) }]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base")
+                    .button(ButtonType::A, Action::KeyHold { key: Some("w".to_string()) })
+                    .button(ButtonType::B, Action::KeyHold { key: Some("space".to_string()) })
+                    .button(ButtonType::X, Action::KeyHold { key: Some("f1".to_string()) })
+                    .build()
             ],
         };
         
@@ -727,19 +2459,7 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::A, vec![Action::KeyHold { key: Some("invalid_key_xyz".to_string()) }]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::A, Action::KeyHold { key: Some("invalid_key_xyz".to_string()) }).build()
             ],
         };
         
@@ -752,27 +2472,7 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: HashMap::new(),
-                    sticks: StickMappings {
-                        left: Some(StickMapping {
-                            mode: StickMode::Directional,
-                            sensitivity: 1.0,
-                            directions: Some(DirectionalKeys {
-                                up: "shift+w".to_string(),
-                                down: "ctrl+s".to_string(),
-                                left: "a".to_string(),
-                                right: "d".to_string(),
-                            }),
-                        }),
-                        right: None,
-                    },
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").stick(StickType::Left, StickMapping::directional(DirectionalKeys::new("shift+w", "ctrl+s", "a", "d"))).build()
             ],
         };
         
@@ -785,27 +2485,7 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: HashMap::new(),
-                    sticks: StickMappings {
-                        left: Some(StickMapping {
-                            mode: StickMode::Directional,
-                            sensitivity: 1.0,
-                            directions: Some(DirectionalKeys {
-                                up: "shift+invalid".to_string(),
-                                down: "s".to_string(),
-                                left: "a".to_string(),
-                                right: "d".to_string(),
-                            }),
-                        }),
-                        right: None,
-                    },
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").stick(StickType::Left, StickMapping::directional(DirectionalKeys::new("shift+invalid", "s", "a", "d"))).build()
             ],
         };
         
@@ -817,32 +2497,8 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::SLR, vec![Action::CycleProfiles]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                },
-                Profile {
-                    name: "game".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::SLR, vec![Action::CycleProfiles]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::SLR, Action::CycleProfiles).build(),
+                ProfileBuilder::new("game").button(ButtonType::SLR, Action::CycleProfiles).build()
             ],
         };
         
@@ -851,37 +2507,21 @@ mod tests {
     
     #[test]
     fn test_cycle_profiles_consistency_invalid() {
-        let config = Config {
+        let mut config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::SLR, vec![Action::CycleProfiles]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                },
-                Profile {
-                    name: "game".to_string(),
-                    description: "".to_string(),
-                    buttons: HashMap::new(), // Missing CycleProfiles!
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::SLR, Action::CycleProfiles).build(),
+                ProfileBuilder::new("game").build() // Missing CycleProfiles!
             ],
         };
         
         let result = config.validate();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("missing CycleProfiles"));
+        
+        // A global profile switch button makes the per-profile binding redundant
+        config.settings.profile_switch_button = Some(ButtonType::SLR);
+        assert!(config.validate().is_ok());
     }
     
     #[test]
@@ -889,32 +2529,8 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::SRR, vec![Action::ToggleGyroMouseR]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                },
-                Profile {
-                    name: "game".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::SRR, vec![Action::ToggleGyroMouseR]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::SRR, Action::ToggleGyroMouseR).build(),
+                ProfileBuilder::new("game").button(ButtonType::SRR, Action::ToggleGyroMouseR).build()
             ],
         };
         
@@ -926,33 +2542,9 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::SRR, vec![Action::ToggleGyroMouseR]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                },
-                Profile {
-                    name: "game".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        // Different button for toggle - inconsistent!
-                        map.insert(ButtonType::SLR, vec![Action::ToggleGyroMouseR]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::SRR, Action::ToggleGyroMouseR).build(),
+                // Different button for toggle - inconsistent!
+                ProfileBuilder::new("game").button(ButtonType::SLR, Action::ToggleGyroMouseR).build()
             ],
         };
         
@@ -966,19 +2558,7 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::A, vec![Action::None { key: Some("w".to_string()) }]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::A, Action::None { key: Some("w".to_string()) }).build()
             ],
         };
         
@@ -992,22 +2572,696 @@ mod tests {
         let config = Config {
             settings: Settings::default(),
             profiles: vec![
-                Profile {
-                    name: "base".to_string(),
-                    description: "".to_string(),
-                    buttons: {
-                        let mut map = HashMap::new();
-                        map.insert(ButtonType::A, vec![Action::None { key: None }]);
-                        map
-                    },
-                    sticks: StickMappings::default(),
-                    gyro: GyroSettings::default(),
-                    gyro_mouse_overrides_left: HashMap::new(),
-                    gyro_mouse_overrides_right: HashMap::new(),
-                }
+                ProfileBuilder::new("base").button(ButtonType::A, Action::None { key: None }).build()
+            ],
+        };
+        
+        assert!(config.validate().is_ok());
+    }
+    
+    #[test]
+    fn test_invalid_sensitivity_clutch_factor() {
+        let config = Config {
+            settings: Settings::default(),
+            profiles: vec![
+                ProfileBuilder::new("base").button(ButtonType::ZL, Action::SensitivityClutch { factor: 0.0 }).build()
+            ],
+        };
+        
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("SensitivityClutch"));
+        
+        // NaN isn't <= 0.0, and infinity saturates every mouse delta
+        for factor in ["nan", "inf"] {
+            let config: Config = toml::from_str(&format!(r#"
+                [[profiles]]
+                name = "base"
+                
+                [profiles.buttons]
+                ZL = [{{ type = "sensitivityclutch", factor = {} }}]
+            "#, factor)).unwrap();
+            let err = config.validate().unwrap_err().to_string();
+            assert!(err.contains("SensitivityClutch factor"), "{}", err);
+        }
+    }
+    
+    #[test]
+    fn test_run_command_requires_command() {
+        let action: Action = toml::from_str(r#"
+            type = "runcommand"
+            command = "notepad.exe"
+        "#).unwrap();
+        assert_eq!(action, Action::RunCommand { command: "notepad.exe".to_string(), args: vec![] });
+        assert!(!Settings::default().allow_commands);
+        
+        let mut config = Config {
+            settings: Settings::default(),
+            profiles: vec![
+                ProfileBuilder::new("base").button(ButtonType::Capture, action).build()
             ],
         };
+        assert!(config.validate().is_ok());
+        
+        config.profiles[0].buttons.insert(
+            ButtonType::Capture,
+            vec![Action::RunCommand { command: " ".to_string(), args: vec![] }],
+        );
+        assert!(config.validate().unwrap_err().to_string().contains("RunCommand"));
+    }
+    
+    #[test]
+    fn test_directional_diagonals_optional() {
+        let dirs: DirectionalKeys = toml::from_str(r#"
+            up = "w"
+            down = "s"
+            left = "a"
+            right = "d"
+        "#).unwrap();
+        assert!(dirs.up_left.is_none());
+        assert_eq!(dirs.diagonal(true, true), None);
+        assert_eq!(dirs.all_directions().len(), 4);
+        
+        let dirs: DirectionalKeys = toml::from_str(r#"
+            up = "w"
+            down = "s"
+            left = "a"
+            right = "d"
+            up_left = "q"
+            down_right = "c"
+        "#).unwrap();
+        assert_eq!(dirs.diagonal(true, true), Some(&[Action::KeyHold { key: Some("q".to_string()) }][..]));
+        assert_eq!(dirs.diagonal(false, false), Some(&[Action::KeyHold { key: Some("c".to_string()) }][..]));
+        assert_eq!(dirs.diagonal(true, false), None);
+        assert_eq!(dirs.all_directions().len(), 6);
+    }
+    
+    #[test]
+    fn test_split_stick_sensitivity() {
+        let mut mapping: StickMapping = toml::from_str(r#"
+            mode = "mouse"
+            sensitivity = 2.0
+            sensitivity_left = 0.5
+            sensitivity_down = 3.0
+        "#).unwrap();
+        
+        // Unset directions fall back to the main sensitivity
+        assert_eq!(mapping.axis_sensitivity(-1.0, -1.0), (0.5, 2.0));
+        assert_eq!(mapping.axis_sensitivity(1.0, 1.0), (2.0, 3.0));
+        assert!(Config::validate_stick_sensitivity(&mapping, "test").is_ok());
+        
+        mapping.sensitivity_up = Some(0.0);
+        let result = Config::validate_stick_sensitivity(&mapping, "test");
+        assert!(result.unwrap_err().to_string().contains("sensitivity_up"));
+        mapping.sensitivity_up = None;
+        
+        // Sensitivity zones: 1.0 inside the first ring, the last ring reached wins
+        mapping.sensitivity_zones = vec![(0.2, 0.5), (0.8, 2.0)];
+        assert!(Config::validate_stick_sensitivity(&mapping, "test").is_ok());
+        assert_eq!(mapping.zone_multiplier(0.1), 1.0);
+        assert_eq!(mapping.zone_multiplier(0.2), 0.5);
+        assert_eq!(mapping.zone_multiplier(0.79), 0.5);
+        assert_eq!(mapping.zone_multiplier(1.0), 2.0);
+        
+        for (zones, message) in [
+            (vec![(0.5, 1.0), (0.3, 2.0)], "must be ascending"),
+            (vec![(0.5, 1.0), (0.5, 2.0)], "must be ascending"),
+            (vec![(1.0, 2.0)], "between 0.0 and 1.0"),
+            (vec![(-0.1, 2.0)], "between 0.0 and 1.0"),
+            (vec![(0.5, f32::NAN)], "multiplier"),
+        ] {
+            mapping.sensitivity_zones = zones;
+            let err = Config::validate_stick_sensitivity(&mapping, "test").unwrap_err().to_string();
+            assert!(err.contains(message), "{}", err);
+        }
+    }
+    
+    #[test]
+    fn test_gyro_output_validation() {
+        let mut profile = ProfileBuilder::new("base").build();
+        profile.gyro.right.output = "scroll".to_string();
         
+        let mut config = Config {
+            settings: Settings::default(),
+            profiles: vec![profile],
+        };
         assert!(config.validate().is_ok());
+        
+        config.profiles[0].gyro.left.output = "joystick".to_string();
+        let result = config.validate();
+        assert!(result.unwrap_err().to_string().contains("gyro output 'joystick'"));
+    }
+    
+    #[test]
+    fn test_summary_is_stable_and_complete() {
+        let config: Config = toml::from_str(r#"
+            [settings]
+            default_profile = "base"
+            
+            [[profiles]]
+            name = "base"
+            description = "Desktop"
+            [profiles.buttons]
+            Y = [{ type = "keyhold", key = "y" }]
+            A = [{ type = "keyhold", key = "space" }]
+            Plus = [{ type = "cycleprofiles" }]
+            [profiles.sticks.right]
+            mode = "mouse"
+            
+            [[profiles]]
+            name = "game"
+            [profiles.buttons]
+            Plus = [{ type = "cycleprofiles" }]
+        "#).unwrap();
+        config.validate().unwrap();
+        
+        let summary = config.summary();
+        assert_eq!(summary, config.summary());
+        
+        // Defaults are spelled out
+        assert!(summary.contains("combo_key_delay_ms = 0"));
+        assert!(summary.contains("hold_time_ms = 500"));
+        assert!(summary.contains("left_stick_deadzone = 0.15\n"));
+        
+        // Buttons sorted, in config syntax
+        let a = summary.find("  A = [{ key = \"space\", type = \"keyhold\" }]").unwrap();
+        let y = summary.find("  Y = ").unwrap();
+        assert!(a < y);
+        assert!(summary.contains("profile 'game'"));
+        assert!(summary.contains("  right stick = {"));
+        assert!(summary.contains("profile switchers: Plus = { type = \"cycleprofiles\" }"));
+    }
+    
+    #[test]
+    fn test_startup_profiles() {
+        let mut config: Config = toml::from_str(r#"
+            [settings.startup_profiles]
+            A = "game"
+            
+            [[profiles]]
+            name = "base"
+            
+            [[profiles]]
+            name = "game"
+        "#).unwrap();
+        assert_eq!(config.settings.startup_profiles[&ButtonType::A], "game");
+        assert_eq!(config.settings.startup_select_ms, 1500);
+        config.validate().unwrap();
+        assert!(config.summary().contains("A = \"game\""));
+        
+        config.settings.startup_profiles.insert(ButtonType::B, "missing".to_string());
+        let result = config.validate();
+        assert!(result.unwrap_err().to_string().contains("unknown profile 'missing'"));
+    }
+    
+    const MERGE_BASE: &str = r#"
+        [settings]
+        default_profile = "base"
+        hold_time_ms = 400
+        sensitivity_factor = [1.0, 2.0]
+        
+        [[profiles]]
+        name = "base"
+        [profiles.buttons]
+        A = [{ type = "keyhold", key = "space" }]
+        B = [{ type = "keyhold", key = "b" }]
+        
+        [[profiles]]
+        name = "menu"
+        [profiles.buttons]
+        A = [{ type = "keyhold", key = "enter" }]
+    "#;
+    
+    #[test]
+    fn test_merge_toml_str_replaces_and_adds_profiles() {
+        let config = Config::merge_toml_str(MERGE_BASE, r#"
+            [settings]
+            sensitivity_factor = [0.5]
+            
+            [[profiles]]
+            name = "base"
+            [profiles.buttons]
+            A = [{ type = "keyhold", key = "w" }]
+            
+            [[profiles]]
+            name = "game"
+        "#).unwrap();
+        
+        // Settings merge field by field
+        assert_eq!(config.settings.sensitivity_factor, vec![0.5]);
+        assert_eq!(config.settings.hold_time_ms, 400);
+        assert_eq!(config.settings.default_profile, "base");
+        
+        // Same name replaces the whole profile, new names are added in order
+        let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["base", "menu", "game"]);
+        assert_eq!(config.profiles[0].buttons[&ButtonType::A], vec![Action::KeyHold { key: Some("w".to_string()) }]);
+        assert!(!config.profiles[0].buttons.contains_key(&ButtonType::B));
+        
+        // The merged result is validated
+        let result = Config::merge_toml_str(MERGE_BASE, "[settings]\nleft_stick_deadzone = 2.0\n");
+        assert!(result.unwrap_err().to_string().contains("left_stick_deadzone"));
+    }
+    
+    #[test]
+    fn test_config_merge() {
+        let mut config: Config = toml::from_str(MERGE_BASE).unwrap();
+        let overrides: Config = toml::from_str(r#"
+            [settings]
+            combo_key_delay_ms = 15
+            
+            [[profiles]]
+            name = "menu"
+            description = "Overridden"
+            
+            [[profiles]]
+            name = "game"
+        "#).unwrap();
+        
+        config.merge(overrides).unwrap();
+        
+        // Defaulted override settings keep the base value
+        assert_eq!(config.settings.combo_key_delay_ms, 15);
+        assert_eq!(config.settings.hold_time_ms, 400);
+        assert_eq!(config.profiles.len(), 3);
+        assert_eq!(config.profiles[1].description, "Overridden");
+        assert!(config.profiles[1].buttons.is_empty());
+    }
+    
+    #[test]
+    fn test_direction_action_lists() {
+        let dirs: DirectionalKeys = toml::from_str(r#"
+            up = [{ type = "keyhold", key = "w" }, { type = "mouseclick", button = "left" }]
+            down = { type = "warpcenter" }
+            left = ""
+            right = "shift+d"
+        "#).unwrap();
+        assert_eq!(dirs.up.len(), 2);
+        assert_eq!(dirs.down, vec![Action::WarpCenter]);
+        assert!(dirs.left.is_empty());
+        assert_eq!(dirs.right, vec![Action::KeyHold { key: Some("shift+d".to_string()) }]);
+        
+        // Single keys are written back as plain strings
+        let written = toml::to_string(&dirs).unwrap();
+        assert!(written.contains("right = \"shift+d\""));
+        let reread: DirectionalKeys = toml::from_str(&written).unwrap();
+        assert_eq!(reread.up, dirs.up);
+        assert!(reread.up_left.is_none());
+        
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.sticks.left]
+            mode = "directional"
+            directions = { up = [{ type = "keyhold", key = "w" }, { type = "runcommand", command = " " }], down = "s", left = "a", right = "d" }
+        "#).unwrap();
+        assert!(config.validate().unwrap_err().to_string().contains("left stick up"));
+        
+        let directions = config.profiles[0].sticks.left.as_mut().unwrap().directions.as_mut().unwrap();
+        directions.up = vec![Action::HoldLayer { profile: "base".to_string() }];
+        assert!(config.validate().unwrap_err().to_string().contains("not allowed in profile 'base' left stick up"));
+    }
+    
+    #[test]
+    fn test_trigger_mappings() {
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.triggers.left]
+            mode = "threshold"
+            actions = [{ type = "mouseclick", button = "right" }]
+            
+            [profiles.triggers.right]
+            mode = "gamepad"
+        "#).unwrap();
+        let left = config.profiles[0].triggers.left.as_ref().unwrap();
+        assert_eq!(left.mode, TriggerMode::Threshold);
+        assert_eq!(left.threshold, 0.5);
+        assert_eq!(config.profiles[0].triggers.right.as_ref().unwrap().mode, TriggerMode::Gamepad);
+        config.validate().unwrap();
+        assert!(config.summary().contains("left trigger = "));
+        
+        config.profiles[0].triggers.left.as_mut().unwrap().threshold = 0.0;
+        assert!(config.validate().unwrap_err().to_string().contains("left trigger"));
+    }
+    
+    /// Records the messages logged by each thread, so parallel tests don't see each other's
+    struct CaptureLogger(std::sync::Mutex<Vec<(std::thread::ThreadId, String)>>);
+    
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+        
+        fn log(&self, record: &log::Record) {
+            self.0.lock().unwrap().push((std::thread::current().id(), record.args().to_string()));
+        }
+        
+        fn flush(&self) {}
+    }
+    
+    static CAPTURE_LOGGER: CaptureLogger = CaptureLogger(std::sync::Mutex::new(Vec::new()));
+    
+    /// Messages logged so far by the current thread
+    fn captured_logs() -> Vec<String> {
+        let thread = std::thread::current().id();
+        CAPTURE_LOGGER.0.lock().unwrap().iter()
+            .filter(|(id, _)| *id == thread)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+    
+    #[test]
+    fn test_load_logs_nothing_before_startup_report() {
+        let _ = log::set_logger(&CAPTURE_LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+        
+        let path = std::env::temp_dir().join(format!("joy2-rs-config-report-{}.toml", std::process::id()));
+        std::fs::write(&path, r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.sticks.right]
+            mode = "directional"
+        "#).unwrap();
+        let loaded = Config::load(&path);
+        let _ = std::fs::remove_file(&path);
+        let config = loaded.unwrap();
+        
+        // The logger doesn't exist yet while the app loads its config
+        assert_eq!(captured_logs(), Vec::<String>::new());
+        
+        config.log_startup_report();
+        let logs = captured_logs();
+        assert!(logs.iter().any(|m| m.contains("right stick: is directional but has no directions")), "{:?}", logs);
+        assert!(logs.iter().any(|m| m.starts_with("Effective configuration:")), "{:?}", logs);
+    }
+    
+    #[test]
+    fn test_lint_warnings() {
+        let config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.buttons]
+            A = [{ type = "cycleprofiles" }, { type = "keyhold", key = "w" }]
+            B = [{ type = "keyhold", key = "shift" }, { type = "mouseclick", button = "left" }]
+            
+            [profiles.sticks.left]
+            mode = "mouse"
+            directions = { up = "w", down = "s", left = "a", right = "d" }
+            
+            [profiles.sticks.right]
+            mode = "directional"
+            
+            [profiles.gyro.right]
+            enabled = true
+        "#).unwrap();
+        config.validate().unwrap();
+        
+        let warnings: Vec<String> = config.lint().iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        assert!(warnings[0].starts_with("profile 'base' button A: mixes one-shot"));
+        assert!(warnings[1].contains("left stick: has directions"));
+        assert!(warnings[2].contains("right stick: is directional but has no directions"));
+        assert!(warnings[3].contains("right gyro: is enabled but never turns on"));
+        
+        // The shipped configs are clean
+        for path in ["configs/default.toml", "configs/ETS2.toml"] {
+            let config: Config = toml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+            assert_eq!(config.lint(), Vec::new(), "{}", path);
+        }
+    }
+    
+    #[test]
+    fn test_side_button_maps() {
+        let config: Config = toml::from_str(r#"
+            [settings]
+            default_profile = "base"
+            
+            [[profiles]]
+            name = "base"
+            
+            [profiles.buttons]
+            A = [{ type = "keyhold", key = "a" }]
+            L = [{ type = "keyhold", key = "l" }]
+            
+            [profiles.buttons_right]
+            A = [{ type = "keyhold", key = "enter" }]
+        "#).unwrap();
+        config.validate().unwrap();
+        let profile = &config.profiles[0];
+        let keyhold = |key: &str| Some(vec![Action::KeyHold { key: Some(key.to_string()) }]);
+        assert_eq!(profile.button_actions(ButtonType::A, ControllerSide::Right).cloned(), keyhold("enter"));
+        assert_eq!(profile.button_actions(ButtonType::A, ControllerSide::Left).cloned(), keyhold("a"));
+        assert_eq!(profile.button_actions(ButtonType::L, ControllerSide::Left).cloned(), keyhold("l"));
+        assert!(config.summary().contains("  A (right) = ["), "{}", config.summary());
+        
+        // Any button can be bound per side, the controller sending it picks the map
+        let mut mirrored = config.clone();
+        mirrored.profiles[0].buttons_left.insert(ButtonType::A, vec![Action::KeyHold { key: Some("b".to_string()) }]);
+        mirrored.validate().unwrap();
+        assert_eq!(mirrored.profiles[0].button_actions(ButtonType::A, ControllerSide::Left).cloned(), keyhold("b"));
+        
+        let mut bad_action = config.clone();
+        bad_action.profiles[0].buttons_right.insert(ButtonType::B, vec![Action::HoldLayer { profile: "missing".to_string() }]);
+        assert!(bad_action.validate().is_err());
+    }
+    
+    #[test]
+    fn test_pulse_stick_config() {
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.sticks.left]
+            mode = "pulse"
+            directions = { up = "up", down = "down", left = "left", right = "right" }
+            pulse_repeat_delay_ms = 400
+            
+            [profiles.sticks.right]
+            mode = "pulse"
+        "#).unwrap();
+        config.settings.default_profile = "base".to_string();
+        config.validate().unwrap();
+        let left = config.profiles[0].sticks.left.as_ref().unwrap();
+        assert_eq!((left.mode, left.pulse_repeat_delay_ms, left.pulse_repeat_interval_ms), (StickMode::Pulse, Some(400), 150));
+        
+        let warnings: Vec<String> = config.lint().iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings, vec!["profile 'base' right stick: is pulse but has no directions".to_string()]);
+        
+        config.profiles[0].sticks.left.as_mut().unwrap().pulse_repeat_interval_ms = 0;
+        assert!(config.validate().unwrap_err().to_string().contains("pulse_repeat_interval_ms must be positive"));
+    }
+    
+    #[test]
+    fn test_malformed_key_combos() {
+        let config_with = |key: &str| {
+            crate::mapping::builder::ConfigBuilder::new()
+                .default_profile("base")
+                .profile(crate::mapping::builder::ProfileBuilder::new("base")
+                    .button(ButtonType::A, Action::KeyHold { key: Some(key.to_string()) }))
+                .build()
+        };
+        
+        assert!(config_with("ctrl+shift+w").is_ok());
+        for (key, message) in [
+            ("shift+", "empty key"),
+            ("+w", "empty key"),
+            ("shift++w", "empty key"),
+            ("ctrl+ctrl+a", "'ctrl' is used twice"),
+            ("Ctrl+control+a", "'control' is used twice"),
+            ("a+shift+A", "'A' is used twice"),
+        ] {
+            let err = config_with(key).unwrap_err().to_string();
+            assert!(err.contains(&format!("Invalid key combo '{}'", key)) && err.contains(message), "{}: {}", key, err);
+        }
+    }
+    
+    #[test]
+    fn test_toggle_profiles_validation() {
+        use crate::mapping::builder::{ConfigBuilder, ProfileBuilder};
+        let toggle = |a: &str, b: &str| Action::ToggleProfiles { a: a.to_string(), b: b.to_string() };
+        let config_with = |combat: Action, menu: Action| {
+            ConfigBuilder::new()
+                .default_profile("combat")
+                .profile(ProfileBuilder::new("combat").button(ButtonType::Plus, combat))
+                .profile(ProfileBuilder::new("menu").button(ButtonType::Plus, menu))
+                .profile(ProfileBuilder::new("driving"))
+                .build()
+        };
+        
+        // Both toggled profiles need the toggle (in either order), the third doesn't
+        assert!(config_with(toggle("combat", "menu"), toggle("menu", "combat")).is_ok());
+        let err = config_with(toggle("combat", "menu"), Action::KeyHold { key: Some("m".to_string()) }).unwrap_err().to_string();
+        assert!(err.contains("Profile 'menu' is missing ToggleProfiles between 'combat' and 'menu' on button Plus"), "{}", err);
+        let err = config_with(toggle("combat", "menu"), toggle("menu", "driving")).unwrap_err().to_string();
+        assert!(err.contains("Profile 'menu' is missing ToggleProfiles"), "{}", err);
+        
+        let err = config_with(toggle("combat", "missing"), toggle("combat", "missing")).unwrap_err().to_string();
+        assert!(err.contains("refers to unknown profile 'missing'"), "{}", err);
+        let err = config_with(toggle("combat", "combat"), toggle("combat", "combat")).unwrap_err().to_string();
+        assert!(err.contains("must name two different profiles"), "{}", err);
+        
+        let parsed: Action = toml::from_str(r#"type = "toggleprofiles"
+a = "combat"
+b = "menu""#).unwrap();
+        assert_eq!(parsed, toggle("combat", "menu"));
+    }
+    
+    #[test]
+    fn test_stick_mouse_base_must_be_positive() {
+        let mut config: Config = toml::from_str(r#"
+            [settings]
+            stick_mouse_base = 4.5
+            
+            [[profiles]]
+            name = "base"
+        "#).unwrap();
+        assert_eq!(config.settings.stick_mouse_base, 4.5);
+        config.validate().unwrap();
+        
+        for base in [0.0, -1.0] {
+            config.settings.stick_mouse_base = base;
+            assert!(config.validate().unwrap_err().to_string().contains("stick_mouse_base must be positive"));
+        }
+    }
+    
+    #[test]
+    fn test_gyro_gamepad_rstick_lint() {
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.gyro.right]
+            enabled = true
+            default_enabled = true
+            output = "gamepad_rstick"
+            
+            [profiles.sticks.left]
+            mode = "gamepad"
+        "#).unwrap();
+        config.validate().unwrap();
+        assert!(config.lint().is_empty());
+        
+        // The left stick redirected to the right gamepad stick would fight the gyro
+        config.profiles[0].sticks.left.as_mut().unwrap().gamepad_stick = Some(StickType::Right);
+        let warnings: Vec<String> = config.lint().iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings, vec![
+            "profile 'base' right gyro: drives the gamepad right stick, which a gamepad-mode stick also drives".to_string()
+        ]);
+    }
+    
+    #[test]
+    fn test_key_repeat_config() {
+        let mut config: Config = toml::from_str(r#"
+            [settings]
+            key_repeat = { repeat_delay_ms = 250 }
+            
+            [[profiles]]
+            name = "base"
+            
+            [profiles.buttons]
+            A = [{ type = "keyhold", key = "w" }]
+            
+            [profiles.key_repeat]
+            A = { repeat_rate_hz = 0.0 }
+            B = { repeat_delay_ms = 100, repeat_rate_hz = 20.0 }
+        "#).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.settings.key_repeat, Some(KeyRepeat { repeat_delay_ms: 250, repeat_rate_hz: 30.0 }));
+        assert_eq!(config.profiles[0].key_repeat[&ButtonType::A].interval(), None);
+        assert_eq!(config.profiles[0].key_repeat[&ButtonType::B].interval(), Some(std::time::Duration::from_millis(50)));
+        assert!(config.summary().contains("B key_repeat = { repeat_delay_ms = 100, repeat_rate_hz = 20.0 }"));
+        
+        let warnings: Vec<String> = config.lint().iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings, vec!["profile 'base' key_repeat button B: has no keyhold action to repeat".to_string()]);
+        
+        config.profiles[0].key_repeat.get_mut(&ButtonType::B).unwrap().repeat_rate_hz = -1.0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("profile 'base' key_repeat button B repeat_rate_hz must be 0 or positive"), "{}", err);
+        config.profiles[0].key_repeat.clear();
+        config.settings.key_repeat = Some(KeyRepeat { repeat_delay_ms: 0, repeat_rate_hz: f32::NAN });
+        assert!(config.validate().unwrap_err().to_string().contains("settings.key_repeat repeat_rate_hz"));
+    }
+    
+    #[test]
+    fn test_confirm_to_activate_config() {
+        let config: Config = toml::from_str(r#"
+            [settings]
+            confirm_to_activate = ["L", "R"]
+            
+            [[profiles]]
+            name = "base"
+        "#).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.settings.confirm_to_activate, vec![ButtonType::L, ButtonType::R]);
+        assert!(Settings::default().confirm_to_activate.is_empty());
+        
+        let mut config = config;
+        config.settings.confirm_to_activate = vec![ButtonType::L, ButtonType::R, ButtonType::L];
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("confirm_to_activate lists L twice"), "{}", err);
+    }
+    
+    #[test]
+    fn test_edge_binding_validation() {
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            
+            [profiles.on_press]
+            A = [{ type = "keyhold", key = "w" }]
+            
+            [profiles.on_release]
+            A = [{ type = "taphold", tap = { type = "keyhold", key = "a" }, hold = { type = "keyhold", key = "b" } }]
+        "#).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("not allowed in profile 'base' on_release button A"), "{}", err);
+        
+        config.profiles[0].on_release.insert(ButtonType::A, vec![Action::HoldLayer { profile: "base".to_string() }]);
+        assert!(config.validate().unwrap_err().to_string().contains("not allowed"));
+        
+        config.profiles[0].on_release.clear();
+        config.validate().unwrap();
+    }
+    
+    #[test]
+    fn test_profile_hook_validation() {
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            on_enter = [{ type = "togglegyromouser" }]
+            on_exit = [{ type = "taphold", tap = { type = "keyhold", key = "a" }, hold = { type = "keyhold", key = "b" } }]
+        "#).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("not allowed in profile 'base' on_exit"), "{}", err);
+        
+        for action in [Action::CycleProfiles, Action::HoldLayer { profile: "base".to_string() }] {
+            config.profiles[0].on_exit = vec![action];
+            assert!(config.validate().unwrap_err().to_string().contains("not allowed in profile 'base' on_exit"));
+        }
+        
+        config.profiles[0].on_exit = vec![Action::KeyHold { key: Some("escape".to_string()) }];
+        config.validate().unwrap();
+    }
+    
+    #[test]
+    fn test_gesture_validation() {
+        let mut config: Config = toml::from_str(r#"
+            [[profiles]]
+            name = "base"
+            gestures = { Shake = [{ type = "taphold", tap = { type = "keyhold", key = "a" }, hold = { type = "keyhold", key = "b" } }] }
+        "#).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("not allowed in profile 'base' gesture Shake"), "{}", err);
+        
+        config.profiles[0].gestures.insert(GestureKind::Shake, vec![Action::HoldLayer { profile: "base".to_string() }]);
+        assert!(config.validate().unwrap_err().to_string().contains("not allowed"));
+        
+        config.profiles[0].gestures.insert(GestureKind::Shake, vec![Action::KeyHold { key: Some("r".to_string()) }]);
+        config.validate().unwrap();
     }
 }
@@ -4,10 +4,19 @@
 //! events and executes the corresponding keyboard/mouse actions based on
 //! the loaded configuration.
 
-use crate::backend::{KeyboardBackend, MouseBackend, MouseButton};
-use crate::mapping::config::{Action, Config, StickMode, ButtonType, StickType, JoyConState, JoyConEvent, ControllerSide};
+use crate::backend::{GamepadAxis, GamepadBackend, KeyboardBackend, MouseBackend, MouseButton};
+use crate::joycon2::types::Stick;
+use crate::manager::ManagerCommand;
+use crate::mapping::clock::{Clock, RealClock};
+use crate::mapping::config::{Action, Config, DirectionalKeys, StickMode, TriggerMode, ButtonType, StickType, JoyConState, JoyConEvent, ControllerSide, GestureKind, GyroOrientation, Profile, GYRO_STICK_FULL_SCALE};
 use log::{debug, info, warn, trace};
-use std::collections::{HashSet, HashMap};
+use crossbeam_channel::Sender;
+use std::collections::{HashSet, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Distance from a screen edge (pixels) at which auto-warp recenters the cursor
+const WARP_EDGE_MARGIN: i32 = 8;
 
 /// Reference counts of sources keeping a key logically held
 #[derive(Default, Debug, Clone, Copy)]
@@ -33,6 +42,8 @@ struct HeldState {
     key_sources: HashMap<String, SourceCounts>,
     /// Keys we have actually sent key_down for (OS state)
     keys_down: HashSet<String>,
+    /// Key bindings latched by a `keytoggle` (one button claim per key)
+    toggled: HashSet<String>,
 }
 
 impl HeldState {
@@ -88,7 +99,290 @@ impl HeldState {
         }
         self.key_sources.clear();
         self.buttons.clear();
+        self.toggled.clear();
+    }
+    
+    /// Latch a key binding down or release it, returns true when it is now held
+    fn toggle<Kb: KeyboardBackend>(&mut self, binding: &str, keyboard: &Kb) -> bool {
+        let keys: Vec<&str> = binding.split('+').map(|k| k.trim()).filter(|k| !k.is_empty()).collect();
+        if self.toggled.remove(binding) {
+            for k in keys.iter().rev() {
+                self.release_key(k, KeySource::Button, keyboard);
+            }
+            false
+        } else {
+            self.toggled.insert(binding.to_string());
+            for k in &keys {
+                self.press_key(k, KeySource::Button, keyboard);
+            }
+            true
+        }
+    }
+}
+
+/// Virtual mouse buttons held by bindings, ref-counted like keys
+#[derive(Default)]
+struct MouseHeldState {
+    /// Number of claimants per button (button_down is sent for the first, button_up after the last)
+    claims: HashMap<MouseButton, u32>,
+    /// Buttons latched by a `mousetoggle` (one claim each)
+    toggled: HashSet<MouseButton>,
+}
+
+impl MouseHeldState {
+    fn press<Mb: MouseBackend>(&mut self, button: MouseButton, mouse: &Mb) {
+        let count = self.claims.entry(button).or_insert(0);
+        if *count == 0 {
+            if let Err(e) = mouse.button_down(button) {
+                warn!("Failed to press mouse button: {}", e);
+                self.claims.remove(&button);
+                return;
+            }
+        }
+        *count += 1;
+        trace!("mouse {:?} claims: {}", button, count);
+    }
+    
+    fn release<Mb: MouseBackend>(&mut self, button: MouseButton, mouse: &Mb) {
+        let Some(count) = self.claims.get_mut(&button) else {
+            return; // Not held
+        };
+        *count -= 1;
+        if *count > 0 {
+            trace!("mouse {:?} partial release, claims: {}", button, count);
+            return;
+        }
+        self.claims.remove(&button);
+        if let Err(e) = mouse.button_up(button) {
+            warn!("Failed to release mouse button: {}", e);
+        }
+    }
+    
+    /// Flip the toggle claim on a button, returns whether it is now latched
+    fn toggle<Mb: MouseBackend>(&mut self, button: MouseButton, mouse: &Mb) -> bool {
+        if self.toggled.remove(&button) {
+            self.release(button, mouse);
+            false
+        } else {
+            self.toggled.insert(button);
+            self.press(button, mouse);
+            true
+        }
+    }
+    
+    fn clear_all<Mb: MouseBackend>(&mut self, mouse: &Mb) {
+        for (button, _) in self.claims.drain() {
+            if let Err(e) = mouse.button_up(button) { warn!("Failed to release mouse button {:?}: {}", button, e); }
+        }
+        self.toggled.clear();
+    }
+}
+
+/// Convert a config mouse button to the backend mouse button
+fn to_backend_button(button: crate::mapping::config::MouseButton) -> MouseButton {
+    match button {
+        crate::mapping::config::MouseButton::Left => MouseButton::Left,
+        crate::mapping::config::MouseButton::Right => MouseButton::Right,
+        crate::mapping::config::MouseButton::Middle => MouseButton::Middle,
+    }
+}
+
+/// Scale a mouse delta down so its length doesn't exceed `max`, keeping its direction
+fn clamp_mouse_delta(dx: f32, dy: f32, max: f32) -> (f32, f32) {
+    let magnitude = (dx * dx + dy * dy).sqrt();
+    if magnitude > max && magnitude > 0.0 {
+        let scale = max / magnitude;
+        (dx * scale, dy * scale)
+    } else {
+        (dx, dy)
+    }
+}
+
+/// Rotate a stick vector to the nearest of `angles` evenly spaced directions, keeping its length
+fn snap_stick_angle(x: f32, y: f32, angles: u8) -> (f32, f32) {
+    if angles == 0 {
+        return (x, y);
+    }
+    let stick = Stick { x, y };
+    let step = 360.0 / angles as f32;
+    let angle = ((stick.angle() / step).round() * step).to_radians();
+    let magnitude = stick.magnitude();
+    (magnitude * angle.cos(), magnitude * angle.sin())
+}
+
+/// Rescale a stick vector's length from `deadzone..1.0` to `anti_deadzone..1.0`
+/// 
+/// Lengths past full deflection are clamped to 1.0, a centered stick stays at (0, 0).
+fn rescale_live_range(x: f32, y: f32, deadzone: f32, anti_deadzone: f32) -> (f32, f32) {
+    let magnitude = Stick { x, y }.magnitude();
+    if magnitude <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let live = ((magnitude - deadzone) / (1.0 - deadzone).max(f32::EPSILON)).clamp(0.0, 1.0);
+    let scale = (anti_deadzone + (1.0 - anti_deadzone) * live) / magnitude;
+    (x * scale, y * scale)
+}
+
+/// Rescale a stick vector outside the deadzone so its length starts at `anti_deadzone` instead of 0
+/// 
+/// Without an anti-deadzone the vector is left as is.
+fn apply_anti_deadzone(x: f32, y: f32, deadzone: f32, anti_deadzone: f32) -> (f32, f32) {
+    if anti_deadzone <= 0.0 {
+        return (x, y);
+    }
+    rescale_live_range(x, y, deadzone, anti_deadzone)
+}
+
+/// Stick position for the held D-pad buttons (8-way, diagonals on the unit circle)
+fn dpad_stick_position(up: bool, down: bool, left: bool, right: bool) -> (f32, f32) {
+    let x = right as i8 - left as i8;
+    let y = up as i8 - down as i8;
+    if x != 0 && y != 0 {
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        (x as f32 * diagonal, y as f32 * diagonal)
+    } else {
+        (x as f32, y as f32)
+    }
+}
+
+/// Pick the gyro rates (deg/s) that move the mouse horizontally and vertically
+/// 
+/// `x`, `y`, `z` are the parsed gyroscope axes (rotation about the controller's
+/// side-to-side axis, face normal and long axis).
+fn gyro_mouse_axes(orientation: GyroOrientation, side: ControllerSide, x: f32, y: f32, z: f32) -> (f32, f32) {
+    match orientation {
+        // Turning left/right spins around the face normal, tilting around the side-to-side axis
+        GyroOrientation::FaceUp => (y, -x),
+        // Upright: turning spins around the long axis
+        GyroOrientation::FaceForward => (-z, -x),
+        // Horizontal: tilting spins around the long axis, which points the other way on each side
+        GyroOrientation::Sideways => match side {
+            ControllerSide::Left => (y, z),
+            ControllerSide::Right => (y, -z),
+        },
+    }
+}
+
+/// Player LED pattern for a profile index
+/// 
+/// The first four profiles light a single LED (profile 1 = LED 1), later ones
+/// use the combinations of two or more LEDs in ascending order, wrapping after 15.
+fn profile_led_pattern(index: usize) -> u8 {
+    const PATTERNS: [u8; 15] = [
+        0b0001, 0b0010, 0b0100, 0b1000,
+        0b0011, 0b0101, 0b0110, 0b0111, 0b1001, 0b1010, 0b1011, 0b1100, 0b1101, 0b1110, 0b1111,
+    ];
+    PATTERNS[index % PATTERNS.len()]
+}
+
+/// Names of the directions a stick position is in
+/// 
+/// Each axis counts past half deflection. When both axes do, an explicit diagonal
+/// binding wins over the two orthogonal ones.
+fn active_direction_names(x: f32, y: f32, directions: &DirectionalKeys) -> Vec<&'static str> {
+    let threshold = 0.5;
+    
+    // Note: Y-axis is inverted on controllers - negative Y is UP, positive Y is DOWN
+    let up = y < -threshold;
+    let down = y > threshold;
+    let left = x < -threshold;
+    let right = x > threshold;
+    
+    if (up || down) && (left || right) && directions.diagonal(up, left).is_some() {
+        return vec![match (up, left) {
+            (true, true) => "up_left",
+            (true, false) => "up_right",
+            (false, true) => "down_left",
+            (false, false) => "down_right",
+        }];
     }
+    
+    let mut names = Vec::new();
+    if up { names.push("up"); }
+    if down { names.push("down"); }
+    if left { names.push("left"); }
+    if right { names.push("right"); }
+    names
+}
+
+/// Controller side a stick belongs to
+fn stick_side(stick: StickType) -> ControllerSide {
+    match stick {
+        StickType::Left => ControllerSide::Left,
+        StickType::Right => ControllerSide::Right,
+    }
+}
+
+/// Individual keys held by the `keyhold` actions of a direction binding
+fn direction_keys(actions: &[Action]) -> impl Iterator<Item = &str> {
+    actions.iter()
+        .filter_map(|action| match action {
+            Action::KeyHold { key: Some(key) } => Some(key.as_str()),
+            _ => None,
+        })
+        .flat_map(|key| key.split('+').map(|k| k.trim()))
+        .filter(|k| !k.is_empty())
+}
+
+/// Momentary layer enabled while its activating button is held
+struct ActiveLayer {
+    button: ButtonType,
+    profile_index: usize,
+}
+
+/// A `taphold` button waiting to find out whether it is a tap or a hold
+struct PendingTapHold {
+    pressed_at: Instant,
+    tap: Action,
+    hold: Action,
+    side: ControllerSide,
+    /// The hold action has been pressed and must be released with the button
+    holding: bool,
+}
+
+/// A combo key down (or up) waiting for its inter-key delay
+struct ScheduledKey {
+    due: Instant,
+    key: String,
+    /// Key up of a tapped combo rather than a key down
+    release: bool,
+    /// The held combo that scheduled it (only its release cancels it), None for a tap
+    binding: Option<String>,
+}
+
+/// Autofire started by a `turbotoggle`
+struct TurboToggle {
+    interval: Duration,
+    /// When the next tap is due
+    next: Instant,
+}
+
+/// Key repeat of a held `keyhold` button (see `KeyRepeat`)
+struct HeldKeyRepeat {
+    /// Last key of each binding the button holds
+    keys: Vec<String>,
+    interval: Duration,
+    /// When the next repeat is due
+    next: Instant,
+}
+
+/// Start-up pause until the `confirm_to_activate` buttons are held together
+struct ConfirmWait {
+    /// Confirm buttons currently held
+    held: HashSet<ButtonType>,
+    /// Whether the player LEDs are lit in the current flash phase
+    lit: bool,
+    /// When the LEDs flip next
+    next_flash: Instant,
+}
+
+/// How often the player LEDs flip while waiting for `confirm_to_activate`
+const CONFIRM_FLASH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A `mouseclick` release delayed until the click lasted `click_min_duration_ms`
+struct ScheduledMouseRelease {
+    due: Instant,
+    button: MouseButton,
 }
 
 /// Gyro mouse state per controller
@@ -96,13 +390,9 @@ impl HeldState {
 struct GyroMouseState {
     left_enabled: bool,
     right_enabled: bool,
-}
-
-/// Current stick positions for continuous movement
-#[derive(Default, Clone, Copy)]
-struct StickState {
-    x: f32,
-    y: f32,
+    /// Output suppressed by a held `gyropause` button (the enabled state is kept)
+    left_paused: bool,
+    right_paused: bool,
 }
 
 /// Executes mapping actions based on Joy-Con events
@@ -115,6 +405,7 @@ where
     keyboard: K,
     mouse: M,
     held_state: HeldState,
+    mouse_held_state: MouseHeldState,
     previous_state: JoyConState,
     
     /// Current active profile index
@@ -123,12 +414,101 @@ where
     /// Current sensitivity factor index
     current_sensitivity_index: usize,
     
+    /// Per-side sensitivity index set by CycleSensitivityLeft/Right (None = the current index)
+    side_sensitivity_index: HashMap<ControllerSide, usize>,
+    
+    /// Momentary layer overlaying the current profile's buttons (HoldLayer)
+    active_layer: Option<ActiveLayer>,
+    
+    /// Factors of the sensitivity clutches currently held (stacked multiplicatively)
+    active_clutches: Vec<f32>,
+    
     /// Gyro mouse state
     gyro_mouse_state: GyroMouseState,
     
     /// Current stick positions (for continuous movement)
-    left_stick: StickState,
-    right_stick: StickState,
+    left_stick: Stick,
+    right_stick: Stick,
+    
+    /// Directions (by name) each directional stick is currently in
+    active_directions: HashMap<StickType, Vec<&'static str>>,
+    /// Pulse-mode sticks that fired since leaving the deadzone, with their next auto-repeat
+    stick_pulses: HashMap<StickType, Option<Instant>>,
+    
+    /// `click_directions` actions started by a stick click, released with the click
+    stick_click_actions: HashMap<ButtonType, Vec<Action>>,
+    
+    /// Combo key downs (and tapped combos' ups) scheduled by `combo_key_delay_ms` (in due order)
+    scheduled_keys: VecDeque<ScheduledKey>,
+    
+    /// Mouse button releases delayed by `click_min_duration_ms` (in due order)
+    scheduled_mouse_releases: VecDeque<ScheduledMouseRelease>,
+    
+    /// Time of the last accepted `mouseclick` press per mouse button
+    click_down_at: HashMap<MouseButton, Instant>,
+    
+    /// `mouseclick` presses dropped by `click_debounce_ms` whose release is still to come
+    debounced_clicks: HashMap<MouseButton, u32>,
+    
+    /// Pressed `taphold` buttons
+    tap_holds: HashMap<ButtonType, PendingTapHold>,
+    
+    /// D-pad buttons held while driving a stick (`dpad_as_stick`)
+    dpad_stick_buttons: HashSet<ButtonType>,
+    
+    /// Mouse movement applied every update for each held `mousemovehold` button
+    mouse_move_holds: HashMap<ButtonType, Vec<(i32, i32)>>,
+    
+    /// Keys (or combos) tapped repeatedly by an active `turbotoggle`
+    turbo_toggles: HashMap<String, TurboToggle>,
+    
+    /// Held `keyhold` buttons whose keys are re-sent by key repeat
+    key_repeats: HashMap<ButtonType, HeldKeyRepeat>,
+    
+    /// Last trigger time of cooldown-limited actions, per button and action kind
+    last_triggered: HashMap<(ButtonType, std::mem::Discriminant<Action>), Instant>,
+    
+    /// Input injection enabled (false while paused by TogglePause)
+    enabled: bool,
+    
+    /// Waiting for the `confirm_to_activate` buttons before the first input is sent
+    confirm_wait: Option<ConfirmWait>,
+    
+    /// Keep-awake mouse nudging enabled (KeepAwake)
+    keep_awake: bool,
+    
+    /// Last time the executor moved the mouse (keep-awake only nudges when idle)
+    last_mouse_move: Instant,
+    
+    /// Last optical mouse sensor position per controller (for per-packet deltas)
+    mouse_sensor_last: HashMap<ControllerSide, (i16, i16)>,
+    
+    /// Controllers currently connected, for feedback that isn't tied to a button
+    connected_sides: HashSet<ControllerSide>,
+    
+    /// Channel for commands back to the controllers (e.g. rumble feedback)
+    command_sender: Option<Sender<ManagerCommand>>,
+    
+    /// Virtual gamepad for gamepad-mode mappings (none = those mappings do nothing)
+    gamepad: Option<Arc<dyn GamepadBackend>>,
+    
+    /// Sides whose threshold-mode trigger is currently past its threshold
+    triggers_pressed: HashSet<ControllerSide>,
+    
+    /// Last (x, y) sent to each gamepad stick (only sent again when it changes)
+    gamepad_sticks: HashMap<StickType, (f32, f32)>,
+    
+    /// Sides whose gyro is deflecting the gamepad right stick (`output = "gamepad_rstick"`)
+    gyro_sticks: HashSet<ControllerSide>,
+    
+    /// Channel for events the executor derives (e.g. `StickActive`), none = not published
+    event_sender: Option<Sender<JoyConEvent>>,
+    
+    /// Sticks currently outside their deadzone
+    active_sticks: HashSet<StickType>,
+    
+    /// Time source for timing features (a `MockClock` in tests)
+    clock: Arc<dyn Clock>,
 }
 
 impl<K, M> MappingExecutor<K, M>
@@ -138,6 +518,13 @@ where
 {
     /// Create a new mapping executor with the given configuration and backends
     pub fn new(config: Config, keyboard: K, mouse: M) -> Self {
+        Self::with_clock(config, keyboard, mouse, RealClock)
+    }
+    
+    /// Create a mapping executor that reads time from `clock` (e.g. a `MockClock` in tests)
+    pub fn with_clock(config: Config, keyboard: K, mouse: M, clock: impl Clock + 'static) -> Self {
+        let clock: Arc<dyn Clock> = Arc::new(clock);
+        
         // Find default profile index
         let current_profile_index = config.profiles.iter()
             .position(|p| p.name == config.settings.default_profile)
@@ -147,18 +534,77 @@ where
             info!("Starting with profile: '{}'", config.profiles[current_profile_index].name);
         }
         
-        Self {
+        let mut executor = Self {
             config,
             keyboard,
             mouse,
             held_state: HeldState::default(),
+            mouse_held_state: MouseHeldState::default(),
             previous_state: JoyConState::default(),
             current_profile_index,
             current_sensitivity_index: 0,
+            side_sensitivity_index: HashMap::new(),
+            active_layer: None,
+            active_clutches: Vec::new(),
             gyro_mouse_state: GyroMouseState::default(),
-            left_stick: StickState::default(),
-            right_stick: StickState::default(),
+            left_stick: Stick::default(),
+            right_stick: Stick::default(),
+            active_directions: HashMap::new(),
+            stick_pulses: HashMap::new(),
+            stick_click_actions: HashMap::new(),
+            scheduled_keys: VecDeque::new(),
+            scheduled_mouse_releases: VecDeque::new(),
+            click_down_at: HashMap::new(),
+            debounced_clicks: HashMap::new(),
+            tap_holds: HashMap::new(),
+            dpad_stick_buttons: HashSet::new(),
+            mouse_move_holds: HashMap::new(),
+            turbo_toggles: HashMap::new(),
+            key_repeats: HashMap::new(),
+            last_triggered: HashMap::new(),
+            enabled: true,
+            confirm_wait: None,
+            keep_awake: false,
+            last_mouse_move: clock.now(),
+            mouse_sensor_last: HashMap::new(),
+            connected_sides: HashSet::new(),
+            command_sender: None,
+            gamepad: None,
+            triggers_pressed: HashSet::new(),
+            gamepad_sticks: HashMap::new(),
+            gyro_sticks: HashSet::new(),
+            event_sender: None,
+            active_sticks: HashSet::new(),
+            clock,
+        };
+        executor.apply_profile_gyro_defaults();
+        executor.apply_profile_sensitivity_default();
+        
+        if !executor.config.settings.confirm_to_activate.is_empty() {
+            info!("🔒 Input paused until {:?} are held together", executor.config.settings.confirm_to_activate);
+            executor.enabled = false;
+            executor.confirm_wait = Some(ConfirmWait {
+                held: HashSet::new(),
+                lit: false,
+                next_flash: executor.clock.now(),
+            });
         }
+        executor
+    }
+    
+    /// Set the channel used to send commands (e.g. rumble feedback) to the controllers
+    pub fn set_command_sender(&mut self, sender: Sender<ManagerCommand>) {
+        self.command_sender = Some(sender);
+    }
+    
+    /// Set the channel the executor publishes its derived events on (e.g. `StickActive`)
+    pub fn set_event_sender(&mut self, sender: Sender<JoyConEvent>) {
+        self.event_sender = Some(sender);
+    }
+    
+    /// Set the virtual gamepad driven by gamepad-mode mappings
+    pub fn set_gamepad_backend(&mut self, gamepad: Arc<dyn GamepadBackend>) {
+        self.gamepad = Some(gamepad);
     }
     
     /// Get the current active profile
@@ -166,8 +612,51 @@ where
         self.config.profiles.get(self.current_profile_index)
     }
     
-    /// Get current button mappings (with gyro mouse overrides if active)
+    /// Name of the active profile (empty if the config has no profiles)
+    pub fn current_profile_name(&self) -> &str {
+        self.current_profile().map(|p| p.name.as_str()).unwrap_or("")
+    }
+    
+    /// Index of the active profile in `config.profiles`
+    pub fn current_profile_index(&self) -> usize {
+        self.current_profile_index
+    }
+    
+    /// Effective sensitivity factor (current level including held clutches)
+    pub fn current_sensitivity(&self) -> f32 {
+        self.sensitivity_factor_at(self.current_sensitivity_index)
+    }
+    
+    /// Whether gyro mouse is currently on for a controller
+    pub fn is_gyro_mouse_enabled(&self, side: ControllerSide) -> bool {
+        match side {
+            ControllerSide::Left => self.gyro_mouse_state.left_enabled,
+            ControllerSide::Right => self.gyro_mouse_state.right_enabled,
+        }
+    }
+    
+    /// Whether input injection is paused (TogglePause)
+    pub fn is_paused(&self) -> bool {
+        !self.enabled
+    }
+    
+    /// Get current button mappings for a button sent by the controller on `side`
+    /// (with gyro mouse overrides if active)
     fn get_button_actions(&self, button: ButtonType, side: ControllerSide) -> Option<Vec<Action>> {
+        // The global profile switch button works the same in every profile
+        if self.config.settings.profile_switch_button == Some(button) {
+            return Some(vec![Action::CycleProfiles]);
+        }
+        
+        // A held layer takes precedence, unmapped buttons fall through to the current profile
+        if let Some(layer) = &self.active_layer {
+            let layer_actions = self.config.profiles.get(layer.profile_index)
+                .and_then(|p| p.button_actions(button, side));
+            if let Some(actions) = layer_actions {
+                return Some(actions.clone());
+            }
+        }
+        
         let profile = self.current_profile()?;
         
         // Check if gyro mouse is active for this side
@@ -188,27 +677,69 @@ where
             }
         }
         
-        // Fall back to normal button mapping
-        profile.buttons.get(&button).cloned()
+        // Fall back to normal button mapping (the side's own map first)
+        profile.button_actions(button, side).cloned()
+    }
+    
+    /// Get a controller's current sensitivity factor (including any held sensitivity clutch)
+    fn get_sensitivity_factor(&self, side: ControllerSide) -> f32 {
+        self.sensitivity_factor_at(self.sensitivity_index(side))
     }
     
-    /// Get current sensitivity factor
-    fn get_sensitivity_factor(&self) -> f32 {
-        self.config.settings.sensitivity_factor
-            .get(self.current_sensitivity_index)
+    /// Sensitivity level index of a controller (its own level if cycled separately)
+    fn sensitivity_index(&self, side: ControllerSide) -> usize {
+        self.side_sensitivity_index.get(&side).copied().unwrap_or(self.current_sensitivity_index)
+    }
+    
+    /// Sensitivity factor of a level, including any held sensitivity clutch
+    fn sensitivity_factor_at(&self, index: usize) -> f32 {
+        let base = self.config.settings.sensitivity_factor
+            .get(index)
             .copied()
-            .unwrap_or(1.0)
+            .unwrap_or(1.0);
+        
+        self.active_clutches.iter().fold(base, |acc, factor| acc * factor)
     }
     
     /// Process a Joy-Con event and execute corresponding actions
     pub fn process_event(&mut self, event: &JoyConEvent) {
+        // Tracked in every state, connections change while paused too
+        match event {
+            JoyConEvent::Connected { side } => {
+                self.connected_sides.insert(*side);
+            }
+            JoyConEvent::Disconnected { side } => {
+                self.connected_sides.remove(side);
+            }
+            _ => {}
+        }
+        
+        if self.confirm_wait.is_some() {
+            self.on_confirm_event(event);
+            return;
+        }
+        
+        if !self.enabled {
+            // Paused: only a TogglePause press gets through
+            if let JoyConEvent::ButtonPressed { button, side } = *event {
+                if self.get_button_actions(button, side).is_some_and(|actions| actions.contains(&Action::TogglePause)) {
+                    self.toggle_pause(side);
+                }
+            }
+            return;
+        }
+        
         match event {
-            JoyConEvent::ButtonPressed(button) => {
-                self.on_button_pressed(*button);
+            JoyConEvent::ButtonPressed { button, side } => {
+                if !self.on_dpad_stick_button(*button, true) && !self.on_stick_click_direction(*button, true) {
+                    self.on_button_pressed(*button, *side);
+                }
             }
             
-            JoyConEvent::ButtonReleased(button) => {
-                self.on_button_released(*button);
+            JoyConEvent::ButtonReleased { button, side } => {
+                if !self.on_dpad_stick_button(*button, false) && !self.on_stick_click_direction(*button, false) {
+                    self.on_button_released(*button, *side);
+                }
             }
             
             JoyConEvent::StickMoved { stick, x, y } => {
@@ -223,127 +754,582 @@ where
                 self.on_state_update(state);
             }
             
+            JoyConEvent::Motion { .. } => {
+                // Derived motion is for external consumers (visualizers, gestures)
+            }
+            
+            JoyConEvent::Trigger { side, value } => {
+                self.on_trigger(*side, *value);
+            }
+            
+            JoyConEvent::MouseSensor { side, x, y, .. } => {
+                self.on_mouse_sensor(*side, *x, *y);
+            }
+            
+            JoyConEvent::ScanTimedOut { side } => {
+                warn!("No {:?} controller found before the scan timeout", side);
+            }
+            
+            JoyConEvent::Gesture { side, kind } => {
+                self.on_gesture(*side, *kind);
+            }
+            
+            JoyConEvent::SyncCombo { side } => {
+                // Notification for external consumers (e.g. a pairing UI)
+                debug!("Sync combo held on {:?} controller", side);
+            }
+            
+            JoyConEvent::SignalStrength { side, rssi } => {
+                trace!("{:?} controller signal: {} dBm", side, rssi);
+            }
+            
             JoyConEvent::Connected { side } => {
                 debug!("Controller {:?} connected", side);
+                self.show_profile_led(*side);
             }
             
             JoyConEvent::Disconnected { side } => {
                 debug!("Controller {:?} disconnected", side);
+                self.mouse_sensor_last.remove(side);
                 self.release_all_held_keys();
             }
+            
+            JoyConEvent::RawPacket { side, data } => {
+                // Raw packets are for external consumers, nothing to map
+                trace!("Raw packet from {:?}: {} bytes", side, data.len());
+            }
+            
+            JoyConEvent::StickActive { .. } => {
+                // Published by the executor itself, nothing to map
+            }
+            
+            JoyConEvent::TelemetryProbe { .. } | JoyConEvent::Telemetry { .. } => {
+                // Measured by the manager's executor thread, nothing to map
+            }
+            
+            JoyConEvent::CalibrationProgress { .. } => {
+                // For status displays, stick events are held back while calibrating
+            }
+            
+            JoyConEvent::StickDrift { .. } => {
+                // Diagnostic only, the manager logs it and recenters if enabled
+            }
         }
     }
     
     /// Update continuous stick movements and held buttons (call this periodically in a timer)
     pub fn update_continuous_movements(&mut self) {
-        // Apply movement for both sticks based on their current positions
-        self.apply_stick_movement(StickType::Left);
-        self.apply_stick_movement(StickType::Right);
+        if self.confirm_wait.is_some() {
+            self.flash_confirm_leds();
+            return;
+        }
         
-        // Re-apply all held button actions to maintain continuous input
-        // This is needed because Joy-Con 2 stops sending button events when held
-        // and Windows needs repeated key_down calls for key repeat to work
-        // for button in self.held_state.buttons.clone() {
-        //     let side = Self::button_to_side(button);
-        //     if let Some(actions) = self.get_button_actions(button, side) {
-        //         for action in &actions {
-        //             // Only re-apply KeyHold actions (not one-time actions like CycleProfiles)
-        //             if matches!(action, Action::KeyHold { .. }) {
-        //                 self.execute_action(action, true, side);
-        //             }
-        //         }
-        //     }
-        // }
-    }
-    
-    /// Handle button press
-    fn on_button_pressed(&mut self, button: ButtonType) {
-        // Track if button was already pressed (to avoid repeating one-time actions)
-        let was_already_pressed = !self.held_state.buttons.insert(button);
+        if !self.enabled {
+            return;
+        }
         
-        // Determine which side this button is from
-        let side = Self::button_to_side(button);
+        // Press combo keys whose inter-key delay has elapsed
+        self.run_scheduled_keys();
         
-        // Get actions (with potential gyro mouse overrides)
-        if let Some(actions) = self.get_button_actions(button, side) {
-            for action in actions {
-                // Only execute one-time actions on first press
-                // KeyHold actions are handled ONLY by update_continuous_movements()
-                match action {
-                    Action::CycleProfiles | 
-                    Action::CycleSensitivity |
-                    Action::ToggleGyroMouseL |
-                    Action::ToggleGyroMouseR => {
-                        if !was_already_pressed {
-                            self.execute_action(&action, true, side);
-                        }
-                    }
-                    Action::KeyHold { .. } => {
-                        // KeyHold actions are ONLY processed in update_continuous_movements()
-                        // This ensures proper keyboard repeat behavior (initial delay + repeat)
-                        // Do nothing here
-                        log::debug!("KeyHold action triggered: {:?}", action);
-                        if !was_already_pressed {
-                            self.execute_action(&action, true, side);
-                        }
-                    }
-                    _ => {
-                        // Execute other actions (MouseClick)
-                        self.execute_action(&action, true, side);
-                    }
-                }
+        // Finish clicks that reached their minimum duration
+        self.run_scheduled_mouse_releases();
+        
+        // Turn taphold buttons held long enough into holds
+        self.run_tap_holds();
+        
+        // Tap keys of active turbo toggles that are due
+        self.run_turbo_toggles();
+        
+        // Re-send keys of held keyhold buttons whose repeat is due
+        self.run_key_repeats();
+        
+        // Nudge the mouse for held mousemovehold buttons
+        for (dx, dy) in self.mouse_move_holds.values().flatten() {
+            if let Err(e) = self.mouse.move_relative(*dx, *dy) {
+                warn!("Failed to move mouse: {}", e);
             }
         }
+        if !self.mouse_move_holds.is_empty() {
+            self.last_mouse_move = self.clock.now();
+        }
+        
+        self.run_keep_awake();
+        
+        // Apply movement for both sticks based on their current positions
+        self.apply_stick_movement(StickType::Left);
+        self.apply_stick_movement(StickType::Right);
     }
     
-    /// Determine which controller side a button belongs to
-    fn button_to_side(button: ButtonType) -> ControllerSide {
-        match button {
-            ButtonType::A | ButtonType::B | ButtonType::X | ButtonType::Y |
-            ButtonType::R | ButtonType::ZR | ButtonType::Plus | ButtonType::Home |
-            ButtonType::RightStickClick | ButtonType::SLR | ButtonType::SRR | ButtonType::Chat => {
-                ControllerSide::Right
+    /// Press (or release) scheduled combo keys that are due
+    fn run_scheduled_keys(&mut self) {
+        let now = self.clock.now();
+        while self.scheduled_keys.front().is_some_and(|s| s.due <= now) {
+            if let Some(scheduled) = self.scheduled_keys.pop_front() {
+                self.run_scheduled_key(&scheduled);
             }
-            _ => ControllerSide::Left
         }
     }
     
-    /// Handle button release
-    fn on_button_released(&mut self, button: ButtonType) {
-        if !self.held_state.buttons.remove(&button) {
-            return; // Wasn't pressed
+    fn run_scheduled_key(&mut self, scheduled: &ScheduledKey) {
+        if scheduled.release {
+            self.held_state.release_key(&scheduled.key, KeySource::Button, &self.keyboard);
+        } else {
+            self.held_state.press_key(&scheduled.key, KeySource::Button, &self.keyboard);
         }
-        
-        // Determine side
-        let side = Self::button_to_side(button);
-        
-        if let Some(actions) = self.get_button_actions(button, side) {
-            for action in actions {
-                self.execute_action(&action, false, side);
+    }
+    
+    /// Queue a combo key down/up, keeping the queue in due order
+    fn schedule_key(&mut self, due: Instant, key: &str, release: bool, binding: Option<&str>) {
+        let pos = self.scheduled_keys.partition_point(|s| s.due <= due);
+        self.scheduled_keys.insert(pos, ScheduledKey {
+            due,
+            key: key.to_string(),
+            release,
+            binding: binding.map(str::to_string),
+        });
+    }
+    
+    /// Send the scheduled keys of tapped combos right away, in order
+    fn flush_tapped_keys(&mut self) {
+        let (taps, held): (VecDeque<_>, VecDeque<_>) = std::mem::take(&mut self.scheduled_keys)
+            .into_iter()
+            .partition(|s| s.binding.is_none());
+        self.scheduled_keys = held;
+        for scheduled in &taps {
+            self.run_scheduled_key(scheduled);
+        }
+    }
+    
+    /// Press and immediately release an action (on_press, taphold taps, gestures, pulses, ...)
+    /// 
+    /// With `combo_key_delay_ms` a `keyhold` combo keeps its spacing: the remaining key downs
+    /// and then the key ups in reverse are scheduled, so no key is lost to the early release.
+    fn tap_action(&mut self, action: &Action, side: ControllerSide) {
+        let delay = Duration::from_millis(self.config.settings.combo_key_delay_ms);
+        match action {
+            Action::KeyHold { key: Some(combo) } if !delay.is_zero() => {
+                let keys: Vec<&str> = combo.split('+').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+                let now = self.clock.now();
+                let steps = keys.iter().map(|k| (*k, false)).chain(keys.iter().rev().map(|k| (*k, true)));
+                for (i, (key, release)) in steps.enumerate() {
+                    if i == 0 {
+                        self.held_state.press_key(key, KeySource::Button, &self.keyboard);
+                    } else {
+                        self.schedule_key(now + delay * i as u32, key, release, None);
+                    }
+                }
+            }
+            _ => {
+                self.execute_action(action, true, side);
+                self.execute_action(action, false, side);
             }
         }
     }
     
-    /// Handle stick movement
-    fn on_stick_moved(&mut self, stick: StickType, x: f32, y: f32) {
-        // Store the stick position for continuous movement
-        match stick {
-            StickType::Left => {
-                self.left_stick.x = x;
-                self.left_stick.y = y;
+    /// Tap the keys of active `turbotoggle`s whose next tap is due
+    fn run_turbo_toggles(&mut self) {
+        let now = self.clock.now();
+        for (binding, turbo) in self.turbo_toggles.iter_mut() {
+            if turbo.next > now {
+                continue;
+            }
+            let keys: Vec<&str> = binding.split('+').map(|k| k.trim()).filter(|k| !k.is_empty()).collect();
+            for key in &keys {
+                self.held_state.press_key(key, KeySource::Button, &self.keyboard);
             }
-            StickType::Right => {
-                self.right_stick.x = x;
-                self.right_stick.y = y;
+            for key in keys.iter().rev() {
+                self.held_state.release_key(key, KeySource::Button, &self.keyboard);
+            }
+            // Skip taps missed while updates were late instead of bursting them
+            turbo.next += turbo.interval;
+            if turbo.next <= now {
+                turbo.next = now + turbo.interval;
             }
         }
-        
-        // Apply the stick movement immediately
-        self.apply_stick_movement(stick);
     }
     
-    /// Apply stick movement based on current stick position
-    fn apply_stick_movement(&mut self, stick: StickType) {
+    /// Send another key_down for the keys of held `keyhold` buttons whose repeat is due
+    fn run_key_repeats(&mut self) {
+        let now = self.clock.now();
+        for repeat in self.key_repeats.values_mut() {
+            if repeat.next > now {
+                continue;
+            }
+            for key in &repeat.keys {
+                // Skip keys not down (yet), e.g. still waiting for combo_key_delay_ms
+                if !self.held_state.keys_down.contains(key) {
+                    continue;
+                }
+                if let Err(e) = self.keyboard.key_down(key) {
+                    warn!("Failed to repeat key '{}': {}", key, e);
+                } else {
+                    trace!("key_down '{}' (repeat)", key);
+                }
+            }
+            // Skip repeats missed while updates were late instead of bursting them
+            repeat.next += repeat.interval;
+            if repeat.next <= now {
+                repeat.next = now + repeat.interval;
+            }
+        }
+    }
+    
+    /// Start key repeat for a `keyhold` binding of a pressed button, if configured
+    /// 
+    /// The profile's per-button `key_repeat` takes precedence over `settings.key_repeat`.
+    fn start_key_repeat(&mut self, button: ButtonType, binding: &str) {
+        let repeat = self.current_profile()
+            .and_then(|p| p.key_repeat.get(&button))
+            .or(self.config.settings.key_repeat.as_ref())
+            .copied();
+        let Some((repeat, interval)) = repeat.and_then(|r| r.interval().map(|i| (r, i))) else {
+            return;
+        };
+        // Like OS key repeat, only the last key of a combo repeats
+        let Some(key) = binding.split('+').map(|k| k.trim()).rfind(|k| !k.is_empty()) else {
+            return;
+        };
+        
+        let next = self.clock.now() + Duration::from_millis(repeat.repeat_delay_ms);
+        self.key_repeats.entry(button)
+            .or_insert_with(|| HeldKeyRepeat { keys: Vec::new(), interval, next })
+            .keys.push(key.to_string());
+    }
+    
+    /// Release mouse buttons whose delayed click release is due
+    fn run_scheduled_mouse_releases(&mut self) {
+        let now = self.clock.now();
+        while self.scheduled_mouse_releases.front().is_some_and(|s| s.due <= now) {
+            if let Some(scheduled) = self.scheduled_mouse_releases.pop_front() {
+                self.mouse_held_state.release(scheduled.button, &self.mouse);
+            }
+        }
+    }
+    
+    /// Press a `mouseclick` button unless it bounced within `click_debounce_ms`
+    fn press_mouse_click(&mut self, button: MouseButton) {
+        let now = self.clock.now();
+        let debounce = Duration::from_millis(self.config.settings.click_debounce_ms);
+        if self.click_down_at.get(&button).is_some_and(|last| now.duration_since(*last) < debounce) {
+            debug!("Ignoring mouse {:?} press within the click debounce", button);
+            *self.debounced_clicks.entry(button).or_insert(0) += 1;
+            return;
+        }
+        
+        self.click_down_at.insert(button, now);
+        self.mouse_held_state.press(button, &self.mouse);
+    }
+    
+    /// Release a `mouseclick` button, delayed until the click lasted `click_min_duration_ms`
+    fn release_mouse_click(&mut self, button: MouseButton) {
+        // The matching press was debounced
+        if let Some(count) = self.debounced_clicks.get_mut(&button) {
+            *count -= 1;
+            if *count == 0 {
+                self.debounced_clicks.remove(&button);
+            }
+            return;
+        }
+        
+        let min_duration = Duration::from_millis(self.config.settings.click_min_duration_ms);
+        let due = self.click_down_at.get(&button).map(|down| *down + min_duration);
+        match due {
+            Some(due) if due > self.clock.now() => {
+                let pos = self.scheduled_mouse_releases.partition_point(|s| s.due <= due);
+                self.scheduled_mouse_releases.insert(pos, ScheduledMouseRelease { due, button });
+            }
+            _ => self.mouse_held_state.release(button, &self.mouse),
+        }
+    }
+    
+    /// Nudge the mouse 1px and back when keep-awake is on and the mouse has been idle
+    fn run_keep_awake(&mut self) {
+        let interval = Duration::from_secs(self.config.settings.keep_awake_interval_secs);
+        if !self.keep_awake || self.clock.now().duration_since(self.last_mouse_move) < interval {
+            return;
+        }
+        
+        debug!("Keep-awake mouse nudge");
+        for (dx, dy) in [(1, 0), (-1, 0)] {
+            if let Err(e) = self.mouse.move_relative(dx, dy) {
+                warn!("Failed to move mouse (keep-awake): {}", e);
+            }
+        }
+        self.last_mouse_move = self.clock.now();
+    }
+    
+    /// Press the hold action of taphold buttons held past `hold_time_ms`
+    fn run_tap_holds(&mut self) {
+        let hold_time = Duration::from_millis(self.config.settings.hold_time_ms);
+        let now = self.clock.now();
+        let due: Vec<(Action, ControllerSide)> = self.tap_holds.values_mut()
+            .filter(|pending| !pending.holding && now.duration_since(pending.pressed_at) >= hold_time)
+            .map(|pending| {
+                pending.holding = true;
+                (pending.hold.clone(), pending.side)
+            })
+            .collect();
+        
+        for (hold, side) in due {
+            self.execute_action(&hold, true, side);
+        }
+    }
+    
+    /// Handle a button press from the controller on `side`
+    fn on_button_pressed(&mut self, button: ButtonType, side: ControllerSide) {
+        // Track if button was already pressed (to avoid repeating one-time actions)
+        let was_already_pressed = !self.held_state.buttons.insert(button);
+        
+        // Get actions (with potential gyro mouse overrides)
+        if let Some(actions) = self.get_button_actions(button, side) {
+            for action in actions {
+                // Only execute one-time actions on first press
+                // KeyHold actions are handled ONLY by update_continuous_movements()
+                match action {
+                    Action::CycleProfiles | 
+                    Action::ToggleProfiles { .. } |
+                    Action::CycleSensitivity |
+                    Action::CycleSensitivityLeft |
+                    Action::CycleSensitivityRight |
+                    Action::ToggleGyroMouseL |
+                    Action::ToggleGyroMouseR => {
+                        // A bouncy button must not cycle/toggle twice
+                        if !was_already_pressed && self.cooldown_elapsed(button, &action) {
+                            self.execute_action(&action, true, side);
+                        }
+                    }
+                    Action::SensitivityClutch { .. } |
+                    Action::GyroPause { .. } |
+                    Action::MouseClick { .. } |
+                    Action::MouseToggle { .. } |
+                    Action::KeyToggle { .. } |
+                    Action::TypeText { .. } |
+                    Action::TurboToggle { .. } |
+                    Action::ReleaseAll |
+                    Action::KeepAwake |
+                    Action::WarpCenter |
+                    Action::Rumble { .. } |
+                    Action::TogglePause |
+                    Action::RunCommand { .. } => {
+                        if !was_already_pressed {
+                            self.execute_action(&action, true, side);
+                        }
+                    }
+                    Action::HoldLayer { profile } => {
+                        if !was_already_pressed {
+                            self.activate_layer(button, &profile);
+                        }
+                    }
+                    Action::MouseMoveHold { dx, dy } => {
+                        if !was_already_pressed {
+                            self.mouse_move_holds.entry(button).or_default().push((dx, dy));
+                        }
+                    }
+                    Action::TapHold { tap, hold } => {
+                        if !was_already_pressed {
+                            self.tap_holds.insert(button, PendingTapHold {
+                                pressed_at: self.clock.now(),
+                                tap: *tap,
+                                hold: *hold,
+                                side,
+                                holding: false,
+                            });
+                        }
+                    }
+                    Action::KeyHold { ref key } => {
+                        // Pressed once, held until release (repeated by key_repeat if configured)
+                        log::debug!("KeyHold action triggered: {:?}", action);
+                        if !was_already_pressed {
+                            self.execute_action(&action, true, side);
+                            if let Some(key) = key {
+                                self.start_key_repeat(button, key);
+                            }
+                        }
+                    }
+                    _ => {
+                        // Execute other actions
+                        self.execute_action(&action, true, side);
+                    }
+                }
+            }
+        }
+        
+        if !was_already_pressed {
+            self.tap_edge_actions(button, true, side);
+        }
+    }
+    
+    /// Tap a button's `on_press` or `on_release` actions (a held layer's first, like `buttons`)
+    fn tap_edge_actions(&mut self, button: ButtonType, pressed: bool, side: ControllerSide) {
+        fn bindings(profile: &Profile, pressed: bool) -> &HashMap<ButtonType, Vec<Action>> {
+            if pressed { &profile.on_press } else { &profile.on_release }
+        }
+        let actions = self.active_layer.as_ref()
+            .and_then(|layer| self.config.profiles.get(layer.profile_index))
+            .and_then(|profile| bindings(profile, pressed).get(&button))
+            .or_else(|| self.current_profile().and_then(|profile| bindings(profile, pressed).get(&button)))
+            .cloned()
+            .unwrap_or_default();
+        
+        for action in &actions {
+            self.tap_action(action, side);
+        }
+    }
+    
+    /// Check and restart the `action_cooldown_ms` of an action on a button
+    fn cooldown_elapsed(&mut self, button: ButtonType, action: &Action) -> bool {
+        let cooldown = Duration::from_millis(self.config.settings.action_cooldown_ms);
+        let now = self.clock.now();
+        let key = (button, std::mem::discriminant(action));
+        
+        if let Some(last) = self.last_triggered.get(&key) {
+            if now.duration_since(*last) < cooldown {
+                debug!("Ignoring {:?} on {:?} within the action cooldown", action, button);
+                return false;
+            }
+        }
+        self.last_triggered.insert(key, now);
+        true
+    }
+    
+    /// Handle a button release from the controller on `side`
+    fn on_button_released(&mut self, button: ButtonType, side: ControllerSide) {
+        // Releasing the layer button ends the layer, whatever the layer maps it to
+        if self.active_layer.as_ref().is_some_and(|layer| layer.button == button) {
+            self.deactivate_layer();
+            return;
+        }
+        
+        if !self.held_state.buttons.remove(&button) {
+            return; // Wasn't pressed
+        }
+        
+        self.mouse_move_holds.remove(&button);
+        self.key_repeats.remove(&button);
+        
+        // Released before hold_time_ms: it was a tap
+        if let Some(pending) = self.tap_holds.remove(&button) {
+            if pending.holding {
+                self.execute_action(&pending.hold, false, pending.side);
+            } else {
+                self.tap_action(&pending.tap, pending.side);
+            }
+        }
+        
+        if let Some(actions) = self.get_button_actions(button, side) {
+            for action in actions {
+                self.execute_action(&action, false, side);
+            }
+        }
+        
+        self.tap_edge_actions(button, false, side);
+    }
+    
+    /// Route a D-pad button to the profile's `dpad_as_stick` stick
+    /// 
+    /// Returns false when the button should go through the normal button mapping.
+    fn on_dpad_stick_button(&mut self, button: ButtonType, pressed: bool) -> bool {
+        if !matches!(button, ButtonType::DpadUp | ButtonType::DpadDown | ButtonType::DpadLeft | ButtonType::DpadRight) {
+            return false;
+        }
+        let Some(stick) = self.current_profile().and_then(|p| p.dpad_as_stick) else {
+            return false;
+        };
+        
+        if pressed {
+            self.dpad_stick_buttons.insert(button);
+        } else {
+            self.dpad_stick_buttons.remove(&button);
+        }
+        
+        let held = &self.dpad_stick_buttons;
+        let (x, y) = dpad_stick_position(
+            held.contains(&ButtonType::DpadUp),
+            held.contains(&ButtonType::DpadDown),
+            held.contains(&ButtonType::DpadLeft),
+            held.contains(&ButtonType::DpadRight),
+        );
+        self.process_event(&JoyConEvent::StickMoved { stick, x, y });
+        true
+    }
+    
+    /// Run the stick's `click_directions` binding for a stick click pushed in a direction
+    /// 
+    /// The direction is read from the last stick position when the click is pressed, and
+    /// the same actions are released with the click even if the stick moved meanwhile.
+    /// Returns false when the click should go through the normal button mapping.
+    fn on_stick_click_direction(&mut self, button: ButtonType, pressed: bool) -> bool {
+        let stick = match button {
+            ButtonType::LeftStickClick => StickType::Left,
+            ButtonType::RightStickClick => StickType::Right,
+            _ => return false,
+        };
+        let side = stick_side(stick);
+        
+        if pressed && self.stick_click_actions.contains_key(&button) {
+            return true; // Repeated press
+        }
+        if !pressed {
+            let Some(actions) = self.stick_click_actions.remove(&button) else {
+                return false;
+            };
+            for action in &actions {
+                self.execute_action(action, false, side);
+            }
+            return true;
+        }
+        
+        let Some(directions) = self.current_profile()
+            .and_then(|p| match stick {
+                StickType::Left => p.sticks.left.as_ref(),
+                StickType::Right => p.sticks.right.as_ref(),
+            })
+            .and_then(|m| m.click_directions.clone()) else {
+            return false;
+        };
+        let (x, y) = match stick {
+            StickType::Left => (self.left_stick.x, self.left_stick.y),
+            StickType::Right => (self.right_stick.x, self.right_stick.y),
+        };
+        
+        let all = directions.all_directions();
+        let actions: Vec<Action> = active_direction_names(x, y, &directions).iter()
+            .filter_map(|name| all.iter().find(|(n, _)| n == name))
+            .flat_map(|(_, actions)| actions.iter().cloned())
+            .collect();
+        if actions.is_empty() {
+            return false;
+        }
+        
+        for action in &actions {
+            self.execute_action(action, true, side);
+        }
+        self.stick_click_actions.insert(button, actions);
+        true
+    }
+    
+    /// Handle stick movement
+    fn on_stick_moved(&mut self, stick: StickType, x: f32, y: f32) {
+        // Store the stick position for continuous movement
+        let position = Stick { x, y };
+        match stick {
+            StickType::Left => self.left_stick = position,
+            StickType::Right => self.right_stick = position,
+        }
+        
+        // Report deadzone crossings (not every packet)
+        let active = position.magnitude() >= self.stick_deadzone(stick);
+        let changed = if active { self.active_sticks.insert(stick) } else { self.active_sticks.remove(&stick) };
+        if changed {
+            self.publish_event(JoyConEvent::StickActive { stick, active });
+        }
+        
+        // Apply the stick movement immediately
+        self.apply_stick_movement(stick);
+    }
+    
+    /// Apply stick movement based on current stick position
+    fn apply_stick_movement(&mut self, stick: StickType) {
         let profile = match self.current_profile() {
             Some(p) => p,
             None => return,
@@ -358,54 +1344,121 @@ where
             return;
         };
         
-        let deadzone = match stick {
-            StickType::Left => self.config.settings.left_stick_deadzone,
-            StickType::Right => self.config.settings.right_stick_deadzone,
-        };
+        let deadzone = self.stick_deadzone(stick);
         
         // Get current stick position
-        let (x, y) = match stick {
-            StickType::Left => (self.left_stick.x, self.left_stick.y),
-            StickType::Right => (self.right_stick.x, self.right_stick.y),
+        let position = match stick {
+            StickType::Left => self.left_stick,
+            StickType::Right => self.right_stick,
         };
+        let (x, y) = (position.x, position.y);
         
         // Apply deadzone
-        let magnitude = (x * x + y * y).sqrt();
+        let magnitude = position.magnitude();
         if magnitude < deadzone {
-            // In deadzone - release any held directional keys
-            if matches!(mapping.mode, StickMode::Directional) {
-                self.release_directional_keys(stick);
+            // In deadzone - release any held directional keys, center the gamepad stick, re-arm pulses
+            match mapping.mode {
+                StickMode::Directional => self.release_directional_keys(stick),
+                StickMode::Gamepad => {
+                    let target = mapping.gamepad_stick.unwrap_or(stick);
+                    self.set_gamepad_stick(target, 0.0, 0.0);
+                }
+                _ => {}
             }
+            self.stick_pulses.remove(&stick);
             return;
         }
         
         match mapping.mode {
             StickMode::Mouse => {
                 // Map to mouse movement with sensitivity factor
-                let sensitivity_factor = self.get_sensitivity_factor();
-                let dx = (x * mapping.sensitivity * sensitivity_factor * 10.0) as i32;
-                let dy = (y * mapping.sensitivity * sensitivity_factor * 10.0) as i32; // Don't invert Y - pushing up should move mouse up
+                let sensitivity_factor = self.get_sensitivity_factor(stick_side(stick));
+                let (x, y) = match mapping.snap_angles {
+                    Some(angles) => snap_stick_angle(x, y, angles),
+                    None => (x, y),
+                };
+                let (x, y) = apply_anti_deadzone(x, y, deadzone, mapping.anti_deadzone);
+                let (sens_x, sens_y) = mapping.axis_sensitivity(x, y);
+                let base = self.config.settings.stick_mouse_base * mapping.zone_multiplier(magnitude);
+                let (dx, dy) = clamp_mouse_delta(
+                    x * sens_x * sensitivity_factor * base,
+                    y * sens_y * sensitivity_factor * base, // Don't invert Y - pushing up should move mouse up
+                    self.config.settings.max_mouse_speed,
+                );
+                let dx = dx as i32;
+                let dy = dy as i32;
+                let warp_at_edge = mapping.warp_at_edge;
                 
                 if dx != 0 || dy != 0 {
                     if let Err(e) = self.mouse.move_relative(dx, dy) {
                         warn!("Failed to move mouse: {}", e);
                     }
+                    self.last_mouse_move = self.clock.now();
+                    if warp_at_edge {
+                        self.warp_if_near_edge();
+                    }
                 }
             }
             
             StickMode::Directional => {
                 // Map to directional keys (WASD or custom)
                 if let Some(directions) = mapping.directions.as_ref().cloned() {
-                    self.handle_directional_keys(x, y, &directions);
+                    self.handle_directional_keys(stick, x, y, &directions);
+                }
+            }
+            
+            StickMode::Pulse => {
+                if let Some(directions) = mapping.directions.as_ref().cloned() {
+                    let repeat = mapping.pulse_repeat_delay_ms
+                        .map(|delay| (Duration::from_millis(delay), Duration::from_millis(mapping.pulse_repeat_interval_ms)));
+                    self.handle_pulse(stick, x, y, &directions, repeat);
                 }
             }
             
+            StickMode::Gamepad => {
+                // The deadzone is only applied here, the raw stick events have none
+                let target = mapping.gamepad_stick.unwrap_or(stick);
+                let (x, y) = rescale_live_range(x, y, deadzone, mapping.anti_deadzone);
+                self.set_gamepad_stick(target, x, y);
+            }
+            
             StickMode::Disabled => {}
         }
     }
     
+    /// Deadzone of a stick, the current profile's override or the global setting
+    fn stick_deadzone(&self, stick: StickType) -> f32 {
+        let profile = self.current_profile();
+        match stick {
+            StickType::Left => profile.and_then(|p| p.left_stick_deadzone).unwrap_or(self.config.settings.left_stick_deadzone),
+            StickType::Right => profile.and_then(|p| p.right_stick_deadzone).unwrap_or(self.config.settings.right_stick_deadzone),
+        }
+    }
+    
+    /// Send a position to a virtual gamepad stick if it changed
+    fn set_gamepad_stick(&mut self, stick: StickType, x: f32, y: f32) {
+        if self.gamepad_sticks.get(&stick) == Some(&(x, y))
+            || (x == 0.0 && y == 0.0 && !self.gamepad_sticks.contains_key(&stick)) {
+            return;
+        }
+        self.gamepad_sticks.insert(stick, (x, y));
+        
+        let Some(gamepad) = &self.gamepad else {
+            return;
+        };
+        let axes = match stick {
+            StickType::Left => [(GamepadAxis::LeftX, x), (GamepadAxis::LeftY, y)],
+            StickType::Right => [(GamepadAxis::RightX, x), (GamepadAxis::RightY, y)],
+        };
+        for (axis, value) in axes {
+            if let Err(e) = gamepad.set_axis(axis, value) {
+                warn!("Failed to set gamepad {:?}: {}", axis, e);
+            }
+        }
+    }
+    
     /// Handle gyroscope update
-    fn on_gyro_update(&mut self, side: ControllerSide, x: f32, y: f32, _z: f32) {
+    fn on_gyro_update(&mut self, side: ControllerSide, x: f32, y: f32, z: f32) {
         let profile = match self.current_profile() {
             Some(p) => p,
             None => return,
@@ -418,6 +1471,16 @@ where
         };
         
         if !gyro_mouse_active {
+            self.release_gyro_stick(side);
+            return;
+        }
+        
+        let paused = match side {
+            ControllerSide::Left => self.gyro_mouse_state.left_paused,
+            ControllerSide::Right => self.gyro_mouse_state.right_paused,
+        };
+        if paused {
+            self.release_gyro_stick(side);
             return;
         }
         
@@ -432,11 +1495,12 @@ where
         }
         
         // Apply sensitivity factor
-        let sensitivity_factor = self.get_sensitivity_factor();
+        let sensitivity_factor = self.get_sensitivity_factor(side);
         
-        // Map gyro to mouse movement, this is button face up behavior
-        let mut dx = y * gyro_settings.sensitivity_x * sensitivity_factor;
-        let mut dy = -x * gyro_settings.sensitivity_y * sensitivity_factor; 
+        // Map gyro to mouse movement for the way the controller is held
+        let (yaw, pitch) = gyro_mouse_axes(gyro_settings.orientation, side, x, y, z);
+        let mut dx = yaw * gyro_settings.sensitivity_x * sensitivity_factor;
+        let mut dy = pitch * gyro_settings.sensitivity_y * sensitivity_factor;
         
         if gyro_settings.invert_x {
             dx = -dx;
@@ -445,224 +1509,2217 @@ where
             dy = -dy;
         }
         
+        if gyro_settings.output == "gamepad_rstick" {
+            // Deflection grows linearly with the rate up to full at GYRO_STICK_FULL_SCALE, gamepad
+            // sticks use the mouse's axis directions (negative Y up)
+            let (stick_x, stick_y) = clamp_mouse_delta(dx / GYRO_STICK_FULL_SCALE, dy / GYRO_STICK_FULL_SCALE, 1.0);
+            self.gyro_sticks.insert(side);
+            self.set_gamepad_stick(StickType::Right, stick_x, stick_y);
+            return;
+        }
+        
+        let (dx, dy) = clamp_mouse_delta(dx, dy, self.config.settings.max_mouse_speed);
         let dx_i = dx as i32;
         let dy_i = dy as i32;
+        let warp_at_edge = gyro_settings.warp_at_edge;
+        
+        if gyro_settings.output == "scroll" {
+            // Tilt to scroll: pitch drives the wheel (tilting forward scrolls down)
+            if dy_i != 0 {
+                if let Err(e) = self.scroll(0, -dy_i) {
+                    warn!("Failed to scroll (gyro): {}", e);
+                }
+            }
+            return;
+        }
         
         if dx_i != 0 || dy_i != 0 {
             if let Err(e) = self.mouse.move_relative(dx_i, dy_i) {
                 warn!("Failed to move mouse (gyro): {}", e);
             }
+            self.last_mouse_move = self.clock.now();
+            if warp_at_edge {
+                self.warp_if_near_edge();
+            }
         }
     }
     
-    /// Handle full state update
-    fn on_state_update(&mut self, state: &JoyConState) {
-        // Update held button states
-        self.sync_button_states(state);
+    /// Center the gamepad right stick if `side`'s gyro was deflecting it and no other gyro is
+    fn release_gyro_stick(&mut self, side: ControllerSide) {
+        if self.gyro_sticks.remove(&side) && self.gyro_sticks.is_empty() {
+            self.set_gamepad_stick(StickType::Right, 0.0, 0.0);
+        }
+    }
+    
+    /// Handle a motion gesture, its actions are tapped (pressed then released)
+    fn on_gesture(&mut self, side: ControllerSide, kind: GestureKind) {
+        let Some(actions) = self.current_profile().and_then(|p| p.gestures.get(&kind)).cloned() else {
+            return;
+        };
         
-        // Store previous state
-        self.previous_state = state.clone();
+        info!("👋 {:?} gesture on {:?} controller", kind, side);
+        for action in &actions {
+            self.tap_action(action, side);
+        }
     }
     
-    /// Execute an action (press or release), for keyhold, this will call held_state methods
-    fn execute_action(&mut self, action: &Action, pressed: bool, _side: ControllerSide) {
-        match action {
-            Action::None { .. } => {
-                // Explicitly do nothing
-            }
-
-            // Key hold actions, call held_state methods
-            Action::KeyHold { key } => {
-                // Skip if key is None or empty string
-                let Some(key_name) = key else {
-                    return;
+    /// Handle a trigger value change
+    fn on_trigger(&mut self, side: ControllerSide, value: f32) {
+        let Some(profile) = self.current_profile() else {
+            return;
+        };
+        let mapping = match side {
+            ControllerSide::Left => profile.triggers.left.as_ref(),
+            ControllerSide::Right => profile.triggers.right.as_ref(),
+        };
+        let Some(mapping) = mapping else {
+            return;
+        };
+        
+        match mapping.mode {
+            TriggerMode::Gamepad => {
+                let axis = match side {
+                    ControllerSide::Left => GamepadAxis::LeftTrigger,
+                    ControllerSide::Right => GamepadAxis::RightTrigger,
                 };
-                
-                // Also skip if key is an empty string
-                if key_name.is_empty() {
-                    return;
-                }
-                
-                // Check if this is a multi-key combo (e.g., "shift+w")
-                let keys: Vec<&str> = key_name.split('+').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
-                if pressed {
-                    for k in &keys { self.held_state.press_key(k, KeySource::Button, &self.keyboard); }
-                } else {
-                    for k in keys.iter().rev() { self.held_state.release_key(k, KeySource::Button, &self.keyboard); }
-                }
-            }
-            
-            Action::MouseMove { dx, dy } => {
-                if pressed {
-                    if let Err(e) = self.mouse.move_relative(*dx, *dy) {
-                        warn!("Failed to move mouse: {}", e);
+                if let Some(gamepad) = &self.gamepad {
+                    if let Err(e) = gamepad.set_axis(axis, value) {
+                        warn!("Failed to set gamepad {:?}: {}", axis, e);
                     }
                 }
             }
             
-            Action::MouseClick { button } => {
-                let btn = match button {
-                    crate::mapping::config::MouseButton::Left => MouseButton::Left,
-                    crate::mapping::config::MouseButton::Right => MouseButton::Right,
-                    crate::mapping::config::MouseButton::Middle => MouseButton::Middle,
-                };
-                
+            TriggerMode::Threshold => {
+                let pressed = value >= mapping.threshold;
+                if pressed == self.triggers_pressed.contains(&side) {
+                    return;
+                }
+                let actions = mapping.actions.clone();
                 if pressed {
-                    if let Err(e) = self.mouse.button_down(btn) {
-                        warn!("Failed to press mouse button: {}", e);
-                    }
+                    self.triggers_pressed.insert(side);
                 } else {
-                    if let Err(e) = self.mouse.button_up(btn) {
-                        warn!("Failed to release mouse button: {}", e);
-                    }
+                    self.triggers_pressed.remove(&side);
+                }
+                for action in &actions {
+                    self.execute_action(action, pressed, side);
                 }
             }
-            
+        }
+    }
+    
+    /// Handle optical mouse sensor update
+    fn on_mouse_sensor(&mut self, side: ControllerSide, x: i16, y: i16) {
+        // The sensor reports a wrapping cumulative position, convert it to a per-packet delta
+        let Some((last_x, last_y)) = self.mouse_sensor_last.insert(side, (x, y)) else {
+            return;
+        };
+        
+        let profile = match self.current_profile() {
+            Some(p) => p,
+            None => return,
+        };
+        
+        let sensor = match side {
+            ControllerSide::Left => &profile.mouse_sensor.left,
+            ControllerSide::Right => &profile.mouse_sensor.right,
+        };
+        
+        if !sensor.enabled {
+            return;
+        }
+        
+        let mut dx = x.wrapping_sub(last_x) as i32;
+        let mut dy = y.wrapping_sub(last_y) as i32;
+        
+        // Drop extreme jumps (sensor re-acquiring the surface)
+        let max_jump = sensor.max_jump as i32;
+        if dx.abs() > max_jump || dy.abs() > max_jump {
+            trace!("Mouse sensor {:?} jump dropped: ({}, {})", side, dx, dy);
+            return;
+        }
+        
+        let deadzone = sensor.deadzone as i32;
+        if dx.abs() <= deadzone { dx = 0; }
+        if dy.abs() <= deadzone { dy = 0; }
+        
+        if sensor.swap_axes {
+            std::mem::swap(&mut dx, &mut dy);
+        }
+        if sensor.invert_x {
+            dx = -dx;
+        }
+        if sensor.invert_y {
+            dy = -dy;
+        }
+        
+        let scale = sensor.sensitivity * self.get_sensitivity_factor(side);
+        let dx_i = (dx as f32 * scale).round() as i32;
+        let dy_i = (dy as f32 * scale).round() as i32;
+        
+        if dx_i != 0 || dy_i != 0 {
+            if let Err(e) = self.mouse.move_relative(dx_i, dy_i) {
+                warn!("Failed to move mouse (sensor): {}", e);
+            }
+            self.last_mouse_move = self.clock.now();
+        }
+    }
+    
+    /// Handle full state update
+    fn on_state_update(&mut self, state: &JoyConState) {
+        // Update held button states
+        self.sync_button_states(state);
+        
+        // Store previous state
+        self.previous_state = state.clone();
+    }
+    
+    /// Execute an action (press or release), for keyhold, this will call held_state methods
+    fn execute_action(&mut self, action: &Action, pressed: bool, side: ControllerSide) {
+        match action {
+            Action::None { .. } => {
+                // Explicitly do nothing
+            }
+
+            // Key hold actions, call held_state methods
+            Action::KeyHold { key } => {
+                // Skip if key is None or empty string
+                let Some(key_name) = key else {
+                    return;
+                };
+                
+                // Also skip if key is an empty string
+                if key_name.is_empty() {
+                    return;
+                }
+                
+                // Check if this is a multi-key combo (e.g., "shift+w")
+                let keys: Vec<&str> = key_name.split('+').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+                let delay = Duration::from_millis(self.config.settings.combo_key_delay_ms);
+                if pressed && !delay.is_zero() {
+                    // First key now, the rest are spaced out by the scheduler
+                    let now = self.clock.now();
+                    for (i, k) in keys.iter().enumerate() {
+                        if i == 0 {
+                            self.held_state.press_key(k, KeySource::Button, &self.keyboard);
+                        } else {
+                            self.schedule_key(now + delay * i as u32, k, false, Some(key_name));
+                        }
+                    }
+                } else if pressed {
+                    for k in &keys { self.held_state.press_key(k, KeySource::Button, &self.keyboard); }
+                } else {
+                    for k in keys.iter().rev() {
+                        // A key still waiting in the scheduler was never pressed, just cancel it
+                        let own = |s: &ScheduledKey| s.binding.as_deref() == Some(key_name.as_str()) && s.key == *k;
+                        if let Some(pos) = self.scheduled_keys.iter().position(own) {
+                            self.scheduled_keys.remove(pos);
+                            continue;
+                        }
+                        self.held_state.release_key(k, KeySource::Button, &self.keyboard);
+                    }
+                }
+            }
+            
+            // Held buttons are moved every update; elsewhere (gestures, hooks) it moves once
+            Action::MouseMove { dx, dy } | Action::MouseMoveHold { dx, dy } => {
+                if pressed {
+                    if let Err(e) = self.mouse.move_relative(*dx, *dy) {
+                        warn!("Failed to move mouse: {}", e);
+                    }
+                    self.last_mouse_move = self.clock.now();
+                }
+            }
+            
+            Action::MouseClick { button } => {
+                let btn = to_backend_button(*button);
+                
+                if pressed {
+                    self.press_mouse_click(btn);
+                } else {
+                    self.release_mouse_click(btn);
+                }
+            }
+            
+            Action::MouseToggle { button } => {
+                if pressed {
+                    let btn = to_backend_button(*button);
+                    
+                    if self.mouse_held_state.toggle(btn, &self.mouse) {
+                        info!("🖱️ Mouse {:?} toggled ON", btn);
+                    } else {
+                        info!("🖱️ Mouse {:?} toggled OFF", btn);
+                    }
+                }
+            }
+            
+            Action::KeyToggle { key } => {
+                if pressed {
+                    if self.held_state.toggle(key, &self.keyboard) {
+                        info!("⌨️ Key '{}' toggled ON", key);
+                    } else {
+                        info!("⌨️ Key '{}' toggled OFF", key);
+                    }
+                }
+            }
+            
+            Action::TurboToggle { key, rate_hz } => {
+                if pressed {
+                    if self.turbo_toggles.remove(key).is_some() {
+                        info!("🔁 Turbo '{}' OFF", key);
+                    } else {
+                        info!("🔁 Turbo '{}' ON ({} Hz)", key, rate_hz);
+                        self.turbo_toggles.insert(key.clone(), TurboToggle {
+                            interval: Duration::from_secs_f64(1.0 / *rate_hz as f64),
+                            next: self.clock.now(),
+                        });
+                    }
+                }
+            }
+            
+            Action::TypeText { text } => {
+                if pressed {
+                    if let Err(e) = self.keyboard.type_text(text) {
+                        warn!("Failed to type {:?}: {}", text, e);
+                    }
+                }
+            }
+            
             Action::CycleProfiles => {
                 if pressed {
                     self.cycle_profiles();
+                    self.feedback_rumble(side);
+                }
+            }
+            
+            Action::ToggleProfiles { a, b } => {
+                if pressed {
+                    self.toggle_profiles(a, b);
+                    self.feedback_rumble(side);
                 }
             }
             
             Action::CycleSensitivity => {
                 if pressed {
                     self.cycle_sensitivity();
+                    self.feedback_rumble(side);
+                }
+            }
+            
+            Action::CycleSensitivityLeft => {
+                if pressed {
+                    self.cycle_side_sensitivity(ControllerSide::Left);
+                    self.feedback_rumble(side);
+                }
+            }
+            
+            Action::CycleSensitivityRight => {
+                if pressed {
+                    self.cycle_side_sensitivity(ControllerSide::Right);
+                    self.feedback_rumble(side);
                 }
             }
             
             Action::ToggleGyroMouseL => {
                 if pressed {
                     self.toggle_gyro_mouse(ControllerSide::Left);
+                    self.feedback_rumble(ControllerSide::Left);
                 }
             }
             
             Action::ToggleGyroMouseR => {
                 if pressed {
                     self.toggle_gyro_mouse(ControllerSide::Right);
+                    self.feedback_rumble(ControllerSide::Right);
+                }
+            }
+            
+            Action::SensitivityClutch { factor } => {
+                if pressed {
+                    self.active_clutches.push(*factor);
+                    debug!("Sensitivity clutch engaged: x{:.2}", factor);
+                } else if let Some(pos) = self.active_clutches.iter().position(|f| f == factor) {
+                    self.active_clutches.remove(pos);
+                    debug!("Sensitivity clutch released: x{:.2}", factor);
+                }
+            }
+            
+            Action::ReleaseAll => {
+                if pressed {
+                    self.release_all();
+                }
+            }
+            
+            Action::TogglePause => {
+                if pressed {
+                    self.toggle_pause(side);
+                }
+            }
+            
+            Action::KeepAwake => {
+                if pressed {
+                    self.keep_awake = !self.keep_awake;
+                    self.last_mouse_move = self.clock.now();
+                    info!("Keep-awake {}", if self.keep_awake { "ON" } else { "OFF" });
+                    self.feedback_rumble(side);
+                }
+            }
+            
+            Action::WarpCenter => {
+                if pressed {
+                    self.warp_to_center();
+                }
+            }
+            
+            Action::Rumble { pattern } => {
+                if pressed && self.config.settings.vibration_enabled {
+                    self.send_command(ManagerCommand::RumblePattern { side, pattern: *pattern });
+                }
+            }
+            
+            Action::GyroPause { side: target } => {
+                let target = target.unwrap_or(side);
+                match target {
+                    ControllerSide::Left => self.gyro_mouse_state.left_paused = pressed,
+                    ControllerSide::Right => self.gyro_mouse_state.right_paused = pressed,
                 }
+                if pressed {
+                    self.release_gyro_stick(target);
+                }
+                debug!("Gyro mouse {:?} {}", target, if pressed { "paused" } else { "resumed" });
+            }
+            
+            Action::RunCommand { command, args } => {
+                if pressed {
+                    self.run_command(command, args);
+                }
+            }
+            
+            Action::HoldLayer { .. } | Action::TapHold { .. } => {
+                // Needs the activating button, handled in on_button_pressed/on_button_released
             }
         }
     }
     
-    /// Cycle to the next profile
-    fn cycle_profiles(&mut self) {
-        if self.config.profiles.is_empty() {
-            return;
+    /// Move the cursor to the center of the primary monitor
+    fn warp_to_center(&mut self) {
+        if let Err(e) = self.mouse.warp_to_center() {
+            warn!("Failed to warp mouse to center: {}", e);
         }
+        self.last_mouse_move = self.clock.now();
+    }
+    
+    /// Recenter the cursor once it gets close to a screen edge (auto-warp)
+    fn warp_if_near_edge(&mut self) {
+        if self.mouse.cursor_near_edge(WARP_EDGE_MARGIN) {
+            debug!("Cursor near screen edge, warping to center");
+            self.warp_to_center();
+        }
+    }
+    
+    /// Overlay another profile's buttons while `button` is held
+    fn activate_layer(&mut self, button: ButtonType, profile: &str) {
+        let Some(profile_index) = self.config.profiles.iter().position(|p| p.name == profile) else {
+            warn!("Layer profile '{}' not found", profile);
+            return;
+        };
         
-        let old_index = self.current_profile_index;
-        let old_name = self.config.profiles[old_index].name.clone();
-        
-        // Cycle to next profile
-        self.current_profile_index = (self.current_profile_index + 1) % self.config.profiles.len();
-        
-        let new_name = self.config.profiles[self.current_profile_index].name.clone();
-        
-        info!("🔄 Cycled profile: '{}' -> '{}'", old_name, new_name);
-        
-        // Release all held keys when switching profiles
+        // Keys held under the old mapping would otherwise get stuck
         self.release_all_held_keys();
+        self.held_state.buttons.insert(button);
+        self.active_layer = Some(ActiveLayer { button, profile_index });
+        info!("⇧ Layer '{}' active while {:?} is held", profile, button);
     }
     
-    /// Cycle through sensitivity factors
-    fn cycle_sensitivity(&mut self) {
-        if self.config.settings.sensitivity_factor.is_empty() {
+    /// Return to the current profile's buttons
+    fn deactivate_layer(&mut self) {
+        if let Some(layer) = self.active_layer.take() {
+            self.release_all_held_keys();
+            info!("⇧ Layer '{}' released", self.config.profiles[layer.profile_index].name);
+        }
+    }
+    
+    /// Spawn an external command without waiting for it to finish
+    /// 
+    /// A detached thread waits for the child, so it is reaped instead of left as a
+    /// zombie, and logs a failed exit.
+    fn run_command(&self, command: &str, args: &[String]) {
+        if !self.config.settings.allow_commands {
+            warn!("Ignoring runcommand '{}': set allow_commands = true in [settings] to enable", command);
             return;
         }
         
-        let old_index = self.current_sensitivity_index;
-        self.current_sensitivity_index = 
-            (self.current_sensitivity_index + 1) % self.config.settings.sensitivity_factor.len();
+        let mut child = match std::process::Command::new(command).args(args).spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                warn!("Failed to start '{}': {}", command, e);
+                return;
+            }
+        };
+        info!("▶️ Started '{}' {:?} (pid {})", command, args, child.id());
         
-        let old_factor = self.config.settings.sensitivity_factor[old_index];
-        let new_factor = self.config.settings.sensitivity_factor[self.current_sensitivity_index];
+        let command = command.to_string();
+        std::thread::spawn(move || match child.wait() {
+            Ok(status) if !status.success() => warn!("'{}' (pid {}) exited with {}", command, child.id(), status),
+            Ok(_) => debug!("'{}' (pid {}) finished", command, child.id()),
+            Err(e) => warn!("Failed to wait for '{}' (pid {}): {}", command, child.id(), e),
+        });
+    }
+    
+    /// Panic button: release every held key/mouse button and disable gyro mouse on both sides
+    fn release_all(&mut self) {
+        self.release_all_held_keys();
+        self.gyro_mouse_state.left_enabled = false;
+        self.gyro_mouse_state.right_enabled = false;
         
-        info!("🎯 Sensitivity: {:.1}x -> {:.1}x", old_factor, new_factor);
+        info!("🛑 Released all held inputs and disabled gyro mouse");
     }
     
-    /// Toggle gyro mouse for a controller side
-    fn toggle_gyro_mouse(&mut self, side: ControllerSide) {
-        let enabled = match side {
-            ControllerSide::Left => {
-                self.gyro_mouse_state.left_enabled = !self.gyro_mouse_state.left_enabled;
-                self.gyro_mouse_state.left_enabled
-            }
-            ControllerSide::Right => {
-                self.gyro_mouse_state.right_enabled = !self.gyro_mouse_state.right_enabled;
-                self.gyro_mouse_state.right_enabled
+    /// Scroll the wheel, applying the `invert_scroll` settings
+    fn scroll(&self, dx: i32, dy: i32) -> Result<(), crate::backend::BackendError> {
+        let settings = &self.config.settings;
+        let dx = if settings.invert_scroll_x { -dx } else { dx };
+        let dy = if settings.invert_scroll { -dy } else { dy };
+        self.mouse.scroll(dx, dy)
+    }
+    
+    /// Pause or resume input injection from outside a mapping (no-op if already in that state)
+    /// 
+    /// Only the controller can end the `confirm_to_activate` wait, this does nothing until then.
+    /// The feedback rumble goes to every connected controller.
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.confirm_wait.is_none() && self.is_paused() != paused {
+            self.flip_pause();
+            for side in [ControllerSide::Left, ControllerSide::Right] {
+                if self.connected_sides.contains(&side) {
+                    self.feedback_rumble(side);
+                }
             }
+        }
+    }
+    
+    /// Whether input is held back until the `confirm_to_activate` buttons are pressed
+    pub fn is_awaiting_confirm(&self) -> bool {
+        self.confirm_wait.is_some()
+    }
+    
+    /// Track the confirm buttons while waiting for `confirm_to_activate`, everything else is ignored
+    fn on_confirm_event(&mut self, event: &JoyConEvent) {
+        let Some(wait) = self.confirm_wait.as_mut() else {
+            return;
         };
+        let confirm = &self.config.settings.confirm_to_activate;
         
-        info!("🎮 Gyro mouse {:?}: {}", side, if enabled { "ENABLED" } else { "DISABLED" });
+        match event {
+            JoyConEvent::ButtonPressed { button, .. } if confirm.contains(button) => {
+                wait.held.insert(*button);
+            }
+            JoyConEvent::ButtonReleased { button, .. } => {
+                wait.held.remove(button);
+            }
+            JoyConEvent::Connected { side } => {
+                // Flash the new controller right away
+                wait.next_flash = self.clock.now();
+                debug!("Controller {:?} connected", side);
+            }
+            _ => {}
+        }
+        
+        if wait.held.len() < confirm.len() {
+            return;
+        }
+        
+        // The confirm buttons' releases arrive once active, they were never pressed there so they do nothing
+        self.confirm_wait = None;
+        info!("🔓 Confirmed, sending real input");
+        for side in [ControllerSide::Left, ControllerSide::Right] {
+            self.restore_player_leds(side);
+        }
+        self.toggle_pause(ControllerSide::Right);
     }
     
-    /// Handle directional keys for stick movement
-    fn handle_directional_keys(
-        &mut self,
-        x: f32,
-        y: f32,
-        directions: &crate::mapping::config::DirectionalKeys,
-    ) {
-        // Determine which keys should be pressed based on stick position
-        let threshold = 0.5;
-        
-        // Note: Y-axis is inverted on controllers - negative Y is UP, positive Y is DOWN
-        let should_press_up = y < -threshold;
-        let should_press_down = y > threshold;
-        let should_press_left = x < -threshold;
-        let should_press_right = x > threshold;
+    /// Flip the player LEDs of both controllers while waiting for `confirm_to_activate`
+    fn flash_confirm_leds(&mut self) {
+        let now = self.clock.now();
+        let Some(wait) = self.confirm_wait.as_mut() else {
+            return;
+        };
+        if now < wait.next_flash {
+            return;
+        }
         
-        // Press/release keys accordingly
-        self.set_stick_key_state(&directions.up, should_press_up);
-        self.set_stick_key_state(&directions.down, should_press_down);
-        self.set_stick_key_state(&directions.left, should_press_left);
-        self.set_stick_key_state(&directions.right, should_press_right);
+        wait.lit = !wait.lit;
+        wait.next_flash = now + CONFIRM_FLASH_INTERVAL;
+        let leds = if wait.lit { 0b1111 } else { 0b0000 };
+        self.send_command(ManagerCommand::SetPlayerLed { side: ControllerSide::Left, leds });
+        self.send_command(ManagerCommand::SetPlayerLed { side: ControllerSide::Right, leds });
     }
     
-    /// Set key state for stick source (press or release). Ensures we don't release a key still held by a button.
-    fn set_stick_key_state(&mut self, key: &str, pressed: bool) {
-        if key.is_empty() { return; }
-        let keys: Vec<&str> = key.split('+').map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
-        if pressed {
-            for k in &keys { self.held_state.press_key(k, KeySource::Stick, &self.keyboard); }
+    /// Put a controller's player LEDs back after flashing (the profile pattern, or the first LED)
+    fn restore_player_leds(&self, side: ControllerSide) {
+        let leds = if self.config.settings.profile_led_indicator {
+            profile_led_pattern(self.current_profile_index)
+        } else {
+            0b0001
+        };
+        self.send_command(ManagerCommand::SetPlayerLed { side, leds });
+    }
+    
+    /// Pause or resume input injection
+    fn toggle_pause(&mut self, side: ControllerSide) {
+        self.flip_pause();
+        self.feedback_rumble(side);
+    }
+    
+    /// Pause or resume input injection, without feedback
+    fn flip_pause(&mut self) {
+        self.enabled = !self.enabled;
+        
+        if self.enabled {
+            info!("▶️ Input resumed");
         } else {
-            for k in keys.iter().rev() { self.held_state.release_key(k, KeySource::Stick, &self.keyboard); }
+            // Nothing may stay pressed or keep moving while the controller is set down
+            self.release_all_held_keys();
+            self.left_stick = Stick::default();
+            self.right_stick = Stick::default();
+            self.mouse_sensor_last.clear();
+            info!("⏸️ Input paused");
         }
     }
     
-    /// Release all directional keys for a stick
-    fn release_directional_keys(&mut self, stick: StickType) {
-        let profile = match self.current_profile() {
-            Some(p) => p,
-            None => return,
+    /// Switch to the profile with the given name
+    /// 
+    /// Returns false if no profile has that name.
+    pub fn set_profile_by_name(&mut self, name: &str) -> bool {
+        let Some(index) = self.config.profiles.iter().position(|p| p.name == name) else {
+            warn!("Profile '{}' not found", name);
+            return false;
         };
         
-        let mapping = match stick {
-            StickType::Left => profile.sticks.left.as_ref(),
-            StickType::Right => profile.sticks.right.as_ref(),
-        };
+        if index != self.current_profile_index {
+            let old_name = self.config.profiles[self.current_profile_index].name.clone();
+            info!("🔄 Switched profile: '{}' -> '{}'", old_name, name);
+            self.switch_profile(index);
+        }
         
-        if let Some(mapping) = mapping {
-            if let Some(directions) = &mapping.directions {
-                let keys = vec![
-                    directions.up.clone(),
-                    directions.down.clone(),
-                    directions.left.clone(),
-                    directions.right.clone(),
-                ];
-                for key in keys {
-                    self.set_stick_key_state(&key, false);
-                }
-            }
+        true
+    }
+    
+    /// Make `index` the active profile, running the old profile's on_exit and the new one's on_enter
+    fn switch_profile(&mut self, index: usize) {
+        let on_exit = self.current_profile().map(|p| p.on_exit.clone()).unwrap_or_default();
+        for action in &on_exit {
+            self.tap_action(action, ControllerSide::Right);
         }
+        // The switch releases everything below, so on_exit combos can't wait for their delay
+        self.flush_tapped_keys();
+        
+        // Release all held keys when switching profiles (including keys held by on_enter)
+        self.release_all_held_keys();
+        
+        self.current_profile_index = index;
+        self.on_profile_switched();
     }
     
-    /// Sync button states with current Joy-Con state
-    fn sync_button_states(&mut self, _buttons: &JoyConState) {
+    /// Reset per-profile state after the active profile changed
+    fn on_profile_switched(&mut self) {
+        if !self.config.settings.persist_gyro_across_profiles {
+            self.apply_profile_gyro_defaults();
+        }
+        self.apply_profile_sensitivity_default();
+        
+        // on_enter actions stay "pressed" until the profile is left
+        let on_enter = self.current_profile().map(|p| p.on_enter.clone()).unwrap_or_default();
+        for action in &on_enter {
+            self.execute_action(action, true, ControllerSide::Right);
+        }
+        
+        self.show_profile_led(ControllerSide::Left);
+        self.show_profile_led(ControllerSide::Right);
+    }
+    
+    /// Show the current profile on a controller's player LEDs (if profile_led_indicator is enabled)
+    fn show_profile_led(&self, side: ControllerSide) {
+        if self.config.settings.profile_led_indicator {
+            let leds = profile_led_pattern(self.current_profile_index);
+            self.send_command(ManagerCommand::SetPlayerLed { side, leds });
+        }
+    }
+    
+    /// Set the gyro mouse state to the current profile's defaults
+    fn apply_profile_gyro_defaults(&mut self) {
+        let Some(profile) = self.current_profile() else {
+            return;
+        };
+        
+        let left = profile.gyro.left.default_enabled;
+        let right = profile.gyro.right.default_enabled;
+        
+        if left != self.gyro_mouse_state.left_enabled || right != self.gyro_mouse_state.right_enabled {
+            info!("🎮 Gyro mouse reset to profile defaults (L: {}, R: {})", left, right);
+        }
+        
+        self.gyro_mouse_state.left_enabled = left;
+        self.gyro_mouse_state.right_enabled = right;
+    }
+    
+    /// Select the current profile's default sensitivity level, if it has one
+    fn apply_profile_sensitivity_default(&mut self) {
+        let Some(index) = self.current_profile().and_then(|p| p.default_sensitivity_index) else {
+            return;
+        };
+        
+        if index >= self.config.settings.sensitivity_factor.len() {
+            warn!("default_sensitivity_index {} is out of range, keeping the current sensitivity", index);
+            return;
+        }
+        
+        if index != self.current_sensitivity_index || !self.side_sensitivity_index.is_empty() {
+            info!("🎯 Sensitivity reset to profile default: {:.1}x", self.config.settings.sensitivity_factor[index]);
+        }
+        self.current_sensitivity_index = index;
+        self.side_sensitivity_index.clear();
+    }
+    
+    /// Cycle to the next profile
+    fn cycle_profiles(&mut self) {
+        if self.config.profiles.is_empty() {
+            return;
+        }
+        
+        let old_index = self.current_profile_index;
+        let old_name = self.config.profiles[old_index].name.clone();
+        
+        // Cycle to next profile
+        let new_index = (self.current_profile_index + 1) % self.config.profiles.len();
+        
+        let new_name = self.config.profiles[new_index].name.clone();
+        
+        info!("🔄 Cycled profile: '{}' -> '{}'", old_name, new_name);
+        
+        self.switch_profile(new_index);
+    }
+    
+    /// Switch to profile `b` when on `a`, otherwise to `a`
+    fn toggle_profiles(&mut self, a: &str, b: &str) {
+        let on_a = self.current_profile().is_some_and(|p| p.name == a);
+        self.set_profile_by_name(if on_a { b } else { a });
+    }
+    
+    /// Cycle through sensitivity factors
+    /// 
+    /// Both controllers follow the new level, dropping their separately cycled levels.
+    fn cycle_sensitivity(&mut self) {
+        if self.config.settings.sensitivity_factor.is_empty() {
+            return;
+        }
+        
+        let old_index = self.current_sensitivity_index;
+        self.current_sensitivity_index = 
+            (self.current_sensitivity_index + 1) % self.config.settings.sensitivity_factor.len();
+        self.side_sensitivity_index.clear();
+        
+        let old_factor = self.config.settings.sensitivity_factor[old_index];
+        let new_factor = self.config.settings.sensitivity_factor[self.current_sensitivity_index];
+        
+        info!("🎯 Sensitivity: {:.1}x -> {:.1}x", old_factor, new_factor);
+    }
+    
+    /// Cycle the sensitivity factor of one controller (its stick, gyro and mouse sensor)
+    fn cycle_side_sensitivity(&mut self, side: ControllerSide) {
+        if self.config.settings.sensitivity_factor.is_empty() {
+            return;
+        }
+        
+        let old_index = self.sensitivity_index(side);
+        let new_index = (old_index + 1) % self.config.settings.sensitivity_factor.len();
+        self.side_sensitivity_index.insert(side, new_index);
+        
+        let old_factor = self.config.settings.sensitivity_factor[old_index];
+        let new_factor = self.config.settings.sensitivity_factor[new_index];
+        
+        info!("🎯 Sensitivity {:?}: {:.1}x -> {:.1}x", side, old_factor, new_factor);
+    }
+    
+    /// Request a short rumble pulse on a controller (if feedback_rumble and vibration are enabled)
+    fn feedback_rumble(&self, side: ControllerSide) {
+        if !self.config.settings.feedback_rumble || !self.config.settings.vibration_enabled {
+            return;
+        }
+        
+        let command = match self.config.settings.feedback_rumble_pattern {
+            Some(pattern) => ManagerCommand::RumblePattern { side, pattern },
+            None => ManagerCommand::Rumble {
+                side,
+                intensity: self.config.settings.feedback_rumble_intensity,
+                duration_ms: self.config.settings.feedback_rumble_duration_ms,
+            },
+        };
+        self.send_command(command);
+    }
+    
+    /// Publish a derived event, dropping it if nobody keeps up
+    fn publish_event(&self, event: JoyConEvent) {
+        if let Some(sender) = &self.event_sender {
+            if let Err(e) = sender.try_send(event) {
+                trace!("{:?} dropped", e.into_inner());
+            }
+        }
+    }
+    
+    /// Send a command to the controllers, dropping it if the channel is full
+    fn send_command(&self, command: ManagerCommand) {
+        if let Some(sender) = &self.command_sender {
+            if sender.try_send(command).is_err() {
+                debug!("{:?} dropped", command);
+            }
+        }
+    }
+    
+    /// Toggle gyro mouse for a controller side
+    pub fn toggle_gyro_mouse(&mut self, side: ControllerSide) {
+        let enabled = match side {
+            ControllerSide::Left => {
+                self.gyro_mouse_state.left_enabled = !self.gyro_mouse_state.left_enabled;
+                self.gyro_mouse_state.left_enabled
+            }
+            ControllerSide::Right => {
+                self.gyro_mouse_state.right_enabled = !self.gyro_mouse_state.right_enabled;
+                self.gyro_mouse_state.right_enabled
+            }
+        };
+        
+        if !enabled {
+            self.release_gyro_stick(side);
+        }
+        
+        info!("🎮 Gyro mouse {:?}: {}", side, if enabled { "ENABLED" } else { "DISABLED" });
+    }
+    
+    /// Handle directional bindings for stick movement
+    /// 
+    /// Keys of `keyhold` actions are held while the stick is in a direction (shared keys stay
+    /// held across direction changes), other actions run pressed on entering a direction and
+    /// released on leaving it.
+    fn handle_directional_keys(
+        &mut self,
+        stick: StickType,
+        x: f32,
+        y: f32,
+        directions: &DirectionalKeys,
+    ) {
+        let active = active_direction_names(x, y, directions);
+        let all = directions.all_directions();
+        let previous = self.active_directions.insert(stick, active.clone()).unwrap_or_default();
+        let side = stick_side(stick);
+        
+        // Leave inactive directions first, keeping keys shared with an active direction held
+        let keep: HashSet<&str> = all.iter()
+            .filter(|(name, _)| active.contains(name))
+            .flat_map(|(_, actions)| direction_keys(actions))
+            .collect();
+        for (name, actions) in &all {
+            if active.contains(name) { continue; }
+            for k in direction_keys(actions).filter(|k| !keep.contains(k)) {
+                self.held_state.release_key(k, KeySource::Stick, &self.keyboard);
+            }
+            if previous.contains(name) {
+                for action in actions.iter().filter(|a| !matches!(a, Action::KeyHold { .. })) {
+                    self.execute_action(action, false, side);
+                }
+            }
+        }
+        
+        for (name, actions) in &all {
+            if !active.contains(name) { continue; }
+            for k in direction_keys(actions) {
+                self.held_state.press_key(k, KeySource::Stick, &self.keyboard);
+            }
+            if !previous.contains(name) {
+                for action in actions.iter().filter(|a| !matches!(a, Action::KeyHold { .. })) {
+                    self.execute_action(action, true, side);
+                }
+            }
+        }
+    }
+    
+    /// Tap a pulse-mode stick's directions once per push, then every `repeat` interval
+    /// after its delay while it stays pushed
+    /// 
+    /// A push only counts once the stick reaches a direction, until then it stays armed.
+    fn handle_pulse(
+        &mut self,
+        stick: StickType,
+        x: f32,
+        y: f32,
+        directions: &DirectionalKeys,
+        repeat: Option<(Duration, Duration)>,
+    ) {
+        let now = self.clock.now();
+        let next = match self.stick_pulses.get(&stick) {
+            None => repeat.map(|(delay, _)| now + delay),
+            Some(Some(due)) if *due <= now => repeat.map(|(_, interval)| *due + interval),
+            Some(_) => return,
+        };
+        
+        let active = active_direction_names(x, y, directions);
+        if active.is_empty() {
+            return;
+        }
+        self.stick_pulses.insert(stick, next);
+        
+        let side = stick_side(stick);
+        let all = directions.all_directions();
+        for (name, actions) in &all {
+            if !active.contains(name) { continue; }
+            for action in actions.iter() {
+                self.tap_action(action, side);
+            }
+        }
+    }
+    
+    /// Release all directional bindings for a stick
+    fn release_directional_keys(&mut self, stick: StickType) {
+        let profile = match self.current_profile() {
+            Some(p) => p,
+            None => return,
+        };
+        
+        let mapping = match stick {
+            StickType::Left => profile.sticks.left.as_ref(),
+            StickType::Right => profile.sticks.right.as_ref(),
+        };
+        
+        let Some(directions) = mapping.and_then(|m| m.directions.clone()) else {
+            return;
+        };
+        let previous = self.active_directions.remove(&stick).unwrap_or_default();
+        let side = stick_side(stick);
+        for (name, actions) in directions.all_directions() {
+            let keys: Vec<&str> = direction_keys(actions).collect();
+            for k in keys.iter().rev() {
+                self.held_state.release_key(k, KeySource::Stick, &self.keyboard);
+            }
+            if previous.contains(&name) {
+                for action in actions.iter().filter(|a| !matches!(a, Action::KeyHold { .. })) {
+                    self.execute_action(action, false, side);
+                }
+            }
+        }
+    }
+    
+    /// Sync button states with current Joy-Con state
+    fn sync_button_states(&mut self, _buttons: &JoyConState) {
         // This is called on every state update to ensure consistency
         // (In case we missed a button event)
     }
     
-    /// Release all currently held keys (e.g., on disconnect or profile switch)
-    fn release_all_held_keys(&mut self) { self.held_state.clear_all(&self.keyboard); }
+    /// Release all currently held keys and mouse buttons (e.g., on disconnect or profile switch)
+    fn release_all_held_keys(&mut self) {
+        self.held_state.clear_all(&self.keyboard);
+        self.mouse_held_state.clear_all(&self.mouse);
+        self.active_directions.clear();
+        self.stick_click_actions.clear();
+        self.triggers_pressed.clear();
+        for stick in self.gamepad_sticks.keys().copied().collect::<Vec<_>>() {
+            self.set_gamepad_stick(stick, 0.0, 0.0);
+        }
+        self.gamepad_sticks.clear();
+        self.gyro_sticks.clear();
+        self.scheduled_keys.clear();
+        self.scheduled_mouse_releases.clear();
+        self.debounced_clicks.clear();
+        self.tap_holds.clear();
+        self.mouse_move_holds.clear();
+        self.turbo_toggles.clear();
+        self.key_repeats.clear();
+        // Re-center the stick the D-pad was holding
+        if !self.dpad_stick_buttons.is_empty() {
+            self.dpad_stick_buttons.clear();
+            match self.current_profile().and_then(|p| p.dpad_as_stick) {
+                Some(StickType::Left) => self.left_stick = Stick::default(),
+                Some(StickType::Right) => self.right_stick = Stick::default(),
+                None => {}
+            }
+        }
+        // Held buttons were forgotten, so their clutch, gyro pause and layer releases will never arrive
+        self.active_clutches.clear();
+        self.gyro_mouse_state.left_paused = false;
+        self.gyro_mouse_state.right_paused = false;
+        self.active_layer = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{BackendError, KeyboardEvent, MockKeyboardBackend, MockMouseBackend, MouseEvent};
+    use crate::mapping::builder::{ConfigBuilder, ProfileBuilder};
+    use crate::mapping::clock::MockClock;
+    use crate::mapping::config::{GyroMapping, KeyRepeat, Settings, StickMapping, TriggerMapping};
+    use std::sync::{Arc, Mutex};
+    use crate::joycon2::types::RumblePattern;
+    
+    type TestExecutor = MappingExecutor<MockKeyboardBackend, MockMouseBackend>;
+    
+    fn down(key: &str) -> KeyboardEvent {
+        KeyboardEvent::KeyDown(key.to_string())
+    }
+    
+    fn up(key: &str) -> KeyboardEvent {
+        KeyboardEvent::KeyUp(key.to_string())
+    }
+    
+    fn keyhold(key: &str) -> Action {
+        Action::KeyHold { key: Some(key.to_string()) }
+    }
+    
+    /// Executor on a validated config with mock backends, returns a clone of its keyboard
+    /// 
+    /// The mouse is recorded by `executor.mouse`.
+    fn test_executor(config: ConfigBuilder) -> (TestExecutor, MockKeyboardBackend) {
+        let keyboard = MockKeyboardBackend::new();
+        let executor = MappingExecutor::new(config.build().unwrap(), keyboard.clone(), MockMouseBackend::new());
+        (executor, keyboard)
+    }
+    
+    /// Executor with default settings and just `profile`
+    fn profile_executor(profile: ProfileBuilder) -> (TestExecutor, MockKeyboardBackend) {
+        test_executor(ConfigBuilder::new().profile(profile))
+    }
+    
+    /// Executor whose A button holds "ctrl+shift+w", for the combo key tests
+    fn combo_executor(combo_key_delay_ms: u64) -> (TestExecutor, MockKeyboardBackend) {
+        test_executor(ConfigBuilder::new()
+            .settings(Settings { combo_key_delay_ms, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::A, keyhold("ctrl+shift+w"))))
+    }
+    
+    /// Drive the executor's time from a fresh `MockClock`
+    fn install_mock_clock<K: KeyboardBackend, M: MouseBackend>(executor: &mut MappingExecutor<K, M>) -> MockClock {
+        let clock = MockClock::new();
+        executor.clock = Arc::new(clock.clone());
+        executor.last_mouse_move = clock.now();
+        clock
+    }
+    
+    #[test]
+    fn test_combo_key_order() {
+        let (mut executor, keyboard) = combo_executor(0);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        
+        assert_eq!(
+            keyboard.events(),
+            vec![down("ctrl"), down("shift"), down("w"), up("w"), up("shift"), up("ctrl")]
+        );
+    }
+    
+    #[test]
+    fn test_combo_key_delay_uses_scheduler() {
+        let (mut executor, keyboard) = combo_executor(5);
+        let clock = install_mock_clock(&mut executor);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("ctrl")]);
+        
+        clock.advance(Duration::from_millis(4));
+        executor.update_continuous_movements();
+        assert_eq!(keyboard.events(), vec![down("ctrl")]);
+        
+        clock.advance(Duration::from_millis(1));
+        executor.update_continuous_movements();
+        assert_eq!(keyboard.events(), vec![down("ctrl"), down("shift")]);
+        
+        clock.advance(Duration::from_millis(5));
+        executor.update_continuous_movements();
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        
+        assert_eq!(
+            keyboard.events(),
+            vec![down("ctrl"), down("shift"), down("w"), up("w"), up("shift"), up("ctrl")]
+        );
+    }
+    
+    #[test]
+    fn test_combo_release_cancels_pending_keys() {
+        let (mut executor, keyboard) = combo_executor(1000);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        executor.update_continuous_movements();
+        
+        assert_eq!(keyboard.events(), vec![down("ctrl"), up("ctrl")]);
+    }
+    
+    #[test]
+    fn test_tapped_combo_keeps_key_delay() {
+        let combo = keyhold("ctrl+shift+w");
+        let config = ConfigBuilder::new()
+            .settings(Settings { combo_key_delay_ms: 5, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").on_press(ButtonType::A, combo.clone()).on_exit(combo))
+            .profile(ProfileBuilder::new("menu"))
+            .build()
+            .unwrap();
+        let keyboard = MockKeyboardBackend::new();
+        let mut executor = MappingExecutor::new(config, keyboard.clone(), MockMouseBackend::new());
+        let clock = install_mock_clock(&mut executor);
+        let tap = vec![down("ctrl"), down("shift"), down("w"), up("w"), up("shift"), up("ctrl")];
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("ctrl")]);
+        
+        // One key every 5ms, releases included
+        for step in 2..=6 {
+            clock.advance(Duration::from_millis(5));
+            executor.update_continuous_movements();
+            assert_eq!(keyboard.events(), tap[..step].to_vec());
+        }
+        
+        // on_exit can't wait for the scheduler, the switch sends the whole combo at once
+        keyboard.clear();
+        assert!(executor.set_profile_by_name("menu"));
+        assert_eq!(keyboard.events(), tap);
+    }
+    
+    #[test]
+    fn test_combo_release_keeps_other_bindings_pending_keys() {
+        let config = ConfigBuilder::new()
+            .settings(Settings { combo_key_delay_ms: 5, ..Settings::default() })
+            .profile(ProfileBuilder::new("base")
+                .button(ButtonType::A, keyhold("ctrl+w"))
+                .button(ButtonType::B, keyhold("alt+w")))
+            .build()
+            .unwrap();
+        let keyboard = MockKeyboardBackend::new();
+        let mut executor = MappingExecutor::new(config, keyboard.clone(), MockMouseBackend::new());
+        let clock = install_mock_clock(&mut executor);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        clock.advance(Duration::from_millis(1));
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        // A's release cancels only its own pending "w", not B's
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        clock.advance(Duration::from_millis(5));
+        executor.update_continuous_movements();
+        
+        assert_eq!(keyboard.events(), vec![down("ctrl"), down("alt"), up("ctrl"), down("w")]);
+    }
+    
+    #[test]
+    fn test_profile_switch_resets_gyro_to_profile_default() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .profile(ProfileBuilder::new("base"))
+            .profile(ProfileBuilder::new("menu").gyro(ControllerSide::Right, GyroMapping { default_enabled: true, ..GyroMapping::default() })));
+        
+        executor.toggle_gyro_mouse(ControllerSide::Left);
+        assert!(executor.set_profile_by_name("menu"));
+        assert_eq!(executor.current_profile_name(), "menu");
+        assert_eq!(executor.current_profile_index(), 1);
+        assert!(!executor.gyro_mouse_state.left_enabled);
+        assert!(executor.gyro_mouse_state.right_enabled);
+        
+        // Opting into persistence keeps the current state
+        executor.config.settings.persist_gyro_across_profiles = true;
+        assert!(executor.set_profile_by_name("base"));
+        assert!(executor.gyro_mouse_state.right_enabled);
+        assert!(!executor.set_profile_by_name("missing"));
+    }
+    
+    #[test]
+    fn test_global_profile_switch_button() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { profile_switch_button: Some(ButtonType::A), ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::A, keyhold("w")))
+            .profile(ProfileBuilder::new("second").button(ButtonType::A, keyhold("w"))));
+        
+        // Takes precedence over A's key mapping, in every profile
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(executor.current_profile_name(), "second");
+        
+        executor.last_triggered.clear();
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(executor.current_profile_name(), "base");
+        assert!(keyboard.events().is_empty());
+    }
+    
+    #[test]
+    fn test_toggle_profiles() {
+        let toggle = Action::ToggleProfiles { a: "combat".to_string(), b: "menu".to_string() };
+        let profile = |name| ProfileBuilder::new(name).button(ButtonType::A, keyhold("w")).button(ButtonType::Plus, toggle.clone());
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .profile(profile("base"))
+            .profile(profile("combat"))
+            .profile(profile("menu")));
+        let press = |executor: &mut MappingExecutor<_, _>, button| {
+            executor.last_triggered.clear();
+            executor.process_event(&JoyConEvent::ButtonPressed { button, side: button.side() });
+        };
+        let release = |executor: &mut MappingExecutor<_, _>, button| {
+            executor.process_event(&JoyConEvent::ButtonReleased { button, side: button.side() });
+        };
+        
+        // From an unrelated profile it goes to the first one
+        press(&mut executor, ButtonType::Plus);
+        release(&mut executor, ButtonType::Plus);
+        assert_eq!(executor.current_profile_name(), "combat");
+        
+        // Then flips between the two, releasing held keys on the way
+        press(&mut executor, ButtonType::A);
+        executor.update_continuous_movements();
+        press(&mut executor, ButtonType::Plus);
+        assert_eq!(executor.current_profile_name(), "menu");
+        let events = keyboard.events();
+        assert!(events.contains(&down("w")) && events.contains(&up("w")), "{:?}", events);
+        release(&mut executor, ButtonType::Plus);
+        
+        press(&mut executor, ButtonType::Plus);
+        assert_eq!(executor.current_profile_name(), "combat");
+    }
+    
+    #[test]
+    fn test_profile_switch_applies_default_sensitivity() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .profile(ProfileBuilder::new("base"))
+            .profile(ProfileBuilder::new("precision").default_sensitivity_index(0)));
+        
+        executor.cycle_sensitivity();
+        executor.cycle_sensitivity();
+        assert_eq!(executor.current_sensitivity_index, 2);
+        
+        assert!(executor.set_profile_by_name("precision"));
+        assert_eq!(executor.current_sensitivity_index, 0);
+        
+        // Profiles without a default keep the current level
+        executor.cycle_sensitivity();
+        assert!(executor.set_profile_by_name("base"));
+        assert_eq!(executor.current_sensitivity_index, 1);
+    }
+    
+    #[test]
+    fn test_clamp_mouse_delta() {
+        // Within the cap: unchanged
+        assert_eq!(clamp_mouse_delta(3.0, -4.0, 10.0), (3.0, -4.0));
+        
+        // Spike: scaled down to the cap, direction preserved
+        let (dx, dy) = clamp_mouse_delta(300.0, -400.0, 50.0);
+        assert!((dx - 30.0).abs() < 1e-4);
+        assert!((dy + 40.0).abs() < 1e-4);
+    }
+    
+    #[test]
+    fn test_apply_anti_deadzone() {
+        // Disabled: unchanged
+        assert_eq!(apply_anti_deadzone(0.2, 0.0, 0.15, 0.0), (0.2, 0.0));
+        
+        // Edge of the deadzone starts at the minimum output, full tilt stays full
+        let (x, y) = apply_anti_deadzone(0.0, -0.15, 0.15, 0.3);
+        assert!(x.abs() < 1e-5 && (y + 0.3).abs() < 1e-5);
+        let (x, _) = apply_anti_deadzone(1.0, 0.0, 0.15, 0.3);
+        assert!((x - 1.0).abs() < 1e-5);
+        
+        // Halfway through the live range
+        let (x, _) = apply_anti_deadzone(0.575, 0.0, 0.15, 0.3);
+        assert!((x - 0.65).abs() < 1e-4);
+        
+        // The gamepad output uses the same curve, rescaled even without an anti-deadzone
+        assert_eq!(rescale_live_range(0.575, 0.0, 0.15, 0.3), apply_anti_deadzone(0.575, 0.0, 0.15, 0.3));
+        let (x, _) = rescale_live_range(0.575, 0.0, 0.15, 0.0);
+        assert!((x - 0.5).abs() < 1e-4);
+        
+        // Diagonals past the unit circle are clamped to it
+        let (x, y) = rescale_live_range(1.0, 1.0, 0.15, 0.3);
+        assert!(((x * x + y * y).sqrt() - 1.0).abs() < 1e-5);
+        assert_eq!(rescale_live_range(0.0, 0.0, 0.15, 0.3), (0.0, 0.0));
+    }
+    
+    #[test]
+    fn test_gyro_mouse_axes() {
+        // Face up keeps the original mapping
+        assert_eq!(gyro_mouse_axes(GyroOrientation::FaceUp, ControllerSide::Right, 1.0, 2.0, 3.0), (2.0, -1.0));
+        assert_eq!(gyro_mouse_axes(GyroOrientation::FaceForward, ControllerSide::Right, 1.0, 2.0, 3.0), (-3.0, -1.0));
+        
+        // Sideways pitch follows the long axis, mirrored between the two sides
+        assert_eq!(gyro_mouse_axes(GyroOrientation::Sideways, ControllerSide::Left, 1.0, 2.0, 3.0), (2.0, 3.0));
+        assert_eq!(gyro_mouse_axes(GyroOrientation::Sideways, ControllerSide::Right, 1.0, 2.0, 3.0), (2.0, -3.0));
+    }
+    
+    #[test]
+    fn test_snap_stick_angle() {
+        // 30 degrees snaps to 0 degrees with 4 directions, length preserved
+        let (x, y) = (30f32.to_radians().cos(), 30f32.to_radians().sin());
+        let (sx, sy) = snap_stick_angle(x, y, 4);
+        assert!((sx - 1.0).abs() < 1e-4 && sy.abs() < 1e-4);
+        
+        // ... and to 45 degrees with 8
+        let (sx, sy) = snap_stick_angle(x, y, 8);
+        assert!((sx - sy).abs() < 1e-4 && sx > 0.0);
+    }
+    
+    #[test]
+    fn test_shake_gesture_taps_actions() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base").gesture(GestureKind::Shake, keyhold("r")));
+        
+        executor.process_event(&JoyConEvent::Gesture { side: ControllerSide::Right, kind: GestureKind::Shake });
+        
+        assert_eq!(keyboard.events(), vec![down("r"), up("r")]);
+    }
+    
+    #[test]
+    fn test_hold_layer_overlays_buttons() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .profile(ProfileBuilder::new("base")
+                .button(ButtonType::B, keyhold("a"))
+                .button(ButtonType::X, keyhold("x"))
+                .button(ButtonType::LeftStickClick, Action::HoldLayer { profile: "shift".to_string() }))
+            .profile(ProfileBuilder::new("shift")
+                .button(ButtonType::B, keyhold("b"))
+                .button(ButtonType::LeftStickClick, keyhold("l"))));
+        
+        // Keys held when the layer changes are released
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::LeftStickClick, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::B, side: ControllerSide::Right });
+        
+        // Layer mapping first, unmapped buttons fall through to the profile
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::B, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::X, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::X, side: ControllerSide::Right });
+        
+        // Releasing the activator ends the layer even though the layer maps it
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::LeftStickClick, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        
+        assert_eq!(
+            keyboard.events(),
+            vec![down("a"), up("a"), down("b"), up("b"), down("x"), up("x"), down("a")]
+        );
+    }
+    
+    #[test]
+    fn test_profile_enter_exit_hooks() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .profile(ProfileBuilder::new("base").on_exit(keyhold("f2")))
+            .profile(ProfileBuilder::new("driving").on_enter(Action::ToggleGyroMouseR).on_enter(keyhold("shift"))));
+        
+        assert!(executor.set_profile_by_name("driving"));
+        assert!(executor.gyro_mouse_state.right_enabled);
+        
+        // Leaving releases what on_enter held
+        assert!(executor.set_profile_by_name("base"));
+        assert_eq!(
+            keyboard.events(),
+            vec![down("f2"), up("f2"), down("shift"), up("shift")]
+        );
+    }
+    
+    #[test]
+    fn test_tap_hold_on_capture() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { hold_time_ms: 500, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::Capture, Action::TapHold {
+                tap: Box::new(keyhold("f12")),
+                hold: Box::new(keyhold("f11")),
+            })));
+        let clock = install_mock_clock(&mut executor);
+        
+        // Short press taps on release
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Capture, side: ControllerSide::Left });
+        clock.advance(Duration::from_millis(499));
+        executor.update_continuous_movements();
+        assert!(keyboard.events().is_empty());
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Capture, side: ControllerSide::Left });
+        
+        // Long press holds from the threshold until release
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Capture, side: ControllerSide::Left });
+        clock.advance(Duration::from_millis(500));
+        executor.update_continuous_movements();
+        clock.advance(Duration::from_millis(500));
+        executor.update_continuous_movements();
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Capture, side: ControllerSide::Left });
+        
+        assert_eq!(
+            keyboard.events(),
+            vec![down("f12"), up("f12"), down("f11"), up("f11")]
+        );
+    }
+    
+    #[test]
+    fn test_mouse_move_hold_tracks_held_buttons() {
+        let (mut executor, _) = profile_executor(ProfileBuilder::new("base").button(ButtonType::DpadLeft, Action::MouseMoveHold { dx: -5, dy: 0 }));
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::DpadLeft, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::DpadLeft, side: ControllerSide::Left });
+        assert_eq!(executor.mouse_move_holds[&ButtonType::DpadLeft], vec![(-5, 0)]);
+        executor.update_continuous_movements();
+        
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::DpadLeft, side: ControllerSide::Left });
+        assert!(executor.mouse_move_holds.is_empty());
+    }
+    
+    #[test]
+    fn test_keep_awake_nudges_only_when_idle() {
+        let (mut executor, _) = profile_executor(ProfileBuilder::new("base").button(ButtonType::Home, Action::KeepAwake));
+        let clock = install_mock_clock(&mut executor);
+        let interval = Duration::from_secs(executor.config.settings.keep_awake_interval_secs);
+        
+        // Off: idle time doesn't matter
+        let start = clock.now();
+        clock.advance(interval);
+        executor.update_continuous_movements();
+        assert_eq!(executor.last_mouse_move, start);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Home, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Home, side: ControllerSide::Right });
+        assert!(executor.keep_awake);
+        
+        // Turning it on counts as activity, no nudge before a full idle interval
+        let enabled_at = clock.now();
+        clock.advance(interval - Duration::from_millis(1));
+        executor.update_continuous_movements();
+        assert_eq!(executor.last_mouse_move, enabled_at);
+        
+        // Idle past the interval: nudged
+        clock.advance(Duration::from_millis(1));
+        executor.update_continuous_movements();
+        assert_eq!(executor.last_mouse_move, clock.now());
+    }
+    
+    #[test]
+    fn test_toggle_pause_ignores_input() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base")
+            .button(ButtonType::A, keyhold("ctrl+shift+w"))
+            .button(ButtonType::Minus, Action::TogglePause));
+        
+        // Pausing releases what was held
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Minus, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Minus, side: ControllerSide::Left });
+        assert!(executor.is_paused());
+        
+        // Ignored while paused
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 1.0, y: 0.0 });
+        assert_eq!(executor.left_stick.x, 0.0);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Minus, side: ControllerSide::Left });
+        assert!(!executor.is_paused());
+        
+        // Released in no particular order, nothing pressed again
+        let events = keyboard.events();
+        assert_eq!(events[..3], [down("ctrl"), down("shift"), down("w")]);
+        assert_eq!(events.len(), 6);
+        assert!(["ctrl", "shift", "w"].iter().all(|key| events[3..].contains(&up(key))), "{:?}", events);
+    }
+    
+    #[test]
+    fn test_set_paused_is_idempotent() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base").button(ButtonType::A, keyhold("ctrl+shift+w")));
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        
+        executor.set_paused(true);
+        executor.set_paused(true);
+        assert!(executor.is_paused());
+        assert_eq!(keyboard.events().len(), 6);
+        
+        executor.set_paused(false);
+        assert!(!executor.is_paused());
+    }
+    
+    #[test]
+    fn test_set_paused_rumbles_connected_controllers() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { vibration_enabled: true, feedback_rumble: true, feedback_rumble_pattern: None, ..Settings::default() })
+            .profile(ProfileBuilder::new("base")));
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        executor.set_command_sender(sender);
+        let rumbled = |receiver: &crossbeam_channel::Receiver<ManagerCommand>| -> Vec<ControllerSide> {
+            receiver.try_iter().filter_map(|command| match command {
+                ManagerCommand::Rumble { side, .. } => Some(side),
+                _ => None,
+            }).collect()
+        };
+        
+        // Only the left controller connected: the feedback goes there
+        executor.process_event(&JoyConEvent::Connected { side: ControllerSide::Left });
+        executor.set_paused(true);
+        assert_eq!(rumbled(&receiver), vec![ControllerSide::Left]);
+        
+        // Both, also while paused
+        executor.process_event(&JoyConEvent::Connected { side: ControllerSide::Right });
+        executor.set_paused(false);
+        assert_eq!(rumbled(&receiver), vec![ControllerSide::Left, ControllerSide::Right]);
+        
+        executor.process_event(&JoyConEvent::Disconnected { side: ControllerSide::Left });
+        executor.set_paused(true);
+        assert_eq!(rumbled(&receiver), vec![ControllerSide::Right]);
+    }
+    
+    #[test]
+    fn test_action_cooldown_ignores_bounces() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { sensitivity_factor: vec![1.0, 2.0, 3.0], ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::Plus, Action::CycleSensitivity)));
+        let clock = install_mock_clock(&mut executor);
+        let tap = |executor: &mut TestExecutor| {
+            executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Plus, side: ControllerSide::Right });
+            executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Plus, side: ControllerSide::Right });
+        };
+        
+        tap(&mut executor);
+        assert_eq!(executor.current_sensitivity(), 2.0);
+        
+        // Bounce inside the cooldown is ignored
+        clock.advance(Duration::from_millis(100));
+        tap(&mut executor);
+        assert_eq!(executor.current_sensitivity(), 2.0);
+        
+        // Cooldown counts from the last accepted press
+        clock.advance(Duration::from_millis(150));
+        tap(&mut executor);
+        assert_eq!(executor.current_sensitivity(), 3.0);
+    }
+    
+    #[test]
+    fn test_dpad_as_stick_drives_stick() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base")
+            .button(ButtonType::DpadUp, keyhold("9"))
+            .dpad_as_stick(StickType::Left));
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::DpadUp, side: ControllerSide::Left });
+        assert_eq!((executor.left_stick.x, executor.left_stick.y), (0.0, 1.0));
+        
+        // Diagonals stay on the unit circle
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::DpadRight, side: ControllerSide::Left });
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        assert_eq!((executor.left_stick.x, executor.left_stick.y), (diagonal, diagonal));
+        
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::DpadUp, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::DpadRight, side: ControllerSide::Left });
+        assert_eq!((executor.left_stick.x, executor.left_stick.y), (0.0, 0.0));
+        
+        // The D-pad button actions are replaced
+        assert!(keyboard.events().is_empty());
+    }
+    
+    #[test]
+    fn test_mouse_button_held_by_two_buttons() {
+        let click = Action::MouseClick { button: crate::mapping::config::MouseButton::Left };
+        let (mut executor, _keyboard) = profile_executor(ProfileBuilder::new("base")
+            .button(ButtonType::R, click.clone())
+            .button(ButtonType::ZR, click));
+        let mouse = executor.mouse.clone();
+        let clock = install_mock_clock(&mut executor);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::R, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::R, side: ControllerSide::Right }); // repeated press report
+        clock.advance(Duration::from_millis(100));
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::ZR, side: ControllerSide::Right });
+        clock.advance(Duration::from_millis(100));
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::R, side: ControllerSide::Right });
+        
+        // Still held by ZR
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left)]);
+        
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::ZR, side: ControllerSide::Right });
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left)]);
+    }
+    
+    #[test]
+    fn test_mouse_click_min_duration_and_debounce() {
+        let (mut executor, _keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { click_min_duration_ms: 20, click_debounce_ms: 30, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::A, Action::MouseClick { button: crate::mapping::config::MouseButton::Left })));
+        let mouse = executor.mouse.clone();
+        let clock = install_mock_clock(&mut executor);
+        
+        // A quick tap is stretched to the minimum duration
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        executor.update_continuous_movements();
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left)]);
+        
+        // A bounce inside the debounce window is ignored, press and release
+        clock.advance(Duration::from_millis(10));
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        
+        clock.advance(Duration::from_millis(10));
+        executor.update_continuous_movements();
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left)]);
+        
+        // The next real click goes through
+        clock.advance(Duration::from_millis(100));
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        clock.advance(Duration::from_millis(50));
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left), MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left)]);
+    }
+    
+    #[test]
+    fn test_warp_center_action_and_auto_warp() {
+        let (mut executor, _keyboard) = profile_executor(ProfileBuilder::new("base").button(ButtonType::Home, Action::WarpCenter));
+        let mouse = executor.mouse.clone();
+        mouse.set_near_edge(true);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Home, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Home, side: ControllerSide::Right });
+        assert_eq!(mouse.events(), vec![MouseEvent::WarpToCenter]);
+        
+        // Stick mouse only recenters when auto-warp is on
+        executor.config.profiles[0].sticks.right = Some(StickMapping::mouse(1.0));
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 1.0, y: 0.0 });
+        assert_eq!(mouse.events().iter().filter(|e| **e == MouseEvent::WarpToCenter).count(), 1);
+        
+        executor.config.profiles[0].sticks.right = Some(StickMapping { warp_at_edge: true, ..StickMapping::mouse(1.0) });
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 1.0, y: 0.0 });
+        assert_eq!(mouse.events().iter().filter(|e| **e == MouseEvent::WarpToCenter).count(), 2);
+    }
+    
+    #[test]
+    fn test_direction_runs_action_list_on_enter_and_exit() {
+        let mut directions = DirectionalKeys::new("w", "s", "a", "d");
+        directions.up.push(Action::MouseClick { button: crate::mapping::config::MouseButton::Left });
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { click_min_duration_ms: 0, click_debounce_ms: 0, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").stick(StickType::Right, StickMapping::directional(directions))));
+        let mouse = executor.mouse.clone();
+        
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 0.0, y: -1.0 });
+        assert_eq!(keyboard.events(), vec![down("w")]);
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left)]);
+        
+        // Staying in the up sector doesn't re-run its actions
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 0.9, y: -0.9 });
+        assert_eq!(keyboard.events(), vec![down("w"), down("d")]);
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left)]);
+        
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 1.0, y: 0.0 });
+        assert_eq!(keyboard.events(), vec![down("w"), down("d"), up("w")]);
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left)]);
+        
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 0.0, y: -1.0 });
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x: 0.0, y: 0.0 });
+        assert_eq!(mouse.events(), vec![MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left), MouseEvent::ButtonDown(MouseButton::Left), MouseEvent::ButtonUp(MouseButton::Left)]);
+        assert_eq!(keyboard.events().last(), Some(&up("w")));
+    }
+    
+    /// Gamepad that records axis updates
+    #[derive(Clone, Default)]
+    struct RecordingGamepad {
+        calls: Arc<Mutex<Vec<(GamepadAxis, f32)>>>,
+    }
+    
+    impl GamepadBackend for RecordingGamepad {
+        fn set_axis(&self, axis: GamepadAxis, value: f32) -> Result<(), BackendError> {
+            self.calls.lock().unwrap().push((axis, value));
+            Ok(())
+        }
+    }
+    
+    #[test]
+    fn test_trigger_threshold_and_gamepad() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base")
+            .trigger(ControllerSide::Left, TriggerMapping::threshold(vec![keyhold("space")]))
+            .trigger(ControllerSide::Right, TriggerMapping::gamepad()));
+        let gamepad = RecordingGamepad::default();
+        executor.set_gamepad_backend(Arc::new(gamepad.clone()));
+        
+        executor.process_event(&JoyConEvent::Trigger { side: ControllerSide::Left, value: 1.0 });
+        executor.process_event(&JoyConEvent::Trigger { side: ControllerSide::Left, value: 0.8 });
+        assert_eq!(keyboard.events(), vec![down("space")]);
+        executor.process_event(&JoyConEvent::Trigger { side: ControllerSide::Left, value: 0.0 });
+        assert_eq!(keyboard.events(), vec![down("space"), up("space")]);
+        assert!(gamepad.calls.lock().unwrap().is_empty());
+        
+        executor.process_event(&JoyConEvent::Trigger { side: ControllerSide::Right, value: 1.0 });
+        executor.process_event(&JoyConEvent::Trigger { side: ControllerSide::Right, value: 0.0 });
+        assert_eq!(*gamepad.calls.lock().unwrap(), vec![(GamepadAxis::RightTrigger, 1.0), (GamepadAxis::RightTrigger, 0.0)]);
+    }
+    
+    #[test]
+    fn test_gyro_gamepad_right_stick() {
+        let (mut executor, _keyboard) = profile_executor(ProfileBuilder::new("base").gyro(ControllerSide::Right, GyroMapping {
+            enabled: true,
+            output: "gamepad_rstick".to_string(),
+            ..GyroMapping::default()
+        }));
+        let gamepad = RecordingGamepad::default();
+        executor.set_gamepad_backend(Arc::new(gamepad.clone()));
+        executor.toggle_gyro_mouse(ControllerSide::Right);
+        let gyro = |x, y| JoyConEvent::GyroUpdate { side: ControllerSide::Right, x, y, z: 0.0 };
+        
+        // Half of GYRO_STICK_FULL_SCALE is half deflection, in the direction the cursor would move
+        executor.process_event(&gyro(0.0, 50.0));
+        executor.process_event(&gyro(-50.0, 0.0));
+        // Faster turns are capped at full deflection
+        executor.process_event(&gyro(0.0, 300.0));
+        // Turning the gyro off centers the stick
+        executor.toggle_gyro_mouse(ControllerSide::Right);
+        
+        assert_eq!(*gamepad.calls.lock().unwrap(), vec![
+            (GamepadAxis::RightX, 0.5), (GamepadAxis::RightY, 0.0),
+            (GamepadAxis::RightX, 0.0), (GamepadAxis::RightY, 0.5),
+            (GamepadAxis::RightX, 1.0), (GamepadAxis::RightY, 0.0),
+            (GamepadAxis::RightX, 0.0), (GamepadAxis::RightY, 0.0),
+        ]);
+        assert!(executor.mouse.events().is_empty());
+    }
+    
+    #[test]
+    fn test_gyro_pause_while_held() {
+        let (mut executor, _keyboard) = profile_executor(ProfileBuilder::new("base")
+            .gyro(ControllerSide::Right, GyroMapping { enabled: true, ..GyroMapping::default() })
+            .button(ButtonType::R, Action::GyroPause { side: None })
+            .button(ButtonType::Y, Action::ToggleGyroMouseR));
+        let clock = install_mock_clock(&mut executor);
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Y, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Y, side: ControllerSide::Right });
+        let gyro = JoyConEvent::GyroUpdate { side: ControllerSide::Right, x: 0.0, y: 500.0, z: 0.0 };
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::R, side: ControllerSide::Right });
+        let before = executor.last_mouse_move;
+        clock.advance(Duration::from_millis(10));
+        executor.process_event(&gyro);
+        assert_eq!(executor.last_mouse_move, before);
+        
+        // Toggling while paused keeps the pause, and the toggle state survives it
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Y, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Y, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Y, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Y, side: ControllerSide::Right });
+        executor.process_event(&gyro);
+        assert_eq!(executor.last_mouse_move, before);
+        assert!(executor.gyro_mouse_state.right_enabled);
+        
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::R, side: ControllerSide::Right });
+        executor.process_event(&gyro);
+        assert_eq!(executor.last_mouse_move, clock.now());
+    }
+    
+    #[test]
+    fn test_invert_scroll() {
+        let (mut executor, _keyboard) = profile_executor(ProfileBuilder::new("base").gyro(ControllerSide::Right, GyroMapping {
+            enabled: true,
+            output: "scroll".to_string(),
+            ..GyroMapping::default()
+        }));
+        executor.gyro_mouse_state.right_enabled = true;
+        let tilt = JoyConEvent::GyroUpdate { side: ControllerSide::Right, x: 500.0, y: 0.0, z: 0.0 };
+        
+        executor.process_event(&tilt);
+        executor.config.settings.invert_scroll = true;
+        executor.process_event(&tilt);
+        
+        let scrolls: Vec<i32> = executor.mouse.events().into_iter()
+            .filter_map(|e| match e { MouseEvent::Scroll { dx: 0, dy } => Some(dy), _ => None })
+            .collect();
+        assert_eq!(scrolls.len(), 2);
+        assert_ne!(scrolls[0], 0);
+        assert_eq!(scrolls[1], -scrolls[0]);
+        
+        // Horizontal scrolling is inverted separately
+        executor.config.settings.invert_scroll_x = true;
+        executor.scroll(120, 0).unwrap();
+        assert_eq!(executor.mouse.events().last(), Some(&MouseEvent::Scroll { dx: -120, dy: 0 }));
+    }
+    
+    #[test]
+    fn test_per_side_sensitivity_cycling() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { sensitivity_factor: vec![1.0, 0.5, 2.0], action_cooldown_ms: 0, ..Settings::default() })
+            .profile(ProfileBuilder::new("base")
+                .button(ButtonType::SLL, Action::CycleSensitivityLeft)
+                .button(ButtonType::Plus, Action::CycleSensitivity)));
+        let press = |executor: &mut TestExecutor, button| {
+            executor.process_event(&JoyConEvent::ButtonPressed { button, side: button.side() });
+            executor.process_event(&JoyConEvent::ButtonReleased { button, side: button.side() });
+        };
+        
+        press(&mut executor, ButtonType::SLL);
+        press(&mut executor, ButtonType::SLL);
+        assert_eq!(executor.get_sensitivity_factor(ControllerSide::Left), 2.0);
+        assert_eq!(executor.get_sensitivity_factor(ControllerSide::Right), 1.0);
+        assert_eq!(executor.current_sensitivity(), 1.0);
+        
+        // The global cycle moves both sides to the next global level
+        press(&mut executor, ButtonType::Plus);
+        assert_eq!(executor.get_sensitivity_factor(ControllerSide::Left), 0.5);
+        assert_eq!(executor.get_sensitivity_factor(ControllerSide::Right), 0.5);
+    }
+    
+    #[test]
+    fn test_rumble_action_sends_pattern() {
+        let (mut executor, _) = profile_executor(ProfileBuilder::new("base").button(ButtonType::R, Action::Rumble { pattern: RumblePattern::Double }));
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        executor.set_command_sender(sender);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::R, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::R, side: ControllerSide::Right });
+        
+        let commands: Vec<ManagerCommand> = receiver.try_iter().collect();
+        assert_eq!(commands, vec![ManagerCommand::RumblePattern {
+            side: ControllerSide::Right,
+            pattern: RumblePattern::Double,
+        }]);
+    }
+    
+    #[test]
+    fn test_rumble_skipped_when_vibration_disabled() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { vibration_enabled: false, feedback_rumble: true, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::R, Action::Rumble { pattern: RumblePattern::Short })));
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        executor.set_command_sender(sender);
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::R, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::R, side: ControllerSide::Right });
+        executor.feedback_rumble(ControllerSide::Right);
+        
+        assert!(receiver.try_recv().is_err());
+    }
+    
+    #[test]
+    fn test_stick_click_direction() {
+        let stick = StickMapping { click_directions: Some(DirectionalKeys::new("shift", "", "", "")), ..StickMapping::mouse(1.0) };
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base")
+            .stick(StickType::Left, stick)
+            .button(ButtonType::LeftStickClick, keyhold("c")));
+        let click = |executor: &mut TestExecutor| {
+            executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::LeftStickClick, side: ControllerSide::Left });
+            executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::LeftStickClick, side: ControllerSide::Left });
+        };
+        
+        // Pushed up: the click runs the "up" binding instead of the button mapping
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.0, y: -1.0 });
+        click(&mut executor);
+        assert_eq!(keyboard.events(), vec![down("shift"), up("shift")]);
+        
+        // Centered or in an unbound direction: normal stick-click mapping
+        keyboard.clear();
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.0, y: 0.0 });
+        click(&mut executor);
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.0, y: 1.0 });
+        click(&mut executor);
+        assert_eq!(keyboard.events(), vec![down("c"), up("c"), down("c"), up("c")]);
+        
+        // Moving the stick while clicked still releases what the click started
+        keyboard.clear();
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.0, y: -1.0 });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::LeftStickClick, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.0, y: 0.0 });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::LeftStickClick, side: ControllerSide::Left });
+        assert_eq!(keyboard.events(), vec![down("shift"), up("shift")]);
+    }
+    
+    #[test]
+    fn test_chat_button_key_toggle() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base")
+            .button(ButtonType::Chat, Action::KeyToggle { key: "ctrl+m".to_string() })
+            .button(ButtonType::A, keyhold("ctrl")));
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Chat, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Chat, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("ctrl"), down("m")]);
+        
+        // A key shared with a held button stays down until both let go
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Chat, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Chat, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("ctrl"), down("m"), up("m")]);
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("ctrl"), down("m"), up("m"), up("ctrl")]);
+    }
+    
+    #[test]
+    fn test_profile_led_indicator() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { profile_led_indicator: true, ..Settings::default() })
+            .profile(ProfileBuilder::new("base"))
+            .profile(ProfileBuilder::new("second")));
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        executor.set_command_sender(sender);
+        
+        assert!(executor.set_profile_by_name("second"));
+        let commands: Vec<ManagerCommand> = receiver.try_iter().collect();
+        assert_eq!(commands, vec![
+            ManagerCommand::SetPlayerLed { side: ControllerSide::Left, leds: 0b0010 },
+            ManagerCommand::SetPlayerLed { side: ControllerSide::Right, leds: 0b0010 },
+        ]);
+        
+        // A controller connecting later picks up the current profile
+        executor.process_event(&JoyConEvent::Connected { side: ControllerSide::Left });
+        assert_eq!(receiver.try_recv(), Ok(ManagerCommand::SetPlayerLed { side: ControllerSide::Left, leds: 0b0010 }));
+        
+        // More than four profiles use LED combinations, then wrap
+        assert_eq!(profile_led_pattern(0), 0b0001);
+        assert_eq!(profile_led_pattern(3), 0b1000);
+        assert_eq!(profile_led_pattern(4), 0b0011);
+        assert_eq!(profile_led_pattern(14), 0b1111);
+        assert_eq!(profile_led_pattern(15), 0b0001);
+        let distinct: HashSet<u8> = (0..15).map(profile_led_pattern).collect();
+        assert_eq!(distinct.len(), 15);
+    }
+    
+    #[test]
+    fn test_stick_gamepad_mode() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { left_stick_deadzone: 0.2, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").stick(StickType::Left, StickMapping::gamepad(StickType::Right))));
+        let gamepad = RecordingGamepad::default();
+        executor.set_gamepad_backend(Arc::new(gamepad.clone()));
+        let calls = || std::mem::take(&mut *gamepad.calls.lock().unwrap());
+        
+        // Inside the deadzone nothing is sent until the stick has been out of it
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.1, y: 0.0 });
+        assert!(calls().is_empty());
+        
+        // The deadzone edge maps to 0 and full deflection to 1, on the configured gamepad stick
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.6, y: 0.0 });
+        let sent = calls();
+        assert_eq!(sent[0].0, GamepadAxis::RightX);
+        assert!((sent[0].1 - 0.5).abs() < 1e-5);
+        assert_eq!(sent[1], (GamepadAxis::RightY, 0.0));
+        
+        // Diagonals are clamped to the unit circle, repeats are not re-sent
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 1.0, y: -1.0 });
+        executor.update_continuous_movements();
+        let sent = calls();
+        assert_eq!(sent.len(), 2);
+        assert!((sent[0].1.hypot(sent[1].1) - 1.0).abs() < 1e-5);
+        
+        // Back in the deadzone centers the stick once
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.0, y: 0.0 });
+        executor.update_continuous_movements();
+        assert_eq!(calls(), vec![(GamepadAxis::RightX, 0.0), (GamepadAxis::RightY, 0.0)]);
+    }
+    
+    #[test]
+    fn test_profile_stick_deadzone_override() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { left_stick_deadzone: 0.15, ..Settings::default() })
+            .profile(ProfileBuilder::new("base").stick(StickType::Left, StickMapping::mouse(1.0)).stick_deadzone(StickType::Left, 0.5))
+            .profile(ProfileBuilder::new("precise").stick(StickType::Left, StickMapping::mouse(1.0))));
+        
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.3, y: 0.0 });
+        assert!(executor.mouse.events().is_empty());
+        
+        // The other profile falls back to the global deadzone
+        assert!(executor.set_profile_by_name("precise"));
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.3, y: 0.0 });
+        assert_eq!(executor.mouse.events(), vec![MouseEvent::Move { dx: 3, dy: 0 }]);
+    }
+    
+    #[test]
+    fn test_stick_mouse_base() {
+        let (mut executor, _) = profile_executor(ProfileBuilder::new("base").stick(StickType::Left, StickMapping::mouse(2.0)));
+        let push = JoyConEvent::StickMoved { stick: StickType::Left, x: 1.0, y: -0.5 };
+        
+        // stick × sensitivity × sensitivity factor × base
+        executor.process_event(&push);
+        executor.config.settings.stick_mouse_base = 25.0;
+        executor.process_event(&push);
+        assert_eq!(executor.mouse.events(), vec![
+            MouseEvent::Move { dx: 20, dy: -10 },
+            MouseEvent::Move { dx: 50, dy: -25 },
+        ]);
+    }
+    
+    #[test]
+    fn test_stick_sensitivity_zones() {
+        let (mut executor, _) = profile_executor(ProfileBuilder::new("base").stick(StickType::Left, StickMapping {
+            sensitivity_zones: vec![(0.0, 0.5), (0.9, 3.0)],
+            ..StickMapping::mouse(1.0)
+        }));
+        
+        // The inner ring moves slower, the rim faster
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 0.8, y: 0.0 });
+        executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x: 1.0, y: 0.0 });
+        assert_eq!(executor.mouse.events(), vec![
+            MouseEvent::Move { dx: 4, dy: 0 },
+            MouseEvent::Move { dx: 30, dy: 0 },
+        ]);
+    }
+    
+    #[test]
+    fn test_stick_active_events() {
+        let (mut executor, _) = test_executor(ConfigBuilder::new()
+            .settings(Settings { right_stick_deadzone: 0.2, ..Settings::default() })
+            .profile(ProfileBuilder::new("base")));
+        let (sender, receiver) = crossbeam_channel::bounded(8);
+        executor.set_event_sender(sender);
+        let mut stick = |x: f32| executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Right, x, y: 0.0 });
+        
+        stick(0.1);
+        stick(0.5);
+        stick(0.9);
+        stick(0.1);
+        stick(0.0);
+        
+        let events: Vec<bool> = receiver.try_iter()
+            .map(|event| match event {
+                JoyConEvent::StickActive { stick: StickType::Right, active } => active,
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(events, vec![true, false]);
+    }
+    
+    #[test]
+    fn test_press_release_actions() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { click_min_duration_ms: 0, click_debounce_ms: 0, ..Settings::default() })
+            .profile(ProfileBuilder::new("base")
+                .button(ButtonType::B, keyhold("shift"))
+                .on_press(ButtonType::B, keyhold("x"))
+                .on_release(ButtonType::B, Action::MouseClick { button: crate::mapping::config::MouseButton::Left })));
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        // A repeated press doesn't tap again
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("shift"), down("x"), up("x")]);
+        assert!(executor.mouse.events().is_empty());
+        
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::B, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("shift"), down("x"), up("x"), up("shift")]);
+        assert_eq!(executor.mouse.events(), vec![
+            MouseEvent::ButtonDown(MouseButton::Left),
+            MouseEvent::ButtonUp(MouseButton::Left),
+        ]);
+    }
+    
+    #[test]
+    fn test_side_button_map_follows_sending_controller() {
+        let config = ConfigBuilder::new()
+            .profile(ProfileBuilder::new("base")
+                .button(ButtonType::B, keyhold("b"))
+                .side_button(ControllerSide::Right, ButtonType::B, keyhold("enter"))
+                .side_button(ControllerSide::Left, ButtonType::DpadUp, keyhold("up")))
+            .build()
+            .unwrap();
+        let keyboard = MockKeyboardBackend::new();
+        let mut executor = MappingExecutor::new(config, keyboard.clone(), MockMouseBackend::new());
+        
+        // Two identical controllers: the same button maps per sending side, `buttons` as fallback
+        for side in [ControllerSide::Right, ControllerSide::Left] {
+            executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side });
+            executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::B, side });
+        }
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::DpadUp, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::DpadUp, side: ControllerSide::Left });
+        
+        assert_eq!(keyboard.events(), vec![down("enter"), up("enter"), down("b"), up("b"), down("up"), up("up")]);
+    }
+    
+    #[test]
+    fn test_pulse_stick() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base").stick(StickType::Left, StickMapping {
+            pulse_repeat_delay_ms: Some(300),
+            pulse_repeat_interval_ms: 100,
+            ..StickMapping::pulse(DirectionalKeys::new("w", "s", "a", "d"))
+        }));
+        let clock = install_mock_clock(&mut executor);
+        let push = |executor: &mut TestExecutor, x, y| {
+            executor.process_event(&JoyConEvent::StickMoved { stick: StickType::Left, x, y });
+        };
+        let taps = |keyboard: &MockKeyboardBackend| keyboard.events().iter().filter(|e| matches!(e, KeyboardEvent::KeyDown(_))).count();
+        
+        // One tap per push, however long the stick stays out
+        push(&mut executor, 0.0, -1.0);
+        push(&mut executor, 0.1, -0.9);
+        executor.update_continuous_movements();
+        assert_eq!(keyboard.events(), vec![down("w"), up("w")]);
+        
+        // Held past the repeat delay, then every interval
+        clock.advance(Duration::from_millis(299));
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 1);
+        clock.advance(Duration::from_millis(1));
+        executor.update_continuous_movements();
+        clock.advance(Duration::from_millis(100));
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 3);
+        
+        // Back to center re-arms, a push short of a direction doesn't fire yet
+        push(&mut executor, 0.0, 0.0);
+        push(&mut executor, 0.3, 0.0);
+        assert_eq!(taps(&keyboard), 3);
+        push(&mut executor, 1.0, 0.0);
+        assert_eq!(keyboard.events()[6..], [down("d"), up("d")]);
+    }
+    
+    #[test]
+    fn test_type_text_action() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base").button(ButtonType::Plus, Action::TypeText { text: "Hi!".to_string() }));
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Plus, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Plus, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Plus, side: ControllerSide::Right });
+        
+        // Typed once
+        assert_eq!(keyboard.events(), vec![KeyboardEvent::TypeText("Hi!".to_string())]);
+    }
+    
+    #[test]
+    fn test_turbo_toggle() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { action_cooldown_ms: 0, ..Settings::default() })
+            .profile(ProfileBuilder::new("base")
+                .button(ButtonType::X, Action::TurboToggle { key: "e".to_string(), rate_hz: 10.0 })
+                .button(ButtonType::Minus, Action::ReleaseAll)));
+        let clock = install_mock_clock(&mut executor);
+        let taps = |keyboard: &MockKeyboardBackend| keyboard.events().iter().filter(|e| **e == down("e")).count();
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::X, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::X, side: ControllerSide::Right });
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 1);
+        
+        // 10 Hz: one tap per 100ms, whether or not the button is held
+        clock.advance(Duration::from_millis(50));
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 1);
+        clock.advance(Duration::from_millis(50));
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 2);
+        
+        // The second press stops it
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::X, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::X, side: ControllerSide::Right });
+        clock.advance(Duration::from_millis(500));
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 2);
+        
+        // ReleaseAll stops it too
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::X, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::X, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Minus, side: ControllerSide::Left });
+        clock.advance(Duration::from_millis(500));
+        executor.update_continuous_movements();
+        assert_eq!(taps(&keyboard), 2);
+        assert_eq!(keyboard.events().last(), Some(&up("e")));
+    }
+    
+    #[test]
+    fn test_key_repeat_timing() {
+        let (mut executor, keyboard) = test_executor(ConfigBuilder::new()
+            .settings(Settings { key_repeat: Some(KeyRepeat { repeat_delay_ms: 300, repeat_rate_hz: 10.0 }), ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::A, keyhold("ctrl+shift+w"))));
+        let clock = install_mock_clock(&mut executor);
+        let events = || keyboard.events();
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(events(), vec![down("ctrl"), down("shift"), down("w")]);
+        
+        // Nothing before the delay, then the last combo key every 100ms
+        clock.advance(Duration::from_millis(299));
+        executor.update_continuous_movements();
+        assert_eq!(events().len(), 3);
+        clock.advance(Duration::from_millis(1));
+        executor.update_continuous_movements();
+        assert_eq!(events()[3..], [down("w")]);
+        clock.advance(Duration::from_millis(50));
+        executor.update_continuous_movements();
+        assert_eq!(events().len(), 4);
+        clock.advance(Duration::from_millis(50));
+        executor.update_continuous_movements();
+        assert_eq!(events()[4..], [down("w")]);
+        
+        // Missed repeats aren't burst out after a late update
+        clock.advance(Duration::from_millis(1000));
+        executor.update_continuous_movements();
+        assert_eq!(events().len(), 6);
+        
+        // Releasing stops the repeat
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        clock.advance(Duration::from_millis(1000));
+        executor.update_continuous_movements();
+        assert_eq!(events()[6..], [up("w"), up("shift"), up("ctrl")]);
+    }
+    
+    #[test]
+    fn test_key_repeat_per_button() {
+        let (mut executor, keyboard) = profile_executor(ProfileBuilder::new("base")
+            .button(ButtonType::A, keyhold("ctrl+shift+w"))
+            .button(ButtonType::B, keyhold("e")));
+        let clock = install_mock_clock(&mut executor);
+        let repeats = |key: &str| keyboard.events().iter().filter(|e| **e == down(key)).count() - 1;
+        
+        // Off by default, OS autorepeat of the single key_down is all there is
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        clock.advance(Duration::from_secs(2));
+        executor.update_continuous_movements();
+        assert_eq!(repeats("e"), 0);
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::B, side: ControllerSide::Right });
+        keyboard.clear();
+        
+        // A per-button setting opts in, and a rate of 0 opts a button out of the global one
+        executor.config.profiles[0].key_repeat.insert(ButtonType::B, KeyRepeat { repeat_delay_ms: 0, repeat_rate_hz: 20.0 });
+        executor.config.profiles[0].key_repeat.insert(ButtonType::A, KeyRepeat { repeat_delay_ms: 0, repeat_rate_hz: 0.0 });
+        executor.config.settings.key_repeat = Some(KeyRepeat::default());
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::B, side: ControllerSide::Right });
+        for _ in 0..4 {
+            executor.update_continuous_movements();
+            clock.advance(Duration::from_millis(50));
+        }
+        assert_eq!((repeats("e"), repeats("w")), (4, 0));
+    }
+    
+    #[test]
+    fn test_confirm_to_activate() {
+        let config = ConfigBuilder::new()
+            .settings(Settings { confirm_to_activate: vec![ButtonType::L, ButtonType::R], ..Settings::default() })
+            .profile(ProfileBuilder::new("base").button(ButtonType::A, keyhold("ctrl+shift+w")))
+            .build()
+            .unwrap();
+        let keyboard = MockKeyboardBackend::new();
+        let clock = MockClock::new();
+        let mut executor = MappingExecutor::with_clock(config, keyboard.clone(), MockMouseBackend::new(), clock.clone());
+        let (sender, receiver) = crossbeam_channel::bounded(16);
+        executor.set_command_sender(sender);
+        assert!(executor.is_paused());
+        assert!(executor.is_awaiting_confirm());
+        
+        // LEDs flash while waiting
+        executor.update_continuous_movements();
+        clock.advance(Duration::from_millis(500));
+        executor.update_continuous_movements();
+        let commands: Vec<ManagerCommand> = receiver.try_iter().collect();
+        assert_eq!(commands, vec![
+            ManagerCommand::SetPlayerLed { side: ControllerSide::Left, leds: 0b1111 },
+            ManagerCommand::SetPlayerLed { side: ControllerSide::Right, leds: 0b1111 },
+            ManagerCommand::SetPlayerLed { side: ControllerSide::Left, leds: 0b0000 },
+            ManagerCommand::SetPlayerLed { side: ControllerSide::Right, leds: 0b0000 },
+        ]);
+        
+        // Nothing is sent, and resuming from outside doesn't skip the confirmation
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::A, side: ControllerSide::Right });
+        executor.set_paused(false);
+        assert!(executor.is_awaiting_confirm());
+        
+        // One confirm button alone isn't enough
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::L, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::L, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::R, side: ControllerSide::Right });
+        assert!(executor.is_awaiting_confirm());
+        assert!(keyboard.events().is_empty());
+        
+        // Both held together activate, and the LEDs are put back
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::L, side: ControllerSide::Left });
+        assert!(!executor.is_awaiting_confirm());
+        assert!(!executor.is_paused());
+        let commands: Vec<ManagerCommand> = receiver.try_iter().collect();
+        assert!(commands.contains(&ManagerCommand::SetPlayerLed { side: ControllerSide::Left, leds: 0b0001 }));
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::L, side: ControllerSide::Left });
+        executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::R, side: ControllerSide::Right });
+        
+        executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+        assert_eq!(keyboard.events(), vec![down("ctrl"), down("shift"), down("w")]);
+    }
 }
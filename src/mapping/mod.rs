@@ -1,7 +1,12 @@
 //! Mapping module - converts Joy-Con inputs to keyboard/mouse actions
 
+pub mod builder;
+pub mod clock;
 pub mod config;
 pub mod executor;
+pub mod window_profiles;
 
-pub use config::{Config, ConfigError};
+pub use builder::{ConfigBuilder, ProfileBuilder};
+pub use clock::{Clock, MockClock, RealClock};
+pub use config::{Config, ConfigError, ConfigWarning};
 pub use executor::MappingExecutor;
@@ -0,0 +1,177 @@
+//! Automatic profile switching based on the foreground window
+//!
+//! `settings.window_profiles` maps window-title or executable-name substrings to
+//! profile names. On Windows a background thread polls the foreground window and
+//! requests a profile switch once the match has been stable for a short time.
+
+use std::collections::HashMap;
+
+/// How often the foreground window is polled (milliseconds)
+pub const WINDOW_POLL_INTERVAL_MS: u64 = 250;
+
+/// Number of consecutive polls a new match must survive before switching
+pub const WINDOW_DEBOUNCE_POLLS: u32 = 4;
+
+/// Find the profile for a window, matching title or exe name (case-insensitive)
+///
+/// When several patterns match, the longest (most specific) one wins.
+pub fn match_window_profile<'a>(
+    window_profiles: &'a HashMap<String, String>,
+    title: &str,
+    exe: &str,
+) -> Option<&'a str> {
+    let title = title.to_lowercase();
+    let exe = exe.to_lowercase();
+
+    window_profiles
+        .iter()
+        .filter(|(pattern, _)| !pattern.is_empty())
+        .filter(|(pattern, _)| {
+            let pattern = pattern.to_lowercase();
+            title.contains(&pattern) || exe.contains(&pattern)
+        })
+        .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        .map(|(_, profile)| profile.as_str())
+}
+
+/// Debounces window matches so rapid focus changes don't thrash profiles
+#[derive(Debug, Default)]
+pub struct WindowProfileDebouncer {
+    candidate: Option<String>,
+    stable_polls: u32,
+    active: Option<String>,
+}
+
+impl WindowProfileDebouncer {
+    /// Feed the latest match, returns a profile name when a switch should happen
+    pub fn update(&mut self, matched: Option<&str>) -> Option<String> {
+        // Windows without a mapping keep the current profile
+        let matched = matched?;
+
+        if self.candidate.as_deref() == Some(matched) {
+            self.stable_polls = self.stable_polls.saturating_add(1);
+        } else {
+            self.candidate = Some(matched.to_string());
+            self.stable_polls = 1;
+        }
+
+        if self.stable_polls >= WINDOW_DEBOUNCE_POLLS && self.active.as_deref() != Some(matched) {
+            self.active = Some(matched.to_string());
+            return self.active.clone();
+        }
+
+        None
+    }
+}
+
+/// Start the foreground window watcher thread
+///
+/// Matching profile names are sent to `profile_sender` until `running` is cleared.
+#[cfg(windows)]
+pub fn spawn_window_watcher(
+    window_profiles: HashMap<String, String>,
+    running: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    profile_sender: crossbeam_channel::Sender<String>,
+) -> std::io::Result<()> {
+    use log::{debug, info};
+    use std::sync::atomic::Ordering;
+
+    std::thread::Builder::new()
+        .name("window-watcher".to_string())
+        .spawn(move || {
+            info!("Window watcher started ({} rules)", window_profiles.len());
+            let mut debouncer = WindowProfileDebouncer::default();
+
+            while running.load(Ordering::SeqCst) {
+                if let Some((title, exe)) = foreground_window() {
+                    let matched = match_window_profile(&window_profiles, &title, &exe);
+                    if let Some(profile) = debouncer.update(matched) {
+                        debug!("Foreground window '{}' ({}) -> profile '{}'", title, exe, profile);
+                        if profile_sender.send(profile).is_err() {
+                            break;
+                        }
+                    }
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(WINDOW_POLL_INTERVAL_MS));
+            }
+
+            info!("Window watcher exited");
+        })?;
+
+    Ok(())
+}
+
+/// Get the foreground window title and executable name
+#[cfg(windows)]
+fn foreground_window() -> Option<(String, String)> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+
+        let mut title_buf = [0u16; 512];
+        let len = GetWindowTextW(hwnd, &mut title_buf).max(0) as usize;
+        let title = String::from_utf16_lossy(&title_buf[..len]);
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+        let mut exe = String::new();
+        if let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            let mut path_buf = [0u16; 512];
+            let mut size = path_buf.len() as u32;
+            if QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(path_buf.as_mut_ptr()), &mut size).is_ok() {
+                let path = String::from_utf16_lossy(&path_buf[..size as usize]);
+                exe = path.rsplit(['\\', '/']).next().unwrap_or_default().to_string();
+            }
+            let _ = CloseHandle(process);
+        }
+
+        Some((title, exe))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_window_profile() {
+        let mut map = HashMap::new();
+        map.insert("Euro Truck".to_string(), "ETS2".to_string());
+        map.insert("eurotrucks2.exe".to_string(), "ETS2".to_string());
+        map.insert("notepad".to_string(), "base".to_string());
+
+        assert_eq!(match_window_profile(&map, "Euro Truck Simulator 2", ""), Some("ETS2"));
+        assert_eq!(match_window_profile(&map, "", "EUROTRUCKS2.EXE"), Some("ETS2"));
+        assert_eq!(match_window_profile(&map, "Untitled - Notepad", "notepad.exe"), Some("base"));
+        assert_eq!(match_window_profile(&map, "Browser", "chrome.exe"), None);
+    }
+
+    #[test]
+    fn test_debouncer_requires_stable_match() {
+        let mut debouncer = WindowProfileDebouncer::default();
+
+        for _ in 0..WINDOW_DEBOUNCE_POLLS - 1 {
+            assert_eq!(debouncer.update(Some("ETS2")), None);
+        }
+        assert_eq!(debouncer.update(Some("ETS2")), Some("ETS2".to_string()));
+
+        // Already active, no repeated switch; unmapped windows keep it
+        assert_eq!(debouncer.update(Some("ETS2")), None);
+        assert_eq!(debouncer.update(None), None);
+
+        // A brief focus change doesn't switch
+        assert_eq!(debouncer.update(Some("base")), None);
+        assert_eq!(debouncer.update(Some("ETS2")), None);
+    }
+}
@@ -0,0 +1,26 @@
+//! Integration tests for releasing held input when a controller disconnects
+
+use joy2_rs::backend::{KeyboardEvent, MockKeyboardBackend, MockMouseBackend};
+use joy2_rs::mapping::config::{Action, ButtonType, ControllerSide, JoyConEvent};
+use joy2_rs::mapping::{ConfigBuilder, MappingExecutor, ProfileBuilder};
+
+#[test]
+fn test_held_keys_released_on_disconnect() {
+    let config = ConfigBuilder::new()
+        .default_profile("base")
+        .profile(ProfileBuilder::new("base").button(ButtonType::A, Action::KeyHold { key: Some("w".to_string()) }))
+        .build()
+        .unwrap();
+    let keyboard = MockKeyboardBackend::new();
+    let mut executor = MappingExecutor::new(config, keyboard.clone(), MockMouseBackend::new());
+
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::A, side: ControllerSide::Right });
+    assert_eq!(keyboard.events(), vec![KeyboardEvent::KeyDown("w".to_string())]);
+
+    // The release never arrives, the controller is gone
+    executor.process_event(&JoyConEvent::Disconnected { side: ControllerSide::Right });
+    assert_eq!(keyboard.events(), vec![
+        KeyboardEvent::KeyDown("w".to_string()),
+        KeyboardEvent::KeyUp("w".to_string()),
+    ]);
+}
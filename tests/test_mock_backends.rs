@@ -1,6 +1,8 @@
 //! Integration tests for mock backends
 
-use joy2_rs::backend::{KeyboardBackend, MockKeyboardBackend, MockMouseBackend, MouseBackend, MouseButton};
+use joy2_rs::backend::{
+    KeyboardBackend, KeyboardEvent, MockKeyboardBackend, MockMouseBackend, MouseBackend, MouseButton, MouseEvent,
+};
 
 #[test]
 fn test_mock_keyboard_backend() {
@@ -13,6 +15,13 @@ fn test_mock_keyboard_backend() {
     
     // Mock accepts any key name (unlike real backend)
     assert!(backend.key_down("invalid_key").is_ok());
+    
+    assert_eq!(backend.events(), vec![
+        KeyboardEvent::KeyDown("w".to_string()),
+        KeyboardEvent::KeyUp("w".to_string()),
+        KeyboardEvent::KeyPress("space".to_string()),
+        KeyboardEvent::KeyDown("invalid_key".to_string()),
+    ]);
 }
 
 #[test]
@@ -24,6 +33,13 @@ fn test_mock_mouse_backend() {
     assert!(backend.button_down(MouseButton::Left).is_ok());
     assert!(backend.button_up(MouseButton::Left).is_ok());
     assert!(backend.click(MouseButton::Right).is_ok());
+    
+    assert_eq!(backend.events(), vec![
+        MouseEvent::Move { dx: 10, dy: -5 },
+        MouseEvent::ButtonDown(MouseButton::Left),
+        MouseEvent::ButtonUp(MouseButton::Left),
+        MouseEvent::Click(MouseButton::Right),
+    ]);
 }
 
 #[test]
@@ -39,4 +55,13 @@ fn test_mock_backends_are_clone() {
     assert!(kb2.key_down("b").is_ok());
     assert!(mb1.move_relative(1, 1).is_ok());
     assert!(mb2.move_relative(2, 2).is_ok());
+    
+    // Clones share one recording
+    assert_eq!(kb1.events(), vec![
+        KeyboardEvent::KeyDown("a".to_string()),
+        KeyboardEvent::KeyDown("b".to_string()),
+    ]);
+    assert_eq!(mb2.events().len(), 2);
+    kb2.clear();
+    assert!(kb1.events().is_empty());
 }
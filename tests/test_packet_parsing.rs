@@ -0,0 +1,148 @@
+//! Integration tests for Joy-Con 2 input report parsing using synthetic packets
+
+use joy2_rs::joycon2::{Joy2L, Joy2R};
+
+/// Minimum input report length accepted by the parsers
+const REPORT_LEN: usize = 0x3C;
+
+/// Raw 12-bit stick center for the default calibration
+const STICK_CENTER: (u16, u16) = ((780 + 3260) / 2, (820 + 3250) / 2);
+
+/// Build a zeroed input report with a full battery
+fn empty_report() -> Vec<u8> {
+    let mut data = vec![0u8; REPORT_LEN];
+    data[31..33].copy_from_slice(&4095u16.to_le_bytes());
+    set_stick(&mut data, 10, STICK_CENTER.0, STICK_CENTER.1);
+    set_stick(&mut data, 13, STICK_CENTER.0, STICK_CENTER.1);
+    data
+}
+
+/// Set the button bitfield (big-endian, left uses bytes 5-6, right uses bytes 4-5)
+fn set_buttons(data: &mut [u8], offset: usize, bits: u16) {
+    data[offset..offset + 2].copy_from_slice(&bits.to_be_bytes());
+}
+
+/// Pack two 12-bit stick values into 3 bytes at `offset`
+fn set_stick(data: &mut [u8], offset: usize, x: u16, y: u16) {
+    data[offset] = (x & 0xFF) as u8;
+    data[offset + 1] = ((x >> 8) & 0x0F) as u8 | ((y & 0x0F) << 4) as u8;
+    data[offset + 2] = (y >> 4) as u8;
+}
+
+/// Set raw accelerometer (0x30) and gyroscope (0x36) axes
+fn set_motion(data: &mut [u8], accel: [i16; 3], gyro: [i16; 3]) {
+    for (i, value) in accel.iter().enumerate() {
+        data[0x30 + i * 2..0x32 + i * 2].copy_from_slice(&value.to_le_bytes());
+    }
+    for (i, value) in gyro.iter().enumerate() {
+        data[0x36 + i * 2..0x38 + i * 2].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn approx(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.01
+}
+
+#[test]
+fn test_short_report_is_ignored() {
+    let mut left = Joy2L::new();
+    let mut data = empty_report();
+    set_buttons(&mut data, 5, 0xFFFF);
+    data.truncate(REPORT_LEN - 1);
+
+    assert!(!left.update(&data));
+    assert!(!left.buttons.zl);
+    assert!(!left.is_connected);
+}
+
+#[test]
+fn test_left_buttons() {
+    let mut left = Joy2L::new();
+    let mut data = empty_report();
+    set_buttons(&mut data, 5, 0x0080 | 0x0002 | 0x2000); // ZL, Up, Capture
+
+    left.update(&data);
+    assert!(left.buttons.zl);
+    assert!(left.buttons.up);
+    assert!(left.buttons.capture);
+    assert!(!left.buttons.l);
+    assert!(!left.buttons.down);
+    assert!(!left.buttons.minus);
+
+    // Right-side offset must not be read by the left parser
+    let mut data = empty_report();
+    set_buttons(&mut data, 4, 0x0080);
+    left.update(&data);
+    assert!(!left.buttons.zl);
+}
+
+#[test]
+fn test_right_buttons() {
+    let mut right = Joy2R::new();
+    let mut data = empty_report();
+    set_buttons(&mut data, 4, 0x0800 | 0x8000 | 0x0010 | 0x0040); // A, ZR, Home, Chat
+
+    right.update(&data);
+    assert!(right.buttons.a);
+    assert!(right.buttons.zr);
+    assert!(right.buttons.home);
+    assert!(right.buttons.chat);
+    assert!(!right.buttons.b);
+    assert!(!right.buttons.plus);
+    assert!(!right.buttons.r3);
+}
+
+#[test]
+fn test_stick_center_and_extremes() {
+    let mut left = Joy2L::new();
+    let mut right = Joy2R::new();
+    let data = empty_report();
+
+    left.update(&data);
+    right.update(&data);
+    assert!(approx(left.analog_stick.x, 0.0) && approx(left.analog_stick.y, 0.0));
+    assert!(approx(right.analog_stick.x, 0.0) && approx(right.analog_stick.y, 0.0));
+
+    // Full right and full up (raw Y max means stick down)
+    let mut data = empty_report();
+    set_stick(&mut data, 10, 3260, 820);
+    set_stick(&mut data, 13, 780, 3250);
+    left.update(&data);
+    right.update(&data);
+    assert!(approx(left.analog_stick.x, 1.0) && approx(left.analog_stick.y, 1.0));
+    assert!(approx(right.analog_stick.x, -1.0) && approx(right.analog_stick.y, -1.0));
+}
+
+#[test]
+fn test_gyro_and_accel() {
+    let mut left = Joy2L::new();
+    let mut data = empty_report();
+    // 6048 raw = 360 deg/s, 4096 raw = 1G
+    set_motion(&mut data, [0, 4096, 0], [6048, 0, -3024]);
+
+    left.update(&data);
+    assert!(approx(left.gyroscope.x, 360.0));
+    assert!(approx(left.gyroscope.y, 180.0));
+    assert!(approx(left.gyroscope.z, 0.0));
+    assert!(approx(left.accelerometer.z, 1.0));
+    assert!(approx(left.accelerometer.x, 0.0));
+
+    let mut right = Joy2R::new();
+    right.update(&data);
+    assert!(approx(right.gyroscope.x, 360.0));
+    assert!(approx(right.accelerometer.z, 1.0));
+}
+
+#[test]
+fn test_mouse_sensor_bytes() {
+    let mut right = Joy2R::new();
+    let mut data = empty_report();
+    data[16..18].copy_from_slice(&(-120i16).to_le_bytes());
+    data[18..20].copy_from_slice(&345i16.to_le_bytes());
+    data[23] = 0x20;
+
+    right.update(&data);
+    assert_eq!(right.mouse.x, -120);
+    assert_eq!(right.mouse.y, 345);
+    assert_eq!(right.mouse.distance, 0x20);
+}
@@ -0,0 +1,88 @@
+//! Integration tests for the system buttons (Plus, Minus, Home, Capture) reaching the mapping
+
+use joy2_rs::backend::{KeyboardEvent, MockKeyboardBackend, MockMouseBackend};
+use joy2_rs::joycon2::controller::{diff_states, ControllerState};
+use joy2_rs::joycon2::{Joy2L, Joy2R};
+use joy2_rs::mapping::config::{Action, ButtonType, ControllerSide, JoyConEvent};
+use joy2_rs::mapping::{ConfigBuilder, MappingExecutor, ProfileBuilder};
+
+/// Minimum input report length accepted by the parsers
+const REPORT_LEN: usize = 0x3C;
+
+/// Input report with the button bitfield (big-endian) set at `offset`
+fn report(offset: usize, bits: u16) -> Vec<u8> {
+    let mut data = vec![0u8; REPORT_LEN];
+    data[offset..offset + 2].copy_from_slice(&bits.to_be_bytes());
+    data
+}
+
+/// Parse a report the way the manager does and return its button events
+fn button_events(controller: &mut dyn ControllerState, data: &[u8]) -> Vec<JoyConEvent> {
+    let before = controller.button_states();
+    assert!(controller.update(data));
+    diff_states(controller.button_states(), before)
+        .into_iter()
+        .map(|(button, pressed)| if pressed {
+            JoyConEvent::ButtonPressed { button, side: button.side() }
+        } else {
+            JoyConEvent::ButtonReleased { button, side: button.side() }
+        })
+        .collect()
+}
+
+fn key(name: &str) -> Action {
+    Action::KeyHold { key: Some(name.to_string()) }
+}
+
+#[test]
+fn test_system_buttons_trigger_their_mappings() {
+    let config = ConfigBuilder::new()
+        .profile(ProfileBuilder::new("base")
+            .button(ButtonType::Home, key("f1"))
+            .button(ButtonType::Plus, key("f2"))
+            .button(ButtonType::Minus, key("f3"))
+            .button(ButtonType::Capture, key("f4")))
+        .build()
+        .unwrap();
+    let keyboard = MockKeyboardBackend::new();
+    let mut executor = MappingExecutor::new(config, keyboard.clone(), MockMouseBackend::new());
+    let mut right = Joy2R::new();
+    let mut left = Joy2L::new();
+
+    // Right: Home (0x0010) and Plus (0x0002), left: Minus (0x0100) and Capture (0x2000)
+    let mut events = button_events(&mut right, &report(4, 0x0010 | 0x0002));
+    events.extend(button_events(&mut left, &report(5, 0x0100 | 0x2000)));
+    assert_eq!(events.len(), 4);
+    for event in &events {
+        executor.process_event(event);
+    }
+
+    let mut pressed: Vec<KeyboardEvent> = keyboard.events();
+    pressed.sort_by_key(|event| format!("{:?}", event));
+    assert_eq!(pressed, ["f1", "f2", "f3", "f4"].map(|k| KeyboardEvent::KeyDown(k.to_string())));
+
+    // Releasing them sends the key ups
+    keyboard.clear();
+    for event in button_events(&mut right, &report(4, 0)).iter().chain(&button_events(&mut left, &report(5, 0))) {
+        executor.process_event(event);
+    }
+    assert_eq!(keyboard.events().len(), 4);
+    assert!(keyboard.events().iter().all(|event| matches!(event, KeyboardEvent::KeyUp(_))));
+}
+
+#[test]
+fn test_home_press_runs_mapped_action() {
+    let config = ConfigBuilder::new()
+        .profile(ProfileBuilder::new("base").button(ButtonType::Home, key("escape")))
+        .build()
+        .unwrap();
+    let keyboard = MockKeyboardBackend::new();
+    let mut executor = MappingExecutor::new(config, keyboard.clone(), MockMouseBackend::new());
+
+    executor.process_event(&JoyConEvent::ButtonPressed { button: ButtonType::Home, side: ControllerSide::Right });
+    executor.process_event(&JoyConEvent::ButtonReleased { button: ButtonType::Home, side: ControllerSide::Right });
+    assert_eq!(keyboard.events(), vec![
+        KeyboardEvent::KeyDown("escape".to_string()),
+        KeyboardEvent::KeyUp("escape".to_string()),
+    ]);
+}